@@ -0,0 +1,26 @@
+//! Generates bindings of `src/test.pr47b` to `OUT_DIR`, which get compiled against `pr47` and
+//! called in `test::compiled`. A build script cannot depend on its own crate, so the generator
+//! modules are included by path.
+
+#[path = "src/codegen.rs"] mod codegen;
+// some fields are only read by tests of the library
+#[allow(dead_code)]
+#[path = "src/concrete.rs"] mod concrete;
+#[path = "src/parse.rs"] mod parse;
+
+fn main() {
+    for path /*: &str*/ in [
+        "src/codegen.rs",
+        "src/concrete.rs",
+        "src/parse.rs",
+        "src/test.pr47b"
+    ] {
+        println!("cargo:rerun-if-changed={}", path);
+    }
+
+    let source: String = std::fs::read_to_string("src/test.pr47b").unwrap();
+    let decls: Vec<concrete::Decl> = parse::parse(&source).unwrap();
+    let code: String = codegen::generate(&decls, "pr47").unwrap();
+    let out_dir: String = std::env::var("OUT_DIR").unwrap();
+    std::fs::write(format!("{}/test_bindings.rs", out_dir), code).unwrap();
+}
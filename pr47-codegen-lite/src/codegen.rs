@@ -0,0 +1,567 @@
+use std::fmt::Write;
+
+use crate::concrete::{Decl, FunctionDecl, Type, TypeDecl};
+
+/// How one parameter is loaded from a VM value
+enum ParamKind {
+    /// Value types, loaded from field `field` of the `ValueTypedData`
+    Value { field: &'static str },
+    /// `&str`, borrowed from a VM `string`
+    Str,
+    /// `&T`, `DataOption::Share`
+    Ref { inner: String },
+    /// `&mut T`, `DataOption::MutShare`
+    MutRef { inner: String },
+    /// `T`, `DataOption::Move`
+    Move { ty: String }
+}
+
+struct Param {
+    kind: ParamKind,
+    nullable: bool
+}
+
+/// How one return value is stored into a VM value
+enum RetKind {
+    /// Value types, created with `Value::ctor`
+    Value { ctor: &'static str },
+    /// Other types, moved into a newly created VM value
+    Move
+}
+
+struct Ret {
+    kind: RetKind,
+    nullable: bool
+}
+
+/// Code fragments of one function binding
+struct Binding<'a> {
+    krate: &'a str,
+    asserts: Vec<String>,
+
+    params: Vec<Param>,
+    param_tyck: Vec<String>,
+    param_options: Vec<String>,
+
+    rets: Vec<Ret>,
+    ret_tyck: Vec<String>,
+    ret_options: Vec<String>,
+
+    exception_tyck: Option<String>
+}
+
+/// Returns the `ValueTypedData` field, `Value` constructor and `TyckInfoPool` getter of value
+/// type `ty`, or `None` if `ty` is not a value type
+fn value_type(ty: &Type) -> Option<(&'static str, &'static str, &'static str)> {
+    match path_type(ty, 0)? {
+        "bool" => Some(("bool_value", "new_bool", "get_bool_type")),
+        "char" => Some(("char_value", "new_char", "get_char_type")),
+        "f64" => Some(("float_value", "new_float", "get_float_type")),
+        "i64" => Some(("int_value", "new_int", "get_int_type")),
+        _ => None
+    }
+}
+
+/// Returns the last path segment of `ty`, if `ty` is a path type with `arg_count` generic
+/// arguments
+fn path_type(ty: &Type, arg_count: usize) -> Option<&str> {
+    match ty {
+        Type::Path { path, args } if args.len() == arg_count => Some(path.last()),
+        _ => None
+    }
+}
+
+fn generic_args(ty: &Type) -> &[Type] {
+    match ty {
+        Type::Path { args, .. } => args,
+        _ => &[]
+    }
+}
+
+fn is_str(ty: &Type) -> bool {
+    path_type(ty, 0) == Some("str")
+}
+
+fn is_unit(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple { elems } if elems.is_empty())
+}
+
+fn check_vm_type(ty: &Type) -> Result<(), String> {
+    match path_type(ty, 0) {
+        Some(name @ ("i8" | "i16" | "i32" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+                     | "u128" | "usize" | "f32")) =>
+            Err(format!("`{}` is not a VM type, use `i64` or `f64` instead", name)),
+        _ => Ok(())
+    }
+}
+
+impl<'a> Binding<'a> {
+    fn add_param(&mut self, ty: &Type) -> Result<(), String> {
+        let (ty, nullable): (&Type, bool) = match path_type(ty, 1) {
+            Some("Option") => (&generic_args(ty)[0], true),
+            _ => (ty, false)
+        };
+        let kind: ParamKind = self.param_kind(ty)?;
+
+        let data_option: String = match &kind {
+            ParamKind::Value { .. } => self.data_option("Copy"),
+            ParamKind::Str | ParamKind::Ref { .. } => self.data_option("Share"),
+            ParamKind::MutRef { .. } => self.data_option("MutShare"),
+            ParamKind::Move { .. } => self.data_option("Move")
+        };
+        let tyck: String = match ty {
+            Type::Ref { inner, .. } => self.tyck(inner),
+            _ => self.tyck(ty)
+        };
+        let tyck: String = self.nullable_tyck(tyck, nullable);
+
+        self.params.push(Param { kind, nullable });
+        self.param_tyck.push(tyck);
+        self.param_options.push(data_option);
+        Ok(())
+    }
+
+    fn param_kind(&mut self, ty: &Type) -> Result<ParamKind, String> {
+        check_vm_type(ty)?;
+        if let Some((field, _, _)) = value_type(ty) {
+            return Ok(ParamKind::Value { field });
+        }
+
+        match ty {
+            Type::Path { .. } => match path_type(ty, generic_args(ty).len()) {
+                Some("Option") => Err("nested `Option` parameters are not supported".into()),
+                Some("Result") => Err("`Result` parameters are not supported".into()),
+                Some("str") => Err("`str` parameters should be passed by reference".into()),
+                _ => {
+                    self.assert_static_base(ty);
+                    Ok(ParamKind::Move { ty: ty.to_string() })
+                }
+            },
+            Type::Ref { mutable, inner } => {
+                check_vm_type(inner)?;
+                if is_str(inner) {
+                    if *mutable {
+                        Err("`&mut str` parameters are not supported".into())
+                    } else {
+                        Ok(ParamKind::Str)
+                    }
+                } else if value_type(inner).is_some() {
+                    Err("references to value types are not supported, pass by value".into())
+                } else if let Type::Path { .. } = &**inner {
+                    self.assert_static_base(inner);
+                    if *mutable {
+                        Ok(ParamKind::MutRef { inner: inner.to_string() })
+                    } else {
+                        Ok(ParamKind::Ref { inner: inner.to_string() })
+                    }
+                } else {
+                    Err("unsupported reference parameter type".into())
+                }
+            },
+            Type::Tuple { .. } => Err("tuple parameters are not supported".into())
+        }
+    }
+
+    /// Add return values of type `ty`, returns if the function may throw exceptions
+    fn add_rets(&mut self, ty: &Type) -> Result<bool, String> {
+        if path_type(ty, 2) == Some("Result") {
+            let (ok_type, err_type): (&Type, &Type) = (&generic_args(ty)[0], &generic_args(ty)[1]);
+            self.assert_static_base(err_type);
+            self.exception_tyck = Some(self.tyck(err_type));
+            if path_type(ok_type, 2) == Some("Result") {
+                return Err("nested `Result` return types are not supported".into());
+            }
+            self.add_rets(ok_type)?;
+            return Ok(true);
+        }
+
+        if let Type::Tuple { elems } = ty {
+            for elem /*: &Type*/ in elems {
+                self.add_ret(elem)?;
+            }
+        } else {
+            self.add_ret(ty)?;
+        }
+        Ok(false)
+    }
+
+    fn add_ret(&mut self, ty: &Type) -> Result<(), String> {
+        let (ty, nullable): (&Type, bool) = match path_type(ty, 1) {
+            Some("Option") => (&generic_args(ty)[0], true),
+            _ => (ty, false)
+        };
+        let kind: RetKind = self.ret_kind(ty)?;
+
+        let data_option: String = match &kind {
+            RetKind::Value { .. } => self.data_option("Copy"),
+            RetKind::Move => self.data_option("Move")
+        };
+        let tyck: String = self.nullable_tyck(self.tyck(ty), nullable);
+
+        self.rets.push(Ret { kind, nullable });
+        self.ret_tyck.push(tyck);
+        self.ret_options.push(data_option);
+        Ok(())
+    }
+
+    fn ret_kind(&mut self, ty: &Type) -> Result<RetKind, String> {
+        check_vm_type(ty)?;
+        if let Some((_, ctor, _)) = value_type(ty) {
+            return Ok(RetKind::Value { ctor });
+        }
+
+        match ty {
+            Type::Path { .. } => match path_type(ty, generic_args(ty).len()) {
+                Some("Option") => Err("nested `Option` return types are not supported".into()),
+                Some("Result") =>
+                    Err("`Result` is only supported as the outermost return type".into()),
+                Some("str") => Err("`str` return type is not supported".into()),
+                _ => {
+                    self.assert_static_base(ty);
+                    Ok(RetKind::Move)
+                }
+            },
+            Type::Ref { .. } => Err("reference return types are not supported".into()),
+            Type::Tuple { .. } =>
+                Err("tuples are only supported as the outermost return type".into())
+        }
+    }
+
+    fn tyck(&self, ty: &Type) -> String {
+        if let Some((_, _, getter)) = value_type(ty) {
+            format!("tyck_info_pool.{}()", getter)
+        } else if is_str(ty) {
+            "tyck_info_pool.get_string_type()".into()
+        } else {
+            format!(
+                "<{krate}::data::traits::Void as {krate}::data::traits::StaticBase<{}>>\
+                 ::tyck_info(tyck_info_pool)",
+                ty,
+                krate = self.krate
+            )
+        }
+    }
+
+    fn nullable_tyck(&self, tyck: String, nullable: bool) -> String {
+        if nullable {
+            format!(
+                "{{ let base: std::ptr::NonNull<{}::data::tyck::TyckInfo> = {}; \
+                 tyck_info_pool.create_nullable_type(base) }}",
+                self.krate, tyck
+            )
+        } else {
+            tyck
+        }
+    }
+
+    fn data_option(&self, option: &str) -> String {
+        format!("{}::ffi::DataOption::{}", self.krate, option)
+    }
+
+    fn assert_static_base(&mut self, ty: &Type) {
+        let assert: String =
+            format!("{}::util::type_assert::assert_static_base::<{}>();", self.krate, ty);
+        if !self.asserts.contains(&assert) {
+            self.asserts.push(assert);
+        }
+    }
+
+    fn gen_signature(&self, out: &mut String) {
+        let krate: &str = self.krate;
+        let exception_tyck: Vec<&str> = self.exception_tyck.iter().map(String::as_str).collect();
+
+        for assert /*: &String*/ in self.asserts.iter() {
+            let _ = writeln!(out, "        {}", assert);
+        }
+        for (name, tyck) /*: (&str, &[String])*/ in [
+            ("params", &self.param_tyck[..]),
+            ("rets", &self.ret_tyck[..])
+        ] {
+            let _ = writeln!(
+                out,
+                "        let {}: [std::ptr::NonNull<{}::data::tyck::TyckInfo>; {}] = [{}];",
+                name, krate, tyck.len(), tyck.join(", ")
+            );
+        }
+        let _ = writeln!(
+            out,
+            "        let exceptions: [std::ptr::NonNull<{}::data::tyck::TyckInfo>; {}] = [{}];",
+            krate, exception_tyck.len(), exception_tyck.join(", ")
+        );
+        let _ = writeln!(out, "        {}::ffi::Signature {{", krate);
+        let _ = writeln!(
+            out,
+            "            func_type: tyck_info_pool.create_function_type(\
+             &params, &rets, &exceptions),"
+        );
+        let _ = writeln!(
+            out,
+            "            param_options: vec![{}].into_boxed_slice(),",
+            self.param_options.join(", ")
+        );
+        let _ = writeln!(
+            out,
+            "            ret_option: vec![{}].into_boxed_slice(),",
+            self.ret_options.join(", ")
+        );
+        let _ = writeln!(out, "            variadic: None,");
+        let _ = writeln!(out, "            ret_arity: {}::ffi::RetArity::Fixed", krate);
+        let _ = writeln!(out, "        }}");
+    }
+
+    /// Generate the body of `FunctionBase::call_rtlc`, calling function `func`
+    fn gen_call(&self, func: &str, fallible: bool, out: &mut String) {
+        let krate: &str = self.krate;
+        let ffi_mod: String = format!("{}::ffi::sync_fn", krate);
+
+        let _ = writeln!(out, "        debug_assert_eq!(args.len(), {});", self.params.len());
+        let _ = writeln!(out, "        debug_assert_eq!(rets.len(), {});", self.rets.len());
+
+        let mut move_checks: Vec<String> = vec![];
+        let mut loads: Vec<String> = vec![];
+        let mut move_loads: Vec<String> = vec![];
+        let mut guards: Vec<String> = vec![];
+
+        for (idx, param) /*: (usize, &Param)*/ in self.params.iter().enumerate() {
+            let arg: String = format!("*args.get_unchecked({})", idx);
+            let value: String = format!("args.get_unchecked({})", idx);
+            let (load, guarded): (String, bool) = match &param.kind {
+                ParamKind::Value { field } => (format!("{}.vt_data.inner.{}", value, field), false),
+                ParamKind::Str => (
+                    format!("{{ let (r, g) = {}::value_into_ref::<String>({})?; (r.as_str(), g) }}",
+                            ffi_mod, arg),
+                    true
+                ),
+                ParamKind::Ref { inner } =>
+                    (format!("{}::value_into_ref::<{}>({})?", ffi_mod, inner, arg), true),
+                ParamKind::MutRef { inner } =>
+                    (format!("{}::value_into_mut_ref::<{}>({})?", ffi_mod, inner, arg), true),
+                ParamKind::Move { ty } => {
+                    let check: String =
+                        format!("{}::value_move_out_check_norm_noalias({})?;", ffi_mod, arg);
+                    move_checks.push(if param.nullable {
+                        format!("if !{}.is_null() {{ {} }}", value, check)
+                    } else {
+                        check
+                    });
+                    let load: String =
+                        format!("{}::value_move_out_norm_noalias::<{}>({})", ffi_mod, ty, arg);
+                    move_loads.push(nullable_load(idx, &value, &load, param.nullable));
+                    continue;
+                }
+            };
+
+            if guarded {
+                loads.push(if param.nullable {
+                    format!(
+                        "let (a{idx}, g{idx}) = if {value}.is_null() {{ (None, None) }} else \
+                         {{ let (r, g) = {load}; (Some(r), Some(g)) }};",
+                        idx = idx, value = value, load = load
+                    )
+                } else {
+                    format!("let (a{idx}, g{idx}) = {load};", idx = idx, load = load)
+                });
+                guards.push(format!("g{}", idx));
+            } else {
+                loads.push(nullable_load(idx, &value, &load, param.nullable));
+            }
+        }
+
+        for stmt /*: String*/ in move_checks.into_iter().chain(loads).chain(move_loads) {
+            let _ = writeln!(out, "        {}", stmt);
+        }
+
+        let call_args: Vec<String> = (0..self.params.len())
+            .map(|idx: usize| format!("a{}", idx))
+            .collect();
+        let call: String = format!("{}({})", func, call_args.join(", "));
+        if fallible || !self.rets.is_empty() {
+            let _ = writeln!(out, "        let ret = {};", call);
+        } else {
+            let _ = writeln!(out, "        {};", call);
+        }
+        for guard /*: &String*/ in guards.iter().rev() {
+            let _ = writeln!(out, "        std::mem::drop({});", guard);
+        }
+
+        let ret_idents: Vec<String> = (0..self.rets.len())
+            .map(|idx: usize| format!("r{}", idx))
+            .collect();
+        let ret_pattern: String = match ret_idents.len() {
+            0 => "()".into(),
+            1 => ret_idents[0].clone(),
+            _ => format!("({})", ret_idents.join(", "))
+        };
+        let stores: Vec<String> = self.rets.iter().enumerate()
+            .map(|(idx, ret): (usize, &Ret)| {
+                let store: String = match &ret.kind {
+                    RetKind::Value { ctor } =>
+                        format!("{}::data::Value::{}(r{})", krate, ctor, idx),
+                    RetKind::Move => format!(
+                        "{{ let value: {krate}::data::Value = \
+                         {krate}::data::Value::new_owned(r{idx}); \
+                         context.add_heap_managed(value); value }}",
+                        krate = krate, idx = idx
+                    )
+                };
+                let store: String = if ret.nullable {
+                    format!(
+                        "match r{idx} {{ Some(r{idx}) => {store}, \
+                         None => {krate}::data::Value::new_null() }}",
+                        idx = idx, store = store, krate = krate
+                    )
+                } else {
+                    store
+                };
+                format!("**rets.get_unchecked({}) = {};", idx, store)
+            })
+            .collect();
+
+        if fallible {
+            let _ = writeln!(out, "        match ret {{");
+            let _ = writeln!(out, "            Ok({}) => {{", ret_pattern);
+            for store /*: &String*/ in stores.iter() {
+                let _ = writeln!(out, "                {}", store);
+            }
+            let _ = writeln!(out, "                Ok(())");
+            let _ = writeln!(out, "            }},");
+            let _ = writeln!(out, "            Err(e) => {{");
+            let _ = writeln!(
+                out,
+                "                let err_value: {krate}::data::Value = \
+                 {krate}::data::Value::new_owned(e);",
+                krate = krate
+            );
+            let _ = writeln!(out, "                context.add_heap_managed(err_value);");
+            let _ = writeln!(
+                out,
+                "                Err({}::ffi::FFIException::Checked(err_value))",
+                krate
+            );
+            let _ = writeln!(out, "            }}");
+            let _ = writeln!(out, "        }}");
+        } else {
+            if !self.rets.is_empty() {
+                let _ = writeln!(out, "        let {} = ret;", ret_pattern);
+            }
+            for store /*: &String*/ in stores.iter() {
+                let _ = writeln!(out, "        {}", store);
+            }
+            let _ = writeln!(out, "        Ok(())");
+        }
+    }
+}
+
+/// Generate the statement loading `a{idx}`, wrapping it with `Option` if nullable
+fn nullable_load(idx: usize, value: &str, load: &str, nullable: bool) -> String {
+    if nullable {
+        format!(
+            "let a{} = if {}.is_null() {{ None }} else {{ Some({}) }};",
+            idx, value, load
+        )
+    } else {
+        format!("let a{} = {};", idx, load)
+    }
+}
+
+fn generate_type(decl: &TypeDecl, krate: &str, out: &mut String) {
+    let name: String = decl.name.clone().unwrap_or_else(|| decl.path.last().to_string());
+    let _ = writeln!(
+        out,
+        "impl {krate}::data::traits::StaticBase<{}> for {krate}::data::traits::Void {{",
+        decl.path,
+        krate = krate
+    );
+    let _ = writeln!(out, "    fn type_name() -> String {{");
+    let _ = writeln!(out, "        {:?}.to_string()", name);
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out, "}}");
+    let _ = writeln!(out);
+}
+
+fn generate_function(decl: &FunctionDecl, krate: &str, out: &mut String) -> Result<(), String> {
+    let mut binding: Binding = Binding {
+        krate,
+        asserts: vec![],
+        params: vec![],
+        param_tyck: vec![],
+        param_options: vec![],
+        rets: vec![],
+        ret_tyck: vec![],
+        ret_options: vec![],
+        exception_tyck: None
+    };
+
+    for (_, ty) /*: &(String, Type)*/ in decl.params.iter() {
+        binding.add_param(ty)?;
+    }
+    let fallible: bool = match &decl.ret {
+        Some(ty) if !is_unit(ty) => binding.add_rets(ty)?,
+        _ => false
+    };
+
+    let fn_name: &str = decl.path.last();
+    let binder_name: String = format!("Pr47Binder_{}", fn_name);
+
+    let _ = writeln!(out, "#[allow(non_camel_case_types)]");
+    let _ = writeln!(out, "pub struct {}();", binder_name);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "impl {}::ffi::sync_fn::FunctionBase for {} {{", krate, binder_name);
+    let _ = writeln!(
+        out,
+        "    fn signature(tyck_info_pool: &mut {krate}::data::tyck::TyckInfoPool) \
+         -> {krate}::ffi::Signature {{",
+        krate = krate
+    );
+    binding.gen_signature(out);
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out);
+
+    for method /*: &str*/ in ["call_rtlc", "call_unchecked"] {
+        let _ = writeln!(out, "    #[allow(unused_variables)]");
+        let _ = writeln!(
+            out,
+            "    unsafe fn {method}<CTX: {krate}::ffi::sync_fn::VMContext>(\
+             context: &mut CTX, args: &[{krate}::data::Value], rets: &[*mut {krate}::data::Value]\
+             ) -> Result<(), {krate}::ffi::FFIException> {{",
+            method = method,
+            krate = krate
+        );
+        if method == "call_rtlc" {
+            binding.gen_call(&decl.path.to_string(), fallible, out);
+        } else {
+            let _ = writeln!(out, "        Self::call_rtlc(context, args, rets)");
+        }
+        let _ = writeln!(out, "    }}");
+        if method == "call_rtlc" {
+            let _ = writeln!(out);
+        }
+    }
+    let _ = writeln!(out, "}}");
+    let _ = writeln!(out);
+
+    let _ = writeln!(out, "#[allow(dead_code)]");
+    let _ = writeln!(
+        out,
+        "pub const PR47BINDER_{}: &{} = &{}();",
+        fn_name.to_uppercase(),
+        binder_name,
+        binder_name
+    );
+    let _ = writeln!(out);
+    Ok(())
+}
+
+/// Generate Rust source code of `StaticBase` and `FunctionBase` implementations for `decls`.
+/// `krate` is the path of the `pr47` crate in generated code, usually `pr47` or `crate`.
+pub fn generate(decls: &[Decl], krate: &str) -> Result<String, String> {
+    let mut out: String = String::new();
+    for decl /*: &Decl*/ in decls {
+        match decl {
+            Decl::Type(decl) => generate_type(decl, krate, &mut out),
+            Decl::Function(decl) => generate_function(decl, krate, &mut out)
+                .map_err(|e: String| format!("line {}: {}", decl.line, e))?
+        }
+    }
+    Ok(out)
+}
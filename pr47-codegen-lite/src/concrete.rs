@@ -0,0 +1,81 @@
+//! Concrete syntax of binding declarations
+//!
+//! ```text
+//! // a host type, with VM type name `std.math.Vec2`
+//! type math::Vec2 = "std.math.Vec2";
+//! // a host type, with VM type name `Foo`
+//! type Foo;
+//! // a host function, bound by binder `Pr47Binder_vec2_new`
+//! fn math::vec2_new(x: f64, y: f64) -> math::Vec2;
+//! fn checked_div(a: i64, b: i64) -> Result<i64, String>;
+//! ```
+
+use std::fmt::{Display, Formatter};
+
+pub enum Decl {
+    Type(TypeDecl),
+    Function(FunctionDecl)
+}
+
+/// `type path = "name";`
+pub struct TypeDecl {
+    pub line: usize,
+    pub path: Path,
+    pub name: Option<String>
+}
+
+/// `fn path(param: Type, ...) -> Type;`
+pub struct FunctionDecl {
+    pub line: usize,
+    pub path: Path,
+    pub params: Vec<(String, Type)>,
+    pub ret: Option<Type>
+}
+
+pub struct Path {
+    pub segments: Vec<String>
+}
+
+impl Path {
+    pub fn last(&self) -> &str {
+        self.segments.last().map(String::as_str).unwrap_or("")
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.segments.join("::"))
+    }
+}
+
+pub enum Type {
+    Path { path: Path, args: Vec<Type> },
+    Ref { mutable: bool, inner: Box<Type> },
+    Tuple { elems: Vec<Type> }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Path { path, args } => {
+                write!(f, "{}", path)?;
+                if !args.is_empty() {
+                    let args: Vec<String> = args.iter().map(Type::to_string).collect();
+                    write!(f, "<{}>", args.join(", "))?;
+                }
+                Ok(())
+            },
+            Type::Ref { mutable, inner } => {
+                write!(f, "&{}{}", if *mutable { "mut " } else { "" }, inner)
+            },
+            Type::Tuple { elems } => {
+                let elems: Vec<String> = elems.iter().map(Type::to_string).collect();
+                if elems.len() == 1 {
+                    write!(f, "({},)", elems[0])
+                } else {
+                    write!(f, "({})", elems.join(", "))
+                }
+            }
+        }
+    }
+}
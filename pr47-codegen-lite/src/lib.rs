@@ -1,3 +1,29 @@
+//! Lightweight binding generator for Pr47, free of `syn` and `quote`
+//!
+//! Reads binding declarations (see `concrete`) and generates `StaticBase` and `FunctionBase`
+//! implementations as Rust source code, which can be written to `OUT_DIR` from `build.rs` and
+//! then `include!`d. Type mapping is the same as `pr47_codegen::pr47_function_bind`. Async
+//! functions and GC child tracing are not supported, use `pr47-codegen` for those.
+//!
+//! ```ignore
+//! // build.rs
+//! let source: String = std::fs::read_to_string("bindings.pr47b").unwrap();
+//! let code: String = pr47_codegen_lite::generate_bindings(&source, "pr47").unwrap();
+//! std::fs::write(format!("{}/bindings.rs", std::env::var("OUT_DIR").unwrap()), code).unwrap();
+//!
+//! // lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+//! ```
+
 pub mod codegen;
 pub mod concrete;
 pub mod parse;
+
+#[cfg(test)] mod test;
+
+/// Parse binding declarations from `source` and generate bindings. `krate` is the path of the
+/// `pr47` crate in generated code.
+pub fn generate_bindings(source: &str, krate: &str) -> Result<String, String> {
+    let decls: Vec<concrete::Decl> = parse::parse(source)?;
+    codegen::generate(&decls, krate)
+}
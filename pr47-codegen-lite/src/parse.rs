@@ -0,0 +1,319 @@
+use crate::concrete::{Decl, FunctionDecl, Path, Type, TypeDecl};
+
+enum Token {
+    Ident(String),
+    Str(String),
+    /// Single character punctuations, and `->`, `::`
+    Punct(&'static str)
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize
+}
+
+const PUNCTS: &[&str] = &["(", ")", "<", ">", ",", ";", ":", "&", "=", "_"];
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { chars: source.chars().peekable(), line: 1, column: 1 }
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let ch: char = self.chars.next()?;
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(ch)
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}, column {}: {}", self.line, self.column, message)
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> Result<(), String> {
+        loop {
+            match self.chars.peek() {
+                Some(ch) if ch.is_whitespace() => {
+                    self.next_char();
+                },
+                Some('/') => {
+                    self.next_char();
+                    match self.next_char() {
+                        Some('/') => {
+                            while !matches!(self.next_char(), Some('\n') | None) {}
+                        },
+                        Some('*') => {
+                            let mut last: char = ' ';
+                            loop {
+                                match self.next_char() {
+                                    Some('/') if last == '*' => break,
+                                    Some(ch) => last = ch,
+                                    None => return Err(self.error("unterminated block comment"))
+                                }
+                            }
+                        },
+                        _ => return Err(self.error("unexpected character `/`"))
+                    }
+                },
+                _ => return Ok(())
+            }
+        }
+    }
+
+    /// Returns the next token, together with its line number
+    fn next_token(&mut self) -> Result<Option<(Token, usize)>, String> {
+        self.skip_whitespace_and_comments()?;
+        let line: usize = self.line;
+
+        let ch: char = if let Some(ch) = self.chars.peek() {
+            *ch
+        } else {
+            return Ok(None);
+        };
+
+        let token: Token = if ch.is_alphabetic() || (ch == '_' && self.ident_follows()) {
+            let mut ident: String = String::new();
+            while let Some(ch) = self.chars.peek() {
+                if ch.is_alphanumeric() || *ch == '_' {
+                    ident.push(*ch);
+                    self.next_char();
+                } else {
+                    break;
+                }
+            }
+            Token::Ident(ident)
+        } else if ch == '"' {
+            self.next_char();
+            Token::Str(self.string_literal()?)
+        } else {
+            let column: usize = self.column;
+            self.next_char();
+            match (ch, self.chars.peek()) {
+                ('-', Some('>')) => {
+                    self.next_char();
+                    Token::Punct("->")
+                },
+                (':', Some(':')) => {
+                    self.next_char();
+                    Token::Punct("::")
+                },
+                _ => {
+                    let mut buf: [u8; 4] = [0; 4];
+                    let ch: &str = ch.encode_utf8(&mut buf);
+                    if let Some(punct) = PUNCTS.iter().find(|punct: &&&str| **punct == ch) {
+                        Token::Punct(punct)
+                    } else {
+                        return Err(format!("line {}, column {}: unexpected character `{}`",
+                                           line, column, ch));
+                    }
+                }
+            }
+        };
+        Ok(Some((token, line)))
+    }
+
+    /// Checks if the `_` under cursor starts an identifier like `_foo`, instead of being a
+    /// standalone `_`
+    fn ident_follows(&self) -> bool {
+        let mut chars = self.chars.clone();
+        chars.next();
+        matches!(chars.next(), Some(ch) if ch.is_alphanumeric() || ch == '_')
+    }
+
+    fn string_literal(&mut self) -> Result<String, String> {
+        let mut ret: String = String::new();
+        loop {
+            match self.next_char() {
+                Some('"') => return Ok(ret),
+                Some('\\') => match self.next_char() {
+                    Some('n') => ret.push('\n'),
+                    Some('t') => ret.push('\t'),
+                    Some('\\') => ret.push('\\'),
+                    Some('"') => ret.push('"'),
+                    _ => return Err(self.error("unknown escape sequence"))
+                },
+                Some(ch) => ret.push(ch),
+                None => return Err(self.error("unterminated string literal"))
+            }
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens.get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("line {}: {}", self.line(), message)
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident == keyword)
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<(), String> {
+        if self.is_punct(punct) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", punct)))
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<String, String> {
+        if let Some(Token::Ident(ident)) = self.peek() {
+            let ident: String = ident.clone();
+            self.pos += 1;
+            Ok(ident)
+        } else {
+            Err(self.error("expected identifier"))
+        }
+    }
+
+    fn parse_decls(&mut self) -> Result<Vec<Decl>, String> {
+        let mut ret: Vec<Decl> = vec![];
+        while self.peek().is_some() {
+            if self.is_keyword("type") {
+                ret.push(Decl::Type(self.parse_type_decl()?));
+            } else if self.is_keyword("fn") {
+                ret.push(Decl::Function(self.parse_function_decl()?));
+            } else if self.is_keyword("async") {
+                return Err(self.error("async functions are not supported, use `pr47-codegen`"));
+            } else {
+                return Err(self.error("expected `type` or `fn`"));
+            }
+        }
+        Ok(ret)
+    }
+
+    fn parse_type_decl(&mut self) -> Result<TypeDecl, String> {
+        let line: usize = self.line();
+        self.pos += 1;
+        let path: Path = self.parse_path()?;
+        let name: Option<String> = if self.is_punct("=") {
+            self.pos += 1;
+            if let Some(Token::Str(name)) = self.peek() {
+                let name: String = name.clone();
+                self.pos += 1;
+                Some(name)
+            } else {
+                return Err(self.error("expected type name string"));
+            }
+        } else {
+            None
+        };
+        self.expect_punct(";")?;
+        Ok(TypeDecl { line, path, name })
+    }
+
+    fn parse_function_decl(&mut self) -> Result<FunctionDecl, String> {
+        let line: usize = self.line();
+        self.pos += 1;
+        let path: Path = self.parse_path()?;
+
+        self.expect_punct("(")?;
+        let mut params: Vec<(String, Type)> = vec![];
+        while !self.is_punct(")") {
+            let name: String = if self.is_punct("_") {
+                self.pos += 1;
+                "_".into()
+            } else {
+                self.expect_ident()?
+            };
+            self.expect_punct(":")?;
+            params.push((name, self.parse_type()?));
+            if !self.is_punct(")") {
+                self.expect_punct(",")?;
+            }
+        }
+        self.pos += 1;
+
+        let ret: Option<Type> = if self.is_punct("->") {
+            self.pos += 1;
+            Some(self.parse_type()?)
+        } else {
+            None
+        };
+        self.expect_punct(";")?;
+        Ok(FunctionDecl { line, path, params, ret })
+    }
+
+    fn parse_path(&mut self) -> Result<Path, String> {
+        let mut segments: Vec<String> = vec![self.expect_ident()?];
+        while self.is_punct("::") {
+            self.pos += 1;
+            segments.push(self.expect_ident()?);
+        }
+        Ok(Path { segments })
+    }
+
+    fn parse_type(&mut self) -> Result<Type, String> {
+        if self.is_punct("&") {
+            self.pos += 1;
+            let mutable: bool = self.is_keyword("mut");
+            if mutable {
+                self.pos += 1;
+            }
+            let inner: Type = self.parse_type()?;
+            Ok(Type::Ref { mutable, inner: Box::new(inner) })
+        } else if self.is_punct("(") {
+            self.pos += 1;
+            let elems: Vec<Type> = self.parse_type_list(")")?;
+            Ok(Type::Tuple { elems })
+        } else {
+            let path: Path = self.parse_path()?;
+            let args: Vec<Type> = if self.is_punct("<") {
+                self.pos += 1;
+                self.parse_type_list(">")?
+            } else {
+                vec![]
+            };
+            Ok(Type::Path { path, args })
+        }
+    }
+
+    /// Parse comma separated types, until (and including) the closing punctuation `close`
+    fn parse_type_list(&mut self, close: &str) -> Result<Vec<Type>, String> {
+        let mut ret: Vec<Type> = vec![];
+        while !self.is_punct(close) {
+            ret.push(self.parse_type()?);
+            if !self.is_punct(close) {
+                self.expect_punct(",")?;
+            }
+        }
+        self.pos += 1;
+        Ok(ret)
+    }
+}
+
+/// Parse binding declarations from `source`
+pub fn parse(source: &str) -> Result<Vec<Decl>, String> {
+    let mut lexer: Lexer = Lexer::new(source);
+    let mut tokens: Vec<(Token, usize)> = vec![];
+    while let Some(token) = lexer.next_token()? {
+        tokens.push(token);
+    }
+
+    Parser { tokens, pos: 0 }.parse_decls()
+}
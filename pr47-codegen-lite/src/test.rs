@@ -0,0 +1,175 @@
+use crate::concrete::{Decl, Type};
+use crate::generate_bindings;
+use crate::parse::parse;
+
+const TEST_SOURCE: &str = include_str!("test.pr47b");
+
+#[test] fn test_parse() {
+    let decls: Vec<Decl> = parse(TEST_SOURCE).unwrap();
+    assert_eq!(decls.len(), 6);
+
+    if let Decl::Type(decl) = &decls[0] {
+        assert_eq!(decl.path.to_string(), "math::Vec2");
+        assert_eq!(decl.name.as_deref(), Some("std.math.Vec2"));
+        assert_eq!(decl.line, 2);
+    } else {
+        panic!()
+    }
+
+    if let Decl::Type(decl) = &decls[1] {
+        assert_eq!(decl.path.to_string(), "Foo");
+        assert!(decl.name.is_none());
+    } else {
+        panic!()
+    }
+
+    if let Decl::Function(decl) = &decls[4] {
+        assert_eq!(decl.path.last(), "inspect");
+        let params: Vec<String> = decl.params.iter()
+            .map(|(name, ty): &(String, Type)| format!("{}: {}", name, ty))
+            .collect();
+        assert_eq!(params, vec!["s: &str", "foo: &Foo", "bar: &mut Foo", "baz: Option<Foo>"]);
+        assert_eq!(decl.ret.as_ref().unwrap().to_string(), "(i64, Option<String>)");
+    } else {
+        panic!()
+    }
+
+    if let Decl::Function(decl) = &decls[5] {
+        assert!(decl.params.is_empty());
+        assert!(decl.ret.is_none());
+    } else {
+        panic!()
+    }
+}
+
+#[test] fn test_parse_failure() {
+    assert_eq!(parse("fn foo(a: i64)").err().unwrap(), "line 1: expected `;`");
+    assert_eq!(parse("type Foo = 42;").err().unwrap(),
+               "line 1, column 12: unexpected character `4`");
+    assert_eq!(parse("\n\nstruct Foo;").err().unwrap(), "line 3: expected `type` or `fn`");
+    assert!(parse("async fn foo();").is_err());
+    assert!(parse("type Foo = \"foo;").is_err());
+}
+
+#[test] fn test_generate() {
+    let code: String = generate_bindings(TEST_SOURCE, "pr47").unwrap();
+
+    assert!(code.contains(
+        "impl pr47::data::traits::StaticBase<math::Vec2> for pr47::data::traits::Void {"
+    ));
+    assert!(code.contains("\"std.math.Vec2\".to_string()"));
+    assert!(code.contains("\"Foo\".to_string()"));
+
+    assert!(code.contains("pub struct Pr47Binder_vec2_new();"));
+    assert!(code.contains("pub const PR47BINDER_VEC2_NEW: &Pr47Binder_vec2_new"));
+    assert!(code.contains("let ret = math::vec2_new(a0, a1);"));
+    assert!(code.contains("Err(pr47::ffi::FFIException::Checked(err_value))"));
+    assert!(code.contains(
+        "pr47::ffi::sync_fn::value_into_mut_ref::<Foo>(*args.get_unchecked(2))?"
+    ));
+    assert!(code.contains(
+        "std::mem::drop(g2);\n        std::mem::drop(g1);\n        std::mem::drop(g0);"
+    ));
+    assert!(code.contains("        noop();\n"));
+}
+
+#[test] fn test_generate_failure() {
+    assert_eq!(generate_bindings("fn foo(a: i32);", "pr47").err().unwrap(),
+               "line 1: `i32` is not a VM type, use `i64` or `f64` instead");
+    assert!(generate_bindings("fn foo(a: &i64);", "pr47").is_err());
+    assert!(generate_bindings("fn foo(a: Option<Option<i64>>);", "pr47").is_err());
+    assert!(generate_bindings("fn foo() -> &Foo;", "pr47").is_err());
+    assert!(generate_bindings("fn foo() -> Option<Result<i64, String>>;", "pr47").is_err());
+}
+
+/// Bindings generated from `test.pr47b` by `build.rs`, compiled against `pr47`
+mod compiled {
+    use std::ptr::null_mut;
+
+    use pr47::data::Value;
+    use pr47::data::wrapper::DynBase;
+    use pr47::ffi::FFIException;
+    use pr47::ffi::sync_fn::{FunctionBase, VMContext};
+
+    pub mod math {
+        pub struct Vec2 {
+            pub x: f64,
+            pub y: f64
+        }
+
+        pub fn vec2_new(x: f64, y: f64) -> Vec2 {
+            Vec2 { x, y }
+        }
+    }
+
+    pub struct Foo();
+
+    fn checked_div(a: i64, b: i64) -> Result<i64, String> {
+        if b == 0 {
+            Err("divide by zero".to_string())
+        } else {
+            Ok(a / b)
+        }
+    }
+
+    fn inspect(s: &str, _foo: &Foo, _bar: &mut Foo, baz: Option<Foo>) -> (i64, Option<String>) {
+        (s.len() as i64, baz.map(|_| s.to_string()))
+    }
+
+    fn noop() {}
+
+    include!(concat!(env!("OUT_DIR"), "/test_bindings.rs"));
+
+    struct TestContext {
+        managed: Vec<Value>
+    }
+
+    impl VMContext for TestContext {
+        fn add_heap_managed(&mut self, value: Value) {
+            self.managed.push(value);
+        }
+
+        fn mark(&mut self, _value: Value) {}
+
+        fn pin_objects(&mut self, _values: &[Value]) -> *mut bool {
+            null_mut()
+        }
+    }
+
+    impl Drop for TestContext {
+        fn drop(&mut self) {
+            for value /*: Value*/ in self.managed.drain(..) {
+                let dyn_base: Box<dyn DynBase> = unsafe { Box::from_raw(value.ptr) };
+                drop(dyn_base);
+            }
+        }
+    }
+
+    #[test] fn test_call_generated() {
+        let mut context: TestContext = TestContext { managed: vec![] };
+        let mut ret: Value = Value::new_null();
+
+        unsafe {
+            let args: [Value; 2] = [Value::new_int(7), Value::new_int(2)];
+            Pr47Binder_checked_div::call_rtlc(&mut context, &args, &[&mut ret as *mut Value])
+                .unwrap();
+            assert_eq!(ret.vt_data.inner.int_value, 3);
+
+            let args: [Value; 2] = [Value::new_int(7), Value::new_int(0)];
+            let result: Result<(), FFIException> =
+                Pr47Binder_checked_div::call_rtlc(&mut context, &args, &[&mut ret as *mut Value]);
+            if let Err(FFIException::Checked(err_value)) = result {
+                assert_eq!(*err_value.get_as_mut_ptr_norm::<String>(), "divide by zero");
+            } else {
+                panic!()
+            }
+
+            let args: [Value; 2] = [Value::new_float(1.0), Value::new_float(2.0)];
+            Pr47Binder_vec2_new::call_rtlc(&mut context, &args, &[&mut ret as *mut Value])
+                .unwrap();
+            let vec2: &math::Vec2 = &*ret.get_as_mut_ptr_norm::<math::Vec2>();
+            assert_eq!((vec2.x, vec2.y), (1.0, 2.0));
+        }
+        assert_eq!(context.managed.len(), 2);
+    }
+}
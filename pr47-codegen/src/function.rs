@@ -0,0 +1,746 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{FnArg, ItemFn, Pat, PatType, ReturnType, Type};
+
+use crate::types::{TypeInfo, analyze_type};
+
+/// How one parameter is loaded from a VM value
+enum ParamKind {
+    /// Value types, loaded from field `field` of the `ValueTypedData`
+    Value { field: Ident },
+    /// `&str`, borrowed from a VM `string`
+    Str,
+    /// `&T`, `DataOption::Share`
+    Ref { inner: Type },
+    /// `&mut T`, `DataOption::MutShare`
+    MutRef { inner: Type },
+    /// `T`, `DataOption::Move`
+    Move { ty: Type }
+}
+
+struct Param {
+    kind: ParamKind,
+    nullable: bool
+}
+
+/// How one return value is stored into a VM value
+enum RetKind {
+    /// Value types, created with `Value::ctor`
+    Value { ctor: Ident },
+    /// Other types, moved into a newly created VM value
+    Move
+}
+
+struct Ret {
+    kind: RetKind,
+    nullable: bool
+}
+
+#[derive(Clone, Copy)]
+enum CallMode {
+    Rtlc,
+    Unchecked,
+    Async
+}
+
+struct Loads {
+    stmts: TokenStream,
+    /// Guard variables and their types, in the order of acquiring
+    guards: Vec<(Ident, TokenStream)>,
+    call_args: Vec<Ident>
+}
+
+/// Code fragments shared by all generated methods
+struct Binding {
+    krate: TokenStream,
+    asserts: Vec<TokenStream>,
+
+    params: Vec<Param>,
+    param_tyck: Vec<TokenStream>,
+    param_options: Vec<TokenStream>,
+
+    rets: Vec<Ret>,
+    ret_tyck: Vec<TokenStream>,
+    ret_options: Vec<TokenStream>,
+
+    exception_tyck: Option<TokenStream>
+}
+
+pub fn generate_function_bind(item: &ItemFn, local: bool) -> Result<TokenStream, String> {
+    if !item.sig.generics.params.is_empty() {
+        return Err("generic functions are not supported by `pr47_function_bind`".into());
+    }
+    if item.sig.variadic.is_some() || item.sig.abi.is_some() {
+        return Err("foreign functions are not supported by `pr47_function_bind`".into());
+    }
+
+    let mut binding: Binding = Binding {
+        krate: if local { quote!(crate) } else { quote!(pr47) },
+        asserts: vec![],
+        params: vec![],
+        param_tyck: vec![],
+        param_options: vec![],
+        rets: vec![],
+        ret_tyck: vec![],
+        ret_options: vec![],
+        exception_tyck: None
+    };
+
+    for arg /*: &FnArg*/ in item.sig.inputs.iter() {
+        match arg {
+            FnArg::Receiver(_) => {
+                return Err("methods are not supported by `pr47_function_bind`".into());
+            },
+            FnArg::Typed(PatType { pat, ty, .. }) => {
+                if !matches!(**pat, Pat::Ident(_) | Pat::Wild(_)) {
+                    return Err("parameter patterns are not supported, use identifiers".into());
+                }
+                binding.add_param(&analyze_type(ty)?)?;
+            }
+        }
+    }
+
+    let ret_info: TypeInfo = match &item.sig.output {
+        ReturnType::Default => TypeInfo::Unit,
+        ReturnType::Type(_, ty) => analyze_type(ty)?
+    };
+    let fallible: bool = binding.add_rets(&ret_info)?;
+
+    let fn_name: &Ident = &item.sig.ident;
+    let vis = &item.vis;
+    let binder_name: Ident = format_ident!("Pr47Binder_{}", fn_name);
+    let binder_const_name: Ident = Ident::new(
+        &format!("PR47BINDER_{}", fn_name.to_string().to_uppercase()),
+        Span::call_site()
+    );
+
+    let krate: &TokenStream = &binding.krate;
+    let signature: TokenStream = binding.gen_signature();
+
+    if item.sig.asyncness.is_some() {
+        let ret_type: TokenStream = match &item.sig.output {
+            ReturnType::Default => quote!(()),
+            ReturnType::Type(_, ty) => quote!(#ty)
+        };
+        let call_rtlc: TokenStream = binding.gen_async_call(fn_name, fallible, &ret_type);
+
+        return Ok(quote! {
+            #item
+
+            #[allow(non_camel_case_types)]
+            #vis struct #binder_name();
+
+            impl #krate::ffi::async_fn::AsyncFunctionBase for #binder_name {
+                fn signature(
+                    tyck_info_pool: &mut #krate::data::tyck::TyckInfoPool
+                ) -> #krate::ffi::Signature {
+                    #signature
+                }
+
+                unsafe fn call_rtlc<
+                    LC: #krate::ffi::async_fn::LockedCtx,
+                    ACTX: #krate::ffi::async_fn::AsyncVMContext<Locked=LC>
+                >(
+                    _context: &ACTX,
+                    args: &[#krate::data::Value]
+                ) -> Result<#krate::ffi::async_fn::Promise<LC>, #krate::ffi::FFIException> {
+                    #call_rtlc
+                }
+            }
+
+            #[allow(dead_code)]
+            #vis const #binder_const_name: &'static #binder_name = &#binder_name();
+        });
+    }
+
+    let call_rtlc: TokenStream = binding.gen_call(fn_name, fallible, CallMode::Rtlc);
+    let call_unchecked: TokenStream = if cfg!(feature = "optimized-rtlc") {
+        binding.gen_call(fn_name, fallible, CallMode::Unchecked)
+    } else {
+        quote! { Self::call_rtlc(context, args, rets) }
+    };
+
+    Ok(quote! {
+        #item
+
+        #[allow(non_camel_case_types)]
+        #vis struct #binder_name();
+
+        impl #krate::ffi::sync_fn::FunctionBase for #binder_name {
+            fn signature(
+                tyck_info_pool: &mut #krate::data::tyck::TyckInfoPool
+            ) -> #krate::ffi::Signature {
+                #signature
+            }
+
+            #[allow(unused_variables)]
+            unsafe fn call_rtlc<CTX: #krate::ffi::sync_fn::VMContext>(
+                context: &mut CTX,
+                args: &[#krate::data::Value],
+                rets: &[*mut #krate::data::Value]
+            ) -> Result<(), #krate::ffi::FFIException> {
+                #call_rtlc
+            }
+
+            #[allow(unused_variables)]
+            unsafe fn call_unchecked<CTX: #krate::ffi::sync_fn::VMContext>(
+                context: &mut CTX,
+                args: &[#krate::data::Value],
+                rets: &[*mut #krate::data::Value]
+            ) -> Result<(), #krate::ffi::FFIException> {
+                #call_unchecked
+            }
+        }
+
+        #[allow(dead_code)]
+        #vis const #binder_const_name: &'static #binder_name = &#binder_name();
+    })
+}
+
+impl Binding {
+    fn add_param(&mut self, type_info: &TypeInfo) -> Result<(), String> {
+        let (kind, nullable): (ParamKind, bool) = match type_info {
+            TypeInfo::Option { ty, inner } => {
+                self.assert_type("assert_option", ty);
+                (self.param_kind(inner)?, true)
+            },
+            _ => (self.param_kind(type_info)?, false)
+        };
+
+        let data_option: TokenStream = match &kind {
+            ParamKind::Value { .. } => self.data_option("Copy"),
+            ParamKind::Str | ParamKind::Ref { .. } => self.data_option("Share"),
+            ParamKind::MutRef { .. } => self.data_option("MutShare"),
+            ParamKind::Move { .. } => self.data_option("Move")
+        };
+        let tyck: TokenStream = self.nullable_tyck(self.param_tyck(type_info), nullable);
+
+        self.params.push(Param { kind, nullable });
+        self.param_tyck.push(tyck);
+        self.param_options.push(data_option);
+        Ok(())
+    }
+
+    fn param_kind(&mut self, type_info: &TypeInfo) -> Result<ParamKind, String> {
+        if let Some(field) = value_field(type_info) {
+            return Ok(ParamKind::Value { field });
+        }
+
+        match type_info {
+            TypeInfo::Opaque { ty } => {
+                self.assert_type("assert_static_base", ty);
+                Ok(ParamKind::Move { ty: ty.clone() })
+            },
+            TypeInfo::Ref { ty, mutable, inner } => {
+                match (&**inner, *mutable) {
+                    (TypeInfo::Str, false) => Ok(ParamKind::Str),
+                    (TypeInfo::Opaque { ty: inner_ty }, false) => {
+                        self.assert_type("assert_const_ref", ty);
+                        self.assert_type("assert_static_base", inner_ty);
+                        Ok(ParamKind::Ref { inner: inner_ty.clone() })
+                    },
+                    (TypeInfo::Opaque { ty: inner_ty }, true) => {
+                        self.assert_type("assert_mut_ref", ty);
+                        self.assert_type("assert_static_base", inner_ty);
+                        Ok(ParamKind::MutRef { inner: inner_ty.clone() })
+                    },
+                    (TypeInfo::Str, true) =>
+                        Err("`&mut str` parameters are not supported".into()),
+                    (inner, _) if inner.is_value_type() =>
+                        Err("references to value types are not supported, pass by value".into()),
+                    _ => Err("unsupported reference parameter type".into())
+                }
+            },
+            TypeInfo::Option { .. } => Err("nested `Option` parameters are not supported".into()),
+            TypeInfo::Result { .. } => Err("`Result` parameters are not supported".into()),
+            TypeInfo::Str => Err("`str` parameters should be passed by reference".into()),
+            TypeInfo::Unit | TypeInfo::Tuple { .. } =>
+                Err("tuple parameters are not supported".into()),
+            _ => unreachable!()
+        }
+    }
+
+    fn param_tyck(&self, type_info: &TypeInfo) -> TokenStream {
+        match type_info {
+            TypeInfo::Option { inner, .. } | TypeInfo::Ref { inner, .. } => self.param_tyck(inner),
+            _ => self.tyck(type_info)
+        }
+    }
+
+    /// Add return values of type `type_info`, returns if the function may throw exceptions
+    fn add_rets(&mut self, type_info: &TypeInfo) -> Result<bool, String> {
+        match type_info {
+            TypeInfo::Result { ty, ok_type, err_type } => {
+                self.assert_type("assert_result", ty);
+                self.assert_type("assert_static_base", err_type);
+                let krate: &TokenStream = &self.krate;
+                self.exception_tyck = Some(quote! {
+                    <#krate::data::traits::Void
+                        as #krate::data::traits::StaticBase<#err_type>>::tyck_info(tyck_info_pool)
+                });
+                if let TypeInfo::Result { .. } = &**ok_type {
+                    return Err("nested `Result` return types are not supported".into());
+                }
+                self.add_rets(ok_type)?;
+                Ok(true)
+            },
+            TypeInfo::Unit => Ok(false),
+            TypeInfo::Tuple { elems } => {
+                for elem /*: &TypeInfo*/ in elems {
+                    self.add_ret(elem)?;
+                }
+                Ok(false)
+            },
+            _ => {
+                self.add_ret(type_info)?;
+                Ok(false)
+            }
+        }
+    }
+
+    fn add_ret(&mut self, type_info: &TypeInfo) -> Result<(), String> {
+        let (kind, nullable): (RetKind, bool) = match type_info {
+            TypeInfo::Option { ty, inner } => {
+                self.assert_type("assert_option", ty);
+                (self.ret_kind(inner)?, true)
+            },
+            _ => (self.ret_kind(type_info)?, false)
+        };
+
+        let data_option: TokenStream = match &kind {
+            RetKind::Value { .. } => self.data_option("Copy"),
+            RetKind::Move => self.data_option("Move")
+        };
+        let tyck: TokenStream = match type_info {
+            TypeInfo::Option { inner, .. } => self.nullable_tyck(self.tyck(inner), true),
+            _ => self.tyck(type_info)
+        };
+
+        self.rets.push(Ret { kind, nullable });
+        self.ret_tyck.push(tyck);
+        self.ret_options.push(data_option);
+        Ok(())
+    }
+
+    fn ret_kind(&mut self, type_info: &TypeInfo) -> Result<RetKind, String> {
+        let ctor: &str = match type_info {
+            TypeInfo::Bool => "new_bool",
+            TypeInfo::Char => "new_char",
+            TypeInfo::Float => "new_float",
+            TypeInfo::Int => "new_int",
+            TypeInfo::Opaque { ty } => {
+                self.assert_type("assert_static_base", ty);
+                return Ok(RetKind::Move);
+            },
+            TypeInfo::Ref { .. } => return Err("reference return types are not supported".into()),
+            TypeInfo::Option { .. } =>
+                return Err("nested `Option` return types are not supported".into()),
+            TypeInfo::Result { .. } =>
+                return Err("`Result` is only supported as the outermost return type".into()),
+            TypeInfo::Tuple { .. } | TypeInfo::Unit =>
+                return Err("tuples are only supported as the outermost return type".into()),
+            TypeInfo::Str => return Err("`str` return type is not supported".into())
+        };
+        Ok(RetKind::Value { ctor: Ident::new(ctor, Span::call_site()) })
+    }
+
+    fn tyck(&self, type_info: &TypeInfo) -> TokenStream {
+        let krate: &TokenStream = &self.krate;
+        match type_info {
+            TypeInfo::Bool => quote!(tyck_info_pool.get_bool_type()),
+            TypeInfo::Char => quote!(tyck_info_pool.get_char_type()),
+            TypeInfo::Float => quote!(tyck_info_pool.get_float_type()),
+            TypeInfo::Int => quote!(tyck_info_pool.get_int_type()),
+            TypeInfo::Str => quote!(tyck_info_pool.get_string_type()),
+            TypeInfo::Opaque { ty } => quote! {
+                <#krate::data::traits::Void
+                    as #krate::data::traits::StaticBase<#ty>>::tyck_info(tyck_info_pool)
+            },
+            _ => unreachable!()
+        }
+    }
+
+    fn nullable_tyck(&self, tyck: TokenStream, nullable: bool) -> TokenStream {
+        if nullable {
+            let krate: &TokenStream = &self.krate;
+            quote! {{
+                let base: std::ptr::NonNull<#krate::data::tyck::TyckInfo> = #tyck;
+                tyck_info_pool.create_nullable_type(base)
+            }}
+        } else {
+            tyck
+        }
+    }
+
+    fn data_option(&self, option: &str) -> TokenStream {
+        let krate: &TokenStream = &self.krate;
+        let option: Ident = Ident::new(option, Span::call_site());
+        quote!(#krate::ffi::DataOption::#option)
+    }
+
+    fn assert_type(&mut self, assertion: &str, ty: &Type) {
+        let krate: &TokenStream = &self.krate;
+        let assertion: Ident = Ident::new(assertion, Span::call_site());
+        self.asserts.push(quote!(#krate::util::type_assert::#assertion::<#ty>();));
+    }
+
+    fn gen_signature(&self) -> TokenStream {
+        let krate: &TokenStream = &self.krate;
+        let asserts: &[TokenStream] = &self.asserts;
+        let param_count: usize = self.params.len();
+        let param_tyck: &[TokenStream] = &self.param_tyck;
+        let param_options: &[TokenStream] = &self.param_options;
+        let ret_count: usize = self.rets.len();
+        let ret_tyck: &[TokenStream] = &self.ret_tyck;
+        let ret_options: &[TokenStream] = &self.ret_options;
+        let exception_tyck: Vec<&TokenStream> = self.exception_tyck.iter().collect();
+        let exception_count: usize = exception_tyck.len();
+
+        quote! {
+            #(#asserts)*
+
+            let params: [std::ptr::NonNull<#krate::data::tyck::TyckInfo>; #param_count] =
+                [#(#param_tyck),*];
+            let rets: [std::ptr::NonNull<#krate::data::tyck::TyckInfo>; #ret_count] =
+                [#(#ret_tyck),*];
+            let exceptions: [std::ptr::NonNull<#krate::data::tyck::TyckInfo>; #exception_count] =
+                [#(#exception_tyck),*];
+
+            #krate::ffi::Signature {
+                func_type: tyck_info_pool.create_function_type(&params, &rets, &exceptions),
+                param_options: vec![#(#param_options),*].into_boxed_slice(),
+                ret_option: vec![#(#ret_options),*].into_boxed_slice(),
+                variadic: None,
+                ret_arity: #krate::ffi::RetArity::Fixed
+            }
+        }
+    }
+
+    /// Generate statements loading arguments from `args` into `a0`, `a1`, ..., acquiring
+    /// ownership guards `g0`, `g1`, ... for borrowed arguments if necessary
+    fn gen_loads(&self, mode: CallMode) -> Loads {
+        let krate: &TokenStream = &self.krate;
+        let (ffi_mod, share_guard, reset_guard): (TokenStream, TokenStream, TokenStream) =
+            match mode {
+                CallMode::Async => (
+                    quote!(#krate::ffi::async_fn),
+                    quote!(#krate::ffi::async_fn::AsyncShareGuard),
+                    quote!(#krate::ffi::async_fn::AsyncResetGuard)
+                ),
+                _ => (
+                    quote!(#krate::ffi::sync_fn),
+                    quote!(Option<#krate::ffi::sync_fn::OwnershipGuard>),
+                    quote!(#krate::ffi::sync_fn::OwnershipGuard)
+                )
+            };
+
+        let mut move_checks: Vec<TokenStream> = vec![];
+        let mut loads: Vec<TokenStream> = vec![];
+        let mut move_loads: Vec<TokenStream> = vec![];
+        let mut guards: Vec<(Ident, TokenStream)> = vec![];
+        let mut call_args: Vec<Ident> = vec![];
+
+        for (idx, param) /*: (usize, &Param)*/ in self.params.iter().enumerate() {
+            let arg: Ident = format_ident!("a{}", idx);
+            let guard: Ident = format_ident!("g{}", idx);
+            let value: TokenStream = quote!((*args.get_unchecked(#idx)));
+            call_args.push(arg.clone());
+
+            let checked: bool = !matches!(mode, CallMode::Unchecked);
+            let (load, guard_ty): (TokenStream, Option<_>) = match (&param.kind, checked) {
+                (ParamKind::Value { field }, _) => (quote!(#value.vt_data.inner.#field), None),
+                (ParamKind::Str, true) => (quote! {{
+                    let (r, g) = #ffi_mod::value_into_ref::<String>(#value)?;
+                    (r.as_str(), g)
+                }}, Some(share_guard.clone())),
+                (ParamKind::Str, false) =>
+                    (quote!((*(#value.get_as_mut_ptr_norm::<String>() as *const String)).as_str()),
+                     None),
+                (ParamKind::Ref { inner }, true) =>
+                    (quote!(#ffi_mod::value_into_ref::<#inner>(#value)?),
+                     Some(share_guard.clone())),
+                (ParamKind::Ref { inner }, false) =>
+                    (quote!(&*(#value.get_as_mut_ptr_norm::<#inner>() as *const #inner)), None),
+                (ParamKind::MutRef { inner }, true) =>
+                    (quote!(#ffi_mod::value_into_mut_ref::<#inner>(#value)?),
+                     Some(reset_guard.clone())),
+                (ParamKind::MutRef { inner }, false) =>
+                    (quote!(&mut *#value.get_as_mut_ptr_norm::<#inner>()), None),
+                (ParamKind::Move { ty }, true) => {
+                    let check: TokenStream =
+                        quote!(#ffi_mod::value_move_out_check_norm_noalias(#value)?;);
+                    move_checks.push(if param.nullable {
+                        quote!(if !#value.is_null() { #check })
+                    } else {
+                        check
+                    });
+                    let load: TokenStream =
+                        quote!(#ffi_mod::value_move_out_norm_noalias::<#ty>(#value));
+                    move_loads.push(nullable_load(&arg, &value, load, param.nullable));
+                    continue;
+                },
+                (ParamKind::Move { ty }, false) => {
+                    let load: TokenStream = quote!(#ffi_mod::value_move_out_norm::<#ty>(#value));
+                    move_loads.push(nullable_load(&arg, &value, load, param.nullable));
+                    continue;
+                }
+            };
+
+            if let Some(guard_ty) = guard_ty {
+                loads.push(if param.nullable {
+                    quote! {
+                        let (#arg, #guard) = if #value.is_null() {
+                            (None, None)
+                        } else {
+                            let (r, g) = #load;
+                            (Some(r), Some(g))
+                        };
+                    }
+                } else {
+                    quote!(let (#arg, #guard) = #load;)
+                });
+                let guard_ty: TokenStream = if param.nullable {
+                    quote!(Option<#guard_ty>)
+                } else {
+                    guard_ty
+                };
+                guards.push((guard, guard_ty));
+            } else {
+                loads.push(nullable_load(&arg, &value, load, param.nullable));
+            }
+        }
+
+        Loads {
+            stmts: quote! {
+                #(#move_checks)*
+                #(#loads)*
+                #(#move_loads)*
+            },
+            guards,
+            call_args
+        }
+    }
+
+    /// Generate the pattern destructing the (successful) return value, and statements storing
+    /// destructed values into `slots`. Heap objects are handed over to `context`.
+    fn gen_stores(&self, context: &Ident, slots: &Ident) -> (TokenStream, Vec<TokenStream>) {
+        let krate: &TokenStream = &self.krate;
+        let ret_idents: Vec<Ident> = (0..self.rets.len())
+            .map(|idx: usize| format_ident!("r{}", idx))
+            .collect();
+        let ret_pattern: TokenStream = match ret_idents.len() {
+            0 => quote!(()),
+            1 => quote!(#(#ret_idents)*),
+            _ => quote!((#(#ret_idents),*))
+        };
+
+        let stores: Vec<TokenStream> = self.rets.iter().enumerate()
+            .map(|(idx, ret): (usize, &Ret)| {
+                let ret_ident: &Ident = &ret_idents[idx];
+                let store: TokenStream = match &ret.kind {
+                    RetKind::Value { ctor } => quote!(#krate::data::Value::#ctor(#ret_ident)),
+                    RetKind::Move => quote! {{
+                        let value: #krate::data::Value =
+                            #krate::data::Value::new_owned(#ret_ident);
+                        #context.add_heap_managed(value);
+                        value
+                    }}
+                };
+                let store: TokenStream = if ret.nullable {
+                    quote! {
+                        match #ret_ident {
+                            Some(#ret_ident) => #store,
+                            None => #krate::data::Value::new_null()
+                        }
+                    }
+                } else {
+                    store
+                };
+                quote!(**#slots.get_unchecked(#idx) = #store;)
+            })
+            .collect();
+
+        (ret_pattern, stores)
+    }
+
+    /// Generate the statement throwing error `e` as a checked exception
+    fn gen_throw(&self, context: &Ident) -> TokenStream {
+        let krate: &TokenStream = &self.krate;
+        quote! {
+            let err_value: #krate::data::Value = #krate::data::Value::new_owned(e);
+            #context.add_heap_managed(err_value);
+            Err(#krate::ffi::FFIException::Checked(err_value))
+        }
+    }
+
+    /// Generate the body of `FunctionBase::call_rtlc` or `FunctionBase::call_unchecked`
+    fn gen_call(&self, fn_name: &Ident, fallible: bool, mode: CallMode) -> TokenStream {
+        let param_count: usize = self.params.len();
+        let ret_count: usize = self.rets.len();
+        let context: Ident = Ident::new("context", Span::call_site());
+        let rets: Ident = Ident::new("rets", Span::call_site());
+
+        let Loads { stmts: loads, guards, call_args } = self.gen_loads(mode);
+        let guards: Vec<&Ident> = guards.iter().rev().map(|(guard, _)| guard).collect();
+        let (ret_pattern, stores): (TokenStream, Vec<TokenStream>) =
+            self.gen_stores(&context, &rets);
+
+        let call: TokenStream = quote!(#fn_name(#(#call_args),*));
+        let handle_ret: TokenStream = if fallible {
+            let throw: TokenStream = self.gen_throw(&context);
+            quote! {
+                match ret {
+                    Ok(#ret_pattern) => {
+                        #(#stores)*
+                        Ok(())
+                    },
+                    Err(e) => {
+                        #throw
+                    }
+                }
+            }
+        } else if ret_count == 0 {
+            quote!(Ok(()))
+        } else {
+            quote! {
+                let #ret_pattern = ret;
+                #(#stores)*
+                Ok(())
+            }
+        };
+        let call: TokenStream = if fallible || ret_count != 0 {
+            quote!(let ret = #call;)
+        } else {
+            quote!(#call;)
+        };
+
+        quote! {
+            debug_assert_eq!(args.len(), #param_count);
+            debug_assert_eq!(rets.len(), #ret_count);
+
+            #loads
+
+            #call
+            #(std::mem::drop(#guards);)*
+
+            #handle_ret
+        }
+    }
+
+    /// Generate the body of `AsyncFunctionBase::call_rtlc`. Ownership guards are carried to
+    /// `AsyncReturnType::resolve` and released there, where the VM run permit is held.
+    fn gen_async_call(&self, fn_name: &Ident, fallible: bool, ret_type: &TokenStream)
+        -> TokenStream
+    {
+        let krate: &TokenStream = &self.krate;
+        let async_fn: TokenStream = quote!(#krate::ffi::async_fn);
+        let param_count: usize = self.params.len();
+        let ret_count: usize = self.rets.len();
+        let context: Ident = Ident::new("locked_ctx", Span::call_site());
+        let dests: Ident = Ident::new("dests", Span::call_site());
+
+        let Loads { stmts: loads, guards, call_args } = self.gen_loads(CallMode::Async);
+        let (guard_idents, guard_types): (Vec<Ident>, Vec<TokenStream>) =
+            guards.into_iter().unzip();
+        let (guards_field, guards_init, release_guards): (TokenStream, TokenStream, TokenStream) =
+            if guard_idents.is_empty() {
+                (quote!(), quote!(), quote!(let AsyncRet { result } = *self;))
+            } else {
+                (
+                    quote!(guards: (#(#guard_types,)*),),
+                    quote!(guards: (#(#guard_idents,)*),),
+                    quote! {
+                        let AsyncRet { guards, result } = *self;
+                        std::mem::drop(guards);
+                    }
+                )
+            };
+        let (ret_pattern, stores): (TokenStream, Vec<TokenStream>) =
+            self.gen_stores(&context, &dests);
+        let stores: TokenStream = if stores.is_empty() {
+            quote!()
+        } else {
+            quote!(unsafe { #(#stores)* })
+        };
+
+        let (is_err, handle_ret): (TokenStream, TokenStream) = if fallible {
+            let throw: TokenStream = self.gen_throw(&context);
+            (quote!(self.result.is_err()), quote! {
+                match result {
+                    Ok(#ret_pattern) => {
+                        #stores
+                        Ok(#ret_count)
+                    },
+                    Err(e) => {
+                        #throw
+                    }
+                }
+            })
+        } else {
+            (quote!(false), quote! {
+                let #ret_pattern = result;
+                #stores
+                Ok(#ret_count)
+            })
+        };
+
+        quote! {
+            struct AsyncRet {
+                #guards_field
+                result: #ret_type
+            }
+
+            impl<LC: #async_fn::LockedCtx> #async_fn::AsyncReturnType<LC> for AsyncRet {
+                fn is_err(&self) -> bool {
+                    #is_err
+                }
+
+                #[allow(unused_variables)]
+                fn resolve(
+                    self: Box<Self>,
+                    locked_ctx: &mut LC,
+                    dests: &[*mut #krate::data::Value]
+                ) -> Result<usize, #krate::data::exception::ExceptionInner> {
+                    debug_assert_eq!(dests.len(), #ret_count);
+
+                    #release_guards
+
+                    #handle_ret
+                }
+            }
+
+            debug_assert_eq!(args.len(), #param_count);
+
+            #loads
+
+            let fut = async move {
+                let result: #ret_type = #fn_name(#(#call_args),*).await;
+                Box::new(AsyncRet { #guards_init result })
+                    as Box<dyn #async_fn::AsyncReturnType<LC>>
+            };
+
+            Ok(#async_fn::Promise(Box::pin(fut)))
+        }
+    }
+}
+
+fn value_field(type_info: &TypeInfo) -> Option<Ident> {
+    let field: &str = match type_info {
+        TypeInfo::Bool => "bool_value",
+        TypeInfo::Char => "char_value",
+        TypeInfo::Float => "float_value",
+        TypeInfo::Int => "int_value",
+        _ => return None
+    };
+    Some(Ident::new(field, Span::call_site()))
+}
+
+fn nullable_load(arg: &Ident, value: &TokenStream, load: TokenStream, nullable: bool)
+    -> TokenStream
+{
+    if nullable {
+        quote!(let #arg = if #value.is_null() { None } else { Some(#load) };)
+    } else {
+        quote!(let #arg = #load;)
+    }
+}
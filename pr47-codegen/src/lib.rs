@@ -1,17 +1,38 @@
 mod attrs;
+mod function;
+mod static_base;
 mod types;
 
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, ItemFn, FnArg};
+use syn::{parse_macro_input, DeriveInput, ItemFn};
 
 use crate::attrs::parse_function_bind_attrs;
+use crate::function::generate_function_bind;
+use crate::static_base::generate_static_base;
 
+/// Generate a `FunctionBase` implementation for a Rust function.
+///
+/// For function `foo`, a binder type `Pr47Binder_foo` and a constant `PR47BINDER_FOO` referring
+/// to a binder instance are generated alongside the function. Parameters and return values are
+/// mapped as follows:
+///
+/// - `i64`, `f64`, `char` and `bool` are VM value types, passed with `DataOption::Copy`
+/// - `&T` and `&str` are shared with `DataOption::Share`, `&mut T` with `DataOption::MutShare`
+/// - Other types are moved between the VM and Rust with `DataOption::Move`
+/// - `Option<T>` is nullable, with `None` corresponding to `null`
+/// - `Result<T, E>` return values throw `E` as checked exceptions
+/// - Tuple return values are returned as multiple values
+///
+/// For `async fn`s, an `AsyncFunctionBase` implementation is generated instead. Ownership guards
+/// of borrowed arguments are held until the returned promise gets resolved.
+///
+/// Use `#[pr47_function_bind(local)]` inside the `pr47` crate itself.
 #[proc_macro_attribute]
 pub fn pr47_function_bind(attr: TokenStream, item: TokenStream) -> TokenStream {
     let item: ItemFn = parse_macro_input!(item as ItemFn);
 
-    let _attrs: Vec<String> = match parse_function_bind_attrs(attr) {
+    let attrs: Vec<String> = match parse_function_bind_attrs(attr) {
         Ok(attrs) => attrs,
         Err(e) => {
             return (quote!{
@@ -20,10 +41,45 @@ pub fn pr47_function_bind(attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     };
 
-    let _args: Vec<&FnArg> = item.sig.inputs.iter().collect::<_>();
-    let ret = quote!{
-        #item
-    };
+    let mut local: bool = false;
+    for attr /*: String*/ in attrs {
+        match attr.as_str() {
+            "local" => local = true,
+            _ => {
+                let e: String = format!("unknown function binder parameter: {}", attr);
+                return (quote!{
+                    compile_error!( #e ) ;
+                }).into()
+            }
+        }
+    }
+
+    match generate_function_bind(&item, local) {
+        Ok(ret) => ret.into(),
+        Err(e) => (quote!{
+            #item
+            compile_error!( #e ) ;
+        }).into()
+    }
+}
+
+/// Derive `StaticBase` for a Rust struct, so that it can be used as a VM type.
+///
+/// The type name defaults to the name of the struct, and can be changed with
+/// `#[pr47(name = "...")]`. Fields mentioning `Value` in their types, like `Value`,
+/// `Option<Value>` or `Vec<Value>`, are reported to the garbage collector as children. Use
+/// `#[pr47(skip)]` on a field to opt out, or `#[pr47(trace)]` to trace a field whose type
+/// implements `ChildValues` but does not mention `Value`.
+///
+/// Use `#[pr47(local)]` inside the `pr47` crate itself.
+#[proc_macro_derive(StaticBase, attributes(pr47))]
+pub fn derive_static_base(item: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(item as DeriveInput);
 
-    ret.into()
+    match generate_static_base(&input) {
+        Ok(ret) => ret.into(),
+        Err(e) => (quote!{
+            compile_error!( #e ) ;
+        }).into()
+    }
 }
@@ -0,0 +1,154 @@
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields, Lit, Meta, NestedMeta, Type};
+
+/// Options given with `#[pr47(...)]` on the type itself
+struct TypeAttrs {
+    local: bool,
+    name: Option<String>
+}
+
+/// How a field gets reported to the garbage collector
+#[derive(Clone, Copy)]
+enum Trace {
+    Auto,
+    Always,
+    Never
+}
+
+fn nested_metas(attrs: &[Attribute]) -> Result<Vec<NestedMeta>, String> {
+    let mut ret: Vec<NestedMeta> = vec![];
+    for attr /*: &Attribute*/ in attrs {
+        if !attr.path.is_ident("pr47") {
+            continue;
+        }
+
+        match attr.parse_meta() {
+            Ok(Meta::List(list)) => ret.extend(list.nested),
+            Ok(_) => return Err("expected `#[pr47(...)]`".into()),
+            Err(e) => return Err(e.to_string())
+        }
+    }
+    Ok(ret)
+}
+
+fn parse_type_attrs(attrs: &[Attribute]) -> Result<TypeAttrs, String> {
+    let mut ret: TypeAttrs = TypeAttrs { local: false, name: None };
+    for nested /*: NestedMeta*/ in nested_metas(attrs)? {
+        match nested {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("local") => ret.local = true,
+            NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("name") => {
+                if let Lit::Str(name) = name_value.lit {
+                    ret.name = Some(name.value());
+                } else {
+                    return Err("`name` should be a string literal".into());
+                }
+            },
+            _ => return Err("unknown type parameter, expected `local` or `name = \"...\"`".into())
+        }
+    }
+    Ok(ret)
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> Result<Trace, String> {
+    let mut ret: Trace = Trace::Auto;
+    for nested /*: NestedMeta*/ in nested_metas(attrs)? {
+        match nested {
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => ret = Trace::Never,
+            NestedMeta::Meta(Meta::Path(path)) if path.is_ident("trace") => ret = Trace::Always,
+            _ => return Err("unknown field parameter, expected `skip` or `trace`".into())
+        }
+    }
+    Ok(ret)
+}
+
+/// Checks if `Value` appears anywhere in the type, like `Value`, `Vec<Value>` or
+/// `HashMap<String, Value>`
+fn mentions_value(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|token: TokenTree| match token {
+        TokenTree::Ident(ident) => ident == "Value",
+        TokenTree::Group(group) => mentions_value(group.stream()),
+        _ => false
+    })
+}
+
+fn is_traced(trace: Trace, ty: &Type) -> bool {
+    match trace {
+        Trace::Auto => mentions_value(quote!{ #ty }),
+        Trace::Always => true,
+        Trace::Never => false
+    }
+}
+
+pub(crate) fn generate_static_base(input: &DeriveInput) -> Result<TokenStream, String> {
+    let type_attrs: TypeAttrs = parse_type_attrs(&input.attrs)?;
+    let krate: TokenStream = if type_attrs.local { quote!{ crate } } else { quote!{ pr47 } };
+
+    let fields: &Fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err("`StaticBase` can only be derived for structs".into())
+    };
+
+    let mut traced: Vec<TokenStream> = vec![];
+    for (idx, field) /*: (usize, &Field)*/ in fields.iter().enumerate() {
+        if !is_traced(parse_field_attrs(&field.attrs)?, &field.ty) {
+            continue;
+        }
+
+        if let Some(ident) = &field.ident {
+            traced.push(quote!{ #ident });
+        } else {
+            let idx: syn::Index = syn::Index::from(idx);
+            traced.push(quote!{ #idx });
+        }
+    }
+
+    let ident: &Ident = &input.ident;
+    let type_name: String = type_attrs.name.unwrap_or_else(|| ident.to_string());
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut where_preds: Vec<TokenStream> = vec![quote!{ #ident #ty_generics: 'static }];
+    if let Some(where_clause) = where_clause {
+        where_preds.extend(where_clause.predicates.iter().map(|pred| quote!{ #pred }));
+    }
+
+    let children: TokenStream = if traced.is_empty() {
+        quote!{}
+    } else {
+        quote!{
+            #[inline]
+            #[allow(clippy::not_unsafe_ptr_arg_deref)]
+            fn children(
+                vself: *const #ident #ty_generics
+            ) -> #krate::data::traits::ChildrenType {
+                unsafe {
+                    let vself: &'static #ident #ty_generics = &*vself;
+                    let iter = Box::new(
+                        std::iter::empty()
+                        #(.chain(#krate::data::traits::ChildValues::child_values(&vself.#traced)))*
+                    );
+                    Some(iter)
+                }
+            }
+        }
+    };
+
+    Ok(quote!{
+        impl #impl_generics #krate::data::traits::StaticBase<#ident #ty_generics>
+            for #krate::data::traits::Void
+            where #(#where_preds),*
+        {
+            fn type_name() -> String {
+                #type_name.to_string()
+            }
+
+            fn tyck_info(
+                tyck_info_pool: &mut #krate::data::tyck::TyckInfoPool
+            ) -> std::ptr::NonNull<#krate::data::tyck::TyckInfo> {
+                tyck_info_pool.create_plain_type(std::any::TypeId::of::<#ident #ty_generics>())
+            }
+
+            #children
+        }
+    })
+}
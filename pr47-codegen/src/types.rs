@@ -1,19 +1,89 @@
-use proc_macro::TokenStream;
+use syn::{GenericArgument, PathArguments, Type, TypePath, TypeReference, TypeTuple};
 
-#[allow(unused)]
 pub enum TypeInfo {
-    AsyncCombustor,
     Bool,
     Char,
-    Combustor,
     Float,
     Int,
-    Opaque { token_stream: TokenStream },
-    Option { inner: Box<TypeInfo> },
-    Ref { mutable: bool, inner: Box<TypeInfo> },
-    Result { ok_type: Box<TypeInfo>, err_type: Box<TypeInfo> },
-    VMObject { inner: Box<TypeInfo> },
-    VMObjectRef { mutable: bool, inner: Box<TypeInfo> },
-    VMVec { inner: Box<TypeInfo> },
-    VMVecRef { mutable: bool, inner: Box<TypeInfo> }
+    Str,
+    Unit,
+    Opaque { ty: Type },
+    Option { ty: Type, inner: Box<TypeInfo> },
+    Ref { ty: Type, mutable: bool, inner: Box<TypeInfo> },
+    Result { ty: Type, ok_type: Box<TypeInfo>, err_type: Box<Type> },
+    Tuple { elems: Vec<TypeInfo> }
+}
+
+impl TypeInfo {
+    pub fn is_value_type(&self) -> bool {
+        matches!(self, TypeInfo::Bool | TypeInfo::Char | TypeInfo::Float | TypeInfo::Int)
+    }
+}
+
+pub fn analyze_type(ty: &Type) -> Result<TypeInfo, String> {
+    match ty {
+        Type::Group(group) => analyze_type(&group.elem),
+        Type::Paren(paren) => analyze_type(&paren.elem),
+        Type::Path(path) => analyze_path_type(ty, path),
+        Type::Reference(TypeReference { mutability, elem, .. }) => Ok(TypeInfo::Ref {
+            ty: ty.clone(),
+            mutable: mutability.is_some(),
+            inner: Box::new(analyze_type(elem)?)
+        }),
+        Type::Tuple(TypeTuple { elems, .. }) => {
+            if elems.is_empty() {
+                Ok(TypeInfo::Unit)
+            } else {
+                let elems: Vec<TypeInfo> = elems.iter()
+                    .map(analyze_type)
+                    .collect::<Result<Vec<_>, String>>()?;
+                Ok(TypeInfo::Tuple { elems })
+            }
+        },
+        _ => Err("unsupported type, only paths, references and tuples are supported".into())
+    }
+}
+
+fn analyze_path_type(ty: &Type, path: &TypePath) -> Result<TypeInfo, String> {
+    if path.qself.is_some() {
+        return Ok(TypeInfo::Opaque { ty: ty.clone() });
+    }
+
+    let last_segment = if let Some(last_segment) = path.path.segments.last() {
+        last_segment
+    } else {
+        return Ok(TypeInfo::Opaque { ty: ty.clone() });
+    };
+
+    let generic_args: Vec<&Type> = match &last_segment.arguments {
+        PathArguments::None => vec![],
+        PathArguments::AngleBracketed(args) => args.args.iter()
+            .filter_map(|arg: &GenericArgument| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None
+            })
+            .collect(),
+        PathArguments::Parenthesized(_) => return Ok(TypeInfo::Opaque { ty: ty.clone() })
+    };
+
+    match (last_segment.ident.to_string().as_str(), generic_args.as_slice()) {
+        ("bool", []) => Ok(TypeInfo::Bool),
+        ("char", []) => Ok(TypeInfo::Char),
+        ("f64", []) => Ok(TypeInfo::Float),
+        ("i64", []) => Ok(TypeInfo::Int),
+        ("str", []) => Ok(TypeInfo::Str),
+        ("Option", [inner]) => Ok(TypeInfo::Option {
+            ty: ty.clone(),
+            inner: Box::new(analyze_type(inner)?)
+        }),
+        ("Result", [ok_type, err_type]) => Ok(TypeInfo::Result {
+            ty: ty.clone(),
+            ok_type: Box::new(analyze_type(ok_type)?),
+            err_type: Box::new((*err_type).clone())
+        }),
+        ("i8" | "i16" | "i32" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+         | "f32", []) =>
+            Err(format!("`{}` is not a VM type, use `i64` or `f64` instead", last_segment.ident)),
+        _ => Ok(TypeInfo::Opaque { ty: ty.clone() })
+    }
 }
@@ -1,10 +1,10 @@
 use std::env;
 
-use xjbutil::async_utils::block_on_future;
 use xjbutil::std_ext::ExpectSilentExt;
 use xjbutil::unchecked::UncheckedSendSync;
 
 use pr47::data::Value;
+use pr47::util::async_utils::block_on_future;
 use pr47::vm::al31fm2::alloc::default_alloc::DefaultAlloc;
 use pr47::vm::al31fm2::compiled::CompiledProgram;
 use pr47::vm::al31fm2::exception::Exception;
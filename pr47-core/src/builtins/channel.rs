@@ -0,0 +1,373 @@
+//! # `channel.rs`: channels for message passing between VM coroutines
+//!
+//! Channel operations may happen both on VM coroutines holding the `Serializer` run permit and on
+//! futures awaited without the permit, so the channel state is protected by its own lock. Values
+//! travelling through the channel always stay in the channel state until they get delivered to
+//! some VM register, so that they are always traced by the garbage collector.
+
+use std::any::TypeId;
+use std::collections::{HashMap, VecDeque};
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::task::{Context, Poll, Waker};
+
+use xjbutil::mem::move_to_heap;
+use xjbutil::void::Void;
+
+use crate::data::Value;
+use crate::data::generic::GenericTypeVT;
+use crate::data::traits::{ChildrenType, StaticBase};
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+use crate::data::wrapper::Wrapper;
+
+/// Result of trying to send a value without waiting
+pub enum TrySendResult {
+    /// The value has been put into channel buffer
+    Sent,
+    /// The channel buffer is full, the value is held by the channel until some receiver takes it
+    Blocked(u64),
+    /// The channel has been closed, the value is discarded
+    Closed
+}
+
+pub struct ChannelState {
+    /// Buffered values
+    queue: VecDeque<Value>,
+    /// Buffer capacity. `None` for unbounded channels, `Some(0)` for rendezvous channels
+    capacity: Option<usize>,
+    closed: bool,
+    /// Values of senders waiting for buffer space, tagged with tickets
+    blocked_sends: VecDeque<(u64, Value)>,
+    /// Values already taken by receivers but not yet delivered to VM registers
+    claimed: HashMap<u64, Value>,
+    next_ticket: u64,
+    /// Wakers of pending senders and receivers, one for each, keyed by their tickets
+    wakers: HashMap<u64, Waker>
+}
+
+// `Value`s in the channel are only dereferenced by VM code holding the `Serializer` run permit,
+// the channel itself just moves them around.
+unsafe impl Send for ChannelState {}
+
+impl ChannelState {
+    fn new(capacity: Option<usize>) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            capacity,
+            closed: false,
+            blocked_sends: VecDeque::new(),
+            claimed: HashMap::new(),
+            next_ticket: 0,
+            wakers: HashMap::new()
+        }
+    }
+
+    pub fn alloc_ticket(&mut self) -> u64 {
+        let ticket: u64 = self.next_ticket;
+        self.next_ticket += 1;
+        ticket
+    }
+
+    pub fn set_capacity(&mut self, capacity: Option<usize>) {
+        self.capacity = capacity;
+        while self.has_space() {
+            if let Some((_, value) /*: (u64, Value)*/) = self.blocked_sends.pop_front() {
+                self.queue.push_back(value);
+            } else {
+                break;
+            }
+        }
+        self.wake_all();
+    }
+
+    pub fn close(&mut self) {
+        self.closed = true;
+        self.wake_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn try_send(&mut self, value: Value) -> TrySendResult {
+        if self.closed {
+            return TrySendResult::Closed;
+        }
+
+        let result: TrySendResult = if self.has_space() {
+            self.queue.push_back(value);
+            TrySendResult::Sent
+        } else {
+            let ticket: u64 = self.alloc_ticket();
+            self.blocked_sends.push_back((ticket, value));
+            TrySendResult::Blocked(ticket)
+        };
+        self.wake_all();
+        result
+    }
+
+    /// Poll a blocked sender denoted by `ticket`. Resolves to `true` if the value has been
+    /// accepted by the channel, or `false` if the channel got closed before that.
+    pub fn poll_send(&mut self, ticket: u64, cx: &mut Context<'_>) -> Poll<bool> {
+        if let Some(idx /*: usize*/) = self.blocked_sends.iter()
+            .position(|(t, _): &(u64, Value)| *t == ticket)
+        {
+            if self.closed {
+                self.blocked_sends.remove(idx);
+                self.wakers.remove(&ticket);
+                Poll::Ready(false)
+            } else {
+                self.register_waker(ticket, cx);
+                Poll::Pending
+            }
+        } else {
+            self.wakers.remove(&ticket);
+            Poll::Ready(true)
+        }
+    }
+
+    /// Take one value from channel without waiting
+    pub fn try_recv(&mut self) -> Option<Value> {
+        let ret: Option<Value> = if let Some(value /*: Value*/) = self.queue.pop_front() {
+            if let Some((_, blocked) /*: (u64, Value)*/) = self.blocked_sends.pop_front() {
+                self.queue.push_back(blocked);
+            }
+            Some(value)
+        } else {
+            self.blocked_sends.pop_front().map(|(_, value): (u64, Value)| value)
+        };
+
+        if ret.is_some() {
+            self.wake_all();
+        }
+        ret
+    }
+
+    /// Poll a receiver denoted by `ticket`. Resolves to `true` if one value has been claimed by
+    /// this receiver, which should be fetched with `take_claimed`; or `false` if the channel has
+    /// been closed and drained.
+    pub fn poll_recv(&mut self, ticket: u64, cx: &mut Context<'_>) -> Poll<bool> {
+        if let Some(value /*: Value*/) = self.try_recv() {
+            self.claimed.insert(ticket, value);
+            self.wakers.remove(&ticket);
+            Poll::Ready(true)
+        } else if self.closed {
+            self.wakers.remove(&ticket);
+            Poll::Ready(false)
+        } else {
+            self.register_waker(ticket, cx);
+            Poll::Pending
+        }
+    }
+
+    /// Forget the waker of the sender or receiver denoted by `ticket`. This is called when a
+    /// pending send or receive operation gets dropped.
+    pub fn remove_waker(&mut self, ticket: u64) {
+        self.wakers.remove(&ticket);
+    }
+
+    pub fn take_claimed(&mut self, ticket: u64) -> Option<Value> {
+        self.claimed.remove(&ticket)
+    }
+
+    /// Put a claimed but never delivered value back to the front of the channel, so that it does
+    /// not get lost when the receiving task is cancelled
+    pub fn unclaim(&mut self, ticket: u64) {
+        if let Some(value /*: Value*/) = self.claimed.remove(&ticket) {
+            self.queue.push_front(value);
+            self.wake_all();
+        }
+    }
+
+    fn has_space(&self) -> bool {
+        match self.capacity {
+            Some(capacity) => self.queue.len() < capacity,
+            None => true
+        }
+    }
+
+    fn register_waker(&mut self, ticket: u64, cx: &mut Context<'_>) {
+        let waker: &mut Waker =
+            self.wakers.entry(ticket).or_insert_with(|| cx.waker().clone());
+        if !waker.will_wake(cx.waker()) {
+            *waker = cx.waker().clone();
+        }
+    }
+
+    fn wake_all(&mut self) {
+        for (_, waker) /*: (u64, Waker)*/ in self.wakers.drain() {
+            waker.wake();
+        }
+    }
+}
+
+pub struct VMGenericChannel {
+    pub(crate) inner: Arc<Mutex<ChannelState>>
+}
+
+impl VMGenericChannel {
+    fn new(capacity: Option<usize>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(ChannelState::new(capacity)))
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, ChannelState> {
+        self.inner.lock().unwrap()
+    }
+}
+
+impl StaticBase<VMGenericChannel> for Void {
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        tyck_info_pool.create_container_type(
+            TypeId::of::<VMGenericChannel>(),
+            &[tyck_info_pool.get_any_type()]
+        )
+    }
+
+    fn tyck(tyck_info: &TyckInfo) -> bool {
+        if let TyckInfo::Container(container_tyck_info) = tyck_info {
+            if container_tyck_info.type_id != TypeId::of::<VMGenericChannel>() {
+                return false;
+            }
+
+            unsafe {
+                container_tyck_info.params.as_ref()[0].as_ref().is_any()
+            }
+        } else {
+            false
+        }
+    }
+
+    fn type_name() -> String {
+        "channel".to_string()
+    }
+
+    fn children(vself: *const VMGenericChannel) -> ChildrenType {
+        unsafe {
+            let state: MutexGuard<ChannelState> = (*vself).lock();
+            let children: Vec<Value> = state.queue.iter()
+                .chain(state.blocked_sends.iter().map(|(_, value): &(u64, Value)| value))
+                .chain(state.claimed.values())
+                .copied()
+                .collect();
+            Some(Box::new(children.into_iter()))
+        }
+    }
+}
+
+#[repr(transparent)]
+pub struct VMChannel<T: 'static> {
+    pub(crate) repr: VMGenericChannel,
+    _phantom: PhantomData<T>
+}
+
+impl<T> StaticBase<VMChannel<T>> for Void
+    where T: 'static,
+          Void: StaticBase<T>
+{
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        let elem_type: NonNull<TyckInfo> = <Void as StaticBase<T>>::tyck_info(tyck_info_pool);
+        tyck_info_pool.create_container_type(TypeId::of::<VMGenericChannel>(), &[elem_type])
+    }
+
+    fn tyck(tyck_info: &TyckInfo) -> bool {
+        if let TyckInfo::Container(container_tyck_info) = tyck_info {
+            if container_tyck_info.type_id != TypeId::of::<VMGenericChannel>() {
+                return false;
+            }
+
+            unsafe {
+                let child_tyck_info: &TyckInfo = container_tyck_info.params.as_ref()[0].as_ref();
+                !child_tyck_info.is_any() && <Void as StaticBase<T>>::tyck(child_tyck_info)
+            }
+        } else {
+            false
+        }
+    }
+
+    fn type_name() -> String {
+        "channel".to_string()
+    }
+
+    fn children(vself: *const VMChannel<T>) -> ChildrenType {
+        <Void as StaticBase<VMGenericChannel>>::children(unsafe { &(*vself).repr })
+    }
+}
+
+pub fn create_vm_channel_vt(
+    tyck_info_pool: &mut TyckInfoPool,
+    elem_type: NonNull<TyckInfo>
+) -> GenericTypeVT {
+    let tyck_info: NonNull<TyckInfo> =
+        tyck_info_pool.create_container_type(TypeId::of::<VMGenericChannel>(), &[elem_type]);
+
+    use crate::data::generic::gen_impls;
+    GenericTypeVT {
+        tyck_info: unsafe { tyck_info.as_ref().get_container_tyck_info_unchecked() },
+        type_name: "channel".to_string(),
+        #[cfg(debug_assertions)]
+        move_out_fn: gen_impls::generic_move_out_ck::<VMGenericChannel>,
+        #[cfg(not(debug_assertions))]
+        move_out_fn: gen_impls::generic_move_out::<VMGenericChannel>,
+        children_fn: gen_impls::generic_children::<VMGenericChannel>,
+        drop_fn: gen_impls::generic_drop::<VMGenericChannel>
+    }
+}
+
+/// Virtual table of `channel<any>`, for channels created by FFI functions. Like
+/// [`any_vec_vt`](crate::builtins::vec::any_vec_vt), the virtual table is created on first use and
+/// never freed.
+pub fn any_channel_vt() -> NonNull<GenericTypeVT> {
+    static ANY_CHANNEL_VT: OnceLock<usize> = OnceLock::new();
+
+    let vt: usize = *ANY_CHANNEL_VT.get_or_init(|| {
+        let tyck_info_pool: &mut TyckInfoPool = Box::leak(Box::new(TyckInfoPool::new()));
+        let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+        let vt: GenericTypeVT = create_vm_channel_vt(tyck_info_pool, any_type);
+        Box::leak(Box::new(vt)) as *mut GenericTypeVT as usize
+    });
+    unsafe { NonNull::new_unchecked(vt as *mut GenericTypeVT) }
+}
+
+/// Creates a channel with given buffer capacity, `None` for unbounded channels
+pub fn channel_with_capacity(capacity: Option<usize>) -> *mut Wrapper<()> {
+    move_to_heap(Wrapper::new_owned(VMGenericChannel::new(capacity))).as_ptr() as *mut _
+}
+
+/// Creates an unbounded channel. Use `std47::channel::NEW_CHANNEL_BIND` to create bounded ones.
+pub fn channel_ctor() -> *mut Wrapper<()> {
+    channel_with_capacity(None)
+}
+
+#[cfg(test)]
+mod test {
+    use std::task::{Context, Poll, Waker};
+
+    use futures::task::noop_waker;
+
+    use crate::builtins::channel::ChannelState;
+
+    #[test] fn test_channel_waker_slots() {
+        let mut state: ChannelState = ChannelState::new(None);
+        let waker: Waker = noop_waker();
+        let mut cx: Context<'_> = Context::from_waker(&waker);
+
+        let ticket1: u64 = state.alloc_ticket();
+        let ticket2: u64 = state.alloc_ticket();
+        for _ in 0..3 {
+            assert_eq!(state.poll_recv(ticket1, &mut cx), Poll::Pending);
+            assert_eq!(state.poll_recv(ticket2, &mut cx), Poll::Pending);
+        }
+        // re-polling a receiver replaces its waker instead of adding more
+        assert_eq!(state.wakers.len(), 2);
+
+        state.remove_waker(ticket2);
+        assert_eq!(state.wakers.len(), 1);
+
+        state.close();
+        assert!(state.wakers.is_empty());
+        assert_eq!(state.poll_recv(ticket1, &mut cx), Poll::Ready(false));
+    }
+}
@@ -0,0 +1,45 @@
+//! # `iter.rs`: iterators of `for` loops
+//!
+//! `ITER-BEGIN` creates a `VMIterator` on the heap and stores it into a stack slot, and
+//! `ITER-NEXT` advances it. Since the iterator is heap managed and lives in a stack slot until the
+//! loop ends, the iterated value is always reachable through it, and no Rust iterator borrowing
+//! VM data ever survives across instructions.
+
+use xjbutil::void::Void;
+
+use crate::data::Value;
+use crate::data::traits::{ChildrenType, StaticBase};
+
+pub enum IterState {
+    /// Elements of a builtin vector, by position
+    Vector,
+    /// Field names of a builtin object, collected when the iteration begins
+    Object(Vec<String>),
+    /// Characters of a string, by byte offset
+    String,
+    /// Elements of a host type, by position, through the `IterElem` FFI function `ffi_func_id`
+    Host(usize)
+}
+
+pub struct VMIterator {
+    pub iterated: Value,
+    /// Position of the next element, or byte offset of the next character for strings
+    pub index: usize,
+    pub state: IterState
+}
+
+impl VMIterator {
+    pub fn new(iterated: Value, state: IterState) -> Self {
+        Self { iterated, index: 0, state }
+    }
+}
+
+impl StaticBase<VMIterator> for Void {
+    fn type_name() -> String { "iterator".into() }
+
+    #[inline] fn children(vself: *const VMIterator) -> ChildrenType {
+        unsafe {
+            Some(Box::new(std::iter::once((*vself).iterated)))
+        }
+    }
+}
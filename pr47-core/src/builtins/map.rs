@@ -0,0 +1,181 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::marker::{PhantomData, PhantomPinned};
+use std::ptr::NonNull;
+
+use xjbutil::mem::move_to_heap;
+use xjbutil::void::Void;
+
+use crate::builtins::set::EqAnyKey;
+use crate::data::generic::GenericTypeVT;
+use crate::data::traits::{ChildrenType, StaticBase};
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+use crate::data::Value;
+use crate::data::wrapper::Wrapper;
+
+/// Keys are compared in the same way as `EQ-ANY`. Entries are stored in dense vectors, so keys
+/// are listed in insertion order until some entry gets removed: removing an entry moves the last
+/// entry into its place.
+pub struct VMGenericMap {
+    indices: HashMap<EqAnyKey, usize>,
+    pub(crate) keys: Vec<Value>,
+    pub(crate) values: Vec<Value>,
+    _pinned: PhantomPinned
+}
+
+impl VMGenericMap {
+    fn new() -> Self {
+        Self {
+            indices: HashMap::new(),
+            keys: Vec::new(),
+            values: Vec::new(),
+            _pinned: PhantomPinned
+        }
+    }
+
+    pub fn get(&self, key: Value) -> Option<Value> {
+        self.indices.get(&EqAnyKey(key)).map(|index: &usize| self.values[*index])
+    }
+
+    /// Associate `value` with `key`, returns the value previously associated with `key`
+    pub fn put(&mut self, key: Value, value: Value) -> Option<Value> {
+        if let Some(index) = self.indices.get(&EqAnyKey(key)) {
+            return Some(std::mem::replace(&mut self.values[*index], value));
+        }
+
+        self.indices.insert(EqAnyKey(key), self.keys.len());
+        self.keys.push(key);
+        self.values.push(value);
+        None
+    }
+
+    /// Remove `key`, returns the value previously associated with `key`
+    pub fn remove(&mut self, key: Value) -> Option<Value> {
+        let index: usize = self.indices.remove(&EqAnyKey(key))?;
+        self.keys.swap_remove(index);
+        let value: Value = self.values.swap_remove(index);
+        if let Some(moved) = self.keys.get(index) {
+            self.indices.insert(EqAnyKey(*moved), index);
+        }
+        Some(value)
+    }
+
+    pub fn contains_key(&self, key: Value) -> bool {
+        self.indices.contains_key(&EqAnyKey(key))
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn keys(&self) -> &[Value] {
+        &self.keys
+    }
+}
+
+impl StaticBase<VMGenericMap> for Void {
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        tyck_info_pool.create_container_type(
+            TypeId::of::<VMGenericMap>(),
+            &[tyck_info_pool.get_any_type(), tyck_info_pool.get_any_type()]
+        )
+    }
+
+    fn tyck(tyck_info: &TyckInfo) -> bool {
+        if let TyckInfo::Container(container_tyck_info) = tyck_info {
+            if container_tyck_info.type_id != TypeId::of::<VMGenericMap>() {
+                return false;
+            }
+
+            unsafe {
+                let params: &[NonNull<TyckInfo>] = container_tyck_info.params.as_ref();
+                params[0].as_ref().is_any() && params[1].as_ref().is_any()
+            }
+        } else {
+            false
+        }
+    }
+
+    fn children(vself: *const VMGenericMap) -> ChildrenType {
+        unsafe {
+            let iter = Box::new((*vself).keys.iter().chain((*vself).values.iter()).copied());
+            Some(iter)
+        }
+    }
+}
+
+#[repr(transparent)]
+pub struct VMMap<K: 'static, V: 'static> {
+    pub(crate) repr: VMGenericMap,
+    _phantom: PhantomData<(K, V)>
+}
+
+impl<K, V> StaticBase<VMMap<K, V>> for Void
+    where K: 'static,
+          V: 'static,
+          Void: StaticBase<K> + StaticBase<V>
+{
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        tyck_info_pool.create_container_type(
+            TypeId::of::<VMMap<K, V>>(),
+            &[tyck_info_pool.get_any_type(), tyck_info_pool.get_any_type()]
+        )
+    }
+
+    fn tyck(tyck_info: &TyckInfo) -> bool {
+        if let TyckInfo::Container(container_tyck_info) = tyck_info {
+            if container_tyck_info.type_id != TypeId::of::<VMGenericMap>() {
+                return false;
+            }
+
+            unsafe {
+                let params: &[NonNull<TyckInfo>] = container_tyck_info.params.as_ref();
+                let (key_tyck_info, value_tyck_info): (&TyckInfo, &TyckInfo) =
+                    (params[0].as_ref(), params[1].as_ref());
+                !key_tyck_info.is_any() && <Void as StaticBase<K>>::tyck(key_tyck_info)
+                    && !value_tyck_info.is_any() && <Void as StaticBase<V>>::tyck(value_tyck_info)
+            }
+        } else {
+            false
+        }
+    }
+
+    fn children(vself: *const VMMap<K, V>) -> ChildrenType {
+        unsafe {
+            let repr: &VMGenericMap = &(*vself).repr;
+            let iter = Box::new(repr.keys.iter().chain(repr.values.iter()).copied());
+            Some(iter)
+        }
+    }
+}
+
+pub fn create_vm_map_vt(
+    tyck_info_pool: &mut TyckInfoPool,
+    key_type: NonNull<TyckInfo>,
+    value_type: NonNull<TyckInfo>
+) -> GenericTypeVT {
+    let tyck_info: NonNull<TyckInfo> = tyck_info_pool.create_container_type(
+        TypeId::of::<VMGenericMap>(),
+        &[key_type, value_type]
+    );
+
+    use crate::data::generic::gen_impls;
+    GenericTypeVT {
+        tyck_info: unsafe { tyck_info.as_ref().get_container_tyck_info_unchecked() },
+        type_name: "map".to_string(),
+        #[cfg(debug_assertions)]
+        move_out_fn: gen_impls::generic_move_out_ck::<VMGenericMap>,
+        #[cfg(not(debug_assertions))]
+        move_out_fn: gen_impls::generic_move_out::<VMGenericMap>,
+        children_fn: gen_impls::generic_children::<VMGenericMap>,
+        drop_fn: gen_impls::generic_drop::<VMGenericMap>
+    }
+}
+
+pub fn map_ctor() -> *mut Wrapper<()> {
+    move_to_heap(Wrapper::new_owned(VMGenericMap::new())).as_ptr() as *mut _
+}
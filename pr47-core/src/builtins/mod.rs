@@ -1,4 +1,7 @@
+#[cfg(feature = "async")] pub mod channel;
 pub mod closure;
+pub mod iter;
+pub mod map;
 pub mod object;
 pub mod set;
 pub mod vec;
@@ -42,7 +42,7 @@ mod ops {
     use crate::builtins::object::{Object, ObjectRef};
     use crate::data::tyck::{TyckInfo, TyckInfoPool};
     use crate::data::Value;
-    use crate::ffi::{DataOption, FFIException, Signature};
+    use crate::ffi::{DataOption, FFIException, RetArity, Signature};
     use crate::ffi::sync_fn::{container_into_ref_noalias, FunctionBase, value_into_ref_noalias, VMContext};
 
     pub struct CreateObjectBind();
@@ -59,6 +59,8 @@ mod ops {
                 ),
                 param_options: boxed_slice![],
                 ret_option: boxed_slice![DataOption::Move],
+                variadic: None,
+                ret_arity: RetArity::Fixed
             }
         }
 
@@ -94,7 +96,9 @@ mod ops {
                     &[object_type, string_type], &[nullable_any], &[]
                 ),
                 param_options: boxed_slice![DataOption::Share, DataOption::Share],
-                ret_option: boxed_slice![DataOption::RawUntyped]
+                ret_option: boxed_slice![DataOption::RawUntyped],
+                variadic: None,
+                ret_arity: RetArity::Fixed
             }
         }
 
@@ -142,7 +146,9 @@ mod ops {
                     DataOption::Share,
                     DataOption::RawUntyped
                 ],
-                ret_option: boxed_slice![]
+                ret_option: boxed_slice![],
+                variadic: None,
+                ret_arity: RetArity::Fixed
             }
         }
 
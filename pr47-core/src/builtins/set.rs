@@ -0,0 +1,189 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::marker::{PhantomData, PhantomPinned};
+use std::ptr::NonNull;
+
+use xjbutil::mem::move_to_heap;
+use xjbutil::void::Void;
+use xjbutil::wide_ptr::WidePointer;
+
+use crate::data::generic::GenericTypeVT;
+use crate::data::traits::{ChildrenType, StaticBase};
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+use crate::data::Value;
+use crate::data::wrapper::Wrapper;
+
+/// Set element or map key, hashed and compared in the same way as `EQ-ANY`: two values are equal
+/// iff they are bitwise equal, that is, the same primitive value or the same reference
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub(crate) struct EqAnyKey(pub(crate) Value);
+
+impl PartialEq for EqAnyKey {
+    fn eq(&self, other: &Self) -> bool {
+        unsafe { self.0.ptr_repr == other.0.ptr_repr }
+    }
+}
+
+impl Eq for EqAnyKey {}
+
+impl Hash for EqAnyKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let repr: WidePointer = unsafe { self.0.ptr_repr };
+        repr.ptr.hash(state);
+        repr.trivia.hash(state);
+    }
+}
+
+/// Elements are stored in a dense vector, so they can be iterated by index with `SET-ELEM`.
+/// Removing an element moves the last element into its place.
+pub struct VMGenericSet {
+    indices: HashMap<EqAnyKey, usize>,
+    pub(crate) elements: Vec<Value>,
+    _pinned: PhantomPinned
+}
+
+impl VMGenericSet {
+    fn new() -> Self {
+        Self {
+            indices: HashMap::new(),
+            elements: Vec::new(),
+            _pinned: PhantomPinned
+        }
+    }
+
+    /// Insert `value`, returns `false` if it is already in the set
+    pub fn insert(&mut self, value: Value) -> bool {
+        if self.indices.contains_key(&EqAnyKey(value)) {
+            return false;
+        }
+
+        self.indices.insert(EqAnyKey(value), self.elements.len());
+        self.elements.push(value);
+        true
+    }
+
+    /// Remove `value`, returns `false` if it is not in the set
+    pub fn remove(&mut self, value: Value) -> bool {
+        if let Some(index) = self.indices.remove(&EqAnyKey(value)) {
+            self.elements.swap_remove(index);
+            if let Some(moved) = self.elements.get(index) {
+                self.indices.insert(EqAnyKey(*moved), index);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn contains(&self, value: Value) -> bool {
+        self.indices.contains_key(&EqAnyKey(value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<Value> {
+        self.elements.get(index).copied()
+    }
+}
+
+impl StaticBase<VMGenericSet> for Void {
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        tyck_info_pool.create_container_type(
+            TypeId::of::<VMGenericSet>(),
+            &[tyck_info_pool.get_any_type()]
+        )
+    }
+
+    fn tyck(tyck_info: &TyckInfo) -> bool {
+        if let TyckInfo::Container(container_tyck_info) = tyck_info {
+            if container_tyck_info.type_id != TypeId::of::<VMGenericSet>() {
+                return false;
+            }
+
+            unsafe {
+                container_tyck_info.params.as_ref()[0].as_ref().is_any()
+            }
+        } else {
+            false
+        }
+    }
+
+    fn children(vself: *const VMGenericSet) -> ChildrenType {
+        unsafe {
+            let iter = Box::new((*vself).elements.iter().copied());
+            Some(iter)
+        }
+    }
+}
+
+#[repr(transparent)]
+pub struct VMSet<T: 'static> {
+    pub(crate) repr: VMGenericSet,
+    _phantom: PhantomData<T>
+}
+
+impl<T> StaticBase<VMSet<T>> for Void
+    where T: 'static,
+          Void: StaticBase<T>
+{
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        tyck_info_pool.create_container_type(
+            TypeId::of::<VMSet<T>>(),
+            &[tyck_info_pool.get_any_type()]
+        )
+    }
+
+    fn tyck(tyck_info: &TyckInfo) -> bool {
+        if let TyckInfo::Container(container_tyck_info) = tyck_info {
+            if container_tyck_info.type_id != TypeId::of::<VMGenericSet>() {
+                return false;
+            }
+
+            unsafe {
+                let child_tyck_info: &TyckInfo = container_tyck_info.params.as_ref()[0].as_ref();
+                !child_tyck_info.is_any() && <Void as StaticBase<T>>::tyck(child_tyck_info)
+            }
+        } else {
+            false
+        }
+    }
+
+    fn children(vself: *const VMSet<T>) -> ChildrenType {
+        unsafe {
+            let iter = Box::new((*vself).repr.elements.iter().copied());
+            Some(iter)
+        }
+    }
+}
+
+pub fn create_vm_set_vt(
+    tyck_info_pool: &mut TyckInfoPool,
+    arg_type: NonNull<TyckInfo>
+) -> GenericTypeVT {
+    let tyck_info: NonNull<TyckInfo> =
+        tyck_info_pool.create_container_type(TypeId::of::<VMGenericSet>(), &[arg_type]);
+
+    use crate::data::generic::gen_impls;
+    GenericTypeVT {
+        tyck_info: unsafe { tyck_info.as_ref().get_container_tyck_info_unchecked() },
+        type_name: "set".to_string(),
+        #[cfg(debug_assertions)]
+        move_out_fn: gen_impls::generic_move_out_ck::<VMGenericSet>,
+        #[cfg(not(debug_assertions))]
+        move_out_fn: gen_impls::generic_move_out::<VMGenericSet>,
+        children_fn: gen_impls::generic_children::<VMGenericSet>,
+        drop_fn: gen_impls::generic_drop::<VMGenericSet>
+    }
+}
+
+pub fn set_ctor() -> *mut Wrapper<()> {
+    move_to_heap(Wrapper::new_owned(VMGenericSet::new())).as_ptr() as *mut _
+}
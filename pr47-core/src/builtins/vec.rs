@@ -1,6 +1,7 @@
 use std::any::TypeId;
 use std::marker::{PhantomData, PhantomPinned};
 use std::ptr::NonNull;
+use std::sync::OnceLock;
 
 use xjbutil::mem::move_to_heap;
 use xjbutil::void::Void;
@@ -24,6 +25,13 @@ impl VMGenericVec {
             _pinned: PhantomPinned
         }
     }
+
+    pub(crate) fn from_values(inner: Vec<Value>) -> Self {
+        Self {
+            inner,
+            _pinned: PhantomPinned
+        }
+    }
 }
 
 impl StaticBase<VMGenericVec> for Void {
@@ -119,3 +127,19 @@ pub fn create_vm_vec_vt(
 pub fn vec_ctor() -> *mut Wrapper<()> {
     move_to_heap(Wrapper::new_owned(VMGenericVec::new())).as_ptr() as *mut _
 }
+
+/// Virtual table of `vector<any>`, for vectors created from Rust data. The virtual table and the
+/// `TyckInfoPool` owning its type information are created on first use and never freed, so that
+/// they outlive any VM value referring to them.
+pub fn any_vec_vt() -> NonNull<GenericTypeVT> {
+    // the virtual table is never modified once created, so its address can be shared by threads
+    static ANY_VEC_VT: OnceLock<usize> = OnceLock::new();
+
+    let vt: usize = *ANY_VEC_VT.get_or_init(|| {
+        let tyck_info_pool: &mut TyckInfoPool = Box::leak(Box::new(TyckInfoPool::new()));
+        let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+        let vt: GenericTypeVT = create_vm_vec_vt(tyck_info_pool, any_type);
+        Box::leak(Box::new(vt)) as *mut GenericTypeVT as usize
+    });
+    unsafe { NonNull::new_unchecked(vt as *mut GenericTypeVT) }
+}
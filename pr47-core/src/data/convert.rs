@@ -0,0 +1,346 @@
+//! # `convert.rs`: conversion between `Value`s and common Rust types
+//!
+//! `FromValue` copies data out of VM values, so it requires the value to be readable. Type
+//! mismatches and ownership violations are reported as `UncheckedException`s, just like RTLC
+//! failures. `IntoValue` creates fresh VM values, and hands heap objects over to the VM through
+//! `VMContext`. Containers are handed over before their elements get converted, and stay pinned
+//! until all elements are inside, so garbage collection may happen at any time during conversion.
+//! The converted value itself is not rooted, the caller should put it somewhere the VM scans
+//! (e.g. the VM stack) before creating more heap objects.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::ptr::NonNull;
+
+use smallvec::{SmallVec, smallvec};
+use xjbutil::mem::move_to_heap;
+
+use crate::builtins::object::Object;
+use crate::builtins::vec::{VMGenericVec, any_vec_vt};
+use crate::data::Value;
+use crate::data::exception::UncheckedException;
+use crate::data::generic::GenericTypeVT;
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+use crate::data::value_typed::{
+    VALUE_TYPE_TAG_MASK,
+    BOOL_TYPE_TAG,
+    CHAR_TYPE_TAG,
+    FLOAT_TYPE_TAG,
+    INT_TYPE_TAG
+};
+use crate::data::wrapper::{OWN_INFO_READ_MASK, Wrapper};
+use crate::ffi::sync_fn::VMContext;
+
+pub trait IntoValue {
+    fn into_value<CTX: VMContext>(self, context: &mut CTX) -> Value;
+}
+
+pub trait FromValue: Sized {
+    /// Type information of the expected VM type, used for reporting type check failures
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo>;
+
+    /// Convert `value` to `Self`. `value` should be a living VM value.
+    unsafe fn from_value(value: Value, tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, UncheckedException>;
+}
+
+/// Multiple values, for passing arguments and returning multiple values
+pub trait IntoValues {
+    fn into_values<CTX: VMContext>(self, context: &mut CTX) -> SmallVec<[Value; 4]>;
+}
+
+/// Multiple values, for receiving arguments and multiple return values
+pub trait FromValues: Sized {
+    /// Count of values required. `from_values` should only be called with exactly `COUNT` values
+    const COUNT: usize;
+
+    unsafe fn from_values(values: &[Value], tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, UncheckedException>;
+}
+
+/// Pin `value` while running `f`, so that objects added into `value` by `f` remain reachable
+fn with_pinned<CTX: VMContext, R>(
+    context: &mut CTX,
+    value: Value,
+    f: impl FnOnce(&mut CTX) -> R
+) -> R {
+    let pinned: *mut bool = context.pin_objects(&[value]);
+    let ret: R = f(context);
+    unpin(pinned);
+    ret
+}
+
+fn unpin(pinned: *mut bool) {
+    if !pinned.is_null() {
+        unsafe { *pinned = false; }
+    }
+}
+
+fn type_check_failure<T: FromValue>(
+    value: Value,
+    tyck_info_pool: &mut TyckInfoPool
+) -> UncheckedException {
+    UncheckedException::TypeCheckFailure {
+        object: value,
+        expected_type: T::tyck_info(tyck_info_pool)
+    }
+}
+
+#[inline(always)] unsafe fn value_type_tag(value: Value) -> Option<usize> {
+    if value.is_value() {
+        Some(value.vt_data.tag & (VALUE_TYPE_TAG_MASK as usize))
+    } else {
+        None
+    }
+}
+
+/// Get the type ID of the data referenced by `value`, `None` if `value` is not a reference
+unsafe fn ref_type_id(value: Value) -> Option<TypeId> {
+    if value.is_null() || value.is_value() {
+        None
+    } else if value.is_container() {
+        let vt: *const GenericTypeVT = value.ptr_repr.trivia as *const GenericTypeVT;
+        Some((*vt).tyck_info.as_ref().type_id)
+    } else {
+        Some((*value.get_as_dyn_base()).dyn_type_id())
+    }
+}
+
+/// Check that `value` references data of type `T` and is readable, then get a reference to it
+unsafe fn ref_check<'a, T: 'static, R: FromValue>(
+    value: Value,
+    tyck_info_pool: &mut TyckInfoPool
+) -> Result<&'a T, UncheckedException> {
+    if ref_type_id(value) != Some(TypeId::of::<T>()) {
+        return Err(type_check_failure::<R>(value, tyck_info_pool));
+    }
+    if !value.ownership_info().is_readable() {
+        return Err(UncheckedException::OwnershipCheckFailure {
+            object: value,
+            expected_mask: OWN_INFO_READ_MASK
+        });
+    }
+    Ok(&*(value.get_as_mut_ptr::<T>() as *const T))
+}
+
+macro_rules! impl_value_typed {
+    ($t:ty, $tag:expr, $field:ident, $ctor:ident, $get_type:ident) => {
+        impl IntoValue for $t {
+            fn into_value<CTX: VMContext>(self, _context: &mut CTX) -> Value {
+                Value::$ctor(self)
+            }
+        }
+
+        impl FromValue for $t {
+            fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+                tyck_info_pool.$get_type()
+            }
+
+            unsafe fn from_value(value: Value, tyck_info_pool: &mut TyckInfoPool)
+                -> Result<Self, UncheckedException>
+            {
+                if value_type_tag(value) == Some($tag) {
+                    Ok(value.vt_data.inner.$field)
+                } else {
+                    Err(type_check_failure::<Self>(value, tyck_info_pool))
+                }
+            }
+        }
+    }
+}
+
+impl_value_typed!(i64, INT_TYPE_TAG, int_value, new_int, get_int_type);
+impl_value_typed!(f64, FLOAT_TYPE_TAG, float_value, new_float, get_float_type);
+impl_value_typed!(char, CHAR_TYPE_TAG, char_value, new_char, get_char_type);
+impl_value_typed!(bool, BOOL_TYPE_TAG, bool_value, new_bool, get_bool_type);
+
+impl IntoValue for String {
+    fn into_value<CTX: VMContext>(self, context: &mut CTX) -> Value {
+        let value: Value = Value::new_owned(self);
+        context.add_heap_managed(value);
+        value
+    }
+}
+
+impl FromValue for String {
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        tyck_info_pool.get_string_type()
+    }
+
+    unsafe fn from_value(value: Value, tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, UncheckedException>
+    {
+        ref_check::<String, Self>(value, tyck_info_pool).cloned()
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value<CTX: VMContext>(self, context: &mut CTX) -> Value {
+        match self {
+            Some(data) => data.into_value(context),
+            None => Value::new_null()
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        let base: NonNull<TyckInfo> = T::tyck_info(tyck_info_pool);
+        tyck_info_pool.create_nullable_type(base)
+    }
+
+    unsafe fn from_value(value: Value, tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, UncheckedException>
+    {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            T::from_value(value, tyck_info_pool).map(Some)
+        }
+    }
+}
+
+/// Vectors created by this conversion are `vector<any>` containers, see `any_vec_vt`
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value<CTX: VMContext>(self, context: &mut CTX) -> Value {
+        let elements: Vec<Value> = Vec::with_capacity(self.len());
+        let value: Value = Value::new_container(
+            move_to_heap(Wrapper::new_owned(VMGenericVec::from_values(elements))).as_ptr() as _,
+            any_vec_vt().as_ptr()
+        );
+        context.add_heap_managed(value);
+
+        with_pinned(context, value, |context: &mut CTX| {
+            // `value` was just created above, and is not shared with anyone else yet
+            let vec: *mut VMGenericVec = unsafe { value.get_as_mut_ptr::<VMGenericVec>() };
+            for element /*: T*/ in self {
+                let element: Value = element.into_value(context);
+                context.mark(element);
+                unsafe { (*vec).inner.push(element); }
+            }
+        });
+        value
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        let elem_type: NonNull<TyckInfo> = T::tyck_info(tyck_info_pool);
+        tyck_info_pool.create_container_type(TypeId::of::<VMGenericVec>(), &[elem_type])
+    }
+
+    unsafe fn from_value(value: Value, tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, UncheckedException>
+    {
+        let vec: &VMGenericVec = ref_check::<VMGenericVec, Self>(value, tyck_info_pool)?;
+        vec.inner.iter()
+            .map(|element: &Value| T::from_value(*element, tyck_info_pool))
+            .collect()
+    }
+}
+
+impl<T: IntoValue> IntoValue for HashMap<String, T> {
+    fn into_value<CTX: VMContext>(self, context: &mut CTX) -> Value {
+        let value: Value = Value::new_owned(Object::new());
+        context.add_heap_managed(value);
+
+        with_pinned(context, value, |context: &mut CTX| {
+            // `value` was just created above, and is not shared with anyone else yet
+            let object: *mut Object = unsafe { value.get_as_mut_ptr::<Object>() };
+            for (key, field) /*: (String, T)*/ in self {
+                let field: Value = field.into_value(context);
+                context.mark(field);
+                unsafe { (*object).fields.insert(key, field); }
+            }
+        });
+        value
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        tyck_info_pool.get_object_type()
+    }
+
+    unsafe fn from_value(value: Value, tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, UncheckedException>
+    {
+        let object: &Object = ref_check::<Object, Self>(value, tyck_info_pool)?;
+        object.fields.iter()
+            .map(|(key, field): (&String, &Value)| {
+                Ok((key.clone(), T::from_value(*field, tyck_info_pool)?))
+            })
+            .collect()
+    }
+}
+
+impl<T: IntoValue> IntoValues for T {
+    fn into_values<CTX: VMContext>(self, context: &mut CTX) -> SmallVec<[Value; 4]> {
+        smallvec![self.into_value(context)]
+    }
+}
+
+impl<T: FromValue> FromValues for T {
+    const COUNT: usize = 1;
+
+    unsafe fn from_values(values: &[Value], tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, UncheckedException>
+    {
+        debug_assert_eq!(values.len(), 1);
+        T::from_value(*values.get_unchecked(0), tyck_info_pool)
+    }
+}
+
+impl IntoValues for () {
+    fn into_values<CTX: VMContext>(self, _context: &mut CTX) -> SmallVec<[Value; 4]> {
+        SmallVec::new()
+    }
+}
+
+impl FromValues for () {
+    const COUNT: usize = 0;
+
+    unsafe fn from_values(values: &[Value], _tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, UncheckedException>
+    {
+        debug_assert!(values.is_empty());
+        Ok(())
+    }
+}
+
+macro_rules! impl_tuple {
+    ($count:expr; $($t:ident, $idx:tt);+) => {
+        impl<$($t: IntoValue),+> IntoValues for ($($t,)+) {
+            fn into_values<CTX: VMContext>(self, context: &mut CTX) -> SmallVec<[Value; 4]> {
+                // converted values stay pinned until all values are converted
+                let mut values: SmallVec<[Value; 4]> = SmallVec::new();
+                let mut pins: SmallVec<[*mut bool; 4]> = SmallVec::new();
+                $(
+                    let value: Value = self.$idx.into_value(context);
+                    values.push(value);
+                    pins.push(context.pin_objects(&[value]));
+                )+
+                for pinned /*: *mut bool*/ in pins {
+                    unpin(pinned);
+                }
+                values
+            }
+        }
+
+        impl<$($t: FromValue),+> FromValues for ($($t,)+) {
+            const COUNT: usize = $count;
+
+            unsafe fn from_values(values: &[Value], tyck_info_pool: &mut TyckInfoPool)
+                -> Result<Self, UncheckedException>
+            {
+                debug_assert_eq!(values.len(), $count);
+                Ok(($($t::from_value(*values.get_unchecked($idx), tyck_info_pool)?,)+))
+            }
+        }
+    }
+}
+
+impl_tuple!(1; T0, 0);
+impl_tuple!(2; T0, 0; T1, 1);
+impl_tuple!(3; T0, 0; T1, 1; T2, 2);
+impl_tuple!(4; T0, 0; T1, 1; T2, 2; T3, 3);
@@ -1,16 +1,31 @@
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 use std::ptr::NonNull;
 
+use xjbutil::unchecked::UnsafeFrom;
+
 use crate::data::Value;
+use crate::data::generic::GenericTypeVT;
 use crate::data::tyck::TyckInfo;
+use crate::data::value_typed::{VALUE_TYPE_TAG_MASK, ValueTypeTag};
+use crate::data::wrapper::{
+    OWN_INFO_COLLECT_MASK,
+    OWN_INFO_GLOBAL_MASK,
+    OWN_INFO_MOVE_MASK,
+    OWN_INFO_OWNED_MASK,
+    OWN_INFO_READ_MASK,
+    OWN_INFO_WRITE_MASK
+};
 
 #[cfg(feature = "async-astd")] use std::convert::Infallible as JoinError;
 #[cfg(feature = "async-tokio")] use tokio::task::JoinError;
+#[cfg(feature = "async-sim")] use std::convert::Infallible as JoinError;
 
 
 pub enum UncheckedException {
     AlreadyAwaited { promise: Value },
     ArgCountMismatch { func_id: usize, expected: usize, got: usize },
+    RetCountMismatch { func_id: usize, expected: usize, got: usize },
+    AsyncCallback { func_id: usize },
     DivideByZero,
     InvalidBinaryOp { bin_op: char, lhs: Value, rhs: Value },
     InvalidCastOp { dest_type: &'static str, src: Value },
@@ -18,10 +33,158 @@ pub enum UncheckedException {
     OwnershipCheckFailure { object: Value, expected_mask: u8 },
     TypeCheckFailure { object: Value, expected_type: NonNull<TyckInfo> },
     OverloadCallFailure { overload_table: usize },
+    MethodNotFound { object: Value, method_id: usize },
+    PropertyNotFound { object: Value, property_id: usize },
+    ReadOnlyProperty { object: Value, property_id: usize },
     UnexpectedNull { value: Value },
     IndexOutOfBounds { indexed: Value, index: i64 },
+    NotIterable { value: Value },
+    #[cfg(feature = "async")]
+    JoinError { inner: JoinError },
     #[cfg(feature = "async")]
-    JoinError { inner: JoinError }
+    TaskCancelled { task_id: u32 }
+}
+
+/// Human-readable rendering of a `Value` carried by an exception: value-typed data are printed
+/// as literals, references and containers are printed by their type names.
+struct ExcValue<'a>(&'a Value);
+
+impl<'a> Display for ExcValue<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let value: &Value = self.0;
+        if value.is_null() {
+            write!(f, "null")
+        } else if value.is_value() {
+            unsafe {
+                match ValueTypeTag::unsafe_from((value.vt_data.tag as u8) & VALUE_TYPE_TAG_MASK) {
+                    ValueTypeTag::Int => write!(f, "{}", value.vt_data.inner.int_value),
+                    ValueTypeTag::Float => write!(f, "{}", value.vt_data.inner.float_value),
+                    ValueTypeTag::Char => write!(f, "{:?}", value.vt_data.inner.char_value),
+                    ValueTypeTag::Bool => write!(f, "{}", value.vt_data.inner.bool_value)
+                }
+            }
+        } else {
+            write!(f, "<{}>", value_type_name(value))
+        }
+    }
+}
+
+/// Human-readable rendering of ownership masks, in form of `GRWMCO`
+struct OwnershipMask(u8);
+
+impl Display for OwnershipMask {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mask: u8 = self.0;
+        for (bit, repr) /*: &(u8, char)*/ in [
+            (OWN_INFO_GLOBAL_MASK, 'G'),
+            (OWN_INFO_READ_MASK, 'R'),
+            (OWN_INFO_WRITE_MASK, 'W'),
+            (OWN_INFO_MOVE_MASK, 'M'),
+            (OWN_INFO_COLLECT_MASK, 'C'),
+            (OWN_INFO_OWNED_MASK, 'O')
+        ].iter() {
+            write!(f, "{}", if mask & bit != 0 { *repr } else { '-' })?;
+        }
+        Ok(())
+    }
+}
+
+/// Given that `value` **MUST** be a non-null reference, get its type name
+fn value_type_name(value: &Value) -> String {
+    unsafe {
+        if value.is_container() {
+            let vt: *const GenericTypeVT = value.ptr_repr.trivia as *const GenericTypeVT;
+            (*vt).type_name.clone()
+        } else {
+            (*value.get_as_dyn_base()).dyn_type_name()
+        }
+    }
+}
+
+impl Display for UncheckedException {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UncheckedException::AlreadyAwaited { promise } =>
+                write!(f, "promise {} has already been awaited", ExcValue(promise)),
+            UncheckedException::ArgCountMismatch { func_id, expected, got } =>
+                write!(f, "function F.{} expects {} argument(s), got {}", func_id, expected, got),
+            UncheckedException::RetCountMismatch { func_id, expected, got } =>
+                write!(f, "function F.{} returns {} value(s), expected {}", func_id, got, expected),
+            UncheckedException::AsyncCallback { func_id } =>
+                write!(f, "function F.{} may await, cannot be used as a synchronous callback",
+                       func_id),
+            UncheckedException::DivideByZero =>
+                write!(f, "divide by zero"),
+            UncheckedException::InvalidBinaryOp { bin_op, lhs, rhs } =>
+                write!(f, "invalid binary operation: {} {} {}",
+                       ExcValue(lhs), bin_op, ExcValue(rhs)),
+            UncheckedException::InvalidCastOp { dest_type, src } =>
+                write!(f, "cannot cast {} to {}", ExcValue(src), dest_type),
+            UncheckedException::InvalidUnaryOp { unary_op, src } =>
+                write!(f, "invalid unary operation: {}{}", unary_op, ExcValue(src)),
+            UncheckedException::OwnershipCheckFailure { object, expected_mask } => {
+                let actual_mask: u8 = if object.is_null() || object.is_value() {
+                    0
+                } else {
+                    unsafe { object.ownership_info() as u8 }
+                };
+                write!(f, "ownership check failed on {}: expected {}, got {}",
+                       ExcValue(object), OwnershipMask(*expected_mask), OwnershipMask(actual_mask))
+            },
+            UncheckedException::TypeCheckFailure { object, expected_type } =>
+                write!(f, "type check failed: expected {}, got {}",
+                       unsafe { expected_type.as_ref() }, ExcValue(object)),
+            UncheckedException::OverloadCallFailure { overload_table } =>
+                write!(f, "no matching overload in overload table #{}", overload_table),
+            UncheckedException::MethodNotFound { object, method_id } =>
+                write!(f, "{} does not have method #{}", ExcValue(object), method_id),
+            UncheckedException::PropertyNotFound { object, property_id } =>
+                write!(f, "{} does not have property #{}", ExcValue(object), property_id),
+            UncheckedException::ReadOnlyProperty { object, property_id } =>
+                write!(f, "property #{} of {} is read-only", property_id, ExcValue(object)),
+            UncheckedException::UnexpectedNull { value } =>
+                write!(f, "unexpected null: {}", ExcValue(value)),
+            UncheckedException::IndexOutOfBounds { indexed, index } =>
+                write!(f, "index {} out of bounds of {}", index, ExcValue(indexed)),
+            UncheckedException::NotIterable { value } =>
+                write!(f, "{} is not iterable", ExcValue(value)),
+            #[cfg(feature = "async")]
+            UncheckedException::JoinError { inner } =>
+                write!(f, "failed joining task: {}", inner),
+            #[cfg(feature = "async")]
+            UncheckedException::TaskCancelled { task_id } =>
+                write!(f, "task #{} has been cancelled", task_id)
+        }
+    }
+}
+
+impl Debug for UncheckedException {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let variant_name: &'static str = match self {
+            UncheckedException::AlreadyAwaited { .. } => "AlreadyAwaited",
+            UncheckedException::ArgCountMismatch { .. } => "ArgCountMismatch",
+            UncheckedException::RetCountMismatch { .. } => "RetCountMismatch",
+            UncheckedException::AsyncCallback { .. } => "AsyncCallback",
+            UncheckedException::DivideByZero => "DivideByZero",
+            UncheckedException::InvalidBinaryOp { .. } => "InvalidBinaryOp",
+            UncheckedException::InvalidCastOp { .. } => "InvalidCastOp",
+            UncheckedException::InvalidUnaryOp { .. } => "InvalidUnaryOp",
+            UncheckedException::OwnershipCheckFailure { .. } => "OwnershipCheckFailure",
+            UncheckedException::TypeCheckFailure { .. } => "TypeCheckFailure",
+            UncheckedException::OverloadCallFailure { .. } => "OverloadCallFailure",
+            UncheckedException::MethodNotFound { .. } => "MethodNotFound",
+            UncheckedException::PropertyNotFound { .. } => "PropertyNotFound",
+            UncheckedException::ReadOnlyProperty { .. } => "ReadOnlyProperty",
+            UncheckedException::UnexpectedNull { .. } => "UnexpectedNull",
+            UncheckedException::IndexOutOfBounds { .. } => "IndexOutOfBounds",
+            UncheckedException::NotIterable { .. } => "NotIterable",
+            #[cfg(feature = "async")]
+            UncheckedException::JoinError { .. } => "JoinError",
+            #[cfg(feature = "async")]
+            UncheckedException::TaskCancelled { .. } => "TaskCancelled"
+        };
+        write!(f, "{}({})", variant_name, self)
+    }
 }
 
 pub type CheckedException = Value;
@@ -31,17 +194,25 @@ pub enum ExceptionInner {
     Checked(CheckedException)
 }
 
+impl Display for ExceptionInner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExceptionInner::Unchecked(unchecked) => write!(f, "{}", unchecked),
+            ExceptionInner::Checked(checked) => write!(f, "{}", ExcValue(checked))
+        }
+    }
+}
+
 impl Debug for ExceptionInner {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        // TODO should we provide prettier printing?
         match self {
-            ExceptionInner::Unchecked(_) => write!(f, "ExceptionInner::Unchecked"),
-            ExceptionInner::Checked(_) => write!(f, "ExceptionInner::Checked")
+            ExceptionInner::Unchecked(unchecked) => write!(f, "Unchecked({:?})", unchecked),
+            ExceptionInner::Checked(checked) => write!(f, "Checked({})", ExcValue(checked))
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct StackTrace {
     pub func_id: usize,
     pub insc_ptr: usize
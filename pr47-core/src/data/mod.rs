@@ -1,3 +1,4 @@
+pub mod convert;
 pub mod exception;
 pub mod generic;
 pub mod traits;
@@ -1,20 +1,34 @@
 use std::any::TypeId;
+use std::collections::HashMap;
 use std::mem::MaybeUninit;
 use std::ptr::{NonNull, addr_of, null_mut};
 
+use pr47_codegen::StaticBase;
 use xjbutil::mem::move_to_heap;
 use xjbutil::void::Void;
 
+use crate::builtins::map::{VMGenericMap, create_vm_map_vt, map_ctor};
+use crate::builtins::set::{VMGenericSet, create_vm_set_vt, set_ctor};
 use crate::builtins::test_container::{
     GenericTestContainer,
     TestContainer,
     create_test_container_vt
 };
 use crate::data::Value;
+use crate::data::convert::{FromValue, FromValues, IntoValue, IntoValues};
+use crate::data::exception::UncheckedException;
 use crate::data::generic::GenericTypeVT;
 use crate::data::traits::{ChildrenType, StaticBase};
 use crate::data::tyck::{TyckInfo, TyckInfoPool};
-use crate::data::wrapper::{Wrapper, WrapperData, DynBase, OwnershipInfo};
+use crate::data::wrapper::{
+    Wrapper,
+    WrapperData,
+    DynBase,
+    OwnershipInfo,
+    OWN_INFO_GLOBAL_MASK,
+    OWN_INFO_READ_MASK
+};
+use crate::ffi::sync_fn::VMContext;
 
 #[allow(dead_code)]
 struct TestStruct {
@@ -321,3 +335,360 @@ impl StaticBase<TestStruct2> for Void {
 
     // TODO deallocate memory here
 }
+
+#[test] fn test_generic_set() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+    let set_vt: GenericTypeVT = create_vm_set_vt(&mut tyck_info_pool, any_type);
+    assert_eq!(set_vt.type_name, "set");
+
+    let v: Value = Value::new_container(set_ctor(), &set_vt as _);
+    assert!(v.is_container());
+
+    let string1: Value = Value::new_owned("114514".to_string());
+    let string2: Value = Value::new_owned("114514".to_string());
+
+    unsafe {
+        let set: &mut VMGenericSet = &mut *(v.get_as_mut_ptr());
+        assert!(set.is_empty());
+
+        assert!(set.insert(Value::new_int(1)));
+        assert!(set.insert(Value::new_int(2)));
+        assert!(!set.insert(Value::new_int(1)));
+        assert!(set.insert(Value::new_int(3)));
+        assert_eq!(set.len(), 3);
+
+        // values are compared in the same way as `EQ-ANY`, two distinct strings with the same
+        // content are distinct elements
+        assert!(set.insert(string1));
+        assert!(set.insert(string2));
+        assert!(!set.insert(string1));
+        assert_eq!(set.len(), 5);
+
+        assert!(set.remove(Value::new_int(1)));
+        assert!(!set.remove(Value::new_int(1)));
+        assert!(!set.contains(Value::new_int(1)));
+        assert_eq!(set.len(), 4);
+
+        // the last element gets moved into the place of the removed one
+        assert_eq!(set.get(0).unwrap().ptr_repr, string2.ptr_repr);
+        assert!(set.remove(string2));
+        assert!(set.contains(string1));
+        assert!(set.contains(Value::new_int(2)));
+        assert!(set.contains(Value::new_int(3)));
+        assert!(set.get(3).is_none());
+
+        let children: Vec<Value> = <Void as StaticBase<VMGenericSet>>::children(set as *const _)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(children.len(), 3);
+        for child /*: &Value*/ in children.iter() {
+            assert!(set.contains(*child));
+        }
+    }
+
+    unsafe {
+        (set_vt.drop_fn)(v.untagged_ptr_field() as *mut _);
+
+        let dyn_base: *mut dyn DynBase = string1.ptr;
+        let dyn_base: Box<dyn DynBase> = Box::from_raw(dyn_base);
+        drop(dyn_base);
+
+        let dyn_base: *mut dyn DynBase = string2.ptr;
+        let dyn_base: Box<dyn DynBase> = Box::from_raw(dyn_base);
+        drop(dyn_base);
+    }
+}
+
+#[test] fn test_generic_map() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let int_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+    let string_type: NonNull<TyckInfo> = tyck_info_pool.get_string_type();
+    let map_vt: GenericTypeVT = create_vm_map_vt(&mut tyck_info_pool, int_type, string_type);
+    assert_eq!(map_vt.type_name, "map");
+
+    let v: Value = Value::new_container(map_ctor(), &map_vt as _);
+    assert!(v.is_container());
+
+    let string1: Value = Value::new_owned("114".to_string());
+    let string2: Value = Value::new_owned("514".to_string());
+    let string3: Value = Value::new_owned("1919810".to_string());
+
+    unsafe {
+        let map: &mut VMGenericMap = &mut *(v.get_as_mut_ptr());
+        assert!(map.is_empty());
+
+        assert!(map.put(Value::new_int(1), string1).is_none());
+        assert!(map.put(Value::new_int(2), string2).is_none());
+        assert_eq!(map.put(Value::new_int(1), string3).unwrap().ptr_repr, string1.ptr_repr);
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(Value::new_int(1)).unwrap().ptr_repr, string3.ptr_repr);
+        assert!(map.get(Value::new_int(3)).is_none());
+
+        let children: Vec<Value> = <Void as StaticBase<VMGenericMap>>::children(map as *const _)
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(children.len(), 4);
+        assert_eq!(children[0].vt_data.inner.int_value, 1);
+        assert_eq!(children[1].vt_data.inner.int_value, 2);
+        assert_eq!(children[2].ptr_repr, string3.ptr_repr);
+        assert_eq!(children[3].ptr_repr, string2.ptr_repr);
+
+        // the last entry gets moved into the place of the removed one
+        assert_eq!(map.remove(Value::new_int(1)).unwrap().ptr_repr, string3.ptr_repr);
+        assert!(map.remove(Value::new_int(1)).is_none());
+        assert!(!map.contains_key(Value::new_int(1)));
+        assert!(map.contains_key(Value::new_int(2)));
+        assert_eq!(map.keys().len(), 1);
+        assert_eq!(map.keys()[0].vt_data.inner.int_value, 2);
+        assert_eq!(map.get(Value::new_int(2)).unwrap().ptr_repr, string2.ptr_repr);
+    }
+
+    unsafe {
+        (map_vt.drop_fn)(v.untagged_ptr_field() as *mut _);
+
+        for string /*: Value*/ in [string1, string2, string3] {
+            let dyn_base: *mut dyn DynBase = string.ptr;
+            let dyn_base: Box<dyn DynBase> = Box::from_raw(dyn_base);
+            drop(dyn_base);
+        }
+    }
+}
+
+/// Ensure unchecked exceptions are rendered in human-readable form
+#[test] fn test_unchecked_exception_display() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+
+    let exc: UncheckedException = UncheckedException::InvalidBinaryOp {
+        bin_op: '+',
+        lhs: Value::new_int(114),
+        rhs: Value::new_char('c')
+    };
+    assert_eq!(format!("{}", exc), "invalid binary operation: 114 + 'c'");
+    assert_eq!(format!("{:?}", exc), "InvalidBinaryOp(invalid binary operation: 114 + 'c')");
+
+    let int_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+    let nullable_int_type: NonNull<TyckInfo> = tyck_info_pool.create_nullable_type(int_type);
+    let exc: UncheckedException = UncheckedException::TypeCheckFailure {
+        object: Value::new_null(),
+        expected_type: nullable_int_type
+    };
+    assert_eq!(format!("{}", exc), "type check failed: expected ?int, got null");
+
+    let value: Value = Value::new_owned(TestStruct2());
+    let exc: UncheckedException = UncheckedException::OwnershipCheckFailure {
+        object: value,
+        expected_mask: OWN_INFO_GLOBAL_MASK
+    };
+    assert_eq!(
+        format!("{}", exc),
+        format!("ownership check failed on <{}>: expected G-----, got -RWMCO", TEST_STRUCT_NAME2)
+    );
+
+    let exc: UncheckedException = UncheckedException::IndexOutOfBounds {
+        indexed: value,
+        index: -1
+    };
+    assert_eq!(format!("{}", exc), format!("index -1 out of bounds of <{}>", TEST_STRUCT_NAME2));
+
+    let exc: UncheckedException = UncheckedException::NotIterable { value: Value::new_int(42) };
+    assert_eq!(format!("{}", exc), "42 is not iterable");
+
+    unsafe {
+        let dyn_base: Box<dyn DynBase> = Box::from_raw(value.ptr);
+        drop(dyn_base);
+    }
+}
+
+struct ConvertTestContext {
+    managed: Vec<Value>
+}
+
+impl VMContext for ConvertTestContext {
+    fn add_heap_managed(&mut self, value: Value) {
+        self.managed.push(value);
+    }
+
+    fn mark(&mut self, _value: Value) {}
+
+    fn pin_objects(&mut self, _values: &[Value]) -> *mut bool {
+        null_mut()
+    }
+}
+
+impl Drop for ConvertTestContext {
+    fn drop(&mut self) {
+        for value /*: Value*/ in self.managed.drain(..) {
+            unsafe {
+                if value.is_container() {
+                    let vt: *const GenericTypeVT = value.ptr_repr.trivia as *const _;
+                    ((*vt).drop_fn)(value.untagged_ptr_field() as *mut _);
+                } else {
+                    let dyn_base: Box<dyn DynBase> = Box::from_raw(value.ptr);
+                    drop(dyn_base);
+                }
+            }
+        }
+    }
+}
+
+#[test] fn test_value_convert() {
+    let mut context: ConvertTestContext = ConvertTestContext { managed: vec![] };
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+
+    unsafe {
+        let value: Value = 42i64.into_value(&mut context);
+        assert_eq!(i64::from_value(value, &mut tyck_info_pool).unwrap(), 42);
+        let value: Value = 4.2f64.into_value(&mut context);
+        assert_eq!(f64::from_value(value, &mut tyck_info_pool).unwrap(), 4.2);
+        let value: Value = 'c'.into_value(&mut context);
+        assert_eq!(char::from_value(value, &mut tyck_info_pool).unwrap(), 'c');
+        let value: Value = true.into_value(&mut context);
+        assert!(bool::from_value(value, &mut tyck_info_pool).unwrap());
+        assert!(context.managed.is_empty());
+
+        let value: Value = "114514".to_string().into_value(&mut context);
+        assert_eq!(String::from_value(value, &mut tyck_info_pool).unwrap(), "114514");
+        assert_eq!(context.managed.len(), 1);
+
+        let value: Value = None::<i64>.into_value(&mut context);
+        assert!(value.is_null());
+        assert_eq!(Option::<i64>::from_value(value, &mut tyck_info_pool).unwrap(), None);
+        let value: Value = Some(7i64).into_value(&mut context);
+        assert_eq!(Option::<i64>::from_value(value, &mut tyck_info_pool).unwrap(), Some(7));
+
+        let value: Value = vec!["a".to_string(), "b".to_string()].into_value(&mut context);
+        assert!(value.is_container());
+        assert_eq!((*(value.ptr_repr.trivia as *const GenericTypeVT)).type_name, "vector");
+        assert_eq!(Vec::<String>::from_value(value, &mut tyck_info_pool).unwrap(), vec!["a", "b"]);
+        assert_eq!(context.managed.len(), 4);
+
+        let mut map: HashMap<String, Vec<i64>> = HashMap::new();
+        map.insert("foo".to_string(), vec![1, 2]);
+        map.insert("bar".to_string(), vec![]);
+        let value: Value = map.clone().into_value(&mut context);
+        assert_eq!(HashMap::<String, Vec<i64>>::from_value(value, &mut tyck_info_pool).unwrap(),
+                   map);
+
+        let values = (1i64, "x".to_string(), false).into_values(&mut context);
+        assert_eq!(values.len(), <(i64, String, bool) as FromValues>::COUNT);
+        let (a, b, c) = <(i64, String, bool)>::from_values(&values, &mut tyck_info_pool).unwrap();
+        assert_eq!((a, b.as_str(), c), (1, "x", false));
+    }
+}
+
+#[test] fn test_value_convert_failure() {
+    let mut context: ConvertTestContext = ConvertTestContext { managed: vec![] };
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+
+    unsafe {
+        let value: Value = true.into_value(&mut context);
+        if let Err(UncheckedException::TypeCheckFailure { expected_type, .. }) =
+            i64::from_value(value, &mut tyck_info_pool)
+        {
+            assert_eq!(expected_type, tyck_info_pool.get_int_type());
+        } else {
+            panic!()
+        }
+
+        let value: Value = vec![1i64, 2i64].into_value(&mut context);
+        if let Err(UncheckedException::TypeCheckFailure { object, expected_type }) =
+            Vec::<bool>::from_value(value, &mut tyck_info_pool)
+        {
+            assert_eq!(i64::from_value(object, &mut tyck_info_pool).unwrap(), 1);
+            assert_eq!(expected_type, tyck_info_pool.get_bool_type());
+        } else {
+            panic!()
+        }
+        assert!(String::from_value(value, &mut tyck_info_pool).is_err());
+        assert!(i64::from_value(Value::new_null(), &mut tyck_info_pool).is_err());
+
+        let value: Value = "moved".to_string().into_value(&mut context);
+        value.set_ownership_info(OwnershipInfo::MovedToRust);
+        assert!(matches!(
+            String::from_value(value, &mut tyck_info_pool),
+            Err(UncheckedException::OwnershipCheckFailure { expected_mask: OWN_INFO_READ_MASK, .. })
+        ));
+        value.set_ownership_info(OwnershipInfo::VMOwned);
+    }
+}
+
+#[derive(StaticBase)]
+#[pr47(local, name = "test.Node")]
+#[allow(dead_code)]
+struct DeriveTestStruct {
+    value: Value,
+    values: Vec<Value>,
+    nullable: Option<Value>,
+    #[pr47(skip)]
+    skipped: Value,
+    int_field: i64
+}
+
+#[derive(StaticBase)]
+#[pr47(local)]
+#[allow(dead_code)]
+struct DeriveTestStruct2(i64, f64);
+
+#[test] fn test_derive_static_base() {
+    assert_eq!(<Void as StaticBase<DeriveTestStruct>>::type_name(), "test.Node");
+    assert_eq!(<Void as StaticBase<DeriveTestStruct2>>::type_name(), "DeriveTestStruct2");
+
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let tyck_info: NonNull<TyckInfo> =
+        <Void as StaticBase<DeriveTestStruct>>::tyck_info(&mut tyck_info_pool);
+    assert!(<Void as StaticBase<DeriveTestStruct>>::tyck(unsafe { tyck_info.as_ref() }));
+
+    let test_struct: DeriveTestStruct = DeriveTestStruct {
+        value: Value::new_int(1),
+        values: vec![Value::new_int(2), Value::new_int(3)],
+        nullable: Some(Value::new_int(4)),
+        skipped: Value::new_int(5),
+        int_field: 6
+    };
+    let children: ChildrenType =
+        <Void as StaticBase<DeriveTestStruct>>::children(&test_struct as *const _);
+    let children: Vec<i64> = children.unwrap()
+        .map(|value: Value| unsafe { value.vt_data.inner.int_value })
+        .collect();
+    assert_eq!(children, vec![1, 2, 3, 4]);
+
+    let test_struct2: DeriveTestStruct2 = DeriveTestStruct2(1, 2.0);
+    assert!(<Void as StaticBase<DeriveTestStruct2>>::children(&test_struct2 as *const _).is_none());
+}
+
+#[cfg(feature = "al31fm2")]
+#[test] fn test_value_convert_gc() {
+    use crate::vm::al31fm2::{AL31F, Combustor};
+    use crate::vm::al31fm2::alloc::Alloc;
+    use crate::vm::al31fm2::alloc::default_alloc::DefaultAlloc;
+
+    // collect garbage on every allocation, so unreachable elements would get freed immediately
+    let mut vm: AL31F<DefaultAlloc> = AL31F::new(DefaultAlloc::with_max_debt(0, 0));
+    vm.alloc.set_gc_allowed(true);
+    let mut combustor: Combustor<DefaultAlloc> = Combustor::new(NonNull::from(&mut vm));
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+
+    let nested: Vec<Vec<String>> = (0..8)
+        .map(|i: i32| (0..8).map(|j: i32| format!("{}-{}", i, j)).collect())
+        .collect();
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    map.insert("foo".to_string(), vec!["1".to_string(), "2".to_string()]);
+    map.insert("bar".to_string(), vec!["3".to_string()]);
+
+    unsafe {
+        let value: Value = nested.clone().into_value(&mut combustor);
+        assert_eq!(Vec::<Vec<String>>::from_value(value, &mut tyck_info_pool).unwrap(), nested);
+
+        let value: Value = map.clone().into_value(&mut combustor);
+        assert_eq!(HashMap::<String, Vec<String>>::from_value(value, &mut tyck_info_pool).unwrap(),
+                   map);
+
+        let values = ("x".to_string(), nested.clone(), "y".to_string())
+            .into_values(&mut combustor);
+        let (a, b, c) = <(String, Vec<Vec<String>>, String)>::from_values(
+            &values, &mut tyck_info_pool
+        ).unwrap();
+        assert_eq!((a.as_str(), b, c.as_str()), ("x", nested, "y"));
+    }
+}
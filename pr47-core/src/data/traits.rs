@@ -1,12 +1,15 @@
 use std::any::TypeId;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::iter::Iterator;
 use std::ptr::NonNull;
 
-use xjbutil::void::Void;
-
 use crate::data::Value;
 use crate::data::tyck::{TyckInfo, TyckInfoPool};
 
+/// Re-exported for code generated by `pr47_codegen`, so that user crates need not depend on
+/// `xjbutil` directly
+pub use xjbutil::void::Void;
+
 pub type ChildrenType = Option<Box<dyn Iterator<Item=Value> + 'static>>;
 
 pub trait StaticBase<T: 'static> {
@@ -41,11 +44,75 @@ pub trait StaticBase<T: 'static> {
 // impl<T, E> !StaticBase<Result<T>> for Void {}
 
 impl StaticBase<String> for Void {
+    fn tyck_info(tyck_info_pool: &mut TyckInfoPool) -> NonNull<TyckInfo> {
+        tyck_info_pool.get_string_type()
+    }
+
     fn type_name() -> String {
         "string".into()
     }
 }
 
+/// Rust data containing VM values, which should be reported as children to the garbage collector.
+/// Used by `#[derive(StaticBase)]` to trace fields.
+pub trait ChildValues {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a>;
+}
+
+impl ChildValues for Value {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a> {
+        Box::new(std::iter::once(*self))
+    }
+}
+
+impl<T: ChildValues> ChildValues for Option<T> {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a> {
+        Box::new(self.iter().flat_map(T::child_values))
+    }
+}
+
+impl<T: ChildValues + ?Sized> ChildValues for Box<T> {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a> {
+        (**self).child_values()
+    }
+}
+
+impl<T: ChildValues> ChildValues for [T] {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a> {
+        Box::new(self.iter().flat_map(T::child_values))
+    }
+}
+
+impl<T: ChildValues, const N: usize> ChildValues for [T; N] {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a> {
+        Box::new(self.iter().flat_map(T::child_values))
+    }
+}
+
+impl<T: ChildValues> ChildValues for Vec<T> {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a> {
+        Box::new(self.iter().flat_map(T::child_values))
+    }
+}
+
+impl<T: ChildValues> ChildValues for VecDeque<T> {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a> {
+        Box::new(self.iter().flat_map(T::child_values))
+    }
+}
+
+impl<K, V: ChildValues, S> ChildValues for HashMap<K, V, S> {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a> {
+        Box::new(self.values().flat_map(V::child_values))
+    }
+}
+
+impl<K, V: ChildValues> ChildValues for BTreeMap<K, V> {
+    fn child_values<'a>(&'a self) -> Box<dyn Iterator<Item=Value> + 'a> {
+        Box::new(self.values().flat_map(V::child_values))
+    }
+}
+
 pub trait VMType<T: 'static> {}
 
 impl<T> VMType<T> for Void where T: 'static, Void: StaticBase<T> {}
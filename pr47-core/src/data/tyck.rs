@@ -1,6 +1,7 @@
 use std::any::TypeId;
 use std::collections::HashSet;
 use std::cmp::{Eq, PartialEq};
+use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use std::hint::unreachable_unchecked;
 use std::mem::{discriminant, forget};
@@ -9,7 +10,11 @@ use std::ptr::NonNull;
 use xjbutil::korobka::Korobka;
 use xjbutil::std_ext::{BoxedExt, VecExt};
 
+use crate::builtins::closure::Closure;
+use crate::builtins::map::VMGenericMap;
 use crate::builtins::object::Object;
+use crate::builtins::set::VMGenericSet;
+use crate::builtins::vec::VMGenericVec;
 
 pub struct ContainerTyckInfo {
     pub type_id: TypeId,
@@ -194,6 +199,72 @@ impl PartialEq for TyckInfo {
 
 impl Eq for TyckInfo {}
 
+fn fmt_type_id(type_id: TypeId, f: &mut Formatter<'_>) -> std::fmt::Result {
+    if type_id == TypeId::of::<i64>() {
+        write!(f, "int")
+    } else if type_id == TypeId::of::<f64>() {
+        write!(f, "float")
+    } else if type_id == TypeId::of::<char>() {
+        write!(f, "char")
+    } else if type_id == TypeId::of::<bool>() {
+        write!(f, "bool")
+    } else if type_id == TypeId::of::<String>() {
+        write!(f, "string")
+    } else if type_id == TypeId::of::<Object>() {
+        write!(f, "object")
+    } else if type_id == TypeId::of::<VMGenericVec>() {
+        write!(f, "vector")
+    } else if type_id == TypeId::of::<VMGenericSet>() {
+        write!(f, "set")
+    } else if type_id == TypeId::of::<VMGenericMap>() {
+        write!(f, "map")
+    } else if type_id == TypeId::of::<Closure>() {
+        write!(f, "closure")
+    } else {
+        write!(f, "{:?}", type_id)
+    }
+}
+
+fn fmt_type_list(types: &[NonNull<TyckInfo>], f: &mut Formatter<'_>) -> std::fmt::Result {
+    for (i, ty) /*: (usize, &NonNull<TyckInfo>)*/ in types.iter().enumerate() {
+        if i != 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", unsafe { ty.as_ref() })?;
+    }
+    Ok(())
+}
+
+impl Display for TyckInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TyckInfo::AnyType => write!(f, "any"),
+            TyckInfo::Plain(type_id) => fmt_type_id(*type_id, f),
+            TyckInfo::Nullable(underlying) => write!(f, "?{}", unsafe { underlying.as_ref() }),
+            TyckInfo::Container(ContainerTyckInfo { type_id, params }) => {
+                fmt_type_id(*type_id, f)?;
+                write!(f, "<")?;
+                fmt_type_list(unsafe { params.as_ref() }, f)?;
+                write!(f, ">")
+            },
+            TyckInfo::Function(FunctionTyckInfo { params, rets, exceptions }) => {
+                write!(f, "func(")?;
+                fmt_type_list(unsafe { params.as_ref() }, f)?;
+                write!(f, ") -> (")?;
+                fmt_type_list(unsafe { rets.as_ref() }, f)?;
+                write!(f, ")")?;
+                let exceptions: &[NonNull<TyckInfo>] = unsafe { exceptions.as_ref() };
+                if !exceptions.is_empty() {
+                    write!(f, " throws (")?;
+                    fmt_type_list(exceptions, f)?;
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 pub struct TyckInfoCommons {
     tyck_info_int: TyckInfo,
     tyck_info_float: TyckInfo,
@@ -14,7 +14,6 @@ pub const err_unexpected_eoi: u32 = 2006;
 pub const err_import_decl_disallow_attr: u32 = 2007;
 pub const err_export_decl_disallow_attr: u32 = 2008;
 pub const err_expected_any_of_0_got_1: u32 = 2009;
-pub const err_no_top_level_var_decl: u32 = 2010;
 pub const err_expected_unqual_id: u32 = 2011;
 pub const err_missing_type_got_0: u32 = 2012;
 pub const err_bad_num_literal_hex_oct_bin: u32 = 2013;
@@ -24,6 +23,23 @@ pub const err_bad_escape: u32 = 2016;
 pub const err_unclosed_string: u32 = 2017;
 pub const err_duplicate_syntax_action_name_0: u32 = 2018;
 pub const err_undefined_identifier_0: u32 = 2019;
+pub const err_duplicate_export_0: u32 = 2020;
+pub const err_no_method_0_on_type_1: u32 = 2021;
+pub const err_method_0_on_nullable_type_1: u32 = 2022;
+pub const err_ambiguous_async_method_0: u32 = 2023;
+pub const err_method_0_expects_1_args_got_2: u32 = 2024;
+pub const err_no_property_0_on_type_1: u32 = 2025;
+pub const err_property_0_on_nullable_type_1: u32 = 2026;
+pub const err_property_0_is_read_only: u32 = 2027;
+pub const err_property_0_expects_type_1_got_2: u32 = 2028;
+pub const err_generic_type_0_expects_1_params_got_2: u32 = 2029;
+pub const err_method_0_expects_arg_type_1_got_2: u32 = 2030;
+pub const err_type_0_is_not_iterable: u32 = 2031;
+pub const err_void_type_not_allowed: u32 = 2032;
+pub const err_cannot_deduce_type: u32 = 2033;
+pub const err_unknown_type_0: u32 = 2034;
+pub const err_cannot_init_0_of_type_1_with_2: u32 = 2035;
+pub const err_redefinition_of_0: u32 = 2036;
 
 // warnings
 pub const warn_commence_placeholder: u32 = 4000;
@@ -54,7 +70,6 @@ pub const fn diag_message(code: u32) -> &'static str {
             err_import_decl_disallow_attr => "cannot add attribute to `import`s",
             err_export_decl_disallow_attr => "cannot add attribute to `export`s",
             err_expected_any_of_0_got_1 => "expected any of ?0, got ?1",
-            err_no_top_level_var_decl => "variable declaration cannot appear at top level",
             err_expected_unqual_id => "expected unqualified identifier",
             err_missing_type_got_0 => "missing type specifier, got ?0",
             err_bad_num_literal_hex_oct_bin =>
@@ -65,6 +80,31 @@ pub const fn diag_message(code: u32) -> &'static str {
             err_unclosed_string => "unclosed string literal",
             err_duplicate_syntax_action_name_0 => "duplicate syntax action name `?0`",
             err_undefined_identifier_0 => "undefined identifier `?0`",
+            err_duplicate_export_0 => "`?0` has already been exported",
+            err_no_method_0_on_type_1 => "type `?1` does not have method `?0`",
+            err_method_0_on_nullable_type_1 =>
+                "cannot call method `?0` on value of nullable type `?1`",
+            err_ambiguous_async_method_0 =>
+                "cannot call method `?0` on `any`, it is async on some types but not on others",
+            err_method_0_expects_1_args_got_2 => "method `?0` expects ?1 arguments, got ?2",
+            err_no_property_0_on_type_1 => "type `?1` does not have property `?0`",
+            err_property_0_on_nullable_type_1 =>
+                "cannot access property `?0` on value of nullable type `?1`",
+            err_property_0_is_read_only => "cannot assign to read-only property `?0`",
+            err_property_0_expects_type_1_got_2 =>
+                "property `?0` is of type `?1`, cannot assign `?2` to it",
+            err_generic_type_0_expects_1_params_got_2 =>
+                "generic type ?0 expects ?1 type parameters, got ?2",
+            err_method_0_expects_arg_type_1_got_2 =>
+                "method `?0` expects argument of type `?1`, got `?2`",
+            err_type_0_is_not_iterable =>
+                "cannot iterate over value of type `?0`",
+            err_void_type_not_allowed => "`void` cannot be used as a value type",
+            err_cannot_deduce_type => "cannot deduce type here",
+            err_unknown_type_0 => "unknown type `?0`",
+            err_cannot_init_0_of_type_1_with_2 =>
+                "cannot initialize `?0` of type `?1` with value of type `?2`",
+            err_redefinition_of_0 => "`?0` has already been defined in this scope",
             _ => "INVALID_ERROR_CODE"
         }
     } else /* if code > note_commence_placeholder */ {
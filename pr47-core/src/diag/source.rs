@@ -36,6 +36,10 @@ impl SourceManager {
         file_id as u32
     }
 
+    pub fn file_name(&self, file_id: u32) -> &str {
+        &self.files[file_id as usize]
+    }
+
     pub fn compute_coord(&self, file_id: u32, file_offset: u32) -> (&str, SourceCoord) {
         let file_id: usize = file_id as usize;
         let file_offset: usize = file_offset as usize;
@@ -0,0 +1,392 @@
+//! # `engine`: high-level embedding facade
+//!
+//! `Engine` collects host FFI functions by name, and host methods and properties by receiver
+//! type, and turns programs into `Module`s. A `Module` is an immutable, shareable program
+//! together with its exported function names, and an `Instance` is a VM running some module, on
+//! which exported functions can be called with Rust-typed arguments and return values. All
+//! `unsafe` VM internals stay behind this facade.
+//!
+//! ## ⚠️⚠️⚠️ Develop stage note ⚠️⚠️⚠
+//! The compiler does not have a code generation backend yet, so `Engine::check` only runs the
+//! frontend and reports diagnostics. Use `Engine::load` to load a prebuilt `CompiledProgram`.
+
+pub mod module;
+pub mod typed;
+
+#[cfg(test)] mod test;
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+
+use crate::data::convert::{FromValue, IntoValue};
+use crate::data::traits::{StaticBase, Void};
+use crate::data::tyck::TyckInfoPool;
+use crate::ffi::{DataOption, Signature};
+use crate::ffi::iter::{HostIterable, ITER_ELEM_METHOD, IterElem};
+use crate::ffi::property::{PropertyGetter, PropertySetter};
+use crate::ffi::sync_fn::Function as FFIFunction;
+use crate::vm::al31fm2::Combustor;
+use crate::vm::al31fm2::alloc::Alloc;
+use crate::vm::al31fm2::compiled::{
+    CompiledProgram,
+    Method,
+    MethodFunc,
+    MethodTable,
+    Property,
+    PropertyTable
+};
+use crate::vm::al31fm2::exception::Exception;
+
+pub use module::{Instance, Module};
+pub use typed::FromRets;
+
+#[cfg(feature = "async")] use crate::ffi::async_fn::AsyncFunction as FFIAsyncFunction;
+#[cfg(feature = "async")] use crate::vm::al31fm2::{AL31F, AsyncCombustor};
+#[cfg(feature = "compiler")] use std::cell::RefCell;
+#[cfg(feature = "compiler")] use crate::diag::{DiagContext, Diagnostic};
+#[cfg(feature = "compiler")] use crate::diag::source::SourceManager;
+#[cfg(feature = "compiler")] use crate::parse::parser::Parser;
+#[cfg(feature = "compiler")] use crate::syntax::ConcreteProgram;
+#[cfg(feature = "compiler")] use crate::vm::al31fm2::compiled::{Export, lower_top_level_vars};
+
+pub struct Engine<A: Alloc> {
+    tyck_info_pool: TyckInfoPool,
+
+    ffi_funcs: Vec<&'static dyn FFIFunction<Combustor<A>>>,
+    ffi_names: HashMap<String, usize>,
+
+    #[cfg(feature = "async")]
+    async_ffi_funcs: Vec<&'static dyn FFIAsyncFunction<AL31F<A>, AsyncCombustor<A>>>,
+    #[cfg(feature = "async")]
+    async_ffi_names: HashMap<String, usize>,
+
+    methods: MethodTable,
+    properties: PropertyTable,
+
+    #[cfg(feature = "compiler")]
+    source_mgr: SourceManager
+}
+
+impl<A: Alloc> Engine<A> {
+    pub fn new() -> Self {
+        Self {
+            tyck_info_pool: TyckInfoPool::new(),
+
+            ffi_funcs: Vec::new(),
+            ffi_names: HashMap::new(),
+
+            #[cfg(feature = "async")]
+            async_ffi_funcs: Vec::new(),
+            #[cfg(feature = "async")]
+            async_ffi_names: HashMap::new(),
+
+            methods: MethodTable::new(),
+            properties: PropertyTable::new(),
+
+            #[cfg(feature = "compiler")]
+            source_mgr: SourceManager::new()
+        }
+    }
+
+    pub fn tyck_info_pool(&mut self) -> &mut TyckInfoPool {
+        &mut self.tyck_info_pool
+    }
+
+    /// Register FFI function `func` under `name`, returns its FFI function ID, or `None` if the
+    /// name has already been taken
+    pub fn register_function(
+        &mut self,
+        name: impl Into<String>,
+        func: &'static dyn FFIFunction<Combustor<A>>
+    ) -> Option<usize> {
+        register(&mut self.ffi_funcs, &mut self.ffi_names, name.into(), func)
+    }
+
+    /// Register async FFI function `func` under `name`, returns its async FFI function ID, or
+    /// `None` if the name has already been taken
+    #[cfg(feature = "async")]
+    pub fn register_async_function(
+        &mut self,
+        name: impl Into<String>,
+        func: &'static dyn FFIAsyncFunction<AL31F<A>, AsyncCombustor<A>>
+    ) -> Option<usize> {
+        register(&mut self.async_ffi_funcs, &mut self.async_ffi_names, name.into(), func)
+    }
+
+    /// Register FFI function `func` as method `name` of host type `T`. The first parameter of
+    /// `func` is the receiver, which must be taken by shared (`&T`) or mutably shared (`&mut T`)
+    /// reference. `func` is also registered as an FFI function named `TypeName::name`.
+    ///
+    /// Returns the FFI function ID of `func`, or `None` if `T` already has a method named `name`,
+    /// or `func` does not take a receiver, or `func` is variadic.
+    pub fn register_method<T: 'static>(
+        &mut self,
+        name: &str,
+        func: &'static dyn FFIFunction<Combustor<A>>
+    ) -> Option<usize>
+        where Void: StaticBase<T>
+    {
+        let type_id: TypeId = <Void as StaticBase<T>>::type_id();
+        if self.methods.get(type_id, name).is_some() {
+            return None;
+        }
+
+        let signature: Signature = func.signature(&mut self.tyck_info_pool);
+        let mutable_self: bool = receiver_mutability(&signature)?;
+        let ffi_func_id: usize = register(
+            &mut self.ffi_funcs,
+            &mut self.ffi_names,
+            format!("{}::{}", <Void as StaticBase<T>>::type_name(), name),
+            func
+        )?;
+        self.methods.add(type_id, name, Method {
+            func: MethodFunc::Sync(ffi_func_id),
+            mutable_self,
+            func_type: signature.func_type
+        });
+        Some(ffi_func_id)
+    }
+
+    /// Register async FFI function `func` as method `name` of host type `T`, see
+    /// `Engine::register_method`. Returns the async FFI function ID of `func`.
+    #[cfg(feature = "async")]
+    pub fn register_async_method<T: 'static>(
+        &mut self,
+        name: &str,
+        func: &'static dyn FFIAsyncFunction<AL31F<A>, AsyncCombustor<A>>
+    ) -> Option<usize>
+        where Void: StaticBase<T>
+    {
+        let type_id: TypeId = <Void as StaticBase<T>>::type_id();
+        if self.methods.get(type_id, name).is_some() {
+            return None;
+        }
+
+        let signature: Signature = func.signature(&mut self.tyck_info_pool);
+        let mutable_self: bool = receiver_mutability(&signature)?;
+        let async_ffi_func_id: usize = register(
+            &mut self.async_ffi_funcs,
+            &mut self.async_ffi_names,
+            format!("{}::{}", <Void as StaticBase<T>>::type_name(), name),
+            func
+        )?;
+        self.methods.add(type_id, name, Method {
+            func: MethodFunc::Async(async_ffi_func_id),
+            mutable_self,
+            func_type: signature.func_type
+        });
+        Some(async_ffi_func_id)
+    }
+
+    /// Make host type `T` iterable by `for` loops, by registering `IterElem<T>` as its method
+    /// `ITER_ELEM_METHOD`. Returns the FFI function ID of the registered `IterElem<T>`, or `None`
+    /// if `T` has already been registered as iterable.
+    pub fn register_iterable<T: HostIterable>(&mut self) -> Option<usize>
+        where Void: StaticBase<T>
+    {
+        let func: &'static dyn FFIFunction<Combustor<A>> =
+            Box::leak(Box::new(IterElem::<T>::new()));
+        self.register_method::<T>(ITER_ELEM_METHOD, func)
+    }
+
+    /// Register property `name` of type `V` on host type `T`, with Rust `getter` and `setter`.
+    /// The getter and setter are also registered as FFI functions named `TypeName::name.get` and
+    /// `TypeName::name.set`. The setter fails on receivers which are not writeable.
+    ///
+    /// Returns `false` if `T` already has a property named `name`.
+    pub fn register_property<T, V>(
+        &mut self,
+        name: &str,
+        getter: impl 'static + Send + Sync + Fn(&T) -> V,
+        setter: impl 'static + Send + Sync + Fn(&mut T, V)
+    ) -> bool
+        where T: 'static,
+              V: 'static + IntoValue + FromValue,
+              Void: StaticBase<T>
+    {
+        let getter: &'static dyn FFIFunction<Combustor<A>> =
+            Box::leak(Box::new(PropertyGetter::new(getter)));
+        let setter: &'static dyn FFIFunction<Combustor<A>> =
+            Box::leak(Box::new(PropertySetter::new(setter)));
+        self.add_property::<T, V>(name, getter, Some(setter))
+    }
+
+    /// Register read-only property `name` of type `V` on host type `T`, see
+    /// `Engine::register_property`
+    pub fn register_readonly_property<T, V>(
+        &mut self,
+        name: &str,
+        getter: impl 'static + Send + Sync + Fn(&T) -> V
+    ) -> bool
+        where T: 'static,
+              V: 'static + IntoValue + FromValue,
+              Void: StaticBase<T>
+    {
+        let getter: &'static dyn FFIFunction<Combustor<A>> =
+            Box::leak(Box::new(PropertyGetter::new(getter)));
+        self.add_property::<T, V>(name, getter, None)
+    }
+
+    fn add_property<T, V>(
+        &mut self,
+        name: &str,
+        getter: &'static dyn FFIFunction<Combustor<A>>,
+        setter: Option<&'static dyn FFIFunction<Combustor<A>>>
+    ) -> bool
+        where T: 'static,
+              V: FromValue,
+              Void: StaticBase<T>
+    {
+        let type_id: TypeId = <Void as StaticBase<T>>::type_id();
+        let getter_name: String = format!("{}::{}.get", <Void as StaticBase<T>>::type_name(), name);
+        let setter_name: String = format!("{}::{}.set", <Void as StaticBase<T>>::type_name(), name);
+        if self.properties.get(type_id, name).is_some()
+            || self.ffi_names.contains_key(&getter_name)
+            || self.ffi_names.contains_key(&setter_name)
+        {
+            return false;
+        }
+
+        let getter: usize = register(&mut self.ffi_funcs, &mut self.ffi_names, getter_name, getter)
+            .unwrap();
+        let setter: Option<usize> = setter.map(|setter: &'static dyn FFIFunction<Combustor<A>>| {
+            register(&mut self.ffi_funcs, &mut self.ffi_names, setter_name, setter).unwrap()
+        });
+        self.properties.add(type_id, name, Property {
+            getter,
+            setter,
+            ty: V::tyck_info(&mut self.tyck_info_pool)
+        })
+    }
+
+    pub fn ffi_function_id(&self, name: &str) -> Option<usize> {
+        self.ffi_names.get(name).copied()
+    }
+
+    #[cfg(feature = "async")]
+    pub fn async_ffi_function_id(&self, name: &str) -> Option<usize> {
+        self.async_ffi_names.get(name).copied()
+    }
+
+    /// FFI function table, indexed by FFI function IDs, suitable for `CompiledProgram::ffi_funcs`
+    pub fn ffi_funcs(&self) -> Box<[&'static dyn FFIFunction<Combustor<A>>]> {
+        self.ffi_funcs.clone().into_boxed_slice()
+    }
+
+    /// Async FFI function table, indexed by async FFI function IDs, suitable for
+    /// `CompiledProgram::async_ffi_funcs`
+    #[cfg(feature = "async")]
+    pub fn async_ffi_funcs(&self)
+        -> Box<[&'static dyn FFIAsyncFunction<AL31F<A>, AsyncCombustor<A>>]>
+    {
+        self.async_ffi_funcs.clone().into_boxed_slice()
+    }
+
+    /// Method table of host types, suitable for `CompiledProgram::methods`
+    pub fn methods(&self) -> MethodTable {
+        self.methods.clone()
+    }
+
+    /// Property table of host types, suitable for `CompiledProgram::properties`
+    pub fn properties(&self) -> PropertyTable {
+        self.properties.clone()
+    }
+
+    /// Load a prebuilt program into a module
+    pub fn load(&self, program: CompiledProgram<A>) -> Module<A> {
+        Module::new(Arc::new(program))
+    }
+
+    #[cfg(feature = "compiler")]
+    pub fn source_manager(&self) -> &SourceManager {
+        &self.source_mgr
+    }
+
+    /// Run the compiler frontend on `source`, returning the diagnostics it reports, with
+    /// locations relative to `Engine::source_manager`. There is no code generation backend yet,
+    /// so this only checks `source`.
+    #[cfg(feature = "compiler")]
+    pub fn check(&mut self, file_name: &str, source: &str) -> Vec<Diagnostic> {
+        let file_id: u32 = self.source_mgr.add_file(file_name, source);
+        let diag: RefCell<DiagContext> = RefCell::new(DiagContext::new());
+
+        let mut parser: Parser = Parser::new(file_id, source, &diag);
+        let program: ConcreteProgram = parser.parse();
+        drop(parser);
+
+        let mut diag: DiagContext = diag.into_inner();
+        let mut symbols: HashMap<String, Export> = HashMap::new();
+        let _ = lower_top_level_vars(&program, &mut symbols, &mut diag);
+        diag.clear_reset()
+    }
+}
+
+fn register<F: ?Sized>(
+    funcs: &mut Vec<&'static F>,
+    names: &mut HashMap<String, usize>,
+    name: String,
+    func: &'static F
+) -> Option<usize> {
+    if names.contains_key(&name) {
+        return None;
+    }
+
+    let func_id: usize = funcs.len();
+    funcs.push(func);
+    names.insert(name, func_id);
+    Some(func_id)
+}
+
+/// Whether the receiver of a method is mutably shared, `None` if the method has no receiver or
+/// is variadic, which method calls do not support
+fn receiver_mutability(signature: &Signature) -> Option<bool> {
+    if signature.variadic.is_some() {
+        return None;
+    }
+    match signature.param_options.first()? {
+        DataOption::Share => Some(false),
+        DataOption::MutShare => Some(true),
+        _ => None
+    }
+}
+
+pub enum CallError {
+    /// No exported function has the given name
+    NoSuchFunction(String),
+    /// The script function returned a different number of values than expected
+    RetCountMismatch { expected: usize, got: usize },
+    /// Some return value cannot be converted into the expected Rust type
+    RetTypeMismatch { index: usize, expected: String },
+    /// The script function threw an exception
+    Exception(Exception)
+}
+
+impl From<Exception> for CallError {
+    fn from(exception: Exception) -> Self {
+        CallError::Exception(exception)
+    }
+}
+
+impl Display for CallError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallError::NoSuchFunction(name) => write!(f, "no exported function named `{}`", name),
+            CallError::RetCountMismatch { expected, got } =>
+                write!(f, "expected {} return values, got {}", expected, got),
+            CallError::RetTypeMismatch { index, expected } =>
+                write!(f, "return value #{} is not of type `{}`", index, expected),
+            CallError::Exception(exception) => write!(f, "{}", exception)
+        }
+    }
+}
+
+impl Debug for CallError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CallError::Exception(exception) => write!(f, "CallError::Exception({:?})", exception),
+            _ => write!(f, "CallError({})", self)
+        }
+    }
+}
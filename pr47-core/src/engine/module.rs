@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use smallvec::SmallVec;
+
+use crate::data::Value;
+use crate::data::convert::IntoValues;
+use crate::data::tyck::TyckInfoPool;
+use crate::engine::CallError;
+use crate::engine::typed::FromRets;
+use crate::vm::al31fm2::{AL31F, Combustor};
+use crate::vm::al31fm2::alloc::Alloc;
+use crate::vm::al31fm2::compiled::{CompiledProgram, Export};
+use crate::vm::al31fm2::exception::Exception;
+
+#[cfg(feature = "async")] use std::future::Future;
+#[cfg(feature = "async")] use crate::vm::al31fm2::isolate::Isolate;
+
+#[cfg(not(feature = "async"))] use std::marker::PhantomPinned;
+#[cfg(not(feature = "async"))] use xjbutil::unchecked::UncheckedSendSync;
+#[cfg(not(feature = "async"))] use crate::vm::al31fm2::globals::Globals;
+#[cfg(not(feature = "async"))]
+use crate::vm::al31fm2::executor::{VMThread, vm_thread_run_function, vm_thread_run_init};
+#[cfg(not(feature = "async"))] use crate::vm::al31fm2::stack::Stack;
+
+/// A loaded program together with the names of its exported functions. Cloning a `Module` is
+/// cheap, the program itself is shared.
+pub struct Module<A: Alloc> {
+    program: Arc<CompiledProgram<A>>,
+    exports: HashMap<String, usize>
+}
+
+impl<A: Alloc> Module<A> {
+    /// Create a module from `program`. Functions in the export table of `program` are callable
+    /// by their qualified names.
+    pub fn new(program: Arc<CompiledProgram<A>>) -> Self {
+        let exports: HashMap<String, usize> = program.exports.iter()
+            .filter_map(|(name, item): (&str, &Export)| match item {
+                Export::Function { func_id, .. } => Some((name.to_string(), *func_id)),
+                _ => None
+            })
+            .collect();
+
+        Self { program, exports }
+    }
+
+    pub fn program(&self) -> &Arc<CompiledProgram<A>> {
+        &self.program
+    }
+
+    /// Make function `func_id` callable as `name`, even if it's not in the export table of the
+    /// program. Returns `false` if there's no such function.
+    pub fn export(&mut self, name: impl Into<String>, func_id: usize) -> bool {
+        if func_id >= self.program.functions.len() {
+            return false;
+        }
+        self.exports.insert(name.into(), func_id);
+        true
+    }
+
+    pub fn exported_function(&self, name: &str) -> Option<usize> {
+        self.exports.get(name).copied()
+    }
+
+    /// Create a new VM instance running this module, with its own allocator and globals. The
+    /// initialization procedure of the program is run on the new instance, and exception thrown
+    /// by it is returned as error.
+    pub fn instantiate(&self, alloc: A) -> Result<Instance<A>, Exception> {
+        #[cfg(feature = "async")]
+        return pollster::block_on(self.instantiate_async(alloc));
+
+        #[cfg(not(feature = "async"))]
+        {
+            let vm: AL31F<A> =
+                AL31F::with_globals(alloc, Globals::for_program(&self.program));
+            let mut thread: Box<VMThread<A>> = Box::new(VMThread {
+                vm,
+                program: NonNull::from(&*self.program),
+                stack: Stack::new(),
+                _phantom: PhantomPinned
+            });
+            unsafe {
+                thread.vm.alloc.add_stack(&thread.stack);
+                pollster::block_on(vm_thread_run_init(&mut thread))?;
+            }
+
+            Ok(Instance {
+                thread,
+                program: self.program.clone(),
+                exports: self.exports.clone()
+            })
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub async fn instantiate_async(&self, alloc: A) -> Result<Instance<A>, Exception> {
+        Ok(Instance {
+            isolate: Isolate::new(alloc, self.program.clone()).await?,
+            exports: self.exports.clone()
+        })
+    }
+}
+
+impl<A: Alloc> Clone for Module<A> {
+    fn clone(&self) -> Self {
+        Self {
+            program: self.program.clone(),
+            exports: self.exports.clone()
+        }
+    }
+}
+
+/// A VM instance running some `Module`
+pub struct Instance<A: Alloc> {
+    #[cfg(feature = "async")]
+    isolate: Isolate<A>,
+
+    // `thread` refers to `program`, so it must be dropped first
+    #[cfg(not(feature = "async"))]
+    thread: Box<VMThread<A>>,
+    #[cfg(not(feature = "async"))]
+    program: Arc<CompiledProgram<A>>,
+
+    exports: HashMap<String, usize>
+}
+
+impl<A: Alloc> Instance<A> {
+    #[cfg(feature = "async")]
+    pub fn program(&self) -> &Arc<CompiledProgram<A>> {
+        self.isolate.program()
+    }
+
+    #[cfg(not(feature = "async"))]
+    pub fn program(&self) -> &Arc<CompiledProgram<A>> {
+        &self.program
+    }
+
+    /// Call exported function `name`, blocking until it completes
+    pub fn call<Args, Rets>(&mut self, name: &str, args: Args) -> Result<Rets, CallError>
+        where Args: IntoValues,
+              Rets: FromRets
+    {
+        #[cfg(feature = "async")]
+        return pollster::block_on(self.call_async(name, args));
+
+        #[cfg(not(feature = "async"))]
+        {
+            let func_id: usize = *self.exports.get(name)
+                .ok_or_else(|| CallError::NoSuchFunction(name.to_string()))?;
+            let args: SmallVec<[Value; 4]> = convert_args(&mut self.thread.vm, args);
+            let arg_pack: (&mut VMThread<A>, usize, &[Value]) = (&mut self.thread, func_id, &args);
+            let rets: Vec<Value> = pollster::block_on(unsafe {
+                vm_thread_run_function::<_, true>(UncheckedSendSync::new(arg_pack))?
+            }).into_inner()?;
+            unsafe { Rets::from_rets(&rets, &mut TyckInfoPool::new()) }
+        }
+    }
+
+    /// Call exported function `name`. The arguments are converted before this function
+    /// returns, so the returned future may be sent to other threads.
+    #[cfg(feature = "async")]
+    pub fn call_async<'a, Args, Rets>(
+        &'a mut self,
+        name: &str,
+        args: Args
+    ) -> impl Future<Output=Result<Rets, CallError>> + Send + 'a
+        where Args: IntoValues + 'a,
+              Rets: FromRets + 'a
+    {
+        let fut = match self.exports.get(name) {
+            Some(func_id /*: &usize*/) => {
+                let args: SmallVec<[Value; 4]> = convert_args(self.isolate.vm_mut(), args);
+                Ok(self.isolate.run_function(*func_id, &args))
+            },
+            None => Err(name.to_string())
+        };
+
+        async move {
+            let result: Result<Vec<Value>, Exception> = match fut {
+                Ok(fut) => fut.await,
+                Err(name /*: String*/) => return Err(CallError::NoSuchFunction(name))
+            };
+            let rets: Vec<Value> = result?;
+            unsafe { Rets::from_rets(&rets, &mut TyckInfoPool::new()) }
+        }
+    }
+}
+
+/// Convert `args` with garbage collection disabled. Converted heap objects are only reachable
+/// from the returned list until they get copied onto the VM stack, and `vm_thread_run_function`
+/// only enables garbage collection again after that.
+fn convert_args<A: Alloc, Args: IntoValues>(
+    vm: &mut AL31F<A>,
+    args: Args
+) -> SmallVec<[Value; 4]> {
+    vm.alloc.set_gc_allowed(false);
+    let mut combustor: Combustor<A> = Combustor::new(NonNull::from(vm));
+    args.into_values(&mut combustor)
+}
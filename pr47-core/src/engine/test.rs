@@ -0,0 +1,493 @@
+use std::any::TypeId;
+
+use xjbutil::boxed_slice;
+
+use crate::builtins::object::Object;
+use crate::engine::{CallError, Engine, Instance, Module};
+use crate::ffi::iter::ITER_ELEM_METHOD;
+use crate::vm::al31fm2::alloc::default_alloc::DefaultAlloc;
+use crate::vm::al31fm2::compiled::{
+    CompiledProgram,
+    Export,
+    Method,
+    MethodFunc,
+    MethodTable,
+    Property,
+    PropertyTable
+};
+use crate::vm::al31fm2::test_program::{
+    PR47BINDER_FFI_CHECKED_DIV,
+    PR47BINDER_FFI_OBJECT_FIELD_COUNT,
+    TestPoint,
+    TestRange,
+    echo_program,
+    exception_no_eh_program,
+    exception_program,
+    fibonacci_program,
+    init_proc_program
+};
+
+#[cfg(feature = "async")] use crate::util::async_utils::block_on_future;
+#[cfg(feature = "compiler")] use std::cell::RefCell;
+#[cfg(feature = "compiler")] use std::collections::HashMap;
+#[cfg(feature = "compiler")] use std::ptr::NonNull;
+#[cfg(feature = "compiler")] use crate::data::tyck::{TyckInfo, TyckInfoPool};
+#[cfg(feature = "compiler")] use crate::diag::DiagContext;
+#[cfg(feature = "compiler")] use crate::parse::parser::Parser;
+#[cfg(feature = "compiler")] use crate::sema::iter::{IterKind, IterResolution, resolve_iteration};
+#[cfg(feature = "compiler")] use crate::sema::map::{MapOp, MapOpSignature, resolve_map_op};
+#[cfg(feature = "compiler")] use crate::sema::method::{
+    MethodResolution,
+    MethodResolveError,
+    resolve_method
+};
+#[cfg(feature = "compiler")] use crate::sema::property::{
+    FieldResolution,
+    PropertyResolveError,
+    is_assignable,
+    resolve_property
+};
+#[cfg(feature = "compiler")] use crate::sema::ty::{map_type_params, resolve_generic_type};
+#[cfg(feature = "compiler")] use crate::syntax::ConcreteProgram;
+#[cfg(feature = "compiler")] use crate::syntax::decl::ConcreteDecl;
+#[cfg(feature = "compiler")] use crate::syntax::token::TokenInner;
+#[cfg(feature = "compiler")] use crate::vm::al31fm2::compiled::{ExportTable, lower_top_level_vars};
+
+fn fibonacci_module() -> Module<DefaultAlloc> {
+    let engine: Engine<DefaultAlloc> = Engine::new();
+    let mut program: CompiledProgram<DefaultAlloc> = fibonacci_program();
+    assert!(program.exports.add("math::fibonacci", Export::Function {
+        func_id: 0,
+        param_types: boxed_slice![None],
+        ret_types: boxed_slice![None]
+    }));
+
+    let mut module: Module<DefaultAlloc> = engine.load(program);
+    assert_eq!(module.exported_function("math::fibonacci"), Some(0));
+    assert!(module.export("fibonacci", 0));
+    assert!(!module.export("nonexistent", 1));
+    module
+}
+
+#[test] fn test_engine_call() {
+    let module: Module<DefaultAlloc> = fibonacci_module();
+    let mut instance: Instance<DefaultAlloc> =
+        module.instantiate(DefaultAlloc::new()).unwrap();
+
+    let result: i64 = instance.call("fibonacci", (10i64,)).unwrap();
+    assert_eq!(result, 55);
+    let result: i64 = instance.call("math::fibonacci", (11i64,)).unwrap();
+    assert_eq!(result, 89);
+    let (result,): (i64,) = instance.call("fibonacci", (7i64,)).unwrap();
+    assert_eq!(result, 13);
+}
+
+#[test] fn test_engine_call_error() {
+    let module: Module<DefaultAlloc> = fibonacci_module();
+    let mut instance: Instance<DefaultAlloc> =
+        module.instantiate(DefaultAlloc::new()).unwrap();
+
+    let result: Result<i64, CallError> = instance.call("fib", (10i64,));
+    assert!(matches!(result, Err(CallError::NoSuchFunction(name)) if name == "fib"));
+
+    let result: Result<bool, CallError> = instance.call("fibonacci", (10i64,));
+    assert!(matches!(result, Err(CallError::RetTypeMismatch { index: 0, expected })
+                             if expected == "bool"));
+
+    let result: Result<(i64, i64), CallError> = instance.call("fibonacci", (10i64,));
+    assert!(matches!(result, Err(CallError::RetCountMismatch { expected: 2, got: 1 })));
+
+    let result: Result<i64, CallError> = instance.call("fibonacci", ());
+    assert!(matches!(result, Err(CallError::Exception(_))));
+}
+
+#[test] fn test_engine_call_after_exception() {
+    let engine: Engine<DefaultAlloc> = Engine::new();
+    let mut module: Module<DefaultAlloc> = engine.load(exception_program());
+    assert!(module.export("foo", 0));
+    let mut instance: Instance<DefaultAlloc> =
+        module.instantiate(DefaultAlloc::new()).unwrap();
+    for _ in 0..2 {
+        let result: i64 = instance.call("foo", ()).unwrap();
+        assert_eq!(result, 114514);
+    }
+
+    let mut module: Module<DefaultAlloc> = engine.load(exception_no_eh_program());
+    assert!(module.export("foo", 0));
+    let mut instance: Instance<DefaultAlloc> =
+        module.instantiate(DefaultAlloc::new()).unwrap();
+    for _ in 0..2 {
+        let result: Result<i64, CallError> = instance.call("foo", ());
+        assert!(matches!(result, Err(CallError::Exception(_))));
+    }
+}
+
+#[test] fn test_engine_init_proc() {
+    let engine: Engine<DefaultAlloc> = Engine::new();
+    let mut module: Module<DefaultAlloc> = engine.load(init_proc_program());
+    assert!(module.export("get", 1));
+    assert!(module.export("set", 2));
+
+    let mut instance1: Instance<DefaultAlloc> =
+        module.instantiate(DefaultAlloc::new()).unwrap();
+    let mut instance2: Instance<DefaultAlloc> =
+        module.instantiate(DefaultAlloc::new()).unwrap();
+    let result: i64 = instance1.call("get", ()).unwrap();
+    assert_eq!(result, 42);
+
+    // constants saved by one instance are invisible to others, and the program is left untouched
+    let (): () = instance1.call("set", (114514i64,)).unwrap();
+    let result: i64 = instance1.call("get", ()).unwrap();
+    assert_eq!(result, 114514);
+    let result: i64 = instance2.call("get", ()).unwrap();
+    assert_eq!(result, 42);
+    assert!(module.program().const_pool[0].is_null());
+}
+
+#[test] fn test_engine_call_heap_args() {
+    let engine: Engine<DefaultAlloc> = Engine::new();
+    let mut module: Module<DefaultAlloc> = engine.load(echo_program());
+    assert!(module.export("echo", 0));
+    // collect on every allocation whenever garbage collection is allowed
+    let mut instance: Instance<DefaultAlloc> =
+        module.instantiate(DefaultAlloc::with_max_debt(0, 0)).unwrap();
+    for _ in 0..3 {
+        let words: Vec<String> = vec!["114".to_string(), "514".to_string()];
+        let (text, result): (String, Vec<String>) =
+            instance.call("echo", ("1919810".to_string(), words.clone())).unwrap();
+        assert_eq!(text, "1919810");
+        assert_eq!(result, words);
+    }
+}
+
+#[test] fn test_engine_register_method() {
+    let mut engine: Engine<DefaultAlloc> = Engine::new();
+    let ffi_func_id: usize = engine.register_method::<Object>(
+        "fieldCount",
+        PR47BINDER_FFI_OBJECT_FIELD_COUNT
+    ).unwrap();
+    assert_eq!(engine.ffi_function_id("object::fieldCount"), Some(ffi_func_id));
+    assert!(engine.register_method::<Object>("fieldCount", PR47BINDER_FFI_OBJECT_FIELD_COUNT)
+        .is_none());
+    assert!(engine.register_method::<Object>("checkedDiv", PR47BINDER_FFI_CHECKED_DIV).is_none());
+
+    let methods: MethodTable = engine.methods();
+    assert_eq!(methods.len(), 1);
+    let method: &Method = methods.get(TypeId::of::<Object>(), "fieldCount").unwrap();
+    assert!(matches!(method.func, MethodFunc::Sync(id) if id == ffi_func_id));
+    assert!(!method.mutable_self);
+    assert!(methods.get(TypeId::of::<String>(), "fieldCount").is_none());
+    assert!(methods.get(TypeId::of::<Object>(), "checkedDiv").is_none());
+}
+
+#[test] fn test_engine_register_property() {
+    let mut engine: Engine<DefaultAlloc> = Engine::new();
+    assert!(engine.register_property(
+        "x",
+        |point: &TestPoint| point.x,
+        |point: &mut TestPoint, x: i64| point.x = x
+    ));
+    assert!(engine.register_readonly_property(
+        "label",
+        |point: &TestPoint| point.label.clone()
+    ));
+    assert!(!engine.register_readonly_property("x", |point: &TestPoint| point.x));
+
+    assert_eq!(engine.ffi_function_id("test.Point::x.get"), Some(0));
+    assert_eq!(engine.ffi_function_id("test.Point::x.set"), Some(1));
+    assert_eq!(engine.ffi_function_id("test.Point::label.get"), Some(2));
+    assert_eq!(engine.ffi_function_id("test.Point::label.set"), None);
+    assert_eq!(engine.ffi_funcs().len(), 3);
+
+    let properties: PropertyTable = engine.properties();
+    assert_eq!(properties.len(), 2);
+    assert_eq!(properties.id("label"), Some(1));
+    assert_eq!(properties.name(1), Some("label"));
+    let property: &Property = properties.get(TypeId::of::<TestPoint>(), "x").unwrap();
+    assert_eq!(property.getter, 0);
+    assert_eq!(property.setter, Some(1));
+    let property: &Property = properties.get(TypeId::of::<TestPoint>(), "label").unwrap();
+    assert_eq!(property.setter, None);
+    assert!(properties.get(TypeId::of::<Object>(), "x").is_none());
+}
+
+#[cfg(feature = "async")]
+#[test] fn test_engine_call_async() {
+    let module: Module<DefaultAlloc> = fibonacci_module();
+    block_on_future(async move {
+        let mut instance: Instance<DefaultAlloc> =
+            module.instantiate_async(DefaultAlloc::new()).await.unwrap();
+        let result: i64 = instance.call_async("fibonacci", (12i64,)).await.unwrap();
+        assert_eq!(result, 144);
+    });
+}
+
+#[cfg(feature = "compiler")]
+#[test] fn test_engine_check() {
+    let mut engine: Engine<DefaultAlloc> = Engine::new();
+    assert!(engine.check("empty.p47", "").is_empty());
+    assert!(engine.check("export.p47", "export (foo, bar::baz);").is_empty());
+    assert!(engine.check("var.p47", "var counter = 0; var cache: int = 1;").is_empty());
+}
+
+#[cfg(feature = "compiler")]
+#[test]
+#[should_panic(expected = "diag_id = 2036")]
+fn test_engine_check_var_redefinition() {
+    let mut engine: Engine<DefaultAlloc> = Engine::new();
+    let _ = engine.check("redef.p47", "var counter = 0; var counter = 1;");
+}
+
+#[cfg(feature = "compiler")]
+#[test] fn test_lower_top_level_vars() {
+    let source: &str = "var counter = 0; const limit = 10; var cache = 1; export (counter, cache);";
+    let diag: RefCell<DiagContext> = RefCell::new(DiagContext::new());
+    let mut parser: Parser = Parser::new(0, source, &diag);
+    let program: ConcreteProgram = parser.parse();
+    drop(parser);
+
+    let mut symbols: HashMap<String, Export> = HashMap::new();
+    let mut diag: DiagContext = diag.into_inner();
+    let globals: Box<[String]> = lower_top_level_vars(&program, &mut symbols, &mut diag);
+    assert_eq!(&*globals, ["counter", "cache"]);
+
+    let mut exports: ExportTable = ExportTable::new();
+    for decl /*: &ConcreteDecl*/ in program.decls.iter() {
+        if let ConcreteDecl::ExportDecl(export_decl) = decl {
+            exports.add_export_decl(export_decl, &symbols, &mut diag);
+        }
+    }
+
+    assert!(!diag.has_diag());
+    assert_eq!(exports.global("counter"), Some(0));
+    assert_eq!(exports.global("cache"), Some(1));
+}
+
+#[cfg(feature = "compiler")]
+#[test] fn test_export_decl() {
+    let source: &str = "export (foo, bar::baz, qux);";
+    let diag: RefCell<DiagContext> = RefCell::new(DiagContext::new());
+    let mut parser: Parser = Parser::new(0, source, &diag);
+    let program: ConcreteProgram = parser.parse();
+    drop(parser);
+
+    let mut symbols: HashMap<String, Export> = HashMap::new();
+    symbols.insert("foo".into(), Export::Function {
+        func_id: 1,
+        param_types: boxed_slice![],
+        ret_types: boxed_slice![]
+    });
+    symbols.insert("bar::baz".into(), Export::Global(2));
+    symbols.insert("qux".into(), Export::Const(3));
+    symbols.insert("unexported".into(), Export::Global(4));
+
+    let mut exports: ExportTable = ExportTable::new();
+    let mut diag: DiagContext = diag.into_inner();
+    for decl /*: &ConcreteDecl*/ in program.decls.iter() {
+        if let ConcreteDecl::ExportDecl(export_decl) = decl {
+            exports.add_export_decl(export_decl, &symbols, &mut diag);
+        }
+    }
+
+    assert!(!diag.has_diag());
+    assert_eq!(exports.len(), 3);
+    assert_eq!(exports.function("foo"), Some(1));
+    assert_eq!(exports.global("bar::baz"), Some(2));
+    assert_eq!(exports.constant("qux"), Some(3));
+    assert_eq!(exports.function("bar::baz"), None);
+    assert!(exports.get("unexported").is_none());
+}
+
+#[cfg(feature = "compiler")]
+#[test] fn test_resolve_method() {
+    let mut engine: Engine<DefaultAlloc> = Engine::new();
+    engine.register_method::<Object>("fieldCount", PR47BINDER_FFI_OBJECT_FIELD_COUNT).unwrap();
+    let methods: MethodTable = engine.methods();
+
+    let tyck_info_pool: &mut TyckInfoPool = engine.tyck_info_pool();
+    let object_type: NonNull<TyckInfo> = tyck_info_pool.get_object_type();
+    let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+    let int_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+    let nullable_object_type: NonNull<TyckInfo> =
+        tyck_info_pool.create_nullable_type(object_type);
+
+    unsafe {
+        let resolution: MethodResolution =
+            resolve_method(&methods, object_type.as_ref(), "fieldCount").unwrap();
+        assert!(matches!(resolution, MethodResolution::Static(_)));
+        assert!(!resolution.is_async());
+        assert_eq!(resolution.param_types().unwrap().len(), 1);
+        let ret_types: &[NonNull<TyckInfo>] = resolution.ret_types().unwrap();
+        assert_eq!(ret_types.len(), 1);
+        assert!(matches!(ret_types[0].as_ref(), TyckInfo::Plain(t) if *t == TypeId::of::<i64>()));
+
+        let resolution: MethodResolution =
+            resolve_method(&methods, any_type.as_ref(), "fieldCount").unwrap();
+        assert!(matches!(resolution, MethodResolution::Dynamic { method_id: 0, is_async: false }));
+        assert!(resolution.param_types().is_none());
+
+        assert_eq!(resolve_method(&methods, any_type.as_ref(), "size").err(),
+                   Some(MethodResolveError::NoSuchMethod));
+        assert_eq!(resolve_method(&methods, int_type.as_ref(), "fieldCount").err(),
+                   Some(MethodResolveError::NoSuchMethod));
+        assert_eq!(resolve_method(&methods, nullable_object_type.as_ref(), "fieldCount").err(),
+                   Some(MethodResolveError::NullableReceiver));
+    }
+}
+
+#[cfg(feature = "compiler")]
+#[test] fn test_resolve_property() {
+    let mut engine: Engine<DefaultAlloc> = Engine::new();
+    engine.register_property(
+        "x",
+        |point: &TestPoint| point.x,
+        |point: &mut TestPoint, x: i64| point.x = x
+    );
+    engine.register_readonly_property(
+        "label",
+        |point: &TestPoint| point.label.clone()
+    );
+    let properties: PropertyTable = engine.properties();
+
+    let tyck_info_pool: &mut TyckInfoPool = engine.tyck_info_pool();
+    let point_type: NonNull<TyckInfo> = tyck_info_pool.create_plain_type(TypeId::of::<TestPoint>());
+    let object_type: NonNull<TyckInfo> = tyck_info_pool.get_object_type();
+    let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+    let int_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+    let string_type: NonNull<TyckInfo> = tyck_info_pool.get_string_type();
+    let nullable_int_type: NonNull<TyckInfo> = tyck_info_pool.create_nullable_type(int_type);
+    let nullable_point_type: NonNull<TyckInfo> = tyck_info_pool.create_nullable_type(point_type);
+
+    unsafe {
+        let resolution: FieldResolution =
+            resolve_property(&properties, point_type.as_ref(), "x", true).unwrap();
+        assert!(matches!(resolution, FieldResolution::Static(Property { setter: Some(1), .. })));
+        let field_type: NonNull<TyckInfo> = resolution.field_type().unwrap();
+        assert!(matches!(field_type.as_ref(), TyckInfo::Plain(t) if *t == TypeId::of::<i64>()));
+
+        assert!(matches!(resolve_property(&properties, point_type.as_ref(), "label", false),
+                         Ok(FieldResolution::Static(_))));
+        assert_eq!(resolve_property(&properties, point_type.as_ref(), "label", true).err(),
+                   Some(PropertyResolveError::ReadOnly));
+        assert_eq!(resolve_property(&properties, point_type.as_ref(), "y", false).err(),
+                   Some(PropertyResolveError::NoSuchProperty));
+        assert_eq!(resolve_property(&properties, nullable_point_type.as_ref(), "x", false).err(),
+                   Some(PropertyResolveError::NullableReceiver));
+        assert_eq!(resolve_property(&properties, int_type.as_ref(), "x", false).err(),
+                   Some(PropertyResolveError::NoSuchProperty));
+
+        assert!(matches!(resolve_property(&properties, any_type.as_ref(), "label", true),
+                         Ok(FieldResolution::Dynamic { property_id: 1 })));
+        assert!(matches!(resolve_property(&properties, any_type.as_ref(), "y", false),
+                         Ok(FieldResolution::ObjectField)));
+        assert!(matches!(resolve_property(&properties, object_type.as_ref(), "x", true),
+                         Ok(FieldResolution::ObjectField)));
+
+        assert!(is_assignable(int_type.as_ref(), int_type.as_ref()));
+        assert!(is_assignable(int_type.as_ref(), any_type.as_ref()));
+        assert!(is_assignable(nullable_int_type.as_ref(), int_type.as_ref()));
+        assert!(!is_assignable(int_type.as_ref(), string_type.as_ref()));
+        assert!(!is_assignable(int_type.as_ref(), nullable_int_type.as_ref()));
+    }
+}
+
+#[cfg(feature = "compiler")]
+#[test] fn test_resolve_map_type() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let int_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+    let string_type: NonNull<TyckInfo> = tyck_info_pool.get_string_type();
+    let bool_type: NonNull<TyckInfo> = tyck_info_pool.get_bool_type();
+
+    assert_eq!(
+        resolve_generic_type(&mut tyck_info_pool, TokenInner::KwdMap, &[int_type]).err(),
+        Some(2)
+    );
+    assert_eq!(
+        resolve_generic_type(&mut tyck_info_pool, TokenInner::KwdVector, &[int_type, int_type])
+            .err(),
+        Some(1)
+    );
+
+    let map_type: NonNull<TyckInfo> =
+        resolve_generic_type(&mut tyck_info_pool, TokenInner::KwdMap, &[int_type, string_type])
+            .unwrap();
+    let vec_type: NonNull<TyckInfo> =
+        resolve_generic_type(&mut tyck_info_pool, TokenInner::KwdVector, &[int_type]).unwrap();
+
+    unsafe {
+        assert_eq!(format!("{}", map_type.as_ref()), "map<int, string>");
+        assert_eq!(map_type_params(map_type.as_ref()), Some((int_type, string_type)));
+        assert!(map_type_params(vec_type.as_ref()).is_none());
+
+        let signature: MapOpSignature =
+            resolve_map_op(&mut tyck_info_pool, int_type, string_type, "get").unwrap();
+        assert_eq!(signature.op, MapOp::Get);
+        assert_eq!(signature.param_types[..], [int_type]);
+        assert_eq!(format!("{}", signature.ret_type.unwrap().as_ref()), "?string");
+
+        let signature: MapOpSignature =
+            resolve_map_op(&mut tyck_info_pool, int_type, string_type, "put").unwrap();
+        assert_eq!(signature.op, MapOp::Put);
+        assert_eq!(signature.param_types[..], [int_type, string_type]);
+        assert!(signature.ret_type.is_none());
+
+        let signature: MapOpSignature =
+            resolve_map_op(&mut tyck_info_pool, int_type, string_type, "remove").unwrap();
+        assert_eq!(signature.ret_type, Some(bool_type));
+
+        let signature: MapOpSignature =
+            resolve_map_op(&mut tyck_info_pool, int_type, string_type, "keys").unwrap();
+        assert!(signature.param_types.is_empty());
+        assert_eq!(signature.ret_type, Some(vec_type));
+
+        assert!(resolve_map_op(&mut tyck_info_pool, int_type, string_type, "size").is_none());
+    }
+}
+
+#[test] fn test_engine_register_iterable() {
+    let mut engine: Engine<DefaultAlloc> = Engine::new();
+    let ffi_func_id: usize = engine.register_iterable::<TestRange>().unwrap();
+    assert_eq!(engine.ffi_function_id("test.Range::$iter-elem"), Some(ffi_func_id));
+    assert!(engine.register_iterable::<TestRange>().is_none());
+
+    let methods: MethodTable = engine.methods();
+    let method: &Method = methods.get(TypeId::of::<TestRange>(), ITER_ELEM_METHOD).unwrap();
+    assert!(matches!(method.func, MethodFunc::Sync(id) if id == ffi_func_id));
+    assert!(!method.mutable_self);
+}
+
+#[cfg(feature = "compiler")]
+#[test] fn test_resolve_iteration() {
+    let mut engine: Engine<DefaultAlloc> = Engine::new();
+    engine.register_iterable::<TestRange>().unwrap();
+    let methods: MethodTable = engine.methods();
+
+    let tyck_info_pool: &mut TyckInfoPool = engine.tyck_info_pool();
+    let int_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+    let char_type: NonNull<TyckInfo> = tyck_info_pool.get_char_type();
+    let string_type: NonNull<TyckInfo> = tyck_info_pool.get_string_type();
+    let object_type: NonNull<TyckInfo> = tyck_info_pool.get_object_type();
+    let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+    let range_type: NonNull<TyckInfo> = tyck_info_pool.create_plain_type(TypeId::of::<TestRange>());
+    let vec_type: NonNull<TyckInfo> =
+        resolve_generic_type(tyck_info_pool, TokenInner::KwdVector, &[string_type]).unwrap();
+    let nullable_vec_type: NonNull<TyckInfo> = tyck_info_pool.create_nullable_type(vec_type);
+
+    unsafe {
+        for (ty, kind, elem_type) /*: (NonNull<TyckInfo>, IterKind, NonNull<TyckInfo>)*/ in [
+            (vec_type, IterKind::Vector, string_type),
+            (object_type, IterKind::Object, string_type),
+            (string_type, IterKind::String, char_type),
+            (range_type, IterKind::Host, int_type),
+            (any_type, IterKind::Dynamic, any_type)
+        ] {
+            let resolution: IterResolution =
+                resolve_iteration(tyck_info_pool, &methods, ty.as_ref()).unwrap();
+            assert_eq!(resolution.kind, kind);
+            assert_eq!(resolution.elem_type, elem_type);
+        }
+
+        assert!(resolve_iteration(tyck_info_pool, &methods, int_type.as_ref()).is_none());
+        assert!(resolve_iteration(tyck_info_pool, &methods, nullable_vec_type.as_ref()).is_none());
+    }
+}
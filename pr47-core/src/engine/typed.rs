@@ -0,0 +1,73 @@
+//! # `typed.rs`: receiving Rust values from script functions
+//!
+//! Arguments are converted with `data::convert::IntoValues`. Return values are converted with
+//! `data::convert::FromValue`, with conversion failures reported as `CallError`s.
+
+use crate::data::Value;
+use crate::data::convert::FromValue;
+use crate::data::exception::UncheckedException;
+use crate::data::tyck::TyckInfoPool;
+use crate::engine::CallError;
+use crate::vm::al31fm2::exception::Exception;
+
+/// Return value lists of script functions
+pub trait FromRets: Sized {
+    /// Convert `rets`. `rets` should be values just returned from a script function.
+    unsafe fn from_rets(rets: &[Value], tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, CallError>;
+}
+
+fn ret_count_check(rets: &[Value], expected: usize) -> Result<(), CallError> {
+    if rets.len() == expected {
+        Ok(())
+    } else {
+        Err(CallError::RetCountMismatch { expected, got: rets.len() })
+    }
+}
+
+unsafe fn ret_at<T: FromValue>(
+    rets: &[Value],
+    index: usize,
+    tyck_info_pool: &mut TyckInfoPool
+) -> Result<T, CallError> {
+    T::from_value(rets[index], tyck_info_pool).map_err(|e: UncheckedException| match e {
+        UncheckedException::TypeCheckFailure { expected_type, .. } =>
+            CallError::RetTypeMismatch { index, expected: expected_type.as_ref().to_string() },
+        e => CallError::Exception(Exception::unchecked_exc(e))
+    })
+}
+
+impl<T: FromValue> FromRets for T {
+    unsafe fn from_rets(rets: &[Value], tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, CallError>
+    {
+        ret_count_check(rets, 1)?;
+        ret_at(rets, 0, tyck_info_pool)
+    }
+}
+
+impl FromRets for () {
+    unsafe fn from_rets(rets: &[Value], _tyck_info_pool: &mut TyckInfoPool)
+        -> Result<Self, CallError>
+    {
+        ret_count_check(rets, 0)
+    }
+}
+
+macro_rules! impl_tuple {
+    ($count:expr; $($t:ident, $idx:tt);+) => {
+        impl<$($t: FromValue),+> FromRets for ($($t,)+) {
+            unsafe fn from_rets(rets: &[Value], tyck_info_pool: &mut TyckInfoPool)
+                -> Result<Self, CallError>
+            {
+                ret_count_check(rets, $count)?;
+                Ok(($(ret_at::<$t>(rets, $idx, tyck_info_pool)?,)+))
+            }
+        }
+    }
+}
+
+impl_tuple!(1; T0, 0);
+impl_tuple!(2; T0, 0; T1, 1);
+impl_tuple!(3; T0, 0; T1, 1; T2, 2);
+impl_tuple!(4; T0, 0; T1, 1; T2, 2; T3, 3);
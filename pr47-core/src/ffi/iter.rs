@@ -0,0 +1,117 @@
+//! # `iter.rs`: iteration over host types
+//!
+//! A `for` loop iterates over a host type `T` by position: it calls an FFI function
+//! `(&T, int) -> (bool, E)` with positions `0, 1, 2, ...`, until the function reports that there's
+//! no element left. The VM only keeps the receiver and the current position in the iterator, so
+//! no Rust iterator borrowing VM data is ever left behind across instructions.
+//!
+//! A host type becomes iterable by implementing `HostIterable`, and registering `IterElem<T>` as
+//! method `ITER_ELEM_METHOD` of `T`. See `Engine::register_iterable`.
+
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use xjbutil::boxed_slice;
+use xjbutil::void::Void;
+
+use crate::data::Value;
+use crate::data::convert::{FromValue, IntoValue};
+use crate::data::traits::StaticBase;
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+use crate::ffi::{DataOption, FFIException, RetArity, Signature};
+use crate::ffi::sync_fn::{Function, OwnershipGuard, VMContext, value_into_ref};
+
+/// Method name under which `IterElem` functions are registered. Not a valid identifier, so that
+/// it never collides with methods callable from scripts.
+pub const ITER_ELEM_METHOD: &str = "$iter-elem";
+
+/// Host types which could be iterated by `for` loops
+pub trait HostIterable: 'static {
+    type Elem: 'static + IntoValue + FromValue;
+
+    /// Get the element at position `index`, or `None` if `index` is past the last element
+    fn iter_elem(&self, index: usize) -> Option<Self::Elem>;
+}
+
+/// Element access of host iterable type `T`, as an FFI function `(&T, int) -> (bool, Elem)`.
+/// Returns `(false, null)` once `index` is past the last element.
+pub struct IterElem<T> {
+    _phantom: PhantomData<fn(&T)>
+}
+
+impl<T> IterElem<T>
+    where T: HostIterable,
+          Void: StaticBase<T>
+{
+    pub fn new() -> Self {
+        Self { _phantom: PhantomData }
+    }
+}
+
+impl<T> Default for IterElem<T>
+    where T: HostIterable,
+          Void: StaticBase<T>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, CTX> Function<CTX> for IterElem<T>
+    where T: HostIterable,
+          CTX: VMContext,
+          Void: StaticBase<T>
+{
+    fn signature(&self, tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let self_type: NonNull<TyckInfo> = <Void as StaticBase<T>>::tyck_info(tyck_info_pool);
+        let index_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+        let bool_type: NonNull<TyckInfo> = tyck_info_pool.get_bool_type();
+        let elem_type: NonNull<TyckInfo> = <T::Elem as FromValue>::tyck_info(tyck_info_pool);
+        Signature {
+            func_type: tyck_info_pool.create_function_type(
+                &[self_type, index_type],
+                &[bool_type, elem_type],
+                &[]
+            ),
+            param_options: boxed_slice![DataOption::Share, DataOption::Copy],
+            ret_option: boxed_slice![DataOption::Copy, DataOption::Move],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc(
+        &self,
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let (receiver, guard): (&T, Option<OwnershipGuard>) =
+            value_into_ref::<T>(*args.get_unchecked(0))?;
+        let index: i64 = args.get_unchecked(1).vt_data.inner.int_value;
+        let elem: Option<T::Elem> = if index >= 0 {
+            receiver.iter_elem(index as usize)
+        } else {
+            None
+        };
+        drop(guard);
+
+        if let Some(elem /*: T::Elem*/) = elem {
+            **rets.get_unchecked(0) = Value::new_bool(true);
+            **rets.get_unchecked(1) = elem.into_value(context);
+        } else {
+            **rets.get_unchecked(0) = Value::new_bool(false);
+            **rets.get_unchecked(1) = Value::new_null();
+        }
+        Ok(())
+    }
+
+    unsafe fn call_unchecked(
+        &self,
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        self.call_rtlc(context, args, rets)
+    }
+}
@@ -0,0 +1,121 @@
+//! # `member.rs`: methods and properties of host types
+//!
+//! Member tables are shared by the compiler, which resolves member accesses on receivers of
+//! statically known types, and by the VM, which dispatches member accesses on `any` receivers.
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::ptr::NonNull;
+
+use crate::data::tyck::TyckInfo;
+
+/// The FFI function implementing a host method
+#[derive(Clone, Copy)]
+pub enum MethodFunc {
+    /// Index into `CompiledProgram::ffi_funcs`
+    Sync(usize),
+    /// Index into `CompiledProgram::async_ffi_funcs`
+    #[cfg(feature = "async")]
+    Async(usize)
+}
+
+/// A method registered on some host type. The receiver (`self`) is always passed as the first
+/// argument of the FFI function.
+#[derive(Clone, Copy)]
+pub struct Method {
+    pub func: MethodFunc,
+    /// Whether the receiver is mutably shared (`&mut self`) instead of shared (`&self`)
+    pub mutable_self: bool,
+    /// Function type of the FFI function, including the receiver parameter
+    pub func_type: NonNull<TyckInfo>
+}
+
+/// A property registered on some host type. Getter and setter are FFI functions `(&T) -> V` and
+/// `(&mut T, V) -> ()` respectively, see [`ffi::property`](crate::ffi::property).
+#[derive(Clone, Copy)]
+pub struct Property {
+    /// Index into `CompiledProgram::ffi_funcs`
+    pub getter: usize,
+    /// Index into `CompiledProgram::ffi_funcs`, `None` for read-only properties
+    pub setter: Option<usize>,
+    /// Type of the property value
+    pub ty: NonNull<TyckInfo>
+}
+
+/// Members (methods or properties) of host types, keyed by member name and receiver type
+///
+/// Every distinct member name gets a member ID, shared by all types having a member of that
+/// name. Accesses on receivers of statically known types get resolved to FFI function calls by
+/// the compiler, while accesses on `any` receivers are compiled to instructions carrying the
+/// member ID (like `CALL-METHOD [METHOD-ID] ...`), and get dispatched on the runtime type of the
+/// receiver.
+#[derive(Clone)]
+pub struct MemberTable<M: Copy> {
+    member_ids: HashMap<String, usize>,
+    names: Vec<String>,
+    members: Vec<HashMap<TypeId, M>>
+}
+
+pub type MethodTable = MemberTable<Method>;
+pub type PropertyTable = MemberTable<Property>;
+
+impl<M: Copy> MemberTable<M> {
+    pub fn new() -> Self {
+        Self { member_ids: HashMap::new(), names: Vec::new(), members: Vec::new() }
+    }
+
+    /// Add `member` to type `type_id` under `name`, returns `false` if the type already has a
+    /// member with the same name
+    pub fn add(&mut self, type_id: TypeId, name: impl Into<String>, member: M) -> bool {
+        let name: String = name.into();
+        let member_id: usize = if let Some(member_id) = self.member_ids.get(&name) {
+            *member_id
+        } else {
+            self.member_ids.insert(name.clone(), self.members.len());
+            self.names.push(name);
+            self.members.push(HashMap::new());
+            self.members.len() - 1
+        };
+
+        match self.members[member_id].entry(type_id) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(member);
+                true
+            }
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.member_ids.get(name).copied()
+    }
+
+    pub fn name(&self, member_id: usize) -> Option<&str> {
+        self.names.get(member_id).map(String::as_str)
+    }
+
+    pub fn get(&self, type_id: TypeId, name: &str) -> Option<&M> {
+        self.get_by_id(self.id(name)?, type_id)
+    }
+
+    pub fn get_by_id(&self, member_id: usize, type_id: TypeId) -> Option<&M> {
+        self.members.get(member_id)?.get(&type_id)
+    }
+
+    /// All members named `name`, of any receiver type
+    pub fn overloads(&self, name: &str) -> impl Iterator<Item=(&TypeId, &M)> {
+        self.id(name)
+            .map(|member_id: usize| &self.members[member_id])
+            .into_iter()
+            .flat_map(|members: &HashMap<TypeId, M>| members.iter())
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.iter().map(HashMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -3,6 +3,9 @@ use std::ptr::NonNull;
 use crate::data::tyck::TyckInfo;
 use crate::data::exception::ExceptionInner;
 
+pub mod iter;
+pub mod member;
+pub mod property;
 pub mod sync_fn;
 
 #[cfg(feature = "async")]
@@ -19,11 +22,122 @@ pub enum DataOption {
     RawUntyped
 }
 
+/// Type constraint applied to each argument of a variadic tail
+#[derive(Clone, Copy)]
+pub enum VariadicType {
+    /// Every variadic argument should be of the given type
+    Typed(NonNull<TyckInfo>),
+    /// Variadic arguments could be of any type
+    Any,
+    /// Every variadic argument should be a promise, no matter what it resolves to
+    AnyPromise
+}
+
+/// Arguments accepted after the fixed parameters listed in `Signature::func_type`
+pub struct VariadicTail {
+    pub var_type: VariadicType,
+    pub data_option: DataOption,
+    /// Minimal count of variadic arguments
+    pub min_count: usize
+}
+
+/// Describes how many values a function returns
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RetArity {
+    /// Returns exactly the values listed in `Signature::func_type`
+    Fixed,
+    /// Returns the values listed in `Signature::func_type`, followed by the results of every
+    /// variadic promise argument, in order
+    FixedThenEachPromise,
+    /// Returns the values listed in `Signature::func_type`, followed by the results of one of the
+    /// variadic promise arguments. All variadic promise arguments should resolve to the same types.
+    FixedThenOnePromise,
+    /// Returns the values listed in `Signature::func_type`, followed by the results of the promise
+    /// parameter at the given index
+    FixedThenPromiseParam(usize)
+}
+
 pub struct Signature {
     pub func_type: NonNull<TyckInfo>,
 
     pub param_options: Box<[DataOption]>,
-    pub ret_option: Box<[DataOption]>
+    pub ret_option: Box<[DataOption]>,
+
+    pub variadic: Option<VariadicTail>,
+    pub ret_arity: RetArity
+}
+
+impl Signature {
+    /// Check if `arg_count` arguments are acceptable by function of this signature
+    pub fn accepts_arg_count(&self, arg_count: usize) -> bool {
+        let fixed_count: usize = self.param_options.len();
+        match &self.variadic {
+            Some(variadic /*: &VariadicTail*/) => arg_count >= fixed_count + variadic.min_count,
+            None => arg_count == fixed_count
+        }
+    }
 }
 
 pub type FFIException = ExceptionInner;
+
+#[cfg(test)]
+mod test {
+    use std::ptr::NonNull;
+
+    use xjbutil::boxed_slice;
+
+    use crate::data::tyck::{TyckInfo, TyckInfoPool};
+    use crate::ffi::{DataOption, RetArity, Signature, VariadicTail, VariadicType};
+
+    fn signature(
+        tyck_info_pool: &mut TyckInfoPool,
+        param_count: usize,
+        variadic_min_count: Option<usize>
+    ) -> Signature {
+        let int_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+        let params: Vec<NonNull<TyckInfo>> = vec![int_type; param_count];
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&params, &[], &[]),
+            param_options: vec![DataOption::Copy; param_count].into_boxed_slice(),
+            ret_option: boxed_slice![],
+            variadic: variadic_min_count.map(|min_count: usize| VariadicTail {
+                var_type: VariadicType::Typed(int_type),
+                data_option: DataOption::Copy,
+                min_count
+            }),
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    #[test]
+    fn test_accepts_arg_count_fixed() {
+        let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+
+        let nullary: Signature = signature(&mut tyck_info_pool, 0, None);
+        assert!(nullary.accepts_arg_count(0));
+        assert!(!nullary.accepts_arg_count(1));
+
+        let binary: Signature = signature(&mut tyck_info_pool, 2, None);
+        assert!(!binary.accepts_arg_count(0));
+        assert!(!binary.accepts_arg_count(1));
+        assert!(binary.accepts_arg_count(2));
+        assert!(!binary.accepts_arg_count(3));
+    }
+
+    #[test]
+    fn test_accepts_arg_count_variadic() {
+        let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+
+        let any_count: Signature = signature(&mut tyck_info_pool, 0, Some(0));
+        assert!(any_count.accepts_arg_count(0));
+        assert!(any_count.accepts_arg_count(1));
+        assert!(any_count.accepts_arg_count(100));
+
+        let fixed_then_some: Signature = signature(&mut tyck_info_pool, 1, Some(2));
+        assert!(!fixed_then_some.accepts_arg_count(0));
+        assert!(!fixed_then_some.accepts_arg_count(1));
+        assert!(!fixed_then_some.accepts_arg_count(2));
+        assert!(fixed_then_some.accepts_arg_count(3));
+        assert!(fixed_then_some.accepts_arg_count(4));
+    }
+}
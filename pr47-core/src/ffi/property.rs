@@ -0,0 +1,159 @@
+//! # `property.rs`: FFI functions built from property getter and setter closures
+//!
+//! Properties of host types are accessed through ordinary FFI functions: a getter takes the
+//! receiver by shared reference (`(&T) -> V`), and a setter takes the receiver by mutable shared
+//! reference (`(&mut T, V) -> ()`). So the `OwnershipInfo` of the receiver is checked in the same
+//! way as for host methods, and a setter fails on receivers which are not writeable.
+//!
+//! Getter and setter closures must be `Send + Sync`: a compiled program referring to them may be
+//! shared by isolates running on different threads.
+
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::sync::{Mutex, MutexGuard};
+
+use xjbutil::boxed_slice;
+use xjbutil::void::Void;
+
+use crate::data::Value;
+use crate::data::convert::{FromValue, IntoValue};
+use crate::data::traits::StaticBase;
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+use crate::ffi::{DataOption, FFIException, RetArity, Signature};
+use crate::ffi::sync_fn::{
+    Function,
+    OwnershipGuard,
+    VMContext,
+    value_into_mut_ref,
+    value_into_ref
+};
+
+/// Getter of property of type `V` on host type `T`, as an FFI function `(&T) -> V`
+pub struct PropertyGetter<T, V, F> {
+    getter: F,
+    _phantom: PhantomData<fn(&T) -> V>
+}
+
+impl<T, V, F> PropertyGetter<T, V, F>
+    where T: 'static,
+          V: 'static + IntoValue + FromValue,
+          F: 'static + Send + Sync + Fn(&T) -> V,
+          Void: StaticBase<T>
+{
+    pub fn new(getter: F) -> Self {
+        Self { getter, _phantom: PhantomData }
+    }
+}
+
+impl<T, V, F, CTX> Function<CTX> for PropertyGetter<T, V, F>
+    where T: 'static,
+          V: 'static + IntoValue + FromValue,
+          F: 'static + Send + Sync + Fn(&T) -> V,
+          CTX: VMContext,
+          Void: StaticBase<T>
+{
+    fn signature(&self, tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let self_type: NonNull<TyckInfo> = <Void as StaticBase<T>>::tyck_info(tyck_info_pool);
+        let value_type: NonNull<TyckInfo> = V::tyck_info(tyck_info_pool);
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[self_type], &[value_type], &[]),
+            param_options: boxed_slice![DataOption::Share],
+            ret_option: boxed_slice![DataOption::Move],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc(
+        &self,
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let (receiver, guard): (&T, Option<OwnershipGuard>) =
+            value_into_ref::<T>(*args.get_unchecked(0))?;
+        let ret: V = (self.getter)(receiver);
+        drop(guard);
+
+        **rets.get_unchecked(0) = ret.into_value(context);
+        Ok(())
+    }
+
+    unsafe fn call_unchecked(
+        &self,
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        self.call_rtlc(context, args, rets)
+    }
+}
+
+/// Setter of property of type `V` on host type `T`, as an FFI function `(&mut T, V) -> ()`
+pub struct PropertySetter<T, V, F> {
+    setter: F,
+    /// Type information pool for reporting conversion failures of the assigned value. Setters
+    /// are leaked once registered, so the type information never dangles.
+    tyck_info_pool: Mutex<TyckInfoPool>,
+    _phantom: PhantomData<fn(&mut T, V)>
+}
+
+impl<T, V, F> PropertySetter<T, V, F>
+    where T: 'static,
+          V: 'static + FromValue,
+          F: 'static + Send + Sync + Fn(&mut T, V),
+          Void: StaticBase<T>
+{
+    pub fn new(setter: F) -> Self {
+        Self { setter, tyck_info_pool: Mutex::new(TyckInfoPool::new()), _phantom: PhantomData }
+    }
+}
+
+impl<T, V, F, CTX> Function<CTX> for PropertySetter<T, V, F>
+    where T: 'static,
+          V: 'static + FromValue,
+          F: 'static + Send + Sync + Fn(&mut T, V),
+          CTX: VMContext,
+          Void: StaticBase<T>
+{
+    fn signature(&self, tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let self_type: NonNull<TyckInfo> = <Void as StaticBase<T>>::tyck_info(tyck_info_pool);
+        let value_type: NonNull<TyckInfo> = V::tyck_info(tyck_info_pool);
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[self_type, value_type], &[], &[]),
+            param_options: boxed_slice![DataOption::MutShare, DataOption::Share],
+            ret_option: boxed_slice![],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc(
+        &self,
+        _context: &mut CTX,
+        args: &[Value],
+        _rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let value: V = {
+            let mut tyck_info_pool: MutexGuard<TyckInfoPool> =
+                self.tyck_info_pool.lock().unwrap();
+            V::from_value(*args.get_unchecked(1), &mut tyck_info_pool)
+                .map_err(FFIException::Unchecked)?
+        };
+
+        let (receiver, guard): (&mut T, OwnershipGuard) =
+            value_into_mut_ref::<T>(*args.get_unchecked(0))?;
+        (self.setter)(receiver, value);
+        drop(guard);
+        Ok(())
+    }
+
+    unsafe fn call_unchecked(
+        &self,
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        self.call_rtlc(context, args, rets)
+    }
+}
@@ -16,6 +16,10 @@ use crate::ffi::{FFIException, Signature};
 pub trait VMContext: 'static + Sized {
     fn add_heap_managed(&mut self, wide_ptr: Value);
     fn mark(&mut self, wide_ptr: Value);
+
+    /// Pin `values`, so that they survive garbage collection until the returned flag is set to
+    /// `false`. The returned pointer is null if the VM does not collect garbage.
+    fn pin_objects(&mut self, values: &[Value]) -> *mut bool;
 }
 
 pub trait FunctionBase: 'static {
@@ -161,7 +165,6 @@ impl Drop for OwnershipGuard {
     where T: 'static,
           Void: StaticBase<T>
 {
-    value.set_ownership_info_norm(OwnershipInfo::MovedToRust);
     value.move_out_norm::<T>()
 }
 
@@ -22,6 +22,7 @@ pub mod util;
 pub mod vm;
 
 #[cfg(feature = "compiler")] pub mod diag;
+#[cfg(feature = "al31fm2")]  pub mod engine;
 #[cfg(feature = "compiler")] pub mod parse;
 #[cfg(feature = "compiler")] pub mod syntax;
 #[cfg(feature = "compiler")] pub mod sema;
@@ -36,8 +37,12 @@ compile_error!("using `async-avoid-block` without `async` is meaningless");
 #[cfg(all(feature = "compiler-pretty-diag", not(feature = "compiler")))]
 compile_error!("using `compiler-pretty-diag` without `compiler` is meaningless");
 
-#[cfg(all(feature = "async-astd", feature = "async-tokio"))]
-compile_error!("features `async-astd` and `async-tokio` are mutually exclusive");
+#[cfg(any(
+    all(feature = "async-astd", feature = "async-tokio"),
+    all(feature = "async-astd", feature = "async-sim"),
+    all(feature = "async-tokio", feature = "async-sim")
+))]
+compile_error!("features `async-astd`, `async-tokio` and `async-sim` are mutually exclusive");
 
 #[cfg(all(feature = "with-log", feature = "with-tracing"))]
 compile_error!("feature `with-log` and `with-tracing` are mutually exclusive");
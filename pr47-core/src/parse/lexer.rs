@@ -33,9 +33,11 @@ static DEFAULT_KEYWORDS_MAP: phf::Map<&'static str, TokenInner<'static>> = phf_m
     "else" => TokenInner::KwdElse,
     "export" => TokenInner::KwdExport,
     "float" => TokenInner::KwdFloat,
+    "for" => TokenInner::KwdFor,
     "func" => TokenInner::KwdFunc,
     "if" => TokenInner::KwdIf,
     "import" => TokenInner::KwdImport,
+    "in" => TokenInner::KwdIn,
     "int" => TokenInner::KwdInt,
     "object" => TokenInner::KwdObject,
     "open" => TokenInner::KwdOpen,
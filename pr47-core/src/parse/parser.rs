@@ -177,6 +177,7 @@ const TOP_LEVEL_DECL_FIRST: &[TokenInner<'static>] = &[
     TokenInner::KwdFunc,
     TokenInner::KwdImport,
     TokenInner::KwdOpen,
+    TokenInner::KwdVar,
 ];
 
 const ATTR_FIRST: &[TokenInner<'static>] = &[TokenInner::SymHash];
@@ -1,14 +1,164 @@
 use super::Parser;
 
-use crate::syntax::stmt::ConcreteCompoundStmt;
+use crate::diag::{DiagMark, diag_data};
+use crate::diag::location::{SourceLoc, SourceRange};
+use crate::syntax::decl::{ConcreteDecl, ConcreteObjectDecl};
+use crate::syntax::expr::ConcreteExpr;
+use crate::syntax::id::Identifier;
+use crate::syntax::stmt::{ConcreteCompoundStmt, ConcreteForStmt, ConcreteStmt};
 use crate::syntax::token::{Token, TokenInner};
 
+const STMT_FAILSAFE: &[&[TokenInner<'static>]] = &[
+    &[TokenInner::SymSemicolon, TokenInner::SymRBrace]
+];
+
 impl<'s, 'd> Parser<'s, 'd> {
     pub fn parse_compound_stmt(
         &mut self,
-        _lbrace_token: Token<'s>,
-        _failsafe_set: &[&[TokenInner<'_>]]
+        lbrace_token: Token<'s>,
+        failsafe_set: &[&[TokenInner<'_>]]
     ) -> Option<ConcreteCompoundStmt<'s>> {
-        todo!();
+        if lbrace_token.token_inner != TokenInner::SymLBrace {
+            self.diag.borrow_mut()
+                .diag(lbrace_token.range.left(), diag_data::err_expected_token_0_got_1)
+                .add_arg2(TokenInner::SymLBrace)
+                .add_arg2(lbrace_token.token_inner)
+                .add_mark(DiagMark::from(lbrace_token.range).add_comment("unexpected token"))
+                .emit();
+            self.skip_to_any_of(failsafe_set);
+            return None;
+        }
+
+        let mut stmts: Vec<ConcreteStmt<'s>> = Vec::new();
+        while self.current_token().token_inner != TokenInner::SymRBrace {
+            if self.current_token().is_eoi() {
+                let _ = self.expect_token(TokenInner::SymRBrace, &[]);
+                return None;
+            }
+
+            if let Some(stmt /*: ConcreteStmt*/) = self.parse_stmt(STMT_FAILSAFE) {
+                stmts.push(stmt);
+            } else {
+                // statement parsing functions skip to `;` or `}` on failure
+                self.skip_optional(TokenInner::SymSemicolon);
+            }
+        }
+        let rbrace_range: SourceRange = self.consume_token().range;
+
+        Some(ConcreteCompoundStmt {
+            stmts,
+            left_brace_loc: lbrace_token.range.left(),
+            right_brace_loc: rbrace_range.left()
+        })
+    }
+
+    pub fn parse_stmt(&mut self, failsafe_set: &[&[TokenInner<'_>]]) -> Option<ConcreteStmt<'s>> {
+        match self.current_token().token_inner {
+            TokenInner::SymLBrace => {
+                let lbrace_token: Token<'s> = self.consume_token();
+                self.parse_compound_stmt(lbrace_token, failsafe_set)
+                    .map(ConcreteStmt::CompoundStmt)
+            },
+            TokenInner::KwdFor => {
+                let for_token: Token<'s> = self.consume_token();
+                self.parse_for_stmt(for_token, failsafe_set).map(ConcreteStmt::ForStmt)
+            },
+            TokenInner::KwdConst | TokenInner::KwdVar => {
+                let kwd_token: Token<'s> = self.consume_token();
+                let kwd_loc: SourceLoc = kwd_token.range.left();
+                let is_const: bool = kwd_token.token_inner == TokenInner::KwdConst;
+                let decl: ConcreteObjectDecl<'s> = self.parse_object_decl(kwd_token, failsafe_set)?;
+                let decl: ConcreteDecl<'s> = if is_const {
+                    ConcreteDecl::ConstDecl(decl)
+                } else {
+                    ConcreteDecl::VarDecl(decl)
+                };
+                Some(ConcreteStmt::DeclStmt(decl, kwd_loc))
+            },
+            _ => {
+                let expr: ConcreteExpr<'s> = self.parse_expression(failsafe_set)?;
+                let semicolon_loc: SourceLoc =
+                    self.expect_n_consume(TokenInner::SymSemicolon, failsafe_set)?.range.left();
+                Some(ConcreteStmt::ExprStmt(expr, semicolon_loc))
+            }
+        }
+    }
+
+    pub fn parse_for_stmt(&mut self, for_token: Token<'s>, failsafe_set: &[&[TokenInner<'_>]])
+        -> Option<ConcreteForStmt<'s>>
+    {
+        let for_kwd_range: SourceRange = for_token.range;
+        let var_name: Identifier<'s> = self.parse_unqual_ident_with_skip(failsafe_set)?;
+        let in_kwd_range: SourceRange =
+            self.expect_n_consume(TokenInner::KwdIn, failsafe_set)?.range;
+        let iterated: ConcreteExpr<'s> = self.parse_expression_no_assign(failsafe_set)?;
+        let lbrace_token: Token<'s> =
+            self.expect_n_consume(TokenInner::SymLBrace, failsafe_set)?;
+        let body: ConcreteCompoundStmt<'s> = self.parse_compound_stmt(lbrace_token, failsafe_set)?;
+
+        Some(ConcreteForStmt {
+            var_name,
+            iterated,
+            body,
+            for_kwd_range,
+            in_kwd_range
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use crate::diag::DiagContext;
+    use crate::parse::parser::Parser;
+    use crate::syntax::expr::ConcreteExpr;
+    use crate::syntax::id::assert_ident_unqual;
+    use crate::syntax::stmt::{ConcreteCompoundStmt, ConcreteForStmt, ConcreteStmt};
+    use crate::syntax::token::Token;
+
+    #[test]
+    fn test_parse_compound_stmt() {
+        let source: &str = "{ var a = b.c(); a.d(e); { f(); } }";
+
+        let diag: RefCell<DiagContext> = RefCell::new(DiagContext::new());
+        let mut parser: Parser = Parser::new(0, source, &diag);
+
+        let lbrace_token: Token = parser.consume_token();
+        let stmt: ConcreteCompoundStmt = parser.parse_compound_stmt(lbrace_token, &[]).unwrap();
+
+        assert_eq!(stmt.stmts.len(), 3);
+        assert!(matches!(stmt.stmts[0], ConcreteStmt::DeclStmt(..)));
+        assert!(matches!(stmt.stmts[1], ConcreteStmt::ExprStmt(..)));
+        assert!(matches!(stmt.stmts[2], ConcreteStmt::CompoundStmt(..)));
+        assert!(!diag.borrow().has_error());
+    }
+
+    #[test]
+    fn test_parse_for_stmt() {
+        let source: &str = "for x in a.b() { print(x); }";
+
+        let diag: RefCell<DiagContext> = RefCell::new(DiagContext::new());
+        let mut parser: Parser = Parser::new(0, source, &diag);
+
+        let for_token: Token = parser.consume_token();
+        let stmt: ConcreteForStmt = parser.parse_for_stmt(for_token, &[]).unwrap();
+
+        assert_ident_unqual(&stmt.var_name, "x");
+        assert!(matches!(stmt.iterated, ConcreteExpr::FuncCallExpr(_)));
+        assert_eq!(stmt.body.stmts.len(), 1);
+        assert!(!diag.borrow().has_error());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_for_stmt_missing_in() {
+        let source: &str = "for x a { print(x); }";
+
+        let diag: RefCell<DiagContext> = RefCell::new(DiagContext::new());
+        let mut parser: Parser = Parser::new(0, source, &diag);
+
+        let for_token: Token = parser.consume_token();
+        let _: Option<ConcreteForStmt> = parser.parse_for_stmt(for_token, &[]);
     }
 }
@@ -70,8 +70,8 @@ impl<'s, 'd> Parser<'s, 'd> {
         let mut decl: ConcreteDecl = self.parse_top_level_decl()?;
 
         match &mut decl {
-            ConcreteDecl::ConstDecl(const_decl) => unsafe {
-                const_decl.attr.replace(attr_list).unchecked_unwrap();
+            ConcreteDecl::ConstDecl(object_decl) | ConcreteDecl::VarDecl(object_decl) => unsafe {
+                object_decl.attr.replace(attr_list).unchecked_unwrap();
             },
             ConcreteDecl::FuncDecl(func_decl) => unsafe {
                 func_decl.attr.replace(attr_list).unchecked_unwrap();
@@ -97,9 +97,6 @@ impl<'s, 'd> Parser<'s, 'd> {
                     .add_mark(open_import_decl.open_kwd_range.into())
                     .emit();
             },
-            ConcreteDecl::VarDecl(_) => {
-                unreachable!("variable declarations cannot appear at top level")
-            },
         }
         Some(decl)
     }
@@ -112,12 +109,9 @@ impl<'s, 'd> Parser<'s, 'd> {
                     .map(ConcreteDecl::ConstDecl)
             },
             TokenInner::KwdVar => {
-                self.diag.borrow_mut()
-                    .diag(self.current_token().range.left(),
-                          diag_data::err_no_top_level_var_decl)
-                    .add_mark(self.current_token().range.into())
-                    .emit();
-                None
+                let var_token: Token<'s> = self.consume_token();
+                self.parse_object_decl(var_token, TOP_LEVEL_DECL_FAILSAFE)
+                    .map(ConcreteDecl::VarDecl)
             },
             TokenInner::KwdFunc => {
                 let func_token: Token<'s> = self.consume_token();
@@ -144,6 +138,7 @@ impl<'s, 'd> Parser<'s, 'd> {
                     .diag(self.current_token().range.left(), diag_data::err_expected_any_of_0_got_1)
                     .add_arg2(awa![
                         TokenInner::KwdConst,
+                        TokenInner::KwdVar,
                         TokenInner::KwdFunc,
                         TokenInner::KwdExport,
                         TokenInner::KwdImport,
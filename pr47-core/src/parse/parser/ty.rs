@@ -50,6 +50,12 @@ impl<'s, 'd> Parser<'s, 'd> {
                 let container_type_token: Token<'s> = self.consume_token();
                 self.parse_generic_type(container_type_token, failsafe_set)
             },
+            // `map` is only a keyword in types, so it could still be used as identifier elsewhere
+            TokenInner::Ident("map") => {
+                let range: SourceRange = self.consume_token().range;
+                let container_type_token: Token<'s> = Token::new(TokenInner::KwdMap, range);
+                self.parse_generic_type(container_type_token, failsafe_set)
+            },
             TokenInner::KwdAuto => {
                 Some(ConcreteType::DeducedType(self.consume_token().range))
             },
@@ -73,6 +79,7 @@ impl<'s, 'd> Parser<'s, 'd> {
                         TokenInner::KwdString,
                         TokenInner::KwdVoid,
                         TokenInner::KwdVector,
+                        TokenInner::KwdMap,
                         TokenInner::KwdAuto,
                         TokenInner::Ident("")
                     ])
@@ -91,7 +98,10 @@ impl<'s, 'd> Parser<'s, 'd> {
         failsafe_set: &[&[TokenInner<'_>]]
     ) -> Option<ConcreteType<'s>> {
         #[cfg(debug_assertions)]
-        assert_eq!(container_type_token.token_inner, TokenInner::KwdVector);
+        assert!(matches!(
+            container_type_token.token_inner,
+            TokenInner::KwdVector | TokenInner::KwdMap
+        ));
 
         let left_angle_range: SourceRange =
             self.expect_n_consume(TokenInner::SymLt, failsafe_set)?.range;
@@ -200,4 +210,35 @@ mod test {
             panic!("should be a generic type");
         }
     }
+
+    #[test]
+    fn test_parse_map_type() {
+        let source: &str = "map<int, ?vector<string>> map";
+        let diag: RefCell<DiagContext> = RefCell::new(DiagContext::new());
+        let mut parser: Parser = Parser::new(
+            0, source, &diag
+        );
+
+        let ty: ConcreteType = parser.parse_type(&[]).unwrap();
+        if let ConcreteType::GenericType(generic_type) = ty {
+            assert_eq!(generic_type.base.token_inner, TokenInner::KwdMap);
+            assert_eq!(generic_type.inner.len(), 2);
+            test_primitive_type(&generic_type.inner[0], TokenInner::KwdInt);
+
+            if let ConcreteType::NullableType(nullable_type) = &generic_type.inner[1] {
+                if let ConcreteType::GenericType(generic_type) = &nullable_type.inner {
+                    assert_eq!(generic_type.base.token_inner, TokenInner::KwdVector);
+                } else {
+                    panic!("should be a generic type");
+                }
+            } else {
+                panic!("should be a nullable type");
+            }
+        } else {
+            panic!("should be a generic type");
+        }
+
+        // `map` is not a keyword outside types
+        assert_eq!(parser.current_token().token_inner, TokenInner::Ident("map"));
+    }
 }
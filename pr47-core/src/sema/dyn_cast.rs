@@ -5,12 +5,16 @@ use crate::sema::expr::{
     AsExpr,
     AwaitExpr,
     BinaryExpr,
+    FieldAssignExpr,
     FieldRefExpr,
     IdRefExpr,
     LiteralExpr,
+    MapOpExpr,
+    MethodCallExpr,
     SubscriptExpr,
     UnaryExpr
 };
+use crate::sema::stmt::{CompoundStmt, ForStmt};
 
 pub enum ASTNode<'s> {
     ObjectDeclNode(ObjectDecl<'s>),
@@ -21,8 +25,13 @@ pub enum ASTNode<'s> {
     BinaryExprNode(BinaryExpr<'s>),
     SubscriptExprNode(SubscriptExpr<'s>),
     FieldRefExprNode(FieldRefExpr<'s>),
+    FieldAssignExprNode(FieldAssignExpr<'s>),
+    MethodCallExprNode(MethodCallExpr<'s>),
+    MapOpExprNode(MapOpExpr<'s>),
     AwaitExprNode(AwaitExpr<'s>),
-    AsExprNode(AsExpr<'s>)
+    AsExprNode(AsExpr<'s>),
+    CompoundStmtNode(CompoundStmt<'s>),
+    ForStmtNode(ForStmt<'s>)
 }
 
 pub unsafe trait DynCast<T> {
@@ -60,5 +69,10 @@ impl_dyn_cast!(UnaryExprNode, UnaryExpr);
 impl_dyn_cast!(BinaryExprNode, BinaryExpr);
 impl_dyn_cast!(SubscriptExprNode, SubscriptExpr);
 impl_dyn_cast!(FieldRefExprNode, FieldRefExpr);
+impl_dyn_cast!(FieldAssignExprNode, FieldAssignExpr);
+impl_dyn_cast!(MethodCallExprNode, MethodCallExpr);
+impl_dyn_cast!(MapOpExprNode, MapOpExpr);
 impl_dyn_cast!(AwaitExprNode, AwaitExpr);
 impl_dyn_cast!(AsExprNode, AsExpr);
+impl_dyn_cast!(CompoundStmtNode, CompoundStmt);
+impl_dyn_cast!(ForStmtNode, ForStmt);
@@ -6,11 +6,15 @@ use xjbutil::value::Value;
 use crate::data::tyck::TyckInfo;
 use crate::sema::arena::{Arena, ArenaPtr};
 use crate::sema::decl::{FuncDecl, ObjectDecl};
+use crate::sema::map::MapOp;
+use crate::sema::method::MethodResolution;
+use crate::sema::property::FieldResolution;
 use crate::syntax::expr::{
     ConcreteAsExpr,
     ConcreteAwaitExpr,
     ConcreteBinaryExpr,
     ConcreteFieldRefExpr,
+    ConcreteFuncCallExpr,
     ConcreteLiteralExpr,
     ConcreteSubscriptExpr,
     ConcreteUnaryExpr,
@@ -25,6 +29,9 @@ pub enum Expr<'s> {
     BinaryExpr(ArenaPtr<'s, BinaryExpr<'s>>),
     SubscriptExpr(ArenaPtr<'s, BinaryExpr<'s>>),
     FieldRefExpr(ArenaPtr<'s, FieldRefExpr<'s>>),
+    FieldAssignExpr(ArenaPtr<'s, FieldAssignExpr<'s>>),
+    MethodCallExpr(ArenaPtr<'s, MethodCallExpr<'s>>),
+    MapOpExpr(ArenaPtr<'s, MapOpExpr<'s>>),
     AwaitExpr(ArenaPtr<'s, AwaitExpr<'s>>),
     AsExpr(ArenaPtr<'s, AsExpr<'s>>)
 }
@@ -41,6 +48,9 @@ impl<'s> Expr<'s> {
             Expr::BinaryExpr(expr) => &expr.get_tricky(arena).maybe_constant_folding,
             Expr::SubscriptExpr(expr) => &expr.get_tricky(arena).maybe_constant_folding,
             Expr::FieldRefExpr(expr) => &expr.get_tricky(arena).maybe_constant_folding,
+            Expr::FieldAssignExpr(_) => return None,
+            Expr::MethodCallExpr(_) => return None,
+            Expr::MapOpExpr(_) => return None,
             Expr::AwaitExpr(_) => return None,
             Expr::AsExpr(expr) => &expr.get_tricky(arena).maybe_constant_folding,
         }).as_ref()
@@ -57,6 +67,9 @@ impl<'s> Expr<'s> {
             Expr::BinaryExpr(expr) => expr.get_tricky(arena).ty,
             Expr::SubscriptExpr(expr) => expr.get_tricky(arena).ty,
             Expr::FieldRefExpr(expr) => expr.get_tricky(arena).ty,
+            Expr::FieldAssignExpr(expr) => expr.get_tricky(arena).value.get_type(arena),
+            Expr::MethodCallExpr(expr) => expr.get_tricky(arena).ty,
+            Expr::MapOpExpr(expr) => expr.get_tricky(arena).ty,
             Expr::AwaitExpr(expr) => Some(expr.get_tricky(arena).ty),
             Expr::AsExpr(expr) => Some(expr.get_tricky(arena).as_type)
         }
@@ -192,6 +205,7 @@ pub struct SubscriptExpr<'s> {
 pub struct FieldRefExpr<'s> {
     pub base: Expr<'s>,
     pub field: &'s str,
+    pub resolution: FieldResolution,
     pub ty: Option<NonNull<TyckInfo>>,
     pub tyck_base: bool,
 
@@ -199,6 +213,37 @@ pub struct FieldRefExpr<'s> {
     pub concrete: &'s ConcreteFieldRefExpr<'s>
 }
 
+/// `base.field = value`
+pub struct FieldAssignExpr<'s> {
+    pub base: Expr<'s>,
+    pub field: &'s str,
+    pub value: Expr<'s>,
+    pub resolution: FieldResolution,
+
+    pub concrete: &'s ConcreteBinaryExpr<'s>
+}
+
+/// `base.method(args)`, where `base` is a host object
+pub struct MethodCallExpr<'s> {
+    pub base: Expr<'s>,
+    pub method: &'s str,
+    pub args: Vec<Expr<'s>>,
+    pub resolution: MethodResolution,
+    pub ty: Option<NonNull<TyckInfo>>,
+
+    pub concrete: &'s ConcreteFuncCallExpr<'s>
+}
+
+/// `base.op(args)`, where `base` is a builtin `map`
+pub struct MapOpExpr<'s> {
+    pub op: MapOp,
+    pub base: Expr<'s>,
+    pub args: Vec<Expr<'s>>,
+    pub ty: Option<NonNull<TyckInfo>>,
+
+    pub concrete: &'s ConcreteFuncCallExpr<'s>
+}
+
 pub struct AwaitExpr<'s> {
     pub expr: Expr<'s>,
     pub ty: NonNull<TyckInfo>,
@@ -0,0 +1,72 @@
+//! # Resolution of `for` loops
+//!
+//! `for x in expr { ... }` is resolved by the static type of `expr`:
+//! - `vector<T>` is iterated by element, `x` is of type `T`;
+//! - `object` is iterated by field name, `x` is of type `string`;
+//! - `string` is iterated by character, `x` is of type `char`;
+//! - host types registered as iterable are iterated through their `IterElem` method, `x` is of
+//!   the element type of that method;
+//! - for `any`, the iteration is dispatched on the runtime type of `expr`, `x` is of type `any`.
+//!
+//! All of these are compiled to `ITER-BEGIN` and `ITER-NEXT`.
+
+use std::any::TypeId;
+use std::ptr::NonNull;
+
+use crate::builtins::object::Object;
+use crate::builtins::vec::VMGenericVec;
+use crate::data::tyck::{ContainerTyckInfo, TyckInfo, TyckInfoPool};
+use crate::ffi::iter::ITER_ELEM_METHOD;
+use crate::ffi::member::{Method, MethodFunc, MethodTable};
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum IterKind {
+    Vector,
+    Object,
+    String,
+    Host,
+    /// The iterated value is `any`, resolved at runtime by `ITER-BEGIN`
+    Dynamic
+}
+
+#[derive(Clone, Copy)]
+pub struct IterResolution {
+    pub kind: IterKind,
+    pub elem_type: NonNull<TyckInfo>
+}
+
+/// Resolve iteration over value of type `iterated_type`, `None` if `iterated_type` is not
+/// iterable
+pub fn resolve_iteration(
+    tyck_info_pool: &TyckInfoPool,
+    methods: &MethodTable,
+    iterated_type: &TyckInfo
+) -> Option<IterResolution> {
+    let (kind, elem_type): (IterKind, NonNull<TyckInfo>) = match iterated_type {
+        TyckInfo::AnyType => (IterKind::Dynamic, tyck_info_pool.get_any_type()),
+        TyckInfo::Container(ContainerTyckInfo { type_id, params })
+            if *type_id == TypeId::of::<VMGenericVec>() =>
+        {
+            (IterKind::Vector, unsafe { params.as_ref()[0] })
+        },
+        TyckInfo::Plain(type_id) if *type_id == TypeId::of::<Object>() =>
+            (IterKind::Object, tyck_info_pool.get_string_type()),
+        TyckInfo::Plain(type_id) if *type_id == TypeId::of::<String>() =>
+            (IterKind::String, tyck_info_pool.get_char_type()),
+        TyckInfo::Plain(type_id) => {
+            let method: &Method = methods.get(*type_id, ITER_ELEM_METHOD)?;
+            if !matches!(method.func, MethodFunc::Sync(_)) {
+                return None;
+            }
+            if let TyckInfo::Function(func_type) = unsafe { method.func_type.as_ref() } {
+                (IterKind::Host, unsafe { func_type.rets.as_ref()[1] })
+            } else {
+                return None;
+            }
+        },
+        _ => return None
+    };
+
+    Some(IterResolution { kind, elem_type })
+}
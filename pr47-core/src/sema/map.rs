@@ -0,0 +1,72 @@
+//! # Builtin operations on maps
+//!
+//! Methods called on receivers of static type `map<K, V>` are builtin operations, which are
+//! compiled to `MAP-*` instructions rather than calls to host functions:
+//! ```text
+//! get(K) -> ?V
+//! put(K, V)
+//! remove(K) -> bool
+//! contains(K) -> bool
+//! len() -> int
+//! keys() -> vector<K>
+//! ```
+
+use std::any::TypeId;
+use std::ptr::NonNull;
+
+use smallvec::{SmallVec, smallvec};
+
+use crate::builtins::vec::VMGenericVec;
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum MapOp {
+    Get,
+    Put,
+    Remove,
+    Contains,
+    Len,
+    Keys
+}
+
+pub struct MapOpSignature {
+    pub op: MapOp,
+    pub param_types: SmallVec<[NonNull<TyckInfo>; 2]>,
+    pub ret_type: Option<NonNull<TyckInfo>>
+}
+
+/// Resolve builtin operation `name` on map of type `map<key_type, value_type>`
+pub fn resolve_map_op(
+    tyck_info_pool: &mut TyckInfoPool,
+    key_type: NonNull<TyckInfo>,
+    value_type: NonNull<TyckInfo>,
+    name: &str
+) -> Option<MapOpSignature> {
+    let (op, param_types, ret_type): (MapOp, SmallVec<_>, Option<NonNull<TyckInfo>>) =
+        match name {
+            "get" => {
+                let ret_type: NonNull<TyckInfo> = match unsafe { value_type.as_ref() } {
+                    TyckInfo::AnyType | TyckInfo::Nullable(_) => value_type,
+                    _ => tyck_info_pool.create_nullable_type(value_type)
+                };
+                (MapOp::Get, smallvec![key_type], Some(ret_type))
+            },
+            "put" => (MapOp::Put, smallvec![key_type, value_type], None),
+            "remove" =>
+                (MapOp::Remove, smallvec![key_type], Some(tyck_info_pool.get_bool_type())),
+            "contains" =>
+                (MapOp::Contains, smallvec![key_type], Some(tyck_info_pool.get_bool_type())),
+            "len" => (MapOp::Len, smallvec![], Some(tyck_info_pool.get_int_type())),
+            "keys" => {
+                let keys_type: NonNull<TyckInfo> = tyck_info_pool.create_container_type(
+                    TypeId::of::<VMGenericVec>(),
+                    &[key_type]
+                );
+                (MapOp::Keys, smallvec![], Some(keys_type))
+            },
+            _ => return None
+        };
+
+    Some(MapOpSignature { op, param_types, ret_type })
+}
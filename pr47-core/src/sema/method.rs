@@ -0,0 +1,95 @@
+//! # Resolution of method calls on host objects
+//!
+//! `obj.method(args)` is resolved by the static type of `obj`:
+//! - for host types and containers, the method registered on that type gets called directly
+//!   as an FFI function, with `obj` being the first argument;
+//! - for `any`, the call is dispatched on the runtime type of `obj`, by the method ID shared by
+//!   all methods named `method`.
+
+use std::ptr::NonNull;
+
+use crate::data::tyck::{ContainerTyckInfo, FunctionTyckInfo, TyckInfo};
+use crate::ffi::member::{Method, MethodFunc, MethodTable};
+
+#[derive(Clone, Copy)]
+pub enum MethodResolution {
+    /// The receiver type is statically known
+    Static(Method),
+    /// The receiver is `any`, dispatch with `CALL-METHOD` or `CALL-METHOD-ASYNC`
+    Dynamic { method_id: usize, is_async: bool }
+}
+
+impl MethodResolution {
+    pub fn is_async(&self) -> bool {
+        match self {
+            MethodResolution::Static(method) => !matches!(method.func, MethodFunc::Sync(_)),
+            MethodResolution::Dynamic { is_async, .. } => *is_async
+        }
+    }
+
+    /// Parameter types of the method, excluding the receiver, `None` if unknown until runtime
+    pub fn param_types(&self) -> Option<&[NonNull<TyckInfo>]> {
+        let func_type: &FunctionTyckInfo = self.func_type()?;
+        let params: &[NonNull<TyckInfo>] = unsafe { func_type.params.as_ref() };
+        Some(&params[1..])
+    }
+
+    /// Return types of the method, `None` if unknown until runtime
+    pub fn ret_types(&self) -> Option<&[NonNull<TyckInfo>]> {
+        Some(unsafe { self.func_type()?.rets.as_ref() })
+    }
+
+    fn func_type(&self) -> Option<&FunctionTyckInfo> {
+        if let MethodResolution::Static(method) = self {
+            if let TyckInfo::Function(func_type) = unsafe { method.func_type.as_ref() } {
+                return Some(func_type);
+            }
+        }
+        None
+    }
+}
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum MethodResolveError {
+    /// No method of given name is registered on the receiver type
+    NoSuchMethod,
+    /// The receiver may be `null`
+    NullableReceiver,
+    /// The receiver is `any`, but methods of given name are sync on some types while async on
+    /// others, so the kind of call cannot be determined
+    AmbiguousAsync
+}
+
+/// Resolve method `name` called on receiver of type `receiver_type`
+pub fn resolve_method(
+    methods: &MethodTable,
+    receiver_type: &TyckInfo,
+    name: &str
+) -> Result<MethodResolution, MethodResolveError> {
+    match receiver_type {
+        TyckInfo::AnyType => {
+            let method_id: usize = methods.id(name)
+                .ok_or(MethodResolveError::NoSuchMethod)?;
+            let mut is_async: Option<bool> = None;
+            for (_, method) /*: (&TypeId, &Method)*/ in methods.overloads(name) {
+                let method_is_async: bool = !matches!(method.func, MethodFunc::Sync(_));
+                match is_async {
+                    Some(is_async) if is_async != method_is_async =>
+                        return Err(MethodResolveError::AmbiguousAsync),
+                    _ => is_async = Some(method_is_async)
+                }
+            }
+            Ok(MethodResolution::Dynamic {
+                method_id,
+                is_async: is_async.unwrap_or(false)
+            })
+        },
+        TyckInfo::Plain(type_id) | TyckInfo::Container(ContainerTyckInfo { type_id, .. }) =>
+            methods.get(*type_id, name)
+                .map(|method: &Method| MethodResolution::Static(*method))
+                .ok_or(MethodResolveError::NoSuchMethod),
+        TyckInfo::Nullable(_) => Err(MethodResolveError::NullableReceiver),
+        _ => Err(MethodResolveError::NoSuchMethod)
+    }
+}
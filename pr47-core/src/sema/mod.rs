@@ -3,5 +3,11 @@ pub mod decl;
 pub mod decl_context;
 pub mod expr;
 pub mod dyn_cast;
+pub mod iter;
+pub mod map;
+pub mod method;
 pub mod phase2;
+pub mod property;
 pub mod scope;
+pub mod stmt;
+pub mod ty;
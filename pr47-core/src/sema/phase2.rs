@@ -1,17 +1,39 @@
+use std::mem::replace;
 use std::ptr::NonNull;
 
 use xjbutil::value::Value;
 
 use crate::data::tyck::{TyckInfo, TyckInfoPool};
 use crate::diag::{diag_data, DiagContext};
+use crate::diag::location::SourceRange;
 use crate::sema::arena::{Arena, ArenaPtr};
 use crate::sema::decl::ObjectDecl;
-use crate::sema::expr::{Expr, IdRefExpr, LiteralExpr};
+use crate::sema::expr::{
+    Expr,
+    FieldAssignExpr,
+    FieldRefExpr,
+    IdRefExpr,
+    LiteralExpr,
+    MapOpExpr,
+    MethodCallExpr
+};
+use crate::sema::iter::{IterResolution, resolve_iteration};
+use crate::sema::map::{MapOpSignature, resolve_map_op};
+use crate::sema::method::{MethodResolution, MethodResolveError, resolve_method};
+use crate::sema::property::{
+    FieldResolution,
+    PropertyResolveError,
+    is_assignable,
+    resolve_property
+};
 use crate::sema::scope::{Scope, ScopeKind};
+use crate::sema::stmt::{CompoundStmt, ForStmt, Stmt};
+use crate::sema::ty::{map_type_params, resolve_generic_type};
 use crate::syntax::expr::{
     ConcreteAsExpr,
     ConcreteAwaitExpr,
     ConcreteBinaryExpr,
+    ConcreteExpr,
     ConcreteFieldRefExpr,
     ConcreteFuncCallExpr,
     ConcreteLiteralExpr,
@@ -19,17 +41,304 @@ use crate::syntax::expr::{
     ConcreteUnaryExpr,
     LiteralExprContent
 };
+use crate::syntax::decl::{ConcreteDecl, ConcreteObjectDecl};
 use crate::syntax::id::Identifier;
-use crate::syntax::visitor::ExprVisitor;
+use crate::syntax::stmt::{ConcreteCompoundStmt, ConcreteForStmt};
+use crate::syntax::token::{Token, TokenInner};
+use crate::syntax::ty::{ConcreteGenericType, ConcreteNullableType, ConcreteType};
+use crate::syntax::visitor::{ExprVisitor, StmtVisitor, TypeVisitor};
+use crate::ffi::member::{MethodTable, PropertyTable};
 
 pub struct SemaPhase2<'s, 'd> {
     scope: Scope<'s>,
     arena: &'s mut Arena<'s>,
     tyck_info_pool: &'s mut TyckInfoPool,
+    methods: &'s MethodTable,
+    properties: &'s PropertyTable,
 
     diag: &'d mut DiagContext
 }
 
+impl<'s, 'd> SemaPhase2<'s, 'd> {
+    pub fn new(
+        arena: &'s mut Arena<'s>,
+        tyck_info_pool: &'s mut TyckInfoPool,
+        methods: &'s MethodTable,
+        properties: &'s PropertyTable,
+        diag: &'d mut DiagContext
+    ) -> Self {
+        Self {
+            scope: Scope::new(ScopeKind::Global),
+            arena,
+            tyck_info_pool,
+            methods,
+            properties,
+            diag
+        }
+    }
+
+    fn visit_method_call_expr(
+        &mut self,
+        func_call_expr: &'s ConcreteFuncCallExpr<'s>,
+        field_ref_expr: &'s ConcreteFieldRefExpr<'s>
+    ) -> Option<Expr<'s>> {
+        let base: Expr<'s> = self.visit_expr(&field_ref_expr.base)?;
+        let mut args: Vec<Expr<'s>> = Vec::with_capacity(func_call_expr.args.len());
+        for arg /*: &ConcreteExpr*/ in func_call_expr.args.iter() {
+            args.push(self.visit_expr(arg)?);
+        }
+
+        let method: &'s str = field_ref_expr.id.as_unqual()?.get_str_value();
+        let receiver_type: NonNull<TyckInfo> = base.get_type(self.arena)
+            .unwrap_or_else(|| self.tyck_info_pool.get_any_type());
+        let receiver_type: &TyckInfo = unsafe { receiver_type.as_ref() };
+
+        if let Some((key_type, value_type)) = map_type_params(receiver_type) {
+            return self.visit_map_op_expr(
+                func_call_expr,
+                field_ref_expr,
+                base,
+                args,
+                (key_type, value_type)
+            );
+        }
+
+        let resolution: MethodResolution = match resolve_method(
+            self.methods,
+            receiver_type,
+            method
+        ) {
+            Ok(resolution) => resolution,
+            Err(e /*: MethodResolveError*/) => {
+                let diag_id: u32 = match e {
+                    MethodResolveError::NoSuchMethod => diag_data::err_no_method_0_on_type_1,
+                    MethodResolveError::NullableReceiver =>
+                        diag_data::err_method_0_on_nullable_type_1,
+                    MethodResolveError::AmbiguousAsync => diag_data::err_ambiguous_async_method_0
+                };
+                self.diag.diag(field_ref_expr.id.source_range().left(), diag_id)
+                    .add_arg(method)
+                    .add_arg(receiver_type)
+                    .add_mark(field_ref_expr.id.source_range().into())
+                    .emit();
+                return None;
+            }
+        };
+
+        if let Some(param_types) = resolution.param_types() {
+            if param_types.len() != args.len() {
+                let diag_id: u32 = diag_data::err_method_0_expects_1_args_got_2;
+                self.diag.diag(func_call_expr.lparen_loc, diag_id)
+                    .add_arg(method)
+                    .add_arg(param_types.len())
+                    .add_arg(args.len())
+                    .emit();
+                return None;
+            }
+        }
+
+        let ty: Option<NonNull<TyckInfo>> = match resolution.ret_types() {
+            Some([ret_type]) if !resolution.is_async() => Some(*ret_type),
+            _ => None
+        };
+
+        let method_call_expr: ArenaPtr<'s, MethodCallExpr<'s>> = ArenaPtr::new_in(MethodCallExpr {
+            base,
+            method,
+            args,
+            resolution,
+            ty,
+            concrete: func_call_expr
+        }, self.arena);
+        Some(Expr::MethodCallExpr(method_call_expr))
+    }
+
+    fn visit_map_op_expr(
+        &mut self,
+        func_call_expr: &'s ConcreteFuncCallExpr<'s>,
+        field_ref_expr: &'s ConcreteFieldRefExpr<'s>,
+        base: Expr<'s>,
+        args: Vec<Expr<'s>>,
+        (key_type, value_type): (NonNull<TyckInfo>, NonNull<TyckInfo>)
+    ) -> Option<Expr<'s>> {
+        let method: &'s str = field_ref_expr.id.as_unqual()?.get_str_value();
+        let signature: MapOpSignature = if let Some(signature) = resolve_map_op(
+            self.tyck_info_pool,
+            key_type,
+            value_type,
+            method
+        ) {
+            signature
+        } else {
+            let receiver_type: NonNull<TyckInfo> = base.get_type(self.arena)?;
+            self.diag.diag(field_ref_expr.id.source_range().left(),
+                           diag_data::err_no_method_0_on_type_1)
+                .add_arg(method)
+                .add_arg(unsafe { receiver_type.as_ref() })
+                .add_mark(field_ref_expr.id.source_range().into())
+                .emit();
+            return None;
+        };
+
+        if signature.param_types.len() != args.len() {
+            self.diag.diag(func_call_expr.lparen_loc, diag_data::err_method_0_expects_1_args_got_2)
+                .add_arg(method)
+                .add_arg(signature.param_types.len())
+                .add_arg(args.len())
+                .emit();
+            return None;
+        }
+
+        for (param_type, arg) /*: (&NonNull<TyckInfo>, &Expr)*/ in
+            signature.param_types.iter().zip(args.iter())
+        {
+            let arg_type: NonNull<TyckInfo> = arg.get_type(self.arena)
+                .unwrap_or_else(|| self.tyck_info_pool.get_any_type());
+            let (param_type, arg_type): (&TyckInfo, &TyckInfo) =
+                unsafe { (param_type.as_ref(), arg_type.as_ref()) };
+            if !is_assignable(param_type, arg_type) {
+                self.diag.diag(func_call_expr.lparen_loc,
+                               diag_data::err_method_0_expects_arg_type_1_got_2)
+                    .add_arg(method)
+                    .add_arg(param_type)
+                    .add_arg(arg_type)
+                    .emit();
+                return None;
+            }
+        }
+
+        let map_op_expr: ArenaPtr<'s, MapOpExpr<'s>> = ArenaPtr::new_in(MapOpExpr {
+            op: signature.op,
+            base,
+            args,
+            ty: signature.ret_type,
+            concrete: func_call_expr
+        }, self.arena);
+        Some(Expr::MapOpExpr(map_op_expr))
+    }
+
+    fn resolve_field(
+        &mut self,
+        field_ref_expr: &'s ConcreteFieldRefExpr<'s>,
+        base: &Expr<'s>,
+        write: bool
+    ) -> Option<(&'s str, FieldResolution)> {
+        let field: &'s str = field_ref_expr.id.as_unqual()?.get_str_value();
+        let receiver_type: NonNull<TyckInfo> = base.get_type(self.arena)
+            .unwrap_or_else(|| self.tyck_info_pool.get_any_type());
+        let receiver_type: &TyckInfo = unsafe { receiver_type.as_ref() };
+
+        match resolve_property(self.properties, receiver_type, field, write) {
+            Ok(resolution) => Some((field, resolution)),
+            Err(e /*: PropertyResolveError*/) => {
+                let diag_id: u32 = match e {
+                    PropertyResolveError::NoSuchProperty => diag_data::err_no_property_0_on_type_1,
+                    PropertyResolveError::NullableReceiver =>
+                        diag_data::err_property_0_on_nullable_type_1,
+                    PropertyResolveError::ReadOnly => diag_data::err_property_0_is_read_only
+                };
+                self.diag.diag(field_ref_expr.id.source_range().left(), diag_id)
+                    .add_arg(field)
+                    .add_arg(receiver_type)
+                    .add_mark(field_ref_expr.id.source_range().into())
+                    .emit();
+                None
+            }
+        }
+    }
+
+    fn visit_field_assign_expr(
+        &mut self,
+        binary_expr: &'s ConcreteBinaryExpr<'s>,
+        field_ref_expr: &'s ConcreteFieldRefExpr<'s>
+    ) -> Option<Expr<'s>> {
+        let base: Expr<'s> = self.visit_expr(&field_ref_expr.base)?;
+        let value: Expr<'s> = self.visit_expr(&binary_expr.rhs)?;
+        let (field, resolution): (&'s str, FieldResolution) =
+            self.resolve_field(field_ref_expr, &base, true)?;
+
+        if let Some(field_type) = resolution.field_type() {
+            let value_type: NonNull<TyckInfo> = value.get_type(self.arena)
+                .unwrap_or_else(|| self.tyck_info_pool.get_any_type());
+            let (field_type, value_type): (&TyckInfo, &TyckInfo) =
+                unsafe { (field_type.as_ref(), value_type.as_ref()) };
+            if !is_assignable(field_type, value_type) {
+                self.diag.diag(binary_expr.op.range.left(),
+                               diag_data::err_property_0_expects_type_1_got_2)
+                    .add_arg(field)
+                    .add_arg(field_type)
+                    .add_arg(value_type)
+                    .add_mark(binary_expr.op.range.into())
+                    .emit();
+                return None;
+            }
+        }
+
+        let field_assign_expr: ArenaPtr<'s, FieldAssignExpr<'s>> = ArenaPtr::new_in(
+            FieldAssignExpr {
+                base,
+                field,
+                value,
+                resolution,
+                concrete: binary_expr
+            },
+            self.arena
+        );
+        Some(Expr::FieldAssignExpr(field_assign_expr))
+    }
+
+    /// Get the name of `id`, reporting an error if `id` is qualified
+    fn unqual_name(&mut self, id: &'s Identifier<'s>) -> Option<&'s str> {
+        if let Some(token) = id.as_unqual() {
+            return Some(token.get_str_value());
+        }
+
+        self.diag.diag(id.source_range().left(), diag_data::err_expected_unqual_id)
+            .add_mark(id.source_range().into())
+            .emit();
+        None
+    }
+
+    fn enter_scope(&mut self, scope_kind: ScopeKind) {
+        let parent: Scope<'s> = replace(&mut self.scope, Scope::new(scope_kind));
+        self.scope.parent = Some(Box::new(parent));
+    }
+
+    fn leave_scope(&mut self) {
+        let parent: Box<Scope<'s>> = self.scope.parent.take().expect("leaving outermost scope");
+        self.scope = *parent;
+    }
+
+    /// Visit all statements of `compound_stmt` in a new local scope, with loop variable
+    /// `loop_var` bound in that scope if any. Statements after a failed one are still visited, so
+    /// that all their diagnostics get reported.
+    fn visit_block(
+        &mut self,
+        compound_stmt: &'s ConcreteCompoundStmt<'s>,
+        loop_var: Option<(&'s str, NonNull<TyckInfo>)>
+    ) -> Option<ArenaPtr<'s, CompoundStmt<'s>>> {
+        self.enter_scope(ScopeKind::Local);
+        if let Some((name, ty)) = loop_var {
+            self.scope.loop_vars.insert(name, ty);
+        }
+        let mut stmts: Vec<Stmt<'s>> = Vec::with_capacity(compound_stmt.stmts.len());
+        let mut failed: bool = false;
+        for stmt /*: &ConcreteStmt*/ in compound_stmt.stmts.iter() {
+            if let Some(stmt /*: Stmt*/) = self.visit_stmt(stmt) {
+                stmts.push(stmt);
+            } else {
+                failed = true;
+            }
+        }
+        self.leave_scope();
+
+        if failed {
+            return None;
+        }
+        Some(ArenaPtr::new_in(CompoundStmt { stmts, concrete: compound_stmt }, self.arena))
+    }
+}
+
 #[allow(unused)]
 impl<'s, 'd> ExprVisitor<'s> for SemaPhase2<'s, 'd> {
     type ExprResult = Option<Expr<'s>>;
@@ -63,10 +372,20 @@ impl<'s, 'd> ExprVisitor<'s> for SemaPhase2<'s, 'd> {
     }
 
     fn visit_binary_expr(&mut self, binary_expr: &'s ConcreteBinaryExpr<'s>) -> Self::ExprResult {
+        if let TokenInner::SymEq = binary_expr.op.token_inner {
+            if let ConcreteExpr::FieldRefExpr(field_ref_expr) = binary_expr.lhs.as_ref() {
+                return self.visit_field_assign_expr(binary_expr, field_ref_expr);
+            }
+        }
+
         todo!()
     }
 
     fn visit_func_call_expr(&mut self, func_call_expr: &'s ConcreteFuncCallExpr<'s>) -> Self::ExprResult {
+        if let ConcreteExpr::FieldRefExpr(field_ref_expr) = func_call_expr.func.as_ref() {
+            return self.visit_method_call_expr(func_call_expr, field_ref_expr);
+        }
+
         todo!()
     }
 
@@ -75,7 +394,22 @@ impl<'s, 'd> ExprVisitor<'s> for SemaPhase2<'s, 'd> {
     }
 
     fn visit_field_ref_expr(&mut self, field_ref_expr: &'s ConcreteFieldRefExpr<'s>) -> Self::ExprResult {
-        todo!()
+        let base: Expr<'s> = self.visit_expr(&field_ref_expr.base)?;
+        let (field, resolution): (&'s str, FieldResolution) =
+            self.resolve_field(field_ref_expr, &base, false)?;
+        let tyck_base: bool = base.get_type(self.arena)
+            .is_none_or(|ty: NonNull<TyckInfo>| unsafe { ty.as_ref().is_any() });
+
+        let field_ref_expr: ArenaPtr<'s, FieldRefExpr<'s>> = ArenaPtr::new_in(FieldRefExpr {
+            base,
+            field,
+            resolution,
+            ty: resolution.field_type(),
+            tyck_base: tyck_base && matches!(resolution, FieldResolution::ObjectField),
+            maybe_constant_folding: None,
+            concrete: field_ref_expr
+        }, self.arena);
+        Some(Expr::FieldRefExpr(field_ref_expr))
     }
 
     fn visit_as_expr(&mut self, as_expr: &'s ConcreteAsExpr<'s>) -> Self::ExprResult {
@@ -86,3 +420,358 @@ impl<'s, 'd> ExprVisitor<'s> for SemaPhase2<'s, 'd> {
         todo!()
     }
 }
+
+#[allow(unused)]
+impl<'s, 'd> StmtVisitor<'s> for SemaPhase2<'s, 'd> {
+    type StmtResult = Option<Stmt<'s>>;
+
+    fn visit_compound_stmt(
+        &mut self,
+        compound_stmt: &'s ConcreteCompoundStmt<'s>
+    ) -> Self::StmtResult {
+        self.visit_block(compound_stmt, None).map(Stmt::CompoundStmt)
+    }
+
+    fn visit_expr_stmt(&mut self, expr: &'s ConcreteExpr<'s>) -> Self::StmtResult {
+        self.visit_expr(expr).map(Stmt::ExprStmt)
+    }
+
+    fn visit_decl_stmt(&mut self, decl: &'s ConcreteDecl<'s>) -> Self::StmtResult {
+        let (concrete, is_const): (&'s ConcreteObjectDecl<'s>, bool) = match decl {
+            ConcreteDecl::ConstDecl(object_decl) => (object_decl, true),
+            ConcreteDecl::VarDecl(object_decl) => (object_decl, false),
+            _ => unreachable!("the parser only produces object declarations as statements")
+        };
+
+        let name: &'s str = self.unqual_name(&concrete.name)?;
+        let init: Expr<'s> = self.visit_expr(&concrete.init_expr)?;
+        let init_type: NonNull<TyckInfo> = init.get_type(self.arena)
+            .unwrap_or_else(|| self.tyck_info_pool.get_any_type());
+        let ty: NonNull<TyckInfo> = match &concrete.obj_type {
+            None | Some(ConcreteType::DeducedType(_)) => init_type,
+            Some(obj_type /*: &ConcreteType*/) => {
+                let ty: NonNull<TyckInfo> = self.visit_type(obj_type)?;
+                let (decl_type, init_type): (&TyckInfo, &TyckInfo) =
+                    unsafe { (ty.as_ref(), init_type.as_ref()) };
+                if !is_assignable(decl_type, init_type) {
+                    self.diag.diag(concrete.eq_range.left(),
+                                   diag_data::err_cannot_init_0_of_type_1_with_2)
+                        .add_arg(name)
+                        .add_arg(decl_type)
+                        .add_arg(init_type)
+                        .add_mark(concrete.eq_range.into())
+                        .emit();
+                    return None;
+                }
+                ty
+            }
+        };
+
+        if self.scope.check_name_collision(name) {
+            self.diag.diag(concrete.name.source_range().left(), diag_data::err_redefinition_of_0)
+                .add_arg(name)
+                .add_mark(concrete.name.source_range().into())
+                .emit();
+            return None;
+        }
+
+        let object_decl: ArenaPtr<'s, ObjectDecl<'s>> = ArenaPtr::new_in(ObjectDecl {
+            name,
+            is_const,
+            ty,
+            init,
+            concrete
+        }, self.arena);
+        self.scope.object_decls.insert(name, object_decl);
+        Some(Stmt::DeclStmt(object_decl))
+    }
+
+    fn visit_for_stmt(&mut self, for_stmt: &'s ConcreteForStmt<'s>) -> Self::StmtResult {
+        let var_name: &'s str = self.unqual_name(&for_stmt.var_name)?;
+        let iterated: Expr<'s> = self.visit_expr(&for_stmt.iterated)?;
+        let iterated_type: NonNull<TyckInfo> = iterated.get_type(self.arena)
+            .unwrap_or_else(|| self.tyck_info_pool.get_any_type());
+        let iterated_type: &TyckInfo = unsafe { iterated_type.as_ref() };
+
+        let resolution: IterResolution = if let Some(resolution) = resolve_iteration(
+            self.tyck_info_pool,
+            self.methods,
+            iterated_type
+        ) {
+            resolution
+        } else {
+            self.diag.diag(for_stmt.in_kwd_range.left(), diag_data::err_type_0_is_not_iterable)
+                .add_arg(iterated_type)
+                .add_mark(for_stmt.in_kwd_range.into())
+                .emit();
+            return None;
+        };
+
+        let body: ArenaPtr<'s, CompoundStmt<'s>> =
+            self.visit_block(&for_stmt.body, Some((var_name, resolution.elem_type)))?;
+        let for_stmt: ArenaPtr<'s, ForStmt<'s>> = ArenaPtr::new_in(ForStmt {
+            var_name,
+            iterated,
+            resolution,
+            body,
+            concrete: for_stmt
+        }, self.arena);
+        Some(Stmt::ForStmt(for_stmt))
+    }
+}
+
+#[allow(unused)]
+impl<'s, 'd> TypeVisitor<'s> for SemaPhase2<'s, 'd> {
+    type TypeResult = Option<NonNull<TyckInfo>>;
+
+    fn visit_primitive_type(&mut self, primitive_type: &'s Token<'s>) -> Self::TypeResult {
+        match primitive_type.token_inner {
+            TokenInner::KwdAny => Some(self.tyck_info_pool.get_any_type()),
+            TokenInner::KwdBool => Some(self.tyck_info_pool.get_bool_type()),
+            TokenInner::KwdChar => Some(self.tyck_info_pool.get_char_type()),
+            TokenInner::KwdFloat => Some(self.tyck_info_pool.get_float_type()),
+            TokenInner::KwdInt => Some(self.tyck_info_pool.get_int_type()),
+            TokenInner::KwdObject => Some(self.tyck_info_pool.get_object_type()),
+            TokenInner::KwdString => Some(self.tyck_info_pool.get_string_type()),
+            _ => {
+                self.diag.diag(primitive_type.range.left(), diag_data::err_void_type_not_allowed)
+                    .add_mark(primitive_type.range.into())
+                    .emit();
+                None
+            }
+        }
+    }
+
+    fn visit_generic_type(&mut self, generic_type: &'s ConcreteGenericType<'s>) -> Self::TypeResult {
+        let mut params: Vec<NonNull<TyckInfo>> = Vec::with_capacity(generic_type.inner.len());
+        for param /*: &ConcreteType*/ in generic_type.inner.iter() {
+            params.push(self.visit_type(param)?);
+        }
+
+        match resolve_generic_type(self.tyck_info_pool, generic_type.base.token_inner, &params) {
+            Ok(ty) => Some(ty),
+            Err(param_count /*: usize*/) => {
+                let range: SourceRange = SourceRange::from_loc_pair(
+                    generic_type.base.range.left(),
+                    generic_type.right_angle
+                );
+                self.diag.diag(generic_type.left_angle,
+                               diag_data::err_generic_type_0_expects_1_params_got_2)
+                    .add_arg2(generic_type.base.token_inner)
+                    .add_arg(param_count)
+                    .add_arg(params.len())
+                    .add_mark(range.into())
+                    .emit();
+                None
+            }
+        }
+    }
+
+    fn visit_nullable_type(&mut self, nullable_type: &'s ConcreteNullableType<'s>) -> Self::TypeResult {
+        let inner: NonNull<TyckInfo> = self.visit_type(&nullable_type.inner)?;
+        Some(self.tyck_info_pool.create_nullable_type(inner))
+    }
+
+    /// `auto` of object declarations is deduced from the initializer before getting here, so
+    /// this is `auto` appearing where there's nothing to deduce from, like `vector<auto>`
+    fn visit_deduced_type(&mut self, deduced_type_source_range: SourceRange) -> Self::TypeResult {
+        self.diag.diag(deduced_type_source_range.left(), diag_data::err_cannot_deduce_type)
+            .add_mark(deduced_type_source_range.into())
+            .emit();
+        None
+    }
+
+    fn visit_user_type(&mut self, user_type: &'s Identifier<'s>) -> Self::TypeResult {
+        if let Some(ty) = user_type.as_unqual()
+            .and_then(|name: &Token<'s>| self.scope.lookup_type(name.get_str_value()))
+        {
+            return Some(ty);
+        }
+
+        self.diag.diag(user_type.source_range().left(), diag_data::err_unknown_type_0)
+            .add_arg(user_type.qual_name())
+            .add_mark(user_type.source_range().into())
+            .emit();
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::ptr::NonNull;
+
+    use smallvec::smallvec;
+
+    use crate::data::tyck::{TyckInfo, TyckInfoPool};
+    use crate::diag::DiagContext;
+    use crate::diag::location::SourceRange;
+    use crate::ffi::member::{MethodTable, PropertyTable};
+    use crate::parse::parser::Parser;
+    use crate::sema::arena::Arena;
+    use crate::sema::decl::ObjectDecl;
+    use crate::sema::iter::IterKind;
+    use crate::sema::phase2::SemaPhase2;
+    use crate::sema::stmt::{CompoundStmt, ForStmt, Stmt};
+    use crate::syntax::id::Identifier;
+    use crate::syntax::stmt::ConcreteStmt;
+    use crate::syntax::token::Token;
+    use crate::syntax::ty::ConcreteType;
+    use crate::syntax::visitor::{StmtVisitor, TypeVisitor};
+
+    /// Run `f` on a fresh `SemaPhase2`. Everything borrowed by sema is leaked, and so are the
+    /// AST nodes parsed by tests, so that all of them live long enough.
+    fn with_sema<'s, R>(f: impl FnOnce(&mut SemaPhase2<'s, '_>) -> R) -> R {
+        let arena: &'s mut Arena<'s> = Box::leak(Box::new(Arena::new()));
+        let tyck_info_pool: &'s mut TyckInfoPool = Box::leak(Box::new(TyckInfoPool::new()));
+        let methods: &'s MethodTable = Box::leak(Box::new(MethodTable::new()));
+        let properties: &'s PropertyTable = Box::leak(Box::new(PropertyTable::new()));
+        let mut diag: DiagContext = DiagContext::new();
+
+        let mut sema: SemaPhase2 =
+            SemaPhase2::new(arena, tyck_info_pool, methods, properties, &mut diag);
+        f(&mut sema)
+    }
+
+    fn parse_type(source: &'static str) -> &'static ConcreteType<'static> {
+        let diag: RefCell<DiagContext> = RefCell::new(DiagContext::new());
+        let mut parser: Parser = Parser::new(0, source, &diag);
+        Box::leak(Box::new(parser.parse_type(&[]).unwrap()))
+    }
+
+    fn parse_stmt(source: &'static str) -> &'static mut ConcreteStmt<'static> {
+        let diag: RefCell<DiagContext> = RefCell::new(DiagContext::new());
+        let mut parser: Parser = Parser::new(0, source, &diag);
+        Box::leak(Box::new(parser.parse_stmt(&[]).unwrap()))
+    }
+
+    #[test]
+    fn test_visit_type() {
+        let int_type: &ConcreteType = parse_type("int");
+        let vec_type: &ConcreteType = parse_type("vector<Point>");
+        with_sema(|sema| {
+            let point_type: NonNull<TyckInfo> = sema.tyck_info_pool.get_object_type();
+            sema.scope.types.insert("Point", point_type);
+
+            assert_eq!(sema.visit_type(int_type), Some(sema.tyck_info_pool.get_int_type()));
+            let vec_type: NonNull<TyckInfo> = sema.visit_type(vec_type).unwrap();
+            if let TyckInfo::Container(container) = unsafe { vec_type.as_ref() } {
+                assert_eq!(unsafe { container.params.as_ref() }, &[point_type]);
+            } else {
+                panic!("should be a container type")
+            }
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "diag_id = 2032")]
+    fn test_visit_void_type() {
+        let ty: &ConcreteType = parse_type("void");
+        with_sema(|sema| sema.visit_type(ty));
+    }
+
+    #[test]
+    #[should_panic(expected = "diag_id = 2033")]
+    fn test_visit_deduced_type() {
+        let ty: &ConcreteType = parse_type("vector<auto>");
+        with_sema(|sema| sema.visit_type(ty));
+    }
+
+    #[test]
+    #[should_panic(expected = "diag_id = 2034")]
+    fn test_visit_unknown_user_type() {
+        let ty: &ConcreteType = parse_type("vector<std::Point>");
+        with_sema(|sema| sema.visit_type(ty));
+    }
+
+    #[test]
+    fn test_visit_decl_stmt() {
+        let stmt: &ConcreteStmt =
+            parse_stmt("{ var a = 1; const b: int = 2; var c: any = \"c\"; }");
+        with_sema(|sema| {
+            let stmt: Stmt = sema.visit_stmt(stmt).unwrap();
+            let block: &CompoundStmt = if let Stmt::CompoundStmt(block) = &stmt {
+                block.get(sema.arena)
+            } else {
+                panic!("should be a compound statement")
+            };
+
+            let decls: Vec<&ObjectDecl> = block.stmts.iter()
+                .map(|stmt: &Stmt| if let Stmt::DeclStmt(decl) = stmt {
+                    decl.get(sema.arena)
+                } else {
+                    panic!("should be a declaration")
+                })
+                .collect();
+            assert_eq!(decls.len(), 3);
+            assert_eq!((decls[0].name, decls[0].is_const), ("a", false));
+            assert_eq!(decls[0].ty, sema.tyck_info_pool.get_int_type());
+            assert_eq!((decls[1].name, decls[1].is_const), ("b", true));
+            assert_eq!(decls[1].ty, sema.tyck_info_pool.get_int_type());
+            assert_eq!(decls[2].ty, sema.tyck_info_pool.get_any_type());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "diag_id = 2035")]
+    fn test_visit_decl_stmt_type_mismatch() {
+        let stmt: &ConcreteStmt = parse_stmt("{ var a: int = \"a\"; }");
+        with_sema(|sema| sema.visit_stmt(stmt).map(|_| ()));
+    }
+
+    #[test]
+    #[should_panic(expected = "diag_id = 2036")]
+    fn test_visit_decl_stmt_redefinition() {
+        let stmt: &ConcreteStmt = parse_stmt("{ var a = 1; const a = 2; }");
+        with_sema(|sema| sema.visit_stmt(stmt).map(|_| ()));
+    }
+
+    #[test]
+    fn test_visit_for_stmt() {
+        // the loop variable is only bound in the loop body
+        let stmt: &ConcreteStmt = parse_stmt("{ for c in \"abc\" { var d = 1; } var c = 2; }");
+        with_sema(|sema| {
+            let stmt: Stmt = sema.visit_stmt(stmt).unwrap();
+            let block: &CompoundStmt = if let Stmt::CompoundStmt(block) = &stmt {
+                block.get(sema.arena)
+            } else {
+                panic!("should be a compound statement")
+            };
+            let for_stmt: &ForStmt = if let Stmt::ForStmt(for_stmt) = &block.stmts[0] {
+                for_stmt.get(sema.arena)
+            } else {
+                panic!("should be a for statement")
+            };
+
+            assert_eq!(for_stmt.var_name, "c");
+            assert_eq!(for_stmt.resolution.kind, IterKind::String);
+            assert_eq!(for_stmt.resolution.elem_type, sema.tyck_info_pool.get_char_type());
+            assert_eq!(for_stmt.body.get(sema.arena).stmts.len(), 1);
+            assert!(sema.scope.lookup_loop_var("c").is_none());
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "diag_id = 2036")]
+    fn test_visit_for_stmt_loop_var_scope() {
+        let stmt: &ConcreteStmt = parse_stmt("for c in \"abc\" { var c = 1; }");
+        with_sema(|sema| sema.visit_stmt(stmt).map(|_| ()));
+    }
+
+    #[test]
+    #[should_panic(expected = "diag_id = 2011")]
+    fn test_visit_for_stmt_qual_var() {
+        // the parser never produces qualified loop variables, so make one by hand
+        let stmt: &mut ConcreteStmt = parse_stmt("for c in \"abc\" { }");
+        if let ConcreteStmt::ForStmt(for_stmt) = stmt {
+            let range: SourceRange = for_stmt.var_name.source_range();
+            for_stmt.var_name = Identifier::Qual(smallvec![
+                Token::new_id("std", range),
+                Token::new_id("c", range)
+            ]);
+        }
+
+        let stmt: &ConcreteStmt = stmt;
+        with_sema(|sema| sema.visit_stmt(stmt).map(|_| ()));
+    }
+}
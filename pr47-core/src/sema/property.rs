@@ -0,0 +1,92 @@
+//! # Resolution of field accesses
+//!
+//! `obj.field` and `obj.field = value` are resolved by the static type of `obj`:
+//! - for host types and containers, the getter or setter of the property registered on that type
+//!   gets called directly as an FFI function;
+//! - for builtin `object`s without such a property, the field of the object gets accessed;
+//! - for `any`, the access is dispatched on the runtime type of `obj`, by the property ID shared
+//!   by all properties named `field`.
+
+use std::any::TypeId;
+use std::ptr::NonNull;
+
+use crate::builtins::object::Object;
+use crate::data::tyck::{ContainerTyckInfo, TyckInfo};
+use crate::ffi::member::{Property, PropertyTable};
+
+#[derive(Clone, Copy)]
+pub enum FieldResolution {
+    /// The receiver is a builtin `object`, access its field directly
+    ObjectField,
+    /// The receiver type is statically known, call the getter or setter
+    Static(Property),
+    /// The receiver is `any`, dispatch with `GET-PROPERTY` or `SET-PROPERTY`
+    Dynamic { property_id: usize }
+}
+
+impl FieldResolution {
+    /// Type of the field, `None` if unknown until runtime
+    pub fn field_type(&self) -> Option<NonNull<TyckInfo>> {
+        if let FieldResolution::Static(property) = self {
+            Some(property.ty)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum PropertyResolveError {
+    /// No property of given name is registered on the receiver type
+    NoSuchProperty,
+    /// The receiver may be `null`
+    NullableReceiver,
+    /// Assigning to a property without setter
+    ReadOnly
+}
+
+/// Resolve field `name` of receiver of type `receiver_type`. `write` tells if the field is being
+/// assigned to.
+pub fn resolve_property(
+    properties: &PropertyTable,
+    receiver_type: &TyckInfo,
+    name: &str,
+    write: bool
+) -> Result<FieldResolution, PropertyResolveError> {
+    let type_id: TypeId = match receiver_type {
+        TyckInfo::AnyType => {
+            return Ok(if let Some(property_id) = properties.id(name) {
+                FieldResolution::Dynamic { property_id }
+            } else {
+                FieldResolution::ObjectField
+            });
+        },
+        TyckInfo::Plain(type_id) | TyckInfo::Container(ContainerTyckInfo { type_id, .. }) =>
+            *type_id,
+        TyckInfo::Nullable(_) => return Err(PropertyResolveError::NullableReceiver),
+        _ => return Err(PropertyResolveError::NoSuchProperty)
+    };
+
+    match properties.get(type_id, name) {
+        Some(property) if write && property.setter.is_none() =>
+            Err(PropertyResolveError::ReadOnly),
+        Some(property) => Ok(FieldResolution::Static(*property)),
+        None if type_id == TypeId::of::<Object>() => Ok(FieldResolution::ObjectField),
+        None => Err(PropertyResolveError::NoSuchProperty)
+    }
+}
+
+/// Check if value of type `value_type` could be assigned to field of type `field_type`. Values of
+/// type `any` are checked at runtime by the setter.
+pub fn is_assignable(field_type: &TyckInfo, value_type: &TyckInfo) -> bool {
+    if field_type.is_any() || value_type.is_any() || field_type == value_type {
+        return true;
+    }
+
+    if let TyckInfo::Nullable(inner) = field_type {
+        unsafe { inner.as_ref() == value_type }
+    } else {
+        false
+    }
+}
@@ -30,7 +30,9 @@ pub struct Scope<'s> {
     pub object_decls: HashMap<&'s str, ArenaPtr<'s, ObjectDecl<'s>>>,
     pub func_decls: HashMap<&'s str, Vec<ArenaPtr<'s, FuncDecl<'s>>>>,
     pub module_decls: HashMap<&'s str, ArenaPtr<'s, ModuleDecl<'s>>>,
-    pub types: HashMap<&'s str, NonNull<TyckInfo>>
+    pub types: HashMap<&'s str, NonNull<TyckInfo>>,
+    /// Loop variables of `for` statements, with their element types
+    pub loop_vars: HashMap<&'s str, NonNull<TyckInfo>>
 }
 
 impl<'s> Scope<'s> {
@@ -42,7 +44,8 @@ impl<'s> Scope<'s> {
             object_decls: HashMap::new(),
             func_decls: HashMap::new(),
             module_decls: HashMap::new(),
-            types: HashMap::new()
+            types: HashMap::new(),
+            loop_vars: HashMap::new()
         }
     }
 
@@ -54,7 +57,8 @@ impl<'s> Scope<'s> {
             object_decls: HashMap::new(),
             func_decls: HashMap::new(),
             module_decls: HashMap::new(),
-            types: HashMap::new()
+            types: HashMap::new(),
+            loop_vars: HashMap::new()
         }
     }
 
@@ -72,7 +76,29 @@ impl<'s> Scope<'s> {
         }
     }
 
+    pub fn lookup_loop_var(&self, name: &str) -> Option<NonNull<TyckInfo>> {
+        if let Some(ty) = self.loop_vars.get(name) {
+            Some(*ty)
+        } else if let Some(parent) = &self.parent {
+            parent.lookup_loop_var(name)
+        } else {
+            None
+        }
+    }
+
+    pub fn lookup_type(&self, name: &str) -> Option<NonNull<TyckInfo>> {
+        if let Some(ty) = self.types.get(name) {
+            Some(*ty)
+        } else if let Some(parent) = &self.parent {
+            parent.lookup_type(name)
+        } else {
+            None
+        }
+    }
+
     pub fn check_name_collision(&self, name: &str) -> bool {
-        self.object_decls.contains_key(name) || self.func_decls.contains_key(name)
+        self.object_decls.contains_key(name)
+            || self.func_decls.contains_key(name)
+            || self.loop_vars.contains_key(name)
     }
 }
@@ -0,0 +1,27 @@
+use crate::sema::arena::ArenaPtr;
+use crate::sema::decl::ObjectDecl;
+use crate::sema::expr::Expr;
+use crate::sema::iter::IterResolution;
+use crate::syntax::stmt::{ConcreteCompoundStmt, ConcreteForStmt};
+
+pub enum Stmt<'s> {
+    ExprStmt(Expr<'s>),
+    CompoundStmt(ArenaPtr<'s, CompoundStmt<'s>>),
+    DeclStmt(ArenaPtr<'s, ObjectDecl<'s>>),
+    ForStmt(ArenaPtr<'s, ForStmt<'s>>)
+}
+
+pub struct CompoundStmt<'s> {
+    pub stmts: Vec<Stmt<'s>>,
+
+    pub concrete: &'s ConcreteCompoundStmt<'s>
+}
+
+pub struct ForStmt<'s> {
+    pub var_name: &'s str,
+    pub iterated: Expr<'s>,
+    pub resolution: IterResolution,
+    pub body: ArenaPtr<'s, CompoundStmt<'s>>,
+
+    pub concrete: &'s ConcreteForStmt<'s>
+}
@@ -0,0 +1,48 @@
+//! # Resolution of builtin generic types
+//!
+//! `vector<T>` and `map<K, V>` are resolved to container types, with the same `TypeId` as the
+//! runtime containers `VMGenericVec` and `VMGenericMap`.
+
+use std::any::TypeId;
+use std::ptr::NonNull;
+
+use crate::builtins::map::VMGenericMap;
+use crate::builtins::vec::VMGenericVec;
+use crate::data::tyck::{ContainerTyckInfo, TyckInfo, TyckInfoPool};
+use crate::syntax::token::TokenInner;
+
+/// Runtime container type and count of type parameters of builtin generic type `base`
+pub fn generic_type_info(base: TokenInner<'_>) -> Option<(TypeId, usize)> {
+    match base {
+        TokenInner::KwdVector => Some((TypeId::of::<VMGenericVec>(), 1)),
+        TokenInner::KwdMap => Some((TypeId::of::<VMGenericMap>(), 2)),
+        _ => None
+    }
+}
+
+/// Resolve builtin generic type `base` with type parameters `params`. Returns expected count of
+/// type parameters if `params` does not match.
+pub fn resolve_generic_type(
+    tyck_info_pool: &mut TyckInfoPool,
+    base: TokenInner<'_>,
+    params: &[NonNull<TyckInfo>]
+) -> Result<NonNull<TyckInfo>, usize> {
+    let (type_id, param_count): (TypeId, usize) =
+        generic_type_info(base).expect("not a builtin generic type");
+    if params.len() != param_count {
+        return Err(param_count);
+    }
+
+    Ok(tyck_info_pool.create_container_type(type_id, params))
+}
+
+/// Key and value types of `map<K, V>`, `None` if `ty` is not a map type
+pub fn map_type_params(ty: &TyckInfo) -> Option<(NonNull<TyckInfo>, NonNull<TyckInfo>)> {
+    if let TyckInfo::Container(ContainerTyckInfo { type_id, params }) = ty {
+        if *type_id == TypeId::of::<VMGenericMap>() {
+            let params: &[NonNull<TyckInfo>] = unsafe { params.as_ref() };
+            return Some((params[0], params[1]));
+        }
+    }
+    None
+}
@@ -0,0 +1,373 @@
+use std::any::TypeId;
+use std::ptr::NonNull;
+use std::sync::{Arc, Mutex};
+use std::task::Context;
+
+use futures::future::poll_fn;
+use xjbutil::boxed_slice;
+use xjbutil::void::Void;
+
+use crate::builtins::channel::{
+    ChannelState,
+    TrySendResult,
+    VMGenericChannel,
+    any_channel_vt,
+    channel_with_capacity
+};
+use crate::data::Value;
+use crate::data::exception::{ExceptionInner, UncheckedException};
+use crate::data::traits::StaticBase;
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+use crate::data::wrapper::OWN_INFO_READ_MASK;
+use crate::ffi::{DataOption, FFIException, RetArity, Signature};
+use crate::ffi::async_fn::{
+    AsyncFunctionBase,
+    AsyncReturnType,
+    AsyncVMContext,
+    Promise,
+    LockedCtx
+};
+use crate::ffi::sync_fn::{FunctionBase, VMContext};
+
+/// Removes the waker of a pending send or receive operation once the operation gets dropped
+struct WakerGuard {
+    state: Arc<Mutex<ChannelState>>,
+    ticket: u64
+}
+
+impl Drop for WakerGuard {
+    fn drop(&mut self) {
+        self.state.lock().unwrap().remove_waker(self.ticket);
+    }
+}
+
+unsafe fn channel_check(value: Value) -> Result<(), FFIException> {
+    if value.is_null() {
+        Err(FFIException::Unchecked(UncheckedException::UnexpectedNull { value }))
+    } else if !value.ownership_info().is_readable() {
+        Err(FFIException::Unchecked(UncheckedException::OwnershipCheckFailure {
+            object: value,
+            expected_mask: OWN_INFO_READ_MASK
+        }))
+    } else {
+        Ok(())
+    }
+}
+
+#[inline(always)] unsafe fn channel_state(value: Value) -> Arc<Mutex<ChannelState>> {
+    (*value.get_as_mut_ptr::<VMGenericChannel>()).inner.clone()
+}
+
+pub struct SendBind();
+
+impl AsyncFunctionBase for SendBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let channel_type: NonNull<TyckInfo> =
+            <Void as StaticBase<VMGenericChannel>>::tyck_info(tyck_info_pool);
+        let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+        let bool_type: NonNull<TyckInfo> = tyck_info_pool.get_bool_type();
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(
+                &[channel_type, any_type], &[bool_type], &[]
+            ),
+            param_options: boxed_slice![DataOption::Share, DataOption::Share],
+            ret_option: boxed_slice![DataOption::Copy],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<LC: LockedCtx, ACTX: AsyncVMContext<Locked=LC>>(
+        _context: &ACTX,
+        args: &[Value]
+    ) -> Result<Promise<LC>, FFIException> {
+        struct AsyncRet(bool);
+
+        impl<LC: LockedCtx> AsyncReturnType<LC> for AsyncRet {
+            fn is_err(&self) -> bool {
+                false
+            }
+
+            fn resolve(self: Box<Self>, _locked_ctx: &mut LC, dests: &[*mut Value])
+                -> Result<usize, ExceptionInner>
+            {
+                unsafe { **dests.get_unchecked(0) = Value::new_bool(self.0); }
+                Ok(1)
+            }
+        }
+
+        let channel: Value = *args.get_unchecked(0);
+        channel_check(channel)?;
+        let state: Arc<Mutex<ChannelState>> = channel_state(channel);
+        let result: TrySendResult = state.lock().unwrap().try_send(*args.get_unchecked(1));
+
+        let fut = async move {
+            let sent: bool = match result {
+                TrySendResult::Sent => true,
+                TrySendResult::Closed => false,
+                TrySendResult::Blocked(ticket) => {
+                    let _guard: WakerGuard = WakerGuard { state: state.clone(), ticket };
+                    poll_fn(|cx: &mut Context<'_>| {
+                        state.lock().unwrap().poll_send(ticket, cx)
+                    }).await
+                }
+            };
+            Box::new(AsyncRet(sent)) as Box<dyn AsyncReturnType<LC>>
+        };
+
+        Ok(Promise(Box::pin(fut)))
+    }
+}
+
+pub const SEND_BIND: &SendBind = &SendBind();
+
+pub struct RecvBind();
+
+impl AsyncFunctionBase for RecvBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let channel_type: NonNull<TyckInfo> =
+            <Void as StaticBase<VMGenericChannel>>::tyck_info(tyck_info_pool);
+        let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+        let bool_type: NonNull<TyckInfo> = tyck_info_pool.get_bool_type();
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(
+                &[channel_type], &[any_type, bool_type], &[]
+            ),
+            param_options: boxed_slice![DataOption::Share],
+            ret_option: boxed_slice![DataOption::Share, DataOption::Copy],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<LC: LockedCtx, ACTX: AsyncVMContext<Locked=LC>>(
+        _context: &ACTX,
+        args: &[Value]
+    ) -> Result<Promise<LC>, FFIException> {
+        struct AsyncRet {
+            state: Arc<Mutex<ChannelState>>,
+            ticket: u64,
+            ok: bool
+        }
+
+        impl<LC: LockedCtx> AsyncReturnType<LC> for AsyncRet {
+            fn is_err(&self) -> bool {
+                false
+            }
+
+            fn resolve(self: Box<Self>, _locked_ctx: &mut LC, dests: &[*mut Value])
+                -> Result<usize, ExceptionInner>
+            {
+                let value: Value = self.state.lock().unwrap()
+                    .take_claimed(self.ticket)
+                    .unwrap_or_else(Value::new_null);
+                unsafe {
+                    **dests.get_unchecked(0) = value;
+                    **dests.get_unchecked(1) = Value::new_bool(self.ok);
+                }
+                Ok(2)
+            }
+        }
+
+        impl Drop for AsyncRet {
+            fn drop(&mut self) {
+                self.state.lock().unwrap().unclaim(self.ticket);
+            }
+        }
+
+        let channel: Value = *args.get_unchecked(0);
+        channel_check(channel)?;
+        let state: Arc<Mutex<ChannelState>> = channel_state(channel);
+        let ticket: u64 = state.lock().unwrap().alloc_ticket();
+
+        let fut = async move {
+            let guard: WakerGuard = WakerGuard { state: state.clone(), ticket };
+            let ok: bool = poll_fn(|cx: &mut Context<'_>| {
+                state.lock().unwrap().poll_recv(ticket, cx)
+            }).await;
+            drop(guard);
+            Box::new(AsyncRet { state, ticket, ok }) as Box<dyn AsyncReturnType<LC>>
+        };
+
+        Ok(Promise(Box::pin(fut)))
+    }
+}
+
+pub const RECV_BIND: &RecvBind = &RecvBind();
+
+pub struct TryRecvBind();
+
+impl FunctionBase for TryRecvBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let channel_type: NonNull<TyckInfo> =
+            <Void as StaticBase<VMGenericChannel>>::tyck_info(tyck_info_pool);
+        let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+        let bool_type: NonNull<TyckInfo> = tyck_info_pool.get_bool_type();
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(
+                &[channel_type], &[any_type, bool_type], &[]
+            ),
+            param_options: boxed_slice![DataOption::Share],
+            ret_option: boxed_slice![DataOption::Share, DataOption::Copy],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        channel_check(*args.get_unchecked(0))?;
+        Self::call_unchecked(context, args, rets)
+    }
+
+    unsafe fn call_unchecked<CTX: VMContext>(
+        _context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let channel: &VMGenericChannel =
+            &*args.get_unchecked(0).get_as_mut_ptr::<VMGenericChannel>();
+        let received: Option<Value> = channel.lock().try_recv();
+
+        **rets.get_unchecked(1) = Value::new_bool(received.is_some());
+        **rets.get_unchecked(0) = received.unwrap_or_else(Value::new_null);
+        Ok(())
+    }
+}
+
+pub const TRY_RECV_BIND: &TryRecvBind = &TryRecvBind();
+
+pub struct CloseBind();
+
+impl FunctionBase for CloseBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let channel_type: NonNull<TyckInfo> =
+            <Void as StaticBase<VMGenericChannel>>::tyck_info(tyck_info_pool);
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[channel_type], &[], &[]),
+            param_options: boxed_slice![DataOption::Share],
+            ret_option: boxed_slice![],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        channel_check(*args.get_unchecked(0))?;
+        Self::call_unchecked(context, args, rets)
+    }
+
+    unsafe fn call_unchecked<CTX: VMContext>(
+        _context: &mut CTX,
+        args: &[Value],
+        _rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let channel: &VMGenericChannel =
+            &*args.get_unchecked(0).get_as_mut_ptr::<VMGenericChannel>();
+        channel.lock().close();
+        Ok(())
+    }
+}
+
+pub const CLOSE_BIND: &CloseBind = &CloseBind();
+
+/// Creates a channel of given buffer capacity. A negative capacity creates an unbounded channel,
+/// while zero capacity creates a channel on which every sender waits until its value gets received.
+pub struct NewChannelBind();
+
+impl FunctionBase for NewChannelBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let i64_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+        let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+        let channel_type: NonNull<TyckInfo> = tyck_info_pool.create_container_type(
+            TypeId::of::<VMGenericChannel>(), &[any_type]
+        );
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[i64_type], &[channel_type], &[]),
+            param_options: boxed_slice![DataOption::Copy],
+            ret_option: boxed_slice![DataOption::Move],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        Self::call_unchecked(context, args, rets)
+    }
+
+    unsafe fn call_unchecked<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let capacity: i64 = args.get_unchecked(0).vt_data.inner.int_value;
+        let channel: Value = Value::new_container(
+            channel_with_capacity(if capacity < 0 { None } else { Some(capacity as usize) }),
+            any_channel_vt().as_ptr()
+        );
+        context.add_heap_managed(channel);
+        **rets.get_unchecked(0) = channel;
+        Ok(())
+    }
+}
+
+pub const NEW_CHANNEL_BIND: &NewChannelBind = &NewChannelBind();
+
+/// Sets the buffer capacity of a channel. A negative capacity makes the channel unbounded, while
+/// zero capacity makes every sender wait until its value gets received.
+pub struct SetCapacityBind();
+
+impl FunctionBase for SetCapacityBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let channel_type: NonNull<TyckInfo> =
+            <Void as StaticBase<VMGenericChannel>>::tyck_info(tyck_info_pool);
+        let i64_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[channel_type, i64_type], &[], &[]),
+            param_options: boxed_slice![DataOption::Share, DataOption::Copy],
+            ret_option: boxed_slice![],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        channel_check(*args.get_unchecked(0))?;
+        Self::call_unchecked(context, args, rets)
+    }
+
+    unsafe fn call_unchecked<CTX: VMContext>(
+        _context: &mut CTX,
+        args: &[Value],
+        _rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let channel: &VMGenericChannel =
+            &*args.get_unchecked(0).get_as_mut_ptr::<VMGenericChannel>();
+        let capacity: i64 = args.get_unchecked(1).vt_data.inner.int_value;
+        channel.lock().set_capacity(if capacity < 0 { None } else { Some(capacity as usize) });
+        Ok(())
+    }
+}
+
+pub const SET_CAPACITY_BIND: &SetCapacityBind = &SetCapacityBind();
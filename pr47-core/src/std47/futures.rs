@@ -1,19 +1,24 @@
 use std::any::TypeId;
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::ptr::NonNull;
-use std::time::Duration;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
 
-use futures::future::select_all;
+use futures::future::{Either, select, select_all};
+use pr47_codegen::pr47_function_bind;
 use smallvec::SmallVec;
 use unchecked_unwrap::UncheckedUnwrap;
 use xjbutil::boxed_slice;
-use xjbutil::async_utils::join_all;
+use xjbutil::void::Void;
 
 use crate::data::Value;
 use crate::data::exception::ExceptionInner;
+use crate::data::traits::StaticBase;
 use crate::data::tyck::{TyckInfo, TyckInfoPool};
-use crate::ffi::{FFIException, Signature};
+use crate::ffi::{DataOption, FFIException, RetArity, Signature, VariadicTail, VariadicType};
 use crate::ffi::async_fn::{
     AsyncFunctionBase,
     AsyncReturnType,
@@ -22,15 +27,38 @@ use crate::ffi::async_fn::{
     PromiseResult,
     LockedCtx
 };
-use crate::ffi::async_fn::{value_move_out_check_norm_noalias, value_move_out_norm_noalias};
+use crate::ffi::async_fn::{
+    value_copy_norm,
+    value_move_out_check_norm_noalias,
+    value_move_out_norm_noalias
+};
+use crate::ffi::sync_fn::{FunctionBase, VMContext};
+use crate::util::async_utils::join_all;
+
+async fn sleep(duration: Duration) {
+    #[cfg(feature = "async-astd")]
+    async_std::task::sleep(duration).await;
+    #[cfg(feature = "async-tokio")]
+    tokio::time::sleep(duration).await;
+    #[cfg(feature = "async-sim")]
+    crate::util::sim_runtime::sleep(duration).await;
+}
 
 pub struct JoinBind();
 
 impl AsyncFunctionBase for JoinBind {
-    fn signature(
-        _tyck_info_pool: &mut TyckInfoPool
-    ) -> Signature {
-        unimplemented!("join operation does not have standard signature")
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[], &[], &[]),
+            param_options: boxed_slice![],
+            ret_option: boxed_slice![],
+            variadic: Some(VariadicTail {
+                var_type: VariadicType::AnyPromise,
+                data_option: DataOption::Move,
+                min_count: 1
+            }),
+            ret_arity: RetArity::FixedThenEachPromise
+        }
     }
 
     unsafe fn call_rtlc<LC: LockedCtx, ACTX: AsyncVMContext<Locked=LC>> (
@@ -96,8 +124,20 @@ pub const JOIN_BIND: &JoinBind = &JoinBind();
 pub struct SelectBind();
 
 impl AsyncFunctionBase for SelectBind {
-    fn signature(_tyck_info_pool: &mut TyckInfoPool) -> Signature {
-        unimplemented!("select operation does not have standard signature")
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let i64_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[], &[i64_type], &[]),
+            param_options: boxed_slice![],
+            ret_option: boxed_slice![DataOption::Copy],
+            variadic: Some(VariadicTail {
+                var_type: VariadicType::AnyPromise,
+                data_option: DataOption::Move,
+                min_count: 1
+            }),
+            ret_arity: RetArity::FixedThenOnePromise
+        }
     }
 
     unsafe fn call_rtlc<LC: LockedCtx, ACTX: AsyncVMContext<Locked=LC>>(
@@ -147,16 +187,309 @@ impl AsyncFunctionBase for SelectBind {
 
 pub const SELECT_BIND: &SelectBind = &SelectBind();
 
-pub struct SleepMillisBind();
+#[pr47_function_bind(local)]
+pub async fn sleep_ms(millis: i64) {
+    sleep(Duration::from_millis(millis.max(0) as u64)).await
+}
+
+pub const SLEEP_MS_BIND: &Pr47Binder_sleep_ms = PR47BINDER_SLEEP_MS;
+
+/// Checked exception raised when a promise wrapped by `timeout` or `deadline` does not resolve in
+/// time
+pub struct TimeoutError {
+    pub duration: Duration
+}
+
+impl StaticBase<TimeoutError> for Void {
+    fn type_name() -> String {
+        "TimeoutError".to_string()
+    }
+}
+
+/// Checked exception raised when a promise attached to a `CancelScope` gets cancelled
+pub struct CancelledError();
+
+impl StaticBase<CancelledError> for Void {
+    fn type_name() -> String {
+        "CancelledError".to_string()
+    }
+}
+
+type PromiseFuture<LC> = Pin<Box<dyn Future<Output=PromiseResult<LC>> + Send>>;
+
+/// A pending promise future abandoned due to timeout or cancellation.
+///
+/// The future may hold `AsyncResetGuard`s or `AsyncShareGuard`s, which modify ownership info of
+/// VM objects on drop. So it should not be dropped by the future executor, but be carried to
+/// `AsyncReturnType::resolve`, where the `Serializer` run permit is held.
+struct Abandoned<LC: LockedCtx>(PromiseFuture<LC>);
+
+// The abandoned future is never polled again, it only gets moved around and then dropped.
+unsafe impl<LC: LockedCtx> Sync for Abandoned<LC> {}
+
+enum Interrupt {
+    Timeout(Duration),
+    Cancelled
+}
+
+struct InterruptedRet<LC: LockedCtx> {
+    abandoned: Abandoned<LC>,
+    interrupt: Interrupt
+}
+
+impl<LC: LockedCtx> AsyncReturnType<LC> for InterruptedRet<LC> {
+    fn is_err(&self) -> bool {
+        true
+    }
+
+    fn resolve(self: Box<Self>, locked_ctx: &mut LC, _dests: &[*mut Value])
+        -> Result<usize, ExceptionInner>
+    {
+        let InterruptedRet { abandoned: Abandoned(abandoned), interrupt } = *self;
+        drop(abandoned);
+
+        let err_value: Value = match interrupt {
+            Interrupt::Timeout(duration) => Value::new_owned(TimeoutError { duration }),
+            Interrupt::Cancelled => Value::new_owned(CancelledError())
+        };
+        locked_ctx.add_heap_managed(err_value);
+        Err(ExceptionInner::Checked(err_value))
+    }
+}
+
+async fn interruptible<LC, F>(fut: PromiseFuture<LC>, interrupter: F, interrupt: Interrupt)
+    -> PromiseResult<LC>
+    where LC: LockedCtx,
+          F: Future<Output=()> + Send + 'static
+{
+    match select(fut, Box::pin(interrupter)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(((), abandoned)) => Box::new(InterruptedRet {
+            abandoned: Abandoned(abandoned),
+            interrupt
+        })
+    }
+}
+
+pub struct TimeoutBind();
+
+impl AsyncFunctionBase for TimeoutBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+        let i64_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+        let timeout_type: NonNull<TyckInfo> =
+            tyck_info_pool.create_plain_type(TypeId::of::<TimeoutError>());
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(
+                &[any_type, i64_type], &[], &[timeout_type]
+            ),
+            param_options: boxed_slice![DataOption::Move, DataOption::Copy],
+            ret_option: boxed_slice![],
+            variadic: None,
+            ret_arity: RetArity::FixedThenPromiseParam(0)
+        }
+    }
+
+    unsafe fn call_rtlc<LC: LockedCtx, ACTX: AsyncVMContext<Locked=LC>>(
+        _context: &ACTX,
+        args: &[Value]
+    ) -> Result<Promise<LC>, FFIException> {
+        let promise: Value = *args.get_unchecked(0);
+        value_move_out_check_norm_noalias(promise)?;
+        let millis: i64 = args.get_unchecked(1).vt_data.inner.int_value;
+        let duration: Duration = Duration::from_millis(millis.max(0) as u64);
+
+        let Promise(fut) = value_move_out_norm_noalias::<Promise<LC>>(promise);
+        Ok(Promise(Box::pin(interruptible(fut, sleep(duration), Interrupt::Timeout(duration)))))
+    }
+}
+
+pub const TIMEOUT_BIND: &TimeoutBind = &TimeoutBind();
+
+pub struct DeadlineBind();
+
+impl AsyncFunctionBase for DeadlineBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+        let instant_type: NonNull<TyckInfo> =
+            tyck_info_pool.create_plain_type(TypeId::of::<Instant>());
+        let timeout_type: NonNull<TyckInfo> =
+            tyck_info_pool.create_plain_type(TypeId::of::<TimeoutError>());
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(
+                &[any_type, instant_type], &[], &[timeout_type]
+            ),
+            param_options: boxed_slice![DataOption::Move, DataOption::Share],
+            ret_option: boxed_slice![],
+            variadic: None,
+            ret_arity: RetArity::FixedThenPromiseParam(0)
+        }
+    }
+
+    unsafe fn call_rtlc<LC: LockedCtx, ACTX: AsyncVMContext<Locked=LC>>(
+        _context: &ACTX,
+        args: &[Value]
+    ) -> Result<Promise<LC>, FFIException> {
+        let promise: Value = *args.get_unchecked(0);
+        value_move_out_check_norm_noalias(promise)?;
+        let deadline: Instant = value_copy_norm::<Instant>(*args.get_unchecked(1))?;
+        let duration: Duration = deadline.saturating_duration_since(Instant::now());
+
+        let Promise(fut) = value_move_out_norm_noalias::<Promise<LC>>(promise);
+        Ok(Promise(Box::pin(interruptible(fut, sleep(duration), Interrupt::Timeout(duration)))))
+    }
+}
+
+pub const DEADLINE_BIND: &DeadlineBind = &DeadlineBind();
+
+struct CancelScopeState {
+    cancelled: bool,
+    next_waiter_id: u64,
+    /// Wakers of pending `Cancelled` futures, one for each future, keyed by waiter ID
+    wakers: HashMap<u64, Waker>
+}
+
+/// A group of promises which can be cancelled together. Cancelling a scope drops all the pending
+/// promises attached to it, and makes awaiting them raise `CancelledError`.
+#[derive(Clone)]
+pub struct CancelScope {
+    inner: Arc<Mutex<CancelScopeState>>
+}
+
+impl CancelScope {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(CancelScopeState {
+                cancelled: false,
+                next_waiter_id: 0,
+                wakers: HashMap::new()
+            }))
+        }
+    }
+
+    pub fn cancel(&self) {
+        let mut state: MutexGuard<CancelScopeState> = self.inner.lock().unwrap();
+        state.cancelled = true;
+        for (_, waker) /*: (u64, Waker)*/ in state.wakers.drain() {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.lock().unwrap().cancelled
+    }
+
+    fn cancelled(&self) -> Cancelled {
+        let mut state: MutexGuard<CancelScopeState> = self.inner.lock().unwrap();
+        let waiter_id: u64 = state.next_waiter_id;
+        state.next_waiter_id += 1;
+        Cancelled { inner: self.inner.clone(), waiter_id }
+    }
+}
+
+/// Future completing once the `CancelScope` gets cancelled
+struct Cancelled {
+    inner: Arc<Mutex<CancelScopeState>>,
+    waiter_id: u64
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state: MutexGuard<CancelScopeState> = self.inner.lock().unwrap();
+        if state.cancelled {
+            return Poll::Ready(());
+        }
 
-impl AsyncFunctionBase for SleepMillisBind {
+        let waker: &mut Waker =
+            state.wakers.entry(self.waiter_id).or_insert_with(|| cx.waker().clone());
+        if !waker.will_wake(cx.waker()) {
+            *waker = cx.waker().clone();
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        if let Ok(mut state /*: MutexGuard<CancelScopeState>*/) = self.inner.lock() {
+            state.wakers.remove(&self.waiter_id);
+        }
+    }
+}
+
+impl Default for CancelScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StaticBase<CancelScope> for Void {
+    fn type_name() -> String {
+        "cancel_scope".to_string()
+    }
+}
+
+pub struct CancelScopeNewBind();
+
+impl FunctionBase for CancelScopeNewBind {
     fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
-        let i64_type: NonNull<TyckInfo> = tyck_info_pool.create_plain_type(TypeId::of::<i64>());
+        let scope_type: NonNull<TyckInfo> =
+            tyck_info_pool.create_plain_type(TypeId::of::<CancelScope>());
 
         Signature {
-            func_type: tyck_info_pool.create_function_type(&[i64_type], &[], &[]),
+            func_type: tyck_info_pool.create_function_type(&[], &[scope_type], &[]),
             param_options: boxed_slice![],
-            ret_option: boxed_slice![]
+            ret_option: boxed_slice![DataOption::Move],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        Self::call_unchecked(context, args, rets)
+    }
+
+    unsafe fn call_unchecked<CTX: VMContext>(
+        context: &mut CTX,
+        _args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let value: Value = Value::new_owned(CancelScope::new());
+        context.add_heap_managed(value);
+        **rets.get_unchecked(0) = value;
+
+        Ok(())
+    }
+}
+
+pub const CANCEL_SCOPE_NEW_BIND: &CancelScopeNewBind = &CancelScopeNewBind();
+
+pub struct CancelScopeAttachBind();
+
+impl AsyncFunctionBase for CancelScopeAttachBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let scope_type: NonNull<TyckInfo> =
+            tyck_info_pool.create_plain_type(TypeId::of::<CancelScope>());
+        let any_type: NonNull<TyckInfo> = tyck_info_pool.get_any_type();
+        let cancelled_type: NonNull<TyckInfo> =
+            tyck_info_pool.create_plain_type(TypeId::of::<CancelledError>());
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(
+                &[scope_type, any_type], &[], &[cancelled_type]
+            ),
+            param_options: boxed_slice![DataOption::Share, DataOption::Move],
+            ret_option: boxed_slice![],
+            variadic: None,
+            ret_arity: RetArity::FixedThenPromiseParam(1)
         }
     }
 
@@ -164,31 +497,85 @@ impl AsyncFunctionBase for SleepMillisBind {
         _context: &ACTX,
         args: &[Value]
     ) -> Result<Promise<LC>, FFIException> {
-        struct AsyncRet();
+        let scope: CancelScope = value_copy_norm::<CancelScope>(*args.get_unchecked(0))?;
+        let promise: Value = *args.get_unchecked(1);
+        value_move_out_check_norm_noalias(promise)?;
 
-        impl<LC: LockedCtx> AsyncReturnType<LC> for AsyncRet {
-            fn is_err(&self) -> bool {
-                false
-            }
+        let Promise(fut) = value_move_out_norm_noalias::<Promise<LC>>(promise);
+        Ok(Promise(Box::pin(interruptible(fut, scope.cancelled(), Interrupt::Cancelled))))
+    }
+}
 
-            fn resolve(self: Box<Self>, _locked_ctx: &mut LC, _dests: &[*mut Value])
-                -> Result<usize, ExceptionInner>
-            {
-                Ok(0)
-            }
+pub const CANCEL_SCOPE_ATTACH_BIND: &CancelScopeAttachBind = &CancelScopeAttachBind();
+
+pub struct CancelScopeCancelBind();
+
+impl FunctionBase for CancelScopeCancelBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let scope_type: NonNull<TyckInfo> =
+            tyck_info_pool.create_plain_type(TypeId::of::<CancelScope>());
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[scope_type], &[], &[]),
+            param_options: boxed_slice![DataOption::Share],
+            ret_option: boxed_slice![],
+            variadic: None,
+            ret_arity: RetArity::Fixed
         }
+    }
 
-        let int_value: i64 = args.get_unchecked(0).vt_data.inner.int_value;
-        let fut = async move {
-            #[cfg(feature = "async-astd")]
-            async_std::task::sleep(Duration::from_millis(int_value as u64)).await;
-            #[cfg(feature = "async-tokio")]
-            tokio::time::sleep(Duration::from_millis(int_value as u64)).await;
-            Box::new(AsyncRet()) as Box<dyn AsyncReturnType<LC>>
-        };
+    unsafe fn call_rtlc<CTX: VMContext>(
+        _context: &mut CTX,
+        args: &[Value],
+        _rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        value_copy_norm::<CancelScope>(*args.get_unchecked(0))?.cancel();
+        Ok(())
+    }
 
-        Ok(Promise(Box::pin(fut)))
+    unsafe fn call_unchecked<CTX: VMContext>(
+        _context: &mut CTX,
+        args: &[Value],
+        _rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let scope: *const CancelScope =
+            args.get_unchecked(0).get_as_mut_ptr_norm::<CancelScope>() as *const _;
+        (*scope).cancel();
+        Ok(())
     }
 }
 
-pub const SLEEP_MS_BIND: &SleepMillisBind = &SleepMillisBind();
+pub const CANCEL_SCOPE_CANCEL_BIND: &CancelScopeCancelBind = &CancelScopeCancelBind();
+
+#[cfg(test)]
+mod test {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll, Waker};
+
+    use futures::task::noop_waker;
+
+    use crate::std47::futures::{CancelScope, Cancelled};
+
+    #[test] fn test_cancel_scope_waker_slots() {
+        let scope: CancelScope = CancelScope::new();
+        let waker: Waker = noop_waker();
+        let mut cx: Context<'_> = Context::from_waker(&waker);
+
+        let mut cancelled1: Cancelled = scope.cancelled();
+        let mut cancelled2: Cancelled = scope.cancelled();
+        for _ in 0..3 {
+            assert_eq!(Pin::new(&mut cancelled1).poll(&mut cx), Poll::Pending);
+            assert_eq!(Pin::new(&mut cancelled2).poll(&mut cx), Poll::Pending);
+        }
+        // re-polling a waiter replaces its waker instead of adding more
+        assert_eq!(scope.inner.lock().unwrap().wakers.len(), 2);
+
+        drop(cancelled2);
+        assert_eq!(scope.inner.lock().unwrap().wakers.len(), 1);
+
+        scope.cancel();
+        assert!(scope.inner.lock().unwrap().wakers.is_empty());
+        assert_eq!(Pin::new(&mut cancelled1).poll(&mut cx), Poll::Ready(()));
+    }
+}
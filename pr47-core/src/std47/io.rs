@@ -1,6 +1,7 @@
 use std::any::TypeId;
 use unchecked_unwrap::UncheckedUnwrap;
 
+use xjbutil::boxed_slice;
 use xjbutil::unchecked::UnsafeFrom;
 
 use crate::data::exception::UncheckedException;
@@ -8,14 +9,24 @@ use crate::data::tyck::TyckInfoPool;
 use crate::data::Value;
 use crate::data::value_typed::ValueTypeTag;
 use crate::data::wrapper::OWN_INFO_READ_MASK;
-use crate::ffi::{FFIException, Signature};
+use crate::ffi::{DataOption, FFIException, RetArity, Signature, VariadicTail, VariadicType};
 use crate::ffi::sync_fn::{FunctionBase, VMContext};
 
 pub struct PrintBind();
 
 impl FunctionBase for PrintBind {
-    fn signature(_tyck_info_pool: &mut TyckInfoPool) -> Signature {
-        unimplemented!("print does not have a standard signature")
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[], &[], &[]),
+            param_options: boxed_slice![],
+            ret_option: boxed_slice![],
+            variadic: Some(VariadicTail {
+                var_type: VariadicType::Any,
+                data_option: DataOption::Share,
+                min_count: 0
+            }),
+            ret_arity: RetArity::Fixed
+        }
     }
 
     unsafe fn call_rtlc<CTX: VMContext>(
@@ -1,23 +1,16 @@
-use pr47_codegen::pr47_function_bind;
-use xjbutil::void::Void;
-
-use crate::data::traits::StaticBase;
+use pr47_codegen::{StaticBase, pr47_function_bind};
 
+#[derive(StaticBase)]
+#[pr47(local, name = "std.math.Vec2")]
 pub struct Vec2(f64, f64);
-pub struct Vec3(f64, f64, f64);
-pub struct Vec4(f64, f64, f64, f64);
 
-impl StaticBase<Vec2> for Void {
-    fn type_name() -> String { "std.math.Vec2".to_string() }
-}
-
-impl StaticBase<Vec3> for Void {
-    fn type_name() -> String { "std.math.Vec3".to_string() }
-}
+#[derive(StaticBase)]
+#[pr47(local, name = "std.math.Vec3")]
+pub struct Vec3(f64, f64, f64);
 
-impl StaticBase<Vec4> for Void {
-    fn type_name() -> String { "std.math.Vec4".to_string() }
-}
+#[derive(StaticBase)]
+#[pr47(local, name = "std.math.Vec4")]
+pub struct Vec4(f64, f64, f64, f64);
 
 #[pr47_function_bind(local)]
 pub fn vec2_new(x: f64, y: f64) -> Vec2 { Vec2(x, y) }
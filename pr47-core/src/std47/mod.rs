@@ -5,4 +5,5 @@ pub mod math;
 pub mod str;
 pub mod time;
 
+#[cfg(feature = "async")] pub mod channel;
 #[cfg(feature = "async")] pub mod futures;
@@ -1,10 +1,476 @@
+//! # `str.rs`: string standard library
+//!
+//! All positions and lengths are counted in characters, not in bytes. `Engine` users may call
+//! `register_str_methods` to make these functions callable as methods of `string`.
+
 use std::num::{ParseFloatError, ParseIntError};
+use std::ptr::NonNull;
 use std::str::ParseBoolError;
 
+use pr47_codegen::pr47_function_bind;
+use xjbutil::boxed_slice;
 use xjbutil::void::Void;
 
+use crate::builtins::vec::VMGenericVec;
+use crate::data::Value;
+use crate::data::convert::{FromValue, IntoValue};
+use crate::data::exception::UncheckedException;
 use crate::data::traits::StaticBase;
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+use crate::data::wrapper::OWN_INFO_READ_MASK;
+use crate::ffi::{DataOption, FFIException, RetArity, Signature};
+use crate::ffi::sync_fn::{
+    FunctionBase,
+    OwnershipGuard,
+    VMContext,
+    value_into_ref,
+    value_into_ref_noalias
+};
+
+#[cfg(feature = "al31fm2")] use crate::engine::Engine;
+#[cfg(feature = "al31fm2")] use crate::vm::al31fm2::alloc::Alloc;
 
 impl StaticBase<ParseIntError> for Void {}
 impl StaticBase<ParseFloatError> for Void {}
 impl StaticBase<ParseBoolError> for Void {}
+
+/// Character index of the first occurrence of `pat` in `s`, or `null` if there's none
+#[pr47_function_bind(local)]
+pub fn str_find(s: &str, pat: &str) -> Option<i64> {
+    s.find(pat).map(|byte_index: usize| s[..byte_index].chars().count() as i64)
+}
+
+#[pr47_function_bind(local)]
+pub fn str_replace(s: &str, from: &str, to: &str) -> String {
+    s.replace(from, to)
+}
+
+#[pr47_function_bind(local)]
+pub fn str_trim(s: &str) -> String {
+    s.trim().to_string()
+}
+
+#[pr47_function_bind(local)]
+pub fn str_starts_with(s: &str, prefix: &str) -> bool {
+    s.starts_with(prefix)
+}
+
+#[pr47_function_bind(local)]
+pub fn str_ends_with(s: &str, suffix: &str) -> bool {
+    s.ends_with(suffix)
+}
+
+/// Characters `[start, end)` of `s`, or `null` if the range is not within `s`
+#[pr47_function_bind(local)]
+pub fn str_substring(s: &str, start: i64, end: i64) -> Option<String> {
+    if start < 0 || end < start {
+        return None;
+    }
+    let (start, end): (usize, usize) = (start as usize, end as usize);
+    let substring: String = s.chars().skip(start).take(end - start).collect();
+    if substring.chars().count() == end - start {
+        Some(substring)
+    } else {
+        None
+    }
+}
+
+#[pr47_function_bind(local)]
+pub fn str_to_upper(s: &str) -> String {
+    s.to_uppercase()
+}
+
+#[pr47_function_bind(local)]
+pub fn str_to_lower(s: &str) -> String {
+    s.to_lowercase()
+}
+
+#[pr47_function_bind(local)]
+pub fn str_parse_int(s: &str) -> Result<i64, ParseIntError> {
+    s.parse()
+}
+
+#[pr47_function_bind(local)]
+pub fn str_parse_float(s: &str) -> Result<f64, ParseFloatError> {
+    s.parse()
+}
+
+/// `s` repeated `count` times, empty if `count` is not positive
+#[pr47_function_bind(local)]
+pub fn str_repeat(s: &str, count: i64) -> String {
+    if count > 0 {
+        s.repeat(count as usize)
+    } else {
+        String::new()
+    }
+}
+
+pub struct SplitBind();
+
+impl FunctionBase for SplitBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let string_type: NonNull<TyckInfo> = tyck_info_pool.get_string_type();
+        let vec_type: NonNull<TyckInfo> = <Vec<String> as FromValue>::tyck_info(tyck_info_pool);
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(
+                &[string_type, string_type],
+                &[vec_type],
+                &[]
+            ),
+            param_options: boxed_slice![DataOption::Share, DataOption::Share],
+            ret_option: boxed_slice![DataOption::Move],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let (s, guard1): (&String, Option<OwnershipGuard>) =
+            value_into_ref(*args.get_unchecked(0))?;
+        let (sep, guard2): (&String, Option<OwnershipGuard>) =
+            value_into_ref(*args.get_unchecked(1))?;
+        let parts: Vec<String> = s.split(sep.as_str()).map(str::to_string).collect();
+        drop(guard2);
+        drop(guard1);
+
+        **rets.get_unchecked(0) = parts.into_value(context);
+        Ok(())
+    }
+
+    unsafe fn call_unchecked<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let s: &String = &*(args.get_unchecked(0).get_as_mut_ptr_norm::<String>() as *const _);
+        let sep: &String = &*(args.get_unchecked(1).get_as_mut_ptr_norm::<String>() as *const _);
+        let parts: Vec<String> = s.split(sep.as_str()).map(str::to_string).collect();
+
+        **rets.get_unchecked(0) = parts.into_value(context);
+        Ok(())
+    }
+}
+
+pub const SPLIT_BIND: &SplitBind = &SplitBind();
+
+pub struct JoinBind();
+
+impl FunctionBase for JoinBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let string_type: NonNull<TyckInfo> = tyck_info_pool.get_string_type();
+        let vec_type: NonNull<TyckInfo> = <Vec<String> as FromValue>::tyck_info(tyck_info_pool);
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(
+                &[vec_type, string_type],
+                &[string_type],
+                &[]
+            ),
+            param_options: boxed_slice![DataOption::Share, DataOption::Share],
+            ret_option: boxed_slice![DataOption::Move],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let parts_value: Value = *args.get_unchecked(0);
+        if !parts_value.ownership_info().is_readable() {
+            return Err(FFIException::Unchecked(UncheckedException::OwnershipCheckFailure {
+                object: parts_value,
+                expected_mask: OWN_INFO_READ_MASK
+            }));
+        }
+        let parts: &VMGenericVec = &*(parts_value.get_as_mut_ptr() as *const _);
+        let (sep, guard): (&String, Option<OwnershipGuard>) =
+            value_into_ref(*args.get_unchecked(1))?;
+
+        let mut joined: String = String::new();
+        for (idx, part) /*: (usize, &Value)*/ in parts.inner.iter().enumerate() {
+            if part.is_null() {
+                return Err(FFIException::Unchecked(UncheckedException::UnexpectedNull {
+                    value: *part
+                }));
+            }
+            if idx != 0 {
+                joined.push_str(sep);
+            }
+            joined.push_str(value_into_ref_noalias::<String>(*part)?);
+        }
+        drop(guard);
+
+        **rets.get_unchecked(0) = joined.into_value(context);
+        Ok(())
+    }
+
+    unsafe fn call_unchecked<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let parts: &VMGenericVec = &*(args.get_unchecked(0).get_as_mut_ptr() as *const _);
+        let sep: &String = &*(args.get_unchecked(1).get_as_mut_ptr_norm::<String>() as *const _);
+
+        let joined: String = parts.inner.iter()
+            .map(|part: &Value| (*part.get_as_mut_ptr_norm::<String>()).as_str())
+            .collect::<Vec<&str>>()
+            .join(sep.as_str());
+
+        **rets.get_unchecked(0) = joined.into_value(context);
+        Ok(())
+    }
+}
+
+pub const JOIN_BIND: &JoinBind = &JoinBind();
+
+pub struct CharsBind();
+
+impl FunctionBase for CharsBind {
+    fn signature(tyck_info_pool: &mut TyckInfoPool) -> Signature {
+        let string_type: NonNull<TyckInfo> = tyck_info_pool.get_string_type();
+        let vec_type: NonNull<TyckInfo> = <Vec<char> as FromValue>::tyck_info(tyck_info_pool);
+
+        Signature {
+            func_type: tyck_info_pool.create_function_type(&[string_type], &[vec_type], &[]),
+            param_options: boxed_slice![DataOption::Share],
+            ret_option: boxed_slice![DataOption::Move],
+            variadic: None,
+            ret_arity: RetArity::Fixed
+        }
+    }
+
+    unsafe fn call_rtlc<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let (s, guard): (&String, Option<OwnershipGuard>) =
+            value_into_ref(*args.get_unchecked(0))?;
+        let chars: Vec<char> = s.chars().collect();
+        drop(guard);
+
+        **rets.get_unchecked(0) = chars.into_value(context);
+        Ok(())
+    }
+
+    unsafe fn call_unchecked<CTX: VMContext>(
+        context: &mut CTX,
+        args: &[Value],
+        rets: &[*mut Value]
+    ) -> Result<(), FFIException> {
+        let s: &String = &*(args.get_unchecked(0).get_as_mut_ptr_norm::<String>() as *const _);
+        let chars: Vec<char> = s.chars().collect();
+
+        **rets.get_unchecked(0) = chars.into_value(context);
+        Ok(())
+    }
+}
+
+pub const CHARS_BIND: &CharsBind = &CharsBind();
+
+/// Register the functions of this module as methods of `string`, and `JOIN_BIND` as FFI function
+/// `std.str.join`. Returns `false` if any of the names has already been taken.
+#[cfg(feature = "al31fm2")]
+pub fn register_str_methods<A: Alloc>(engine: &mut Engine<A>) -> bool {
+    engine.register_method::<String>("find", PR47BINDER_STR_FIND).is_some()
+        && engine.register_method::<String>("replace", PR47BINDER_STR_REPLACE).is_some()
+        && engine.register_method::<String>("trim", PR47BINDER_STR_TRIM).is_some()
+        && engine.register_method::<String>("startsWith", PR47BINDER_STR_STARTS_WITH).is_some()
+        && engine.register_method::<String>("endsWith", PR47BINDER_STR_ENDS_WITH).is_some()
+        && engine.register_method::<String>("substring", PR47BINDER_STR_SUBSTRING).is_some()
+        && engine.register_method::<String>("toUpper", PR47BINDER_STR_TO_UPPER).is_some()
+        && engine.register_method::<String>("toLower", PR47BINDER_STR_TO_LOWER).is_some()
+        && engine.register_method::<String>("parseInt", PR47BINDER_STR_PARSE_INT).is_some()
+        && engine.register_method::<String>("parseFloat", PR47BINDER_STR_PARSE_FLOAT).is_some()
+        && engine.register_method::<String>("repeat", PR47BINDER_STR_REPEAT).is_some()
+        && engine.register_method::<String>("split", SPLIT_BIND).is_some()
+        && engine.register_method::<String>("chars", CHARS_BIND).is_some()
+        && engine.register_function("std.str.join", JOIN_BIND).is_some()
+}
+
+#[cfg(test)]
+mod test {
+    use std::ptr::null_mut;
+
+    use crate::data::Value;
+    use crate::data::convert::{FromValue, IntoValue};
+    use crate::data::exception::{ExceptionInner, UncheckedException};
+    use crate::data::generic::GenericTypeVT;
+    use crate::data::tyck::TyckInfoPool;
+    use crate::data::wrapper::DynBase;
+    use crate::ffi::FFIException;
+    use crate::ffi::sync_fn::{FunctionBase, VMContext};
+    use crate::std47::str::{
+        JoinBind,
+        Pr47Binder_str_find,
+        Pr47Binder_str_parse_int,
+        Pr47Binder_str_repeat,
+        Pr47Binder_str_substring,
+        SplitBind,
+        str_find,
+        str_repeat,
+        str_substring
+    };
+
+    #[cfg(feature = "al31fm2")] use std::any::TypeId;
+    #[cfg(feature = "al31fm2")] use crate::engine::Engine;
+    #[cfg(feature = "al31fm2")] use crate::ffi::member::{Method, MethodTable};
+    #[cfg(feature = "al31fm2")] use crate::std47::str::register_str_methods;
+    #[cfg(feature = "al31fm2")] use crate::vm::al31fm2::alloc::default_alloc::DefaultAlloc;
+
+    struct StrTestContext {
+        managed: Vec<Value>
+    }
+
+    impl VMContext for StrTestContext {
+        fn add_heap_managed(&mut self, value: Value) {
+            self.managed.push(value);
+        }
+
+        fn mark(&mut self, _value: Value) {}
+
+        fn pin_objects(&mut self, _values: &[Value]) -> *mut bool {
+            null_mut()
+        }
+    }
+
+    impl Drop for StrTestContext {
+        fn drop(&mut self) {
+            for value /*: Value*/ in self.managed.drain(..) {
+                unsafe {
+                    if value.is_container() {
+                        let vt: *const GenericTypeVT = value.ptr_repr.trivia as *const _;
+                        ((*vt).drop_fn)(value.untagged_ptr_field() as *mut _);
+                    } else {
+                        let dyn_base: Box<dyn DynBase> = Box::from_raw(value.ptr);
+                        drop(dyn_base);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Call `F` with `args` through `FunctionBase::call_rtlc`, returning the only return value
+    unsafe fn call_rtlc<F: FunctionBase>(
+        context: &mut StrTestContext,
+        args: &[Value]
+    ) -> Result<Value, FFIException> {
+        let mut ret: Value = Value::new_null();
+        F::call_rtlc(context, args, &[&mut ret as *mut Value])?;
+        Ok(ret)
+    }
+
+    #[test]
+    fn test_str_functions() {
+        assert_eq!(str_find("αβγ.δ", "."), Some(3));
+        assert_eq!(str_find("αβγ", "."), None);
+        assert_eq!(str_substring("αβγδ", 1, 3), Some("βγ".to_string()));
+        assert_eq!(str_substring("αβγδ", 0, 4), Some("αβγδ".to_string()));
+        assert_eq!(str_substring("αβγδ", 2, 5), None);
+        assert_eq!(str_substring("αβγδ", 2, 1), None);
+        assert_eq!(str_repeat("ab", 3), "ababab");
+        assert_eq!(str_repeat("ab", -1), "");
+    }
+
+    #[test]
+    fn test_generated_binders() {
+        let mut context: StrTestContext = StrTestContext { managed: vec![] };
+        let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+
+        unsafe {
+            let s: Value = "αβγ.δ".to_string().into_value(&mut context);
+            let dot: Value = ".".to_string().into_value(&mut context);
+            let comma: Value = ",".to_string().into_value(&mut context);
+
+            let found: Value =
+                call_rtlc::<Pr47Binder_str_find>(&mut context, &[s, dot]).ok().unwrap();
+            assert_eq!(found.vt_data.inner.int_value, 3);
+            let found: Value =
+                call_rtlc::<Pr47Binder_str_find>(&mut context, &[s, comma]).ok().unwrap();
+            assert!(found.is_null());
+
+            let args: [Value; 3] = [s, Value::new_int(1), Value::new_int(3)];
+            let substring: Value =
+                call_rtlc::<Pr47Binder_str_substring>(&mut context, &args).ok().unwrap();
+            assert_eq!(String::from_value(substring, &mut tyck_info_pool).unwrap(), "βγ");
+            let args: [Value; 3] = [s, Value::new_int(2), Value::new_int(9)];
+            let substring: Value =
+                call_rtlc::<Pr47Binder_str_substring>(&mut context, &args).ok().unwrap();
+            assert!(substring.is_null());
+
+            let args: [Value; 2] = [dot, Value::new_int(3)];
+            let repeated: Value =
+                call_rtlc::<Pr47Binder_str_repeat>(&mut context, &args).ok().unwrap();
+            assert_eq!(String::from_value(repeated, &mut tyck_info_pool).unwrap(), "...");
+
+            let int_str: Value = "-42".to_string().into_value(&mut context);
+            let parsed: Value =
+                call_rtlc::<Pr47Binder_str_parse_int>(&mut context, &[int_str]).ok().unwrap();
+            assert_eq!(parsed.vt_data.inner.int_value, -42);
+            let managed_count: usize = context.managed.len();
+            let err: FFIException =
+                call_rtlc::<Pr47Binder_str_parse_int>(&mut context, &[s]).err().unwrap();
+            assert!(matches!(err, ExceptionInner::Checked(_)));
+            assert_eq!(context.managed.len(), managed_count + 1);
+        }
+    }
+
+    #[test]
+    fn test_split_join_binds() {
+        let mut context: StrTestContext = StrTestContext { managed: vec![] };
+        let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+
+        unsafe {
+            let s: Value = "a,b,,c".to_string().into_value(&mut context);
+            let comma: Value = ",".to_string().into_value(&mut context);
+            let dash: Value = "-".to_string().into_value(&mut context);
+
+            let parts: Value = call_rtlc::<SplitBind>(&mut context, &[s, comma]).ok().unwrap();
+            assert!(parts.is_container());
+            assert_eq!(
+                Vec::<String>::from_value(parts, &mut tyck_info_pool).unwrap(),
+                vec!["a", "b", "", "c"]
+            );
+
+            let joined: Value = call_rtlc::<JoinBind>(&mut context, &[parts, dash]).ok().unwrap();
+            assert_eq!(String::from_value(joined, &mut tyck_info_pool).unwrap(), "a-b--c");
+
+            let empty: Value = Vec::<String>::new().into_value(&mut context);
+            let joined: Value = call_rtlc::<JoinBind>(&mut context, &[empty, dash]).ok().unwrap();
+            assert_eq!(String::from_value(joined, &mut tyck_info_pool).unwrap(), "");
+
+            let with_null: Value = vec![Some("a".to_string()), None].into_value(&mut context);
+            let err: FFIException =
+                call_rtlc::<JoinBind>(&mut context, &[with_null, dash]).err().unwrap();
+            assert!(matches!(
+                err,
+                ExceptionInner::Unchecked(UncheckedException::UnexpectedNull { .. })
+            ));
+        }
+    }
+
+    #[cfg(feature = "al31fm2")]
+    #[test]
+    fn test_register_str_methods() {
+        let mut engine: Engine<DefaultAlloc> = Engine::new();
+        assert!(register_str_methods(&mut engine));
+        assert!(!register_str_methods(&mut engine));
+        assert!(engine.ffi_function_id("string::split").is_some());
+        assert!(engine.ffi_function_id("std.str.join").is_some());
+
+        let methods: MethodTable = engine.methods();
+        for name /*: &str*/ in ["find", "substring", "parseInt", "split", "chars"].iter().copied() {
+            let method: &Method = methods.get(TypeId::of::<String>(), name).unwrap();
+            assert!(!method.mutable_self);
+        }
+    }
+}
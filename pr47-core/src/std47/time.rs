@@ -8,7 +8,7 @@ use xjbutil::void::Void;
 use crate::data::traits::StaticBase;
 use crate::data::tyck::{TyckInfo, TyckInfoPool};
 use crate::data::Value;
-use crate::ffi::{DataOption, FFIException, Signature};
+use crate::ffi::{DataOption, FFIException, RetArity, Signature};
 use crate::ffi::sync_fn::{FunctionBase, VMContext};
 
 impl StaticBase<Instant> for Void {}
@@ -26,7 +26,9 @@ impl FunctionBase for DurationForMillisBind {
         Signature {
             func_type: tyck_info_pool.create_function_type(&[i64_type], &[duration_type], &[]),
             param_options: boxed_slice![DataOption::Copy],
-            ret_option: boxed_slice![DataOption::Move]
+            ret_option: boxed_slice![DataOption::Move],
+            variadic: None,
+            ret_arity: RetArity::Fixed
         }
     }
 
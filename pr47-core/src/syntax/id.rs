@@ -32,6 +32,17 @@ impl<'a> Identifier<'a> {
         }
     }
 
+    /// Get the full name of this identifier, with qualifiers joined by `::`
+    pub fn qual_name(&self) -> String {
+        match self {
+            Identifier::Unqual(token) => token.get_str_value().to_string(),
+            Identifier::Qual(tokens) => tokens.iter()
+                .map(Token::get_str_value)
+                .collect::<Vec<&str>>()
+                .join("::")
+        }
+    }
+
     pub fn as_unqual(&self) -> Option<&Token<'a>> {
         match self {
             Identifier::Unqual(t) => Some(t),
@@ -1,13 +1,37 @@
-use crate::diag::location::SourceLoc;
+//! # Concrete syntax tree of statements
+//!
+//! Statement syntax:
+//! ```text
+//! statement ::= compound-statement
+//!             | for-statement
+//!             | declaration-statement
+//!             | expression-statement
+//!
+//! compound-statement ::= '{' statement-list '}'
+//!
+//! statement-list ::= statement statement-list
+//!                  | NIL
+//!
+//! for-statement ::= 'for' ID 'in' expression compound-statement
+//!
+//! declaration-statement ::= const-declaration
+//!                         | var-declaration
+//!
+//! expression-statement ::= expression ';'
+//! ```
+
+use crate::diag::location::{SourceLoc, SourceRange};
 
 use crate::syntax::decl::ConcreteDecl;
 use crate::syntax::expr::ConcreteExpr;
+use crate::syntax::id::Identifier;
 
 #[cfg_attr(test, derive(Debug))]
 pub enum ConcreteStmt<'a> {
     CompoundStmt(ConcreteCompoundStmt<'a>),
     ExprStmt(ConcreteExpr<'a>, SourceLoc),
-    DeclStmt(ConcreteDecl<'a>, SourceLoc)
+    DeclStmt(ConcreteDecl<'a>, SourceLoc),
+    ForStmt(ConcreteForStmt<'a>)
 }
 
 #[cfg_attr(test, derive(Debug))]
@@ -17,3 +41,13 @@ pub struct ConcreteCompoundStmt<'a> {
     pub left_brace_loc: SourceLoc,
     pub right_brace_loc: SourceLoc
 }
+
+#[cfg_attr(test, derive(Debug))]
+pub struct ConcreteForStmt<'a> {
+    pub var_name: Identifier<'a>,
+    pub iterated: ConcreteExpr<'a>,
+    pub body: ConcreteCompoundStmt<'a>,
+
+    pub for_kwd_range: SourceRange,
+    pub in_kwd_range: SourceRange
+}
@@ -25,10 +25,13 @@ pub enum TokenInner<'a> {
     KwdExport,
     KwdFalse,
     KwdFloat,
+    KwdFor,
     KwdFunc,
     KwdIf,
     KwdImport,
+    KwdIn,
     KwdInt,
+    KwdMap,
     KwdObject,
     KwdOpen,
     KwdReturn,
@@ -216,10 +219,13 @@ impl<'a> Display for Token<'a> {
             KwdExport => write!(f, "⟨export⟩"),
             KwdFalse => write!(f, "⟨false⟩"),
             KwdFloat => write!(f, "⟨float⟩"),
+            KwdFor => write!(f, "⟨for⟩"),
             KwdFunc => write!(f, "⟨func⟩"),
             KwdIf => write!(f, "⟨if⟩"),
             KwdImport => write!(f, "⟨import⟩"),
+            KwdIn => write!(f, "⟨in⟩"),
             KwdInt => write!(f, "⟨int⟩"),
+            KwdMap => write!(f, "⟨map⟩"),
             KwdObject => write!(f, "⟨object⟩"),
             KwdOpen => write!(f, "⟨open⟩"),
             KwdReturn => write!(f, "⟨return⟩"),
@@ -326,10 +332,13 @@ impl<'a> Display2 for TokenInner<'a> {
             TokenInner::KwdExport => write!(fmt, "'export'"),
             TokenInner::KwdFalse => write!(fmt, "'false'"),
             TokenInner::KwdFloat => write!(fmt, "'float'"),
+            TokenInner::KwdFor => write!(fmt, "'for'"),
             TokenInner::KwdFunc => write!(fmt, "'func'"),
             TokenInner::KwdIf => write!(fmt, "'if'"),
             TokenInner::KwdImport => write!(fmt, "'import'"),
+            TokenInner::KwdIn => write!(fmt, "'in'"),
             TokenInner::KwdInt => write!(fmt, "'int'"),
+            TokenInner::KwdMap => write!(fmt, "'map'"),
             TokenInner::KwdObject => write!(fmt, "'object'"),
             TokenInner::KwdOpen => write!(fmt, "'open'"),
             TokenInner::KwdReturn => write!(fmt, "'any'"),
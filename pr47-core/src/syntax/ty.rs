@@ -15,7 +15,8 @@
 //!
 //! primitive-type ::= 'any' | 'char' | 'float' | 'int' | 'object' | 'string' | 'void'
 //!
-//! generic-type ::= 'vector' '<' generic-type-parameter '>'
+//! generic-type ::= 'vector' '<' generic-type-parameter-list '>'
+//!                | 'map' '<' generic-type-parameter-list '>'
 //!
 //! generic-type-parameter-list ::= generic-type-parameter-list ',' type
 //!                               | type
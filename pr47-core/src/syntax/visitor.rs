@@ -13,7 +13,7 @@ use crate::syntax::expr::{
     ConcreteUnaryExpr
 };
 use crate::syntax::id::Identifier;
-use crate::syntax::stmt::{ConcreteCompoundStmt, ConcreteStmt};
+use crate::syntax::stmt::{ConcreteCompoundStmt, ConcreteForStmt, ConcreteStmt};
 use crate::syntax::token::Token;
 use crate::syntax::ty::{ConcreteGenericType, ConcreteNullableType, ConcreteType};
 
@@ -93,7 +93,8 @@ pub trait StmtVisitor<'s> {
         match stmt {
             ConcreteStmt::CompoundStmt(compound_stmt) => self.visit_compound_stmt(compound_stmt),
             ConcreteStmt::ExprStmt(expr_stmt, _) => self.visit_expr_stmt(expr_stmt),
-            ConcreteStmt::DeclStmt(decl_stmt, _) => self.visit_decl_stmt(decl_stmt)
+            ConcreteStmt::DeclStmt(decl_stmt, _) => self.visit_decl_stmt(decl_stmt),
+            ConcreteStmt::ForStmt(for_stmt) => self.visit_for_stmt(for_stmt)
         }
     }
 
@@ -104,6 +105,7 @@ pub trait StmtVisitor<'s> {
 
     fn visit_expr_stmt(&mut self, expr: &'s ConcreteExpr<'s>) -> Self::StmtResult;
     fn visit_decl_stmt(&mut self, decl: &'s ConcreteDecl<'s>) -> Self::StmtResult;
+    fn visit_for_stmt(&mut self, for_stmt: &'s ConcreteForStmt<'s>) -> Self::StmtResult;
 }
 
 pub trait TypeVisitor<'s> {
@@ -0,0 +1,10 @@
+//! # `async_utils.rs`: async runtime facilities of the selected backend
+//!
+//! Re-exports `xjbutil::async_utils` for `async-tokio` and `async-astd`, or the deterministic
+//! `sim_runtime` for `async-sim`.
+
+#[cfg(any(feature = "async-astd", feature = "async-tokio"))]
+pub use xjbutil::async_utils::*;
+
+#[cfg(feature = "async-sim")]
+pub use crate::util::sim_runtime::*;
@@ -1,4 +1,6 @@
 pub mod append;
 pub mod type_assert;
 
+#[cfg(feature = "async")] pub mod async_utils;
 #[cfg(feature = "async")] pub mod serializer;
+#[cfg(feature = "async-sim")] pub mod sim_runtime;
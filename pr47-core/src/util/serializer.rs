@@ -10,17 +10,21 @@
 //! solve this problem easily.
 
 use std::cell::UnsafeCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::mem::transmute;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::Waker;
 
 use futures::future::JoinAll;
+use futures::task::AtomicWaker;
 use unchecked_unwrap::UncheckedUnwrap;
-use xjbutil::async_utils::{Mutex, MutexGuard, join_all, oneshot, task, yield_now};
-use xjbutil::async_utils::oneshot::{Receiver, Sender};
 use xjbutil::unchecked::{UncheckedCellOps, UncheckedOption};
 
+use crate::util::async_utils::{Mutex, MutexGuard, join_all, oneshot, task, yield_now};
+use crate::util::async_utils::oneshot::{Receiver, Sender};
+
 /// A `Arc<Mutex>` is basically a "serializer" context, serializing accesses to `Data`.
 pub type Serializer<Data> = Arc<Mutex<Data>>;
 
@@ -72,6 +76,38 @@ pub async fn co_spawn<'a, 'b, D, FN, ARGS, FUT, RET>(
     (join_handle, lock)
 }
 
+/// Cancellation state of one task
+///
+/// Unlike other shared data, the token can be checked without the running permit, so that a task
+/// blocked on a `Future` can still notice cancellation and stop awaiting.
+#[derive(Default)]
+pub struct CancelToken {
+    cancelled: AtomicBool,
+    /// Waker of the task, registered before the task blocks on a `Future`
+    waker: AtomicWaker
+}
+
+impl CancelToken {
+    /// Check if the task has been requested to be cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Register `waker` to be woken on cancellation, replacing the previously registered one.
+    pub fn register(&self, waker: &Waker) {
+        self.waker.register(waker);
+    }
+
+    /// Mark the task cancelled and wake it, returning `false` if it was already cancelled.
+    fn cancel(&self) -> bool {
+        if self.cancelled.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        self.waker.wake();
+        true
+    }
+}
+
 /// Context shared by all coroutines in the same serialization group
 pub struct CoroutineSharedData {
     /// Tracks the task ID allocation status.
@@ -79,7 +115,33 @@ pub struct CoroutineSharedData {
     /// All running tasks. The key part is task ID, and the value part serves as a receiver of
     /// "task completion" signal. Note that the main task (task_id == 0) itself is not managed
     /// by this `HashMap`
-    running_tasks: HashMap<u32, Receiver<()>>
+    running_tasks: HashMap<u32, Receiver<()>>,
+    /// Cancel tokens of all running tasks. Cancellation is cooperative: a task checks its token
+    /// whenever it regains running permission or gets woken while blocked, and then stops by
+    /// itself.
+    cancel_tokens: HashMap<u32, Arc<CancelToken>>,
+    /// Tracks the task group ID allocation status.
+    next_group_id: u32,
+    /// All alive task groups, keyed by group ID.
+    task_groups: HashMap<u32, TaskGroupData>
+}
+
+/// Bookkeeping of one task group
+struct TaskGroupData {
+    /// The task which created this group. Cancelling the owner task also cancels all tasks in
+    /// this group, so that groups created by grouped tasks nest naturally.
+    owner_task: u32,
+    /// Tasks in this group which have not exited yet.
+    members: HashSet<u32>,
+    /// Liveness of the group handle. Once the handle is gone without joining the group, and all
+    /// the members have exited, nobody could refer to this group any more.
+    handle: Weak<()>
+}
+
+impl TaskGroupData {
+    fn is_dead(&self) -> bool {
+        self.members.is_empty() && self.handle.strong_count() == 0
+    }
 }
 
 impl CoroutineSharedData {
@@ -87,7 +149,10 @@ impl CoroutineSharedData {
     pub fn new() -> Self {
         Self {
             next_task_id: 1,
-            running_tasks: HashMap::new()
+            running_tasks: HashMap::new(),
+            cancel_tokens: HashMap::new(),
+            next_group_id: 0,
+            task_groups: HashMap::new()
         }
     }
 
@@ -99,6 +164,7 @@ impl CoroutineSharedData {
     pub fn add_task(&mut self, rx: Receiver<()>) -> u32 {
         let task_id: u32 = self.get_next_id();
         self.running_tasks.insert(task_id, rx);
+        self.cancel_tokens.insert(task_id, Arc::new(CancelToken::default()));
         task_id
     }
 
@@ -106,6 +172,112 @@ impl CoroutineSharedData {
     /// is called on child task exit, in order to reduce the burden of main task.
     pub fn remove_task(&mut self, task_id: u32) {
         self.running_tasks.remove(&task_id);
+        self.cancel_tokens.remove(&task_id);
+        self.task_groups.retain(|_, group: &mut TaskGroupData| group.owner_task != task_id);
+        for group /*: &mut TaskGroupData*/ in self.task_groups.values_mut() {
+            group.members.remove(&task_id);
+        }
+        self.remove_dead_task_groups();
+    }
+
+    /// Request the given task to be cancelled, returning `false` if the task has already exited.
+    /// Tasks in groups created by the given task get cancelled as well.
+    pub fn cancel_task(&mut self, task_id: u32) -> bool {
+        if self.running_tasks.contains_key(&task_id) {
+            self.cancel_task_tree(task_id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Create a new task group owned by task `owner_task`, returning the allocated group ID
+    /// together with the group handle. Groups never joined are removed once their handles get
+    /// dropped and all their members have exited, even if the owner task never exits.
+    pub fn create_task_group(&mut self, owner_task: u32) -> (u32, Arc<()>) {
+        self.remove_dead_task_groups();
+
+        let group_id: u32 = self.next_group_id;
+        self.next_group_id += 1;
+        let handle: Arc<()> = Arc::new(());
+        self.task_groups.insert(group_id, TaskGroupData {
+            owner_task,
+            members: HashSet::new(),
+            handle: Arc::downgrade(&handle)
+        });
+        (group_id, handle)
+    }
+
+    /// Put task `task_id` into group `group_id`, returning `false` if either the group or the task
+    /// does not exist.
+    pub fn add_to_task_group(&mut self, group_id: u32, task_id: u32) -> bool {
+        if !self.running_tasks.contains_key(&task_id) {
+            return false;
+        }
+
+        if let Some(group /*: &mut TaskGroupData*/) = self.task_groups.get_mut(&group_id) {
+            group.members.insert(task_id);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Request all tasks in group `group_id` to be cancelled.
+    pub fn cancel_task_group(&mut self, group_id: u32) {
+        let members: Vec<u32> = if let Some(group /*: &TaskGroupData*/)
+            = self.task_groups.get(&group_id)
+        {
+            group.members.iter().copied().collect()
+        } else {
+            return;
+        };
+
+        for task_id /*: u32*/ in members {
+            self.cancel_task(task_id);
+        }
+    }
+
+    /// Remove group `group_id` from context. This is called after the group has been joined.
+    pub fn remove_task_group(&mut self, group_id: u32) {
+        self.task_groups.remove(&group_id);
+    }
+
+    /// Remove task groups which could no longer be joined, nor be cancelled along with their owners.
+    fn remove_dead_task_groups(&mut self) {
+        self.task_groups.retain(|_, group: &mut TaskGroupData| !group.is_dead());
+    }
+
+    /// Cancel the given task, together with members of task groups owned by it, recursively.
+    /// Tasks blocked on a `Future` get woken, so that they could stop awaiting.
+    fn cancel_task_tree(&mut self, task_id: u32) {
+        let newly_cancelled: bool = self.cancel_tokens.get(&task_id)
+            .is_some_and(|token: &Arc<CancelToken>| token.cancel());
+        if !newly_cancelled {
+            return;
+        }
+
+        let owned_members: Vec<u32> = self.task_groups.values()
+            .filter(|group: &&TaskGroupData| group.owner_task == task_id)
+            .flat_map(|group: &TaskGroupData| group.members.iter().copied())
+            .collect();
+        for member /*: u32*/ in owned_members {
+            if self.running_tasks.contains_key(&member) {
+                self.cancel_task_tree(member);
+            }
+        }
+    }
+
+    /// Check if the given task has been requested to be cancelled.
+    pub fn is_cancelled(&self, task_id: u32) -> bool {
+        self.cancel_tokens.get(&task_id)
+            .is_some_and(|token: &Arc<CancelToken>| token.is_cancelled())
+    }
+
+    /// Retrieve the cancel token of the given task. The main task, as well as tasks already
+    /// exited, cannot be cancelled and get a token never cancelled.
+    pub fn cancel_token(&self, task_id: u32) -> Arc<CancelToken> {
+        self.cancel_tokens.get(&task_id).cloned().unwrap_or_default()
     }
 
     /// Retrieve all tasks and their "completion signal receiver", cleaning internal storage of
@@ -194,12 +366,13 @@ impl<SD: 'static + Send> CoroutineContext<SD> {
         ret
     }
 
-    /// Spawn a new `task` managed by the current serialization group
+    /// Spawn a new `task` managed by the current serialization group, returning the allocated
+    /// task ID together with the join handle.
     pub async fn co_spawn_task<FN, ARGS, FUT, RET>(
         &self,
         f: FN,
         args: ARGS
-    ) -> task::JoinHandle<RET>
+    ) -> (u32, task::JoinHandle<RET>)
         where FN: (FnOnce(CoroutineContext<SD>, ARGS) -> FUT) + Send + 'static,
               ARGS: Send + 'static,
               FUT: Future<Output=RET> + Send,
@@ -226,7 +399,47 @@ impl<SD: 'static + Send> CoroutineContext<SD> {
             },
             ()).await;
         unsafe { self.acquire_permit(new_permit); }
-        join_handle
+        (task_id, join_handle)
+    }
+
+    /// Given the fact that the permit is held, request the task `task_id` in the same
+    /// serialization group to be cancelled.
+    pub fn cancel_task(&self, task_id: u32) -> bool {
+        unsafe { self.permit.get_mut_ref_unchecked().get_mut().0.cancel_task(task_id) }
+    }
+
+    /// Given the fact that the permit is held, create a new task group owned by the current
+    /// `task`, returning the group ID together with the group handle.
+    pub fn create_task_group(&self) -> (u32, Arc<()>) {
+        unsafe {
+            self.permit.get_mut_ref_unchecked().get_mut().0.create_task_group(self.task_id)
+        }
+    }
+
+    /// Given the fact that the permit is held, put task `task_id` into group `group_id`.
+    pub fn add_to_task_group(&self, group_id: u32, task_id: u32) -> bool {
+        unsafe {
+            self.permit.get_mut_ref_unchecked().get_mut().0.add_to_task_group(group_id, task_id)
+        }
+    }
+
+    /// Given the fact that the permit is held, request all tasks in group `group_id` to be
+    /// cancelled.
+    pub fn cancel_task_group(&self, group_id: u32) {
+        unsafe { self.permit.get_mut_ref_unchecked().get_mut().0.cancel_task_group(group_id) }
+    }
+
+    /// Given the fact that the permit is held, retrieve the cancel token of the current `task`.
+    pub fn cancel_token(&self) -> Arc<CancelToken> {
+        unsafe { self.permit.get_mut_ref_unchecked().get_mut().0.cancel_token(self.task_id) }
+    }
+
+    /// Given the fact that the permit is held, check if the current `task` has been requested to
+    /// be cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.task_id != 0 && unsafe {
+            self.permit.get_mut_ref_unchecked().get_mut().0.is_cancelled(self.task_id)
+        }
     }
 
     /// Called on main `task` exit, wait for all other `task`s to finish.
@@ -279,9 +492,10 @@ unsafe impl<SD: 'static + Send> Sync for CoroutineContext<SD> {}
 mod test {
     use std::time::Duration;
 
-    use xjbutil::async_utils::{block_on_future, testing_sleep};
+    use crate::util::async_utils::{block_on_future, oneshot, testing_sleep};
+    use crate::util::async_utils::oneshot::{Receiver, Sender};
 
-    use crate::util::serializer::CoroutineContext;
+    use crate::util::serializer::{CoroutineContext, CoroutineSharedData};
 
     #[test]
     fn basic_test_print() {
@@ -310,4 +524,28 @@ mod test {
         block_on_future(test_impl());
         eprintln!("mission accomplished");
     }
+
+    #[test]
+    fn test_unjoined_task_group() {
+        let mut shared_data: CoroutineSharedData = CoroutineSharedData::new();
+        let (_tx, rx): (Sender<()>, Receiver<()>) = oneshot::channel();
+        let task_id: u32 = shared_data.add_task(rx);
+
+        // groups owned by the main task, with the handles dropped without joining
+        let (group_id, handle) = shared_data.create_task_group(0);
+        assert!(shared_data.add_to_task_group(group_id, task_id));
+        drop(handle);
+        let (empty_group_id, handle) = shared_data.create_task_group(0);
+        drop(handle);
+
+        // the group with a running member is kept, so that cancellation still cascades
+        let (live_group_id, _handle) = shared_data.create_task_group(0);
+        assert!(shared_data.task_groups.contains_key(&group_id));
+        assert!(!shared_data.task_groups.contains_key(&empty_group_id));
+
+        shared_data.remove_task(task_id);
+        assert!(!shared_data.task_groups.contains_key(&group_id));
+        assert!(shared_data.task_groups.contains_key(&live_group_id));
+        assert_eq!(shared_data.task_groups.len(), 1);
+    }
 }
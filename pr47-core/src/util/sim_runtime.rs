@@ -0,0 +1,634 @@
+//! # `sim_runtime.rs`: deterministic, single-threaded async runtime
+//!
+//! This runtime provides the same `spawn`/`yield`/`sleep`/`Mutex`/`oneshot` facilities as
+//! `xjbutil::async_utils`, but runs everything on the current thread. Whenever multiple `task`s
+//! are ready, the next one to poll is picked by a seeded pseudo-random generator, and `sleep`
+//! is driven by a virtual clock which jumps directly to the next timer once no `task` is ready.
+//! As a result, given the same seed, task interleavings and timings are fully reproducible.
+
+use std::cell::{Cell, RefCell, RefMut, UnsafeCell};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+
+pub use futures::future::join_all;
+
+/// Seed used by `block_on_future` if environment variable `PR47_SIM_SEED` is not set
+pub const DEFAULT_SEED: u64 = 0x2017_0407_4747_4747;
+
+type LocalTask = Pin<Box<dyn Future<Output=()>>>;
+
+/// Task ID reserved for the future passed to `block_on_future`
+const MAIN_TASK_ID: usize = 0;
+
+struct Runtime {
+    /// Spawned tasks. A task being polled is temporarily removed from this map
+    tasks: RefCell<BTreeMap<usize, LocalTask>>,
+    next_task_id: Cell<usize>,
+    /// IDs of tasks woken up, shared with `Waker`s
+    ready: Arc<StdMutex<BTreeSet<usize>>>,
+    /// State of the xorshift64* generator
+    rng_state: Cell<u64>,
+    /// Virtual time elapsed since the runtime started
+    clock: Cell<Duration>,
+    /// Pending timers, keyed by deadline and registration order
+    timers: RefCell<BTreeMap<(Duration, u64), Waker>>,
+    next_timer_seq: Cell<u64>
+}
+
+impl Runtime {
+    fn new(seed: u64) -> Self {
+        Self {
+            tasks: RefCell::new(BTreeMap::new()),
+            next_task_id: Cell::new(MAIN_TASK_ID + 1),
+            ready: Arc::new(StdMutex::new(BTreeSet::new())),
+            // xorshift generators get stuck at zero
+            rng_state: Cell::new(if seed == 0 { DEFAULT_SEED } else { seed }),
+            clock: Cell::new(Duration::ZERO),
+            timers: RefCell::new(BTreeMap::new()),
+            next_timer_seq: Cell::new(0)
+        }
+    }
+
+    fn next_random(&self) -> u64 {
+        let mut x: u64 = self.rng_state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.rng_state.set(x);
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn waker(&self, task_id: usize) -> Waker {
+        Waker::from(Arc::new(TaskWaker { task_id, ready: self.ready.clone() }))
+    }
+
+    fn spawn(&self, task: LocalTask) {
+        let task_id: usize = self.next_task_id.get();
+        self.next_task_id.set(task_id + 1);
+        self.tasks.borrow_mut().insert(task_id, task);
+        self.ready.lock().unwrap().insert(task_id);
+    }
+
+    /// Pick a ready task randomly, removing it from the ready set
+    fn pick_ready(&self) -> Option<usize> {
+        let mut ready: StdMutexGuard<BTreeSet<usize>> = self.ready.lock().unwrap();
+        if ready.is_empty() {
+            return None;
+        }
+        let idx: usize = (self.next_random() % ready.len() as u64) as usize;
+        let task_id: usize = *ready.iter().nth(idx).unwrap();
+        ready.remove(&task_id);
+        Some(task_id)
+    }
+
+    /// Advance the virtual clock to the earliest timer, firing all timers due at that time.
+    /// Returns `false` if there's no timer at all.
+    fn advance_clock(&self) -> bool {
+        let mut timers: RefMut<BTreeMap<(Duration, u64), Waker>> = self.timers.borrow_mut();
+        let deadline: Duration = if let Some(((deadline, _), _)) = timers.iter().next() {
+            *deadline
+        } else {
+            return false;
+        };
+
+        self.clock.set(deadline);
+        while let Some(entry /*: OccupiedEntry<(Duration, u64), Waker>*/) = timers.first_entry() {
+            if entry.key().0 > deadline {
+                break;
+            }
+            entry.remove().wake();
+        }
+        true
+    }
+
+    fn register_timer(&self, deadline: Duration, waker: Waker) {
+        let seq: u64 = self.next_timer_seq.get();
+        self.next_timer_seq.set(seq + 1);
+        self.timers.borrow_mut().insert((deadline, seq), waker);
+    }
+}
+
+struct TaskWaker {
+    task_id: usize,
+    ready: Arc<StdMutex<BTreeSet<usize>>>
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.ready.lock().unwrap().insert(self.task_id);
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Rc<Runtime>>> = const { RefCell::new(None) };
+}
+
+fn with_runtime<R>(f: impl FnOnce(&Runtime) -> R) -> R {
+    let runtime: Rc<Runtime> = CURRENT.with(|current: &RefCell<Option<Rc<Runtime>>>| {
+        current.borrow()
+            .clone()
+            .expect("not running inside a simulation runtime, use `block_on_future`")
+    });
+    f(&runtime)
+}
+
+/// Uninstalls the current runtime on exit, even when panicking
+struct RuntimeGuard();
+
+impl Drop for RuntimeGuard {
+    fn drop(&mut self) {
+        let runtime: Option<Rc<Runtime>> =
+            CURRENT.with(|current: &RefCell<Option<Rc<Runtime>>>| current.borrow_mut().take());
+        // Drop remaining tasks after the runtime has been uninstalled
+        drop(runtime);
+    }
+}
+
+/// Run `fut` to completion with a runtime seeded by `seed`. Tasks spawned by `fut` which are still
+/// running when `fut` completes are dropped.
+pub fn block_on_future_with_seed<F: Future>(seed: u64, fut: F) -> F::Output {
+    let runtime: Rc<Runtime> = Rc::new(Runtime::new(seed));
+    CURRENT.with(|current: &RefCell<Option<Rc<Runtime>>>| {
+        let mut current: RefMut<Option<Rc<Runtime>>> = current.borrow_mut();
+        assert!(current.is_none(), "simulation runtimes cannot be nested");
+        *current = Some(runtime.clone());
+    });
+    let _guard: RuntimeGuard = RuntimeGuard();
+
+    let mut fut: Pin<Box<F>> = Box::pin(fut);
+    let main_waker: Waker = runtime.waker(MAIN_TASK_ID);
+    runtime.ready.lock().unwrap().insert(MAIN_TASK_ID);
+
+    loop {
+        let task_id: usize = if let Some(task_id /*: usize*/) = runtime.pick_ready() {
+            task_id
+        } else if runtime.advance_clock() {
+            continue;
+        } else {
+            panic!("deadlock: no task is ready and no timer is pending");
+        };
+
+        if task_id == MAIN_TASK_ID {
+            let mut cx: Context = Context::from_waker(&main_waker);
+            if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+                return output;
+            }
+            continue;
+        }
+
+        let task: Option<LocalTask> = runtime.tasks.borrow_mut().remove(&task_id);
+        if let Some(mut task /*: LocalTask*/) = task {
+            let waker: Waker = runtime.waker(task_id);
+            let mut cx: Context = Context::from_waker(&waker);
+            if task.as_mut().poll(&mut cx).is_pending() {
+                runtime.tasks.borrow_mut().insert(task_id, task);
+            }
+        }
+    }
+}
+
+/// Run `fut` to completion, using the seed given by environment variable `PR47_SIM_SEED`, or
+/// `DEFAULT_SEED` if not set
+pub fn block_on_future<F: Future>(fut: F) -> F::Output {
+    let seed: u64 = std::env::var("PR47_SIM_SEED")
+        .ok()
+        .and_then(|seed: String| seed.parse().ok())
+        .unwrap_or(DEFAULT_SEED);
+    block_on_future_with_seed(seed, fut)
+}
+
+/// Virtual time elapsed since the current runtime started
+pub fn now() -> Duration {
+    with_runtime(|runtime: &Runtime| runtime.clock.get())
+}
+
+pub struct Sleep {
+    duration: Duration,
+    deadline: Option<Duration>
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let duration: Duration = self.duration;
+        with_runtime(|runtime: &Runtime| {
+            let now: Duration = runtime.clock.get();
+            let deadline: Duration = *self.deadline.get_or_insert(now + duration);
+            if now >= deadline {
+                Poll::Ready(())
+            } else {
+                runtime.register_timer(deadline, cx.waker().clone());
+                Poll::Pending
+            }
+        })
+    }
+}
+
+/// Sleep for `duration` of virtual time
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep { duration, deadline: None }
+}
+
+pub use sleep as testing_sleep;
+
+pub struct YieldNow {
+    yielded: bool
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            Poll::Ready(())
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Give other ready tasks a chance to run
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+/// File system operations. These are performed synchronously, so that they complete without
+/// consuming any virtual time.
+pub mod fs {
+    use std::io;
+    use std::path::Path;
+
+    pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+}
+
+pub mod task {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+    use std::task::{Context, Poll, Waker};
+
+    use super::{Runtime, with_runtime};
+
+    struct JoinState<T> {
+        output: Option<T>,
+        waker: Option<Waker>
+    }
+
+    /// Handle of a spawned task. Dropping the handle detaches the task.
+    pub struct JoinHandle<T> {
+        state: Arc<StdMutex<JoinState<T>>>
+    }
+
+    impl<T> Future for JoinHandle<T> {
+        type Output = T;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+            let mut state: StdMutexGuard<JoinState<T>> = self.state.lock().unwrap();
+            if let Some(output /*: T*/) = state.output.take() {
+                Poll::Ready(output)
+            } else {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    pub fn spawn<F>(fut: F) -> JoinHandle<F::Output>
+        where F: Future + 'static,
+              F::Output: 'static
+    {
+        let state: Arc<StdMutex<JoinState<F::Output>>> = Arc::new(StdMutex::new(JoinState {
+            output: None,
+            waker: None
+        }));
+        let state_clone: Arc<StdMutex<JoinState<F::Output>>> = state.clone();
+
+        with_runtime(|runtime: &Runtime| runtime.spawn(Box::pin(async move {
+            let output: F::Output = fut.await;
+            let mut state: StdMutexGuard<JoinState<F::Output>> = state_clone.lock().unwrap();
+            state.output = Some(output);
+            if let Some(waker /*: Waker*/) = state.waker.take() {
+                waker.wake();
+            }
+        })));
+
+        JoinHandle { state }
+    }
+}
+
+struct MutexState {
+    locked: bool,
+    next_waiter_id: u64,
+    /// Pending `Lock`s in FIFO order, each with one slot keyed by its waiter ID
+    waiters: VecDeque<(u64, Waker)>
+}
+
+impl MutexState {
+    fn wake_front(&self) {
+        if let Some((_, waker)) /*: Option<&(u64, Waker)>*/ = self.waiters.front() {
+            waker.wake_by_ref();
+        }
+    }
+}
+
+/// An asynchronous mutex, waking up waiters in FIFO order
+pub struct Mutex<T: ?Sized> {
+    state: StdMutex<MutexState>,
+    data: UnsafeCell<T>
+}
+
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            state: StdMutex::new(MutexState {
+                locked: false,
+                next_waiter_id: 0,
+                waiters: VecDeque::new()
+            }),
+            data: UnsafeCell::new(data)
+        }
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    pub fn lock(&self) -> Lock<'_, T> {
+        Lock { mutex: self, waiter_id: None }
+    }
+}
+
+pub struct Lock<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>,
+    waiter_id: Option<u64>
+}
+
+impl<'a, T: ?Sized> Future for Lock<'a, T> {
+    type Output = MutexGuard<'a, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<MutexGuard<'a, T>> {
+        let this: &mut Lock<'a, T> = self.get_mut();
+        let mut state: StdMutexGuard<MutexState> = this.mutex.state.lock().unwrap();
+        if state.locked {
+            if let Some(waiter_id /*: u64*/) = this.waiter_id {
+                let waker: &mut Waker = &mut state.waiters.iter_mut()
+                    .find(|(id, _): &&mut (u64, Waker)| *id == waiter_id)
+                    .expect("waiter slot removed while the lock is pending")
+                    .1;
+                if !waker.will_wake(cx.waker()) {
+                    *waker = cx.waker().clone();
+                }
+            } else {
+                let waiter_id: u64 = state.next_waiter_id;
+                state.next_waiter_id += 1;
+                state.waiters.push_back((waiter_id, cx.waker().clone()));
+                this.waiter_id = Some(waiter_id);
+            }
+            Poll::Pending
+        } else {
+            state.locked = true;
+            if let Some(waiter_id /*: u64*/) = this.waiter_id.take() {
+                state.waiters.retain(|(id, _): &(u64, Waker)| *id != waiter_id);
+            }
+            Poll::Ready(MutexGuard { mutex: this.mutex })
+        }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for Lock<'a, T> {
+    fn drop(&mut self) {
+        if let Some(waiter_id /*: u64*/) = self.waiter_id {
+            let mut state: StdMutexGuard<MutexState> = self.mutex.state.lock().unwrap();
+            state.waiters.retain(|(id, _): &(u64, Waker)| *id != waiter_id);
+            // this waiter may have been woken by an unlock it will never take, so pass the
+            // wakeup on to the next live waiter
+            if !state.locked {
+                state.wake_front();
+            }
+        }
+    }
+}
+
+pub struct MutexGuard<'a, T: ?Sized> {
+    mutex: &'a Mutex<T>
+}
+
+// moving the guard to another thread moves the unique access to `T` along, like `&mut T` does.
+// Sharing the guard only shares `&T`. Same bounds as `tokio::sync::MutexGuard`.
+unsafe impl<'a, T: ?Sized + Send> Send for MutexGuard<'a, T> {}
+unsafe impl<'a, T: ?Sized + Send + Sync> Sync for MutexGuard<'a, T> {}
+
+impl<'a, T: ?Sized> Deref for MutexGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for MutexGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for MutexGuard<'a, T> {
+    fn drop(&mut self) {
+        let mut state: StdMutexGuard<MutexState> = self.mutex.state.lock().unwrap();
+        state.locked = false;
+        // the woken waiter removes its own slot once it takes the lock, or passes the wakeup on
+        // when dropped
+        state.wake_front();
+    }
+}
+
+pub mod oneshot {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex as StdMutex, MutexGuard as StdMutexGuard};
+    use std::task::{Context, Poll, Waker};
+
+    struct Inner<T> {
+        value: Option<T>,
+        sender_dropped: bool,
+        receiver_dropped: bool,
+        waker: Option<Waker>
+    }
+
+    pub struct Sender<T> {
+        inner: Arc<StdMutex<Inner<T>>>
+    }
+
+    pub struct Receiver<T> {
+        inner: Arc<StdMutex<Inner<T>>>
+    }
+
+    #[derive(Debug)]
+    pub struct RecvError();
+
+    pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+        let inner: Arc<StdMutex<Inner<T>>> = Arc::new(StdMutex::new(Inner {
+            value: None,
+            sender_dropped: false,
+            receiver_dropped: false,
+            waker: None
+        }));
+        (Sender { inner: inner.clone() }, Receiver { inner })
+    }
+
+    impl<T> Sender<T> {
+        /// Send `value` to the receiver, giving it back if the receiver has been dropped
+        pub fn send(self, value: T) -> Result<(), T> {
+            let mut inner: StdMutexGuard<Inner<T>> = self.inner.lock().unwrap();
+            if inner.receiver_dropped {
+                return Err(value);
+            }
+            inner.value = Some(value);
+            if let Some(waker /*: Waker*/) = inner.waker.take() {
+                waker.wake();
+            }
+            Ok(())
+        }
+    }
+
+    impl<T> Drop for Sender<T> {
+        fn drop(&mut self) {
+            let mut inner: StdMutexGuard<Inner<T>> = self.inner.lock().unwrap();
+            inner.sender_dropped = true;
+            if let Some(waker /*: Waker*/) = inner.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+
+    impl<T> Future for Receiver<T> {
+        type Output = Result<T, RecvError>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let mut inner: StdMutexGuard<Inner<T>> = self.inner.lock().unwrap();
+            if let Some(value /*: T*/) = inner.value.take() {
+                Poll::Ready(Ok(value))
+            } else if inner.sender_dropped {
+                Poll::Ready(Err(RecvError()))
+            } else {
+                inner.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+
+    impl<T> Drop for Receiver<T> {
+        fn drop(&mut self) {
+            self.inner.lock().unwrap().receiver_dropped = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures::future::poll_fn;
+
+    use crate::util::sim_runtime::{
+        Lock,
+        Mutex,
+        MutexGuard,
+        block_on_future_with_seed,
+        now,
+        sleep,
+        task,
+        yield_now
+    };
+
+    fn interleaving(seed: u64) -> Vec<u32> {
+        let trace: Rc<RefCell<Vec<u32>>> = Rc::new(RefCell::new(Vec::new()));
+        block_on_future_with_seed(seed, async {
+            let mut handles: Vec<task::JoinHandle<()>> = Vec::new();
+            for i in 0..4 {
+                let trace: Rc<RefCell<Vec<u32>>> = trace.clone();
+                handles.push(task::spawn(async move {
+                    for j in 0..4 {
+                        trace.borrow_mut().push(i * 10 + j);
+                        yield_now().await;
+                    }
+                }));
+            }
+            for handle /*: JoinHandle<()>*/ in handles {
+                handle.await;
+            }
+        });
+        Rc::try_unwrap(trace).unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_deterministic_interleaving() {
+        assert_eq!(interleaving(42), interleaving(42));
+        assert_eq!(interleaving(42).len(), 16);
+    }
+
+    #[test]
+    fn test_virtual_clock() {
+        let elapsed: Duration = block_on_future_with_seed(1, async {
+            let handle: task::JoinHandle<Duration> = task::spawn(async {
+                sleep(Duration::from_secs(3600)).await;
+                now()
+            });
+            sleep(Duration::from_secs(60)).await;
+            assert_eq!(now(), Duration::from_secs(60));
+            handle.await
+        });
+        assert_eq!(elapsed, Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_mutex_dropped_waiter() {
+        block_on_future_with_seed(7, async {
+            let mutex: Rc<Mutex<u32>> = Rc::new(Mutex::new(0));
+            let guard: MutexGuard<u32> = mutex.lock().await;
+
+            let mutex1: Rc<Mutex<u32>> = mutex.clone();
+            let dropped: task::JoinHandle<()> = task::spawn(async move {
+                let mut lock: Pin<Box<Lock<u32>>> = Box::pin(mutex1.lock());
+                poll_fn(|cx: &mut Context<'_>| {
+                    assert!(lock.as_mut().poll(cx).is_pending());
+                    assert!(lock.as_mut().poll(cx).is_pending());
+                    Poll::Ready(())
+                }).await;
+            });
+            let mutex2: Rc<Mutex<u32>> = mutex.clone();
+            let waiting: task::JoinHandle<u32> = task::spawn(async move {
+                let mut guard: MutexGuard<u32> = mutex2.lock().await;
+                *guard += 1;
+                *guard
+            });
+
+            dropped.await;
+            sleep(Duration::from_secs(1)).await;
+            // re-polling a pending lock keeps one slot, and a dropped lock gives its slot up
+            assert_eq!(mutex.state.lock().unwrap().waiters.len(), 1);
+
+            drop(guard);
+            assert_eq!(waiting.await, 1);
+            assert!(mutex.state.lock().unwrap().waiters.is_empty());
+        });
+    }
+}
@@ -4,6 +4,7 @@ pub mod no_gc_alloc;
 use xjbutil::flex::FlexArray;
 
 use crate::data::Value;
+use crate::vm::al31fm2::globals::Globals;
 use crate::vm::al31fm2::stack::Stack;
 
 pub type AllocPin = FlexArray<bool, Value>;
@@ -16,6 +17,9 @@ pub trait Alloc: 'static + Send + Sync {
     /// Remove one stack from `Alloc` management
     unsafe fn remove_stack(&mut self, stack: *const Stack);
 
+    /// Set the global variables table to be scanned as a root set
+    unsafe fn set_globals(&mut self, globals: *const Globals);
+
     /// Make the object denoted by `data` pointer managed
     unsafe fn add_managed(&mut self, data: Value);
 
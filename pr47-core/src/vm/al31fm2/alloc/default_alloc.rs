@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::ptr::null;
 
 use unchecked_unwrap::UncheckedUnwrap;
 
@@ -6,11 +7,13 @@ use crate::data::Value;
 use crate::data::generic::GenericTypeVT;
 use crate::data::wrapper::{DynBase, OWN_INFO_COLLECT_MASK, OWN_INFO_GLOBAL_MASK};
 use crate::vm::al31fm2::alloc::{Alloc, AllocPin};
+use crate::vm::al31fm2::globals::Globals;
 use crate::vm::al31fm2::stack::Stack;
 
 /// Default allocator for `AL31F`, with STW GC.
 pub struct DefaultAlloc {
     stacks: Vec<*const Stack>,
+    globals: *const Globals,
     managed: Vec<Value>,
     pinned: Vec<AllocPin>,
     debt: usize,
@@ -44,6 +47,7 @@ impl DefaultAlloc {
     pub fn with_max_debt(max_debt: usize, max_pin_debt: usize) -> Self {
         Self {
             stacks: Vec::new(),
+            globals: null(),
             managed: Vec::new(),
             pinned: Vec::new(),
             debt: 0,
@@ -109,6 +113,10 @@ impl Alloc for DefaultAlloc {
         let _removed = self.stacks.remove(self.stacks.binary_search(&stack).unchecked_unwrap());
     }
 
+    unsafe fn set_globals(&mut self, globals: *const Globals) {
+        self.globals = globals;
+    }
+
     unsafe fn add_managed(&mut self, data: Value) {
         if self.max_debt < self.debt && self.gc_allowed {
             self.collect();
@@ -159,6 +167,15 @@ impl Alloc for DefaultAlloc {
             }
         }
 
+        if !self.globals.is_null() {
+            let globals: &Globals = &*self.globals;
+            for global /*: &Value*/ in globals.values().iter().chain(globals.consts().iter()) {
+                if !global.is_null() && !global.is_value() {
+                    to_scan.push_back(*global);
+                }
+            }
+        }
+
         for pin /*: &AllocPin*/ in self.pinned.iter() {
             for pinned_object /*: &Value*/ in pin.flex().iter() {
                 if !pinned_object.is_null() && !pinned_object.is_value() {
@@ -241,6 +258,7 @@ mod test {
     use crate::data::wrapper::Wrapper;
     use crate::vm::al31fm2::alloc::Alloc;
     use crate::vm::al31fm2::alloc::default_alloc::DefaultAlloc;
+    use crate::vm::al31fm2::globals::Globals;
     use crate::vm::al31fm2::stack::{Stack, StackSlice};
 
     #[test] fn test_default_collector_simple() {
@@ -346,4 +364,29 @@ mod test {
             assert!(!alloc.contains_ptr(container.ptr_repr));
         }
     }
+
+    #[test] fn test_default_collector_globals() {
+        let mut alloc: DefaultAlloc = DefaultAlloc::new();
+        let mut globals: Globals =
+            Globals::with_names(&["cache".to_string(), "counter".to_string()]);
+
+        let str1: Value = Value::new_owned::<String>("114".into());
+        let str2: Value = Value::new_owned::<String>("514".into());
+
+        unsafe {
+            alloc.set_globals(&globals);
+            alloc.add_managed(str1);
+            alloc.add_managed(str2);
+
+            assert!(globals.set("cache", str1));
+            globals.set_unchecked(1, Value::new_int(42));
+            alloc.collect();
+            assert!(alloc.contains_ptr(str1.ptr_repr));
+            assert!(!alloc.contains_ptr(str2.ptr_repr));
+
+            assert!(globals.set("cache", Value::new_null()));
+            alloc.collect();
+            assert!(!alloc.contains_ptr(str1.ptr_repr));
+        }
+    }
 }
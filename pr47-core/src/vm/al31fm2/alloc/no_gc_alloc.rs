@@ -1,5 +1,6 @@
 use crate::data::Value;
 use crate::vm::al31fm2::alloc::Alloc;
+use crate::vm::al31fm2::globals::Globals;
 use crate::vm::al31fm2::stack::Stack;
 
 pub struct NoGCAlloc {
@@ -27,6 +28,8 @@ impl Alloc for NoGCAlloc {
 
     #[inline(always)] unsafe fn remove_stack(&mut self, _stack: *const Stack) {}
 
+    #[inline(always)] unsafe fn set_globals(&mut self, _globals: *const Globals) {}
+
     unsafe fn add_managed(&mut self, data: Value) {
         self.managed.push(data);
     }
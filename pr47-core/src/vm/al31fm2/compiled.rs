@@ -1,4 +1,6 @@
 use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+use std::collections::hash_map::Entry;
 use std::ptr::NonNull;
 use xjbutil::slice_arena::SliceArena;
 
@@ -9,8 +11,15 @@ use crate::vm::al31fm2::Combustor;
 use crate::vm::al31fm2::alloc::Alloc;
 use crate::vm::al31fm2::insc::Insc;
 
+pub use crate::ffi::member::{MemberTable, Method, MethodFunc, MethodTable, Property, PropertyTable};
+
 #[cfg(feature = "async")] use crate::ffi::async_fn::AsyncFunction as FFIAsyncFunction;
 #[cfg(feature = "async")] use crate::vm::al31fm2::{AL31F, AsyncCombustor};
+#[cfg(feature = "compiler")] use crate::diag::DiagContext;
+#[cfg(feature = "compiler")] use crate::diag::diag_data;
+#[cfg(feature = "compiler")] use crate::diag::location::SourceLoc;
+#[cfg(feature = "compiler")] use crate::syntax::ConcreteProgram;
+#[cfg(feature = "compiler")] use crate::syntax::decl::{ConcreteDecl, ConcreteExportDecl};
 
 pub struct ExceptionHandlingBlock {
     pub insc_ptr_range: (usize, usize),
@@ -80,15 +89,276 @@ impl CompiledFunction {
     }
 }
 
+#[cfg(feature = "compiler")]
+pub struct DebugInfo {
+    /// Function names, indexed by function ID
+    pub func_names: Box<[String]>,
+    /// Source locations of instructions, indexed by instruction pointer
+    pub insc_locs: Box<[SourceLoc]>,
+    /// Variable names of registers, indexed by function ID and then register index
+    pub reg_names: Box<[Box<[Option<String>]>]>
+}
+
+#[cfg(feature = "compiler")]
+impl DebugInfo {
+    pub fn new(
+        func_names: Box<[String]>,
+        insc_locs: Box<[SourceLoc]>,
+        reg_names: Box<[Box<[Option<String>]>]>
+    ) -> Self {
+        Self {
+            func_names,
+            insc_locs,
+            reg_names
+        }
+    }
+
+    pub fn func_name(&self, func_id: usize) -> Option<&str> {
+        self.func_names.get(func_id).map(String::as_str)
+    }
+
+    pub fn insc_loc(&self, insc_ptr: usize) -> Option<SourceLoc> {
+        self.insc_locs.get(insc_ptr)
+            .copied()
+            .filter(|loc: &SourceLoc| !loc.is_unknown())
+    }
+
+    pub fn reg_name(&self, func_id: usize, reg: usize) -> Option<&str> {
+        self.reg_names.get(func_id)?.get(reg)?.as_deref()
+    }
+}
+
+/// An item exported by a program
+#[derive(Clone)]
+pub enum Export {
+    Function {
+        func_id: usize,
+        /// Parameter types, same as `CompiledFunction::param_tyck_info`
+        param_types: Box<[Option<NonNull<TyckInfo>>]>,
+        /// Return types, `None` for values not checked by compiler
+        ret_types: Box<[Option<NonNull<TyckInfo>>]>
+    },
+    Global(usize),
+    Const(usize)
+}
+
+/// Exported items of a program, keyed by qualified names (like `foo::bar`)
+pub struct ExportTable {
+    exports: HashMap<String, Export>
+}
+
+impl ExportTable {
+    pub fn new() -> Self {
+        Self { exports: HashMap::new() }
+    }
+
+    /// Export `item` under `name`, returns `false` if `name` has already been exported
+    pub fn add(&mut self, name: impl Into<String>, item: Export) -> bool {
+        match self.exports.entry(name.into()) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(item);
+                true
+            }
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Export> {
+        self.exports.get(name)
+    }
+
+    pub fn function(&self, name: &str) -> Option<usize> {
+        match self.exports.get(name)? {
+            Export::Function { func_id, .. } => Some(*func_id),
+            _ => None
+        }
+    }
+
+    pub fn global(&self, name: &str) -> Option<usize> {
+        match self.exports.get(name)? {
+            Export::Global(global_id) => Some(*global_id),
+            _ => None
+        }
+    }
+
+    pub fn constant(&self, name: &str) -> Option<usize> {
+        match self.exports.get(name)? {
+            Export::Const(const_id) => Some(*const_id),
+            _ => None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(&str, &Export)> {
+        self.exports.iter().map(|(name, item): (&String, &Export)| (name.as_str(), item))
+    }
+
+    pub fn len(&self) -> usize {
+        self.exports.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exports.is_empty()
+    }
+
+    /// Export items listed by `export_decl`. `symbols` maps qualified names of all top-level
+    /// items of the program to their compiled forms. Undefined or duplicate items are reported
+    /// to `diag`.
+    #[cfg(feature = "compiler")]
+    pub fn add_export_decl(
+        &mut self,
+        export_decl: &ConcreteExportDecl<'_>,
+        symbols: &HashMap<String, Export>,
+        diag: &mut DiagContext
+    ) {
+        for ident /*: &Identifier*/ in export_decl.exported_idents.iter() {
+            let name: String = ident.qual_name();
+            if let Some(item /*: &Export*/) = symbols.get(&name) {
+                if !self.add(name.clone(), item.clone()) {
+                    diag.diag(ident.source_range().left(), diag_data::err_duplicate_export_0)
+                        .add_arg(name)
+                        .emit();
+                }
+            } else {
+                diag.diag(ident.source_range().left(), diag_data::err_undefined_identifier_0)
+                    .add_arg(name)
+                    .emit();
+            }
+        }
+    }
+}
+
+/// Lower top-level `var` declarations of `program` to globals, allocating one global slot for
+/// each of them in declaration order. Returns names of the globals, which make up
+/// `CompiledProgram::globals`, and adds them to `symbols` so that they may be exported.
+/// Redefinitions of top-level items are reported to `diag`. Initializers are left to
+/// `CompiledProgram::init_proc`.
+#[cfg(feature = "compiler")]
+pub fn lower_top_level_vars(
+    program: &ConcreteProgram<'_>,
+    symbols: &mut HashMap<String, Export>,
+    diag: &mut DiagContext
+) -> Box<[String]> {
+    let mut globals: Vec<String> = Vec::new();
+    for decl /*: &ConcreteDecl*/ in program.decls.iter() {
+        if let ConcreteDecl::VarDecl(var_decl) = decl {
+            let name: String = var_decl.name.qual_name();
+            match symbols.entry(name.clone()) {
+                Entry::Occupied(_) => {
+                    diag.diag(var_decl.name.source_range().left(), diag_data::err_redefinition_of_0)
+                        .add_arg(name)
+                        .add_mark(var_decl.name.source_range().into())
+                        .emit();
+                },
+                Entry::Vacant(entry) => {
+                    entry.insert(Export::Global(globals.len()));
+                    globals.push(name);
+                }
+            }
+        }
+    }
+    globals.into_boxed_slice()
+}
+
+/// A compiled program, ready for execution
+///
+/// Once constructed, a `CompiledProgram` is never mutated by the executor, and
+/// all type information it refers to (`TyckInfo`s, VTs) lives as long as the program itself. So a
+/// program may be wrapped in an `Arc` and shared by several isolates running on different threads,
+/// see [`Isolate`](crate::vm::al31fm2::isolate::Isolate). Even `SAVE-CONST` does not write to the
+/// program: each VM instance copies the constant pool into its own constant slots, see
+/// [`Globals`](crate::vm::al31fm2::globals::Globals).
 pub struct CompiledProgram<A: Alloc> {
     pub slice_arena: SliceArena<8192, 8>,
 
     pub code: Box<[Insc]>,
     pub const_pool: Box<[Value]>,
-    pub init_proc: usize,
+    pub globals: Box<[String]>,
+    /// Function filling the constant slots and globals of a VM instance, run once on each instance
+    /// before any other function
+    pub init_proc: Option<usize>,
     pub functions: Box<[CompiledFunction]>,
+    pub exports: ExportTable,
+    pub methods: MethodTable,
+    pub properties: PropertyTable,
 
     pub ffi_funcs: Box<[&'static dyn FFIFunction<Combustor<A>>]>,
     #[cfg(feature = "async")]
-    pub async_ffi_funcs: Box<[&'static dyn FFIAsyncFunction<AL31F<A>, AsyncCombustor<A>>]>
+    pub async_ffi_funcs: Box<[&'static dyn FFIAsyncFunction<AL31F<A>, AsyncCombustor<A>>]>,
+
+    #[cfg(feature = "compiler")]
+    pub debug_info: Option<Box<DebugInfo>>
+}
+
+impl<A: Alloc> CompiledProgram<A> {
+    /// Whether function `func_id` may suspend its coroutine, by awaiting in its own code or in
+    /// functions it calls. Calls through function pointers, closures and overload tables are
+    /// assumed to suspend, since their callees are not known until runtime.
+    ///
+    /// This is checked before running script callbacks which must complete synchronously, like
+    /// the comparator of `VEC-SORT`.
+    pub fn may_await(&self, func_id: usize) -> bool {
+        let mut visited_funcs: HashSet<usize> = HashSet::new();
+        let mut pending_funcs: Vec<usize> = vec![func_id];
+        while let Some(func_id /*: usize*/) = pending_funcs.pop() {
+            if !visited_funcs.insert(func_id) {
+                continue;
+            }
+
+            let function: &CompiledFunction = &self.functions[func_id];
+            let mut visited: HashSet<usize> = HashSet::new();
+            let mut pending: Vec<usize> = vec![function.start_addr];
+            if let Some(exc_handlers) = &function.exc_handlers {
+                pending.extend(exc_handlers.iter()
+                    .map(|handler: &ExceptionHandlingBlock| handler.handler_addr));
+            }
+
+            while let Some(insc_ptr /*: usize*/) = pending.pop() {
+                if !visited.insert(insc_ptr) {
+                    continue;
+                }
+
+                match &self.code[insc_ptr] {
+                    #[cfg(feature = "async")]
+                    Insc::Await(..) => return true,
+                    #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+                    Insc::AwaitGroup(_) => return true,
+                    Insc::CallPtr(..) | Insc::TailCallPtr(..) | Insc::CallOverload(..) =>
+                        return true,
+                    Insc::Call(callee, _, _) => {
+                        pending_funcs.push(*callee);
+                        pending.push(insc_ptr + 1);
+                    },
+                    Insc::TailCall(callee, _) => pending_funcs.push(*callee),
+                    Insc::ReturnNothing
+                    | Insc::ReturnOne(_)
+                    | Insc::Return(_)
+                    | Insc::Raise(_) => {},
+                    Insc::Jump(dest) => pending.push(*dest),
+                    Insc::JumpIfTrue(_, dest)
+                    | Insc::JumpIfFalse(_, dest)
+                    | Insc::IterNext(_, _, dest) => {
+                        pending.push(*dest);
+                        pending.push(insc_ptr + 1);
+                    },
+                    _ => pending.push(insc_ptr + 1)
+                }
+            }
+        }
+        false
+    }
 }
+
+// `CompiledProgram` is immutable once constructed, see its document for the sharing model.
+// What stops the compiler from deriving `Send`/`Sync` are raw pointers:
+// - `NonNull<TyckInfo>`s in instructions, functions and member tables point into `TyckInfoPool`s
+//   whose entries are never moved nor mutated once created, and the pools outlive the program;
+// - `NonNull<GenericTypeVT>`s point to VTs which are read-only tables of plain data and `fn`
+//   pointers, allocated before and living as long as the program;
+// - values in the constant pool are never managed by any VM allocator, so no garbage collector
+//   running on another thread would mark or free them;
+// - FFI functions are either stateless binders or built from `Send + Sync` closures (see
+//   `ffi::property`, where the setters also keep a `TyckInfoPool` behind a `Mutex`), and are only
+//   called with a context of the calling thread.
+// So sharing these pointers among threads is fine as long as the allocator itself may be.
+unsafe impl<A: Alloc + Send> Send for CompiledProgram<A> {}
+unsafe impl<A: Alloc + Sync> Sync for CompiledProgram<A> {}
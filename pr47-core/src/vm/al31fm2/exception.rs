@@ -1,8 +1,15 @@
+use std::fmt::{Debug, Display, Formatter};
+
 use xjbutil::void::Void;
 
 use crate::data::exception::{CheckedException, ExceptionInner, StackTrace, UncheckedException};
 use crate::data::traits::{ChildrenType, StaticBase};
 
+#[cfg(feature = "compiler")] use crate::diag::location::SourceCoord;
+#[cfg(feature = "compiler")] use crate::diag::source::SourceManager;
+#[cfg(feature = "compiler")] use crate::vm::al31fm2::alloc::Alloc;
+#[cfg(feature = "compiler")] use crate::vm::al31fm2::compiled::{CompiledProgram, DebugInfo};
+
 pub struct Exception {
     pub inner: ExceptionInner,
     pub trace: Vec<StackTrace>
@@ -36,6 +43,70 @@ impl Exception {
     }
 }
 
+#[cfg(feature = "compiler")]
+impl Exception {
+    /// Render the stack trace of this exception into human-readable form, with function names
+    /// and source locations taken from the debug information of `program`. Frames without debug
+    /// information fall back to raw function IDs and instruction pointers.
+    pub fn render_trace<A: Alloc>(
+        &self,
+        program: &CompiledProgram<A>,
+        source_mgr: &SourceManager
+    ) -> String {
+        let debug_info: Option<&DebugInfo> = program.debug_info.as_deref();
+
+        let mut result: String = format!("uncaught exception: {:?}\n", self.inner);
+        for (i, trace) /*: (usize, &StackTrace)*/ in self.trace.iter().enumerate() {
+            // the innermost frame records the instruction next to the one raising the exception,
+            // while outer frames record the calling instructions
+            let insc_ptr: usize = if i == 0 {
+                trace.insc_ptr.saturating_sub(1)
+            } else {
+                trace.insc_ptr
+            };
+
+            result.push_str("    at ");
+            match debug_info.and_then(|debug_info| debug_info.func_name(trace.func_id)) {
+                Some(func_name) => result.push_str(func_name),
+                None => result.push_str(&format!("F.{}", trace.func_id))
+            }
+
+            match debug_info.and_then(|debug_info| debug_info.insc_loc(insc_ptr)) {
+                Some(loc /*: SourceLoc*/) => {
+                    let (_, coord): (&str, SourceCoord) = loc.compute_coord(source_mgr);
+                    result.push_str(&format!(
+                        " ({}:{}:{})\n",
+                        source_mgr.file_name(loc.file_id),
+                        coord.line + 1,
+                        coord.col + 1
+                    ));
+                },
+                None => result.push_str(&format!(" (L.{})\n", insc_ptr))
+            }
+        }
+        result
+    }
+}
+
+impl Display for Exception {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.inner)?;
+        for trace /*: &StackTrace*/ in self.trace.iter() {
+            write!(f, "\n    at F.{} (L.{})", trace.func_id, trace.insc_ptr)?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Exception {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Exception")
+            .field("inner", &self.inner)
+            .field("trace", &self.trace)
+            .finish()
+    }
+}
+
 impl StaticBase<Exception> for Void {
     fn type_name() -> String { "Exception".into() }
 
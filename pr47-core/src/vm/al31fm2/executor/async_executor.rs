@@ -10,7 +10,10 @@ use xjbutil::unchecked::UncheckedSendSync;
 use xjbutil::wide_ptr::WidePointer;
 
 use crate::builtins::closure::Closure;
+use crate::builtins::iter::{IterState, VMIterator};
+use crate::builtins::map::VMGenericMap;
 use crate::builtins::object::Object;
+use crate::builtins::set::VMGenericSet;
 use crate::builtins::vec::VMGenericVec;
 use crate::data::Value;
 use crate::data::exception::UncheckedException;
@@ -25,12 +28,25 @@ use crate::vm::al31fm2::exception::Exception;
 use crate::vm::al31fm2::executor::checked_bin_ops::*;
 use crate::vm::al31fm2::executor::checked_cast_ops::*;
 use crate::vm::al31fm2::executor::checked_unary_ops::*;
+use crate::vm::al31fm2::executor::iter::{iter_begin, iter_next_builtin};
+use crate::vm::al31fm2::executor::method::{
+    PropertyAccess,
+    object_field_get,
+    object_field_put,
+    resolve_method,
+    resolve_property
+};
 use crate::vm::al31fm2::executor::overload::call_overload;
 use crate::vm::al31fm2::executor::rtti::check_type;
 use crate::vm::al31fm2::executor::unwinding::*;
 use crate::vm::al31fm2::insc::Insc;
 use crate::vm::al31fm2::stack::{Stack, StackSlice};
 
+#[cfg(feature = "al31fm2-builtin-ops")] use std::cmp::Ordering;
+#[cfg(feature = "al31fm2-builtin-ops")] use std::mem::{replace, take};
+#[cfg(feature = "al31fm2-builtin-ops")] use crate::data::generic::GenericTypeVT;
+
+#[cfg(feature = "async")] use std::any::TypeId;
 #[cfg(feature = "async")] use std::hint::unreachable_unchecked;
 #[cfg(feature = "async")] use std::mem::transmute;
 #[cfg(feature = "async")] use futures::FutureExt;
@@ -41,9 +57,14 @@ use crate::vm::al31fm2::stack::{Stack, StackSlice};
 #[cfg(feature = "async")] use crate::ffi::async_fn::AsyncFunction as FFIAsyncFunction;
 #[cfg(feature = "async")] use crate::util::serializer::CoroutineContext;
 #[cfg(feature = "async")] use crate::vm::al31fm2::AsyncCombustor;
+#[cfg(feature = "async")] use crate::vm::al31fm2::globals::Globals;
+#[cfg(feature = "async")]
+use crate::vm::al31fm2::executor::coroutine_spawn::{TaskHandle, cancellable};
+#[cfg(feature = "async")] use crate::vm::al31fm2::executor::method::resolve_async_method;
 
+#[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))] use std::sync::Arc;
 #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
-use crate::vm::al31fm2::executor::coroutine_spawn::coroutine_spawn;
+use crate::vm::al31fm2::executor::coroutine_spawn::{TaskGroup, coroutine_spawn, task_group_join};
 
 include!("get_vm_makro.rs");
 include!("impl_makro.rs");
@@ -78,7 +99,9 @@ pub async fn create_vm_main_thread<A: Alloc>(
     program: &CompiledProgram<A>
 ) -> Box<VMThread<A>> {
     let mut ret = Box::new(VMThread {
-        vm: CoroutineContext::main_context(AL31F::new(alloc)).await,
+        vm: CoroutineContext::main_context(
+            AL31F::with_globals(alloc, Globals::for_program(program))
+        ).await,
         program: NonNull::from(program),
         stack: Stack::new(),
         _phantom: PhantomPinned
@@ -120,18 +143,29 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
     #[cfg(feature = "async")] cx: &mut Context<'_>,
     #[cfg(not(feature = "async"))] _cx: &mut Context<'_>
 ) -> Poll<Result<Vec<Value>, Exception>> {
+    // awaited promises are wrapped by `cancellable`, which checks for cancellation before polling
+    // the promise, and resolves into `TaskCancelled` once the task gets cancelled
     #[cfg(feature = "async")]
     if let Some(fut) = &mut this.awaiting_promise {
         if let Poll::Ready(promise_result) = fut.poll_unpin(cx) {
+            this.awaiting_promise = None;
+
             let insc: &Insc = &this.thread.program.as_ref().code[this.insc_ptr - 1];
             let mut value_dests: SmallVec<[*mut Value; 4]> = smallvec![];
-            if let Insc::Await(_, dests) = insc {
-                for i in 0..dests.len() {
-                    value_dests.push(this.slice.get_value_mut_ref(*dests.get_unchecked(i)));
-                }
-            } else {
-                unreachable_unchecked()
-            };
+            match insc {
+                Insc::Await(_, dests) => {
+                    for i in 0..dests.len() {
+                        value_dests.push(this.slice.get_value_mut_ref(*dests.get_unchecked(i)));
+                    }
+                },
+                #[cfg(feature = "al31fm2-builtin-ops")]
+                Insc::Spawn(_, _, dest) => {
+                    value_dests.push(this.slice.get_value_mut_ref(*dest));
+                },
+                #[cfg(feature = "al31fm2-builtin-ops")]
+                Insc::AwaitGroup(_) => {},
+                _ => unreachable_unchecked()
+            }
 
             if let Err(e) = promise_result.resolve(get_vm!(this.thread), &value_dests) {
                 match e {
@@ -159,6 +193,15 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
         }
     }
 
+    #[cfg(feature = "async")]
+    if this.thread.vm.is_cancelled() {
+        return Poll::Ready(Err(unchecked_exception_unwind_stack(
+            UncheckedException::TaskCancelled { task_id: this.thread.vm.task_id },
+            &mut this.thread.stack,
+            this.insc_ptr
+        )));
+    }
+
     let slice: &mut StackSlice = &mut this.slice;
     let thread: &mut VMThread<A> = this.thread;
     let program: &CompiledProgram<A> = thread.program.as_ref();
@@ -353,12 +396,20 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
             Insc::MakeNull(dst) =>
                 slice.set_value(*dst, Value::new_null()),
             Insc::LoadConst(const_id, dst) => {
-                let constant: Value = *thread.program.as_ref().const_pool.get_unchecked(*const_id);
+                let constant: Value = get_vm!(thread).globals.get_const_unchecked(*const_id);
                 slice.set_value(*dst, constant);
             }
             Insc::SaveConst(const_src, const_id) => {
                 let constant: Value = slice.get_value(*const_src);
-                *thread.program.as_mut().const_pool.get_unchecked_mut(*const_id) = constant;
+                get_vm!(thread).globals.set_const_unchecked(*const_id, constant);
+            }
+            Insc::LoadGlobal(global_id, dst) => {
+                let global: Value = get_vm!(thread).globals.get_unchecked(*global_id);
+                slice.set_value(*dst, global);
+            }
+            Insc::StoreGlobal(src, global_id) => {
+                let global: Value = slice.get_value(*src);
+                get_vm!(thread).globals.set_unchecked(*global_id, global);
             }
             Insc::CastFloatInt(src, dst) =>
                 impl_cast_op![slice, src, dst, f64, i64, float_value, new_int],
@@ -496,6 +547,55 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
                     }
                 }
             },
+            Insc::TailCall(func_id, args) => {
+                #[cfg(not(debug_assertions))]
+                let compiled: &CompiledFunction = program.functions.get_unchecked(*func_id);
+                #[cfg(debug_assertions)]
+                let compiled: &CompiledFunction = &program.functions[*func_id];
+
+                debug_assert_eq!(compiled.arg_count, args.len());
+                *slice = thread.stack.func_tail_call_reuse_stack(
+                    *func_id,
+                    compiled.stack_size,
+                    args
+                );
+                insc_ptr = compiled.start_addr;
+            },
+            Insc::TailCallPtr(func, args) => {
+                let func: Value = slice.get_value(*func);
+                if func.is_value() {
+                    let func_id: usize = func.vt_data.inner.int_value as usize;
+
+                    #[cfg(not(debug_assertions))]
+                    let compiled: &CompiledFunction = program.functions.get_unchecked(func_id);
+                    #[cfg(debug_assertions)]
+                    let compiled: &CompiledFunction = &program.functions[func_id];
+
+                    debug_assert_eq!(compiled.arg_count, args.len());
+                    *slice = thread.stack.func_tail_call_reuse_stack(
+                        func_id,
+                        compiled.stack_size,
+                        args
+                    );
+                    insc_ptr = compiled.start_addr;
+                } else {
+                    let closure: &Closure = &*(func.get_as_mut_ptr::<Closure>() as *const _);
+                    let func_id: usize = closure.func_id;
+
+                    #[cfg(not(debug_assertions))]
+                    let compiled: &CompiledFunction = program.functions.get_unchecked(func_id);
+                    #[cfg(debug_assertions)]
+                    let compiled: &CompiledFunction = &program.functions[func_id];
+
+                    *slice = thread.stack.closure_tail_call_reuse_stack(
+                        func_id,
+                        compiled.stack_size,
+                        &closure.captures,
+                        args
+                    );
+                    insc_ptr = compiled.start_addr;
+                }
+            },
             Insc::ReturnNothing => {
                 if let Some((prev_stack_slice, ret_addr)) =
                     thread.stack.done_func_call_shrink_stack0()
@@ -623,6 +723,155 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
                     }
                 }
             },
+            Insc::CallMethod(method_id, args, ret_value_locs) => {
+                let args_len: usize = args.len();
+                for i /*: usize*/ in 0..args_len {
+                    let arg_idx: usize = *args.get_unchecked(i);
+                    *ffi_args.get_unchecked_mut(i) = slice.get_value(arg_idx);
+                }
+
+                let ffi_func_id: usize =
+                    match resolve_method(program, *method_id, &ffi_args[0..args_len]) {
+                        Ok(ffi_func_id) => ffi_func_id,
+                        Err(e /*: UncheckedException*/) => {
+                            return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                                e, &mut thread.stack, insc_ptr
+                            )));
+                        }
+                    };
+                let ffi_function: &'static dyn FFIFunction<Combustor<A>>
+                    = program.ffi_funcs[ffi_func_id];
+
+                let ret_locs_len: usize = ret_value_locs.len();
+                for i /*: usize*/ in 0..ret_locs_len {
+                    let ret_value_loc_idx: usize = *ret_value_locs.get_unchecked(i);
+                    *ffi_rets.get_unchecked_mut(i) = slice.get_value_mut_ref(ret_value_loc_idx);
+                }
+
+                let mut combustor: Combustor<A> = Combustor::new(NonNull::from(get_vm!(thread)));
+
+                if let Err(e /*: FFIException*/) = ffi_function.call_rtlc(
+                    &mut combustor,
+                    &ffi_args[0..args_len],
+                    &ffi_rets[0..ret_locs_len]
+                ) {
+                    match e {
+                        FFIException::Checked(checked) => {
+                            let (new_slice, insc_ptr_next): (StackSlice, usize) =
+                                checked_exception_unwind_stack(
+                                    get_vm!(thread),
+                                    program,
+                                    checked,
+                                    &mut thread.stack,
+                                    insc_ptr
+                                )?;
+                            *slice = new_slice;
+                            insc_ptr = insc_ptr_next;
+                        },
+                        FFIException::Unchecked(unchecked) => {
+                            return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                                unchecked, &mut thread.stack, insc_ptr
+                            )));
+                        }
+                    }
+                }
+            },
+            Insc::GetProperty(property_id, src, dest) => {
+                let object: Value = slice.get_value(*src);
+                let result: Result<(), FFIException> =
+                    match resolve_property(program, *property_id, object) {
+                        Ok(PropertyAccess::Property(property)) => {
+                            let getter: &'static dyn FFIFunction<Combustor<A>>
+                                = program.ffi_funcs[property.getter];
+                            let mut combustor: Combustor<A> =
+                                Combustor::new(NonNull::from(get_vm!(thread)));
+                            getter.call_rtlc(
+                                &mut combustor,
+                                &[object],
+                                &[slice.get_value_mut_ref(*dest)]
+                            )
+                        },
+                        Ok(PropertyAccess::ObjectField(field)) => {
+                            object_field_get(object, field)
+                                .map(|value: Value| slice.set_value(*dest, value))
+                                .map_err(FFIException::Unchecked)
+                        },
+                        Err(e /*: UncheckedException*/) => Err(FFIException::Unchecked(e))
+                    };
+
+                if let Err(e /*: FFIException*/) = result {
+                    match e {
+                        FFIException::Checked(checked) => {
+                            let (new_slice, insc_ptr_next): (StackSlice, usize) =
+                                checked_exception_unwind_stack(
+                                    get_vm!(thread),
+                                    program,
+                                    checked,
+                                    &mut thread.stack,
+                                    insc_ptr
+                                )?;
+                            *slice = new_slice;
+                            insc_ptr = insc_ptr_next;
+                        },
+                        FFIException::Unchecked(unchecked) => {
+                            return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                                unchecked, &mut thread.stack, insc_ptr
+                            )));
+                        }
+                    }
+                }
+            },
+            Insc::SetProperty(property_id, dest, src) => {
+                let object: Value = slice.get_value(*dest);
+                let value: Value = slice.get_value(*src);
+                let result: Result<(), FFIException> =
+                    match resolve_property(program, *property_id, object) {
+                        Ok(PropertyAccess::Property(property)) => {
+                            if let Some(setter) = property.setter {
+                                let setter: &'static dyn FFIFunction<Combustor<A>>
+                                    = program.ffi_funcs[setter];
+                                let mut combustor: Combustor<A> =
+                                    Combustor::new(NonNull::from(get_vm!(thread)));
+                                setter.call_rtlc(&mut combustor, &[object, value], &[])
+                            } else {
+                                Err(FFIException::Unchecked(
+                                    UncheckedException::ReadOnlyProperty {
+                                        object,
+                                        property_id: *property_id
+                                    }
+                                ))
+                            }
+                        },
+                        Ok(PropertyAccess::ObjectField(field)) => {
+                            object_field_put(object, field, value)
+                                .map(|()| get_vm!(thread).alloc.mark_object(value))
+                                .map_err(FFIException::Unchecked)
+                        },
+                        Err(e /*: UncheckedException*/) => Err(FFIException::Unchecked(e))
+                    };
+
+                if let Err(e /*: FFIException*/) = result {
+                    match e {
+                        FFIException::Checked(checked) => {
+                            let (new_slice, insc_ptr_next): (StackSlice, usize) =
+                                checked_exception_unwind_stack(
+                                    get_vm!(thread),
+                                    program,
+                                    checked,
+                                    &mut thread.stack,
+                                    insc_ptr
+                                )?;
+                            *slice = new_slice;
+                            insc_ptr = insc_ptr_next;
+                        },
+                        FFIException::Unchecked(unchecked) => {
+                            return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                                unchecked, &mut thread.stack, insc_ptr
+                            )));
+                        }
+                    }
+                }
+            },
             #[cfg(feature = "async")]
             Insc::FFICallAsync(async_ffi_func_id, args, ret) => {
                 #[cfg(not(debug_assertions))]
@@ -673,6 +922,60 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
                 }
             },
             #[cfg(feature = "async")]
+            Insc::CallMethodAsync(method_id, args, ret) => {
+                let args_len: usize = args.len();
+                for i /*: usize*/ in 0..args_len {
+                    let arg_idx: usize = *args.get_unchecked(i);
+                    *ffi_args.get_unchecked_mut(i) = slice.get_value(arg_idx);
+                }
+
+                let async_ffi_func_id: usize =
+                    match resolve_async_method(program, *method_id, &ffi_args[0..args_len]) {
+                        Ok(async_ffi_func_id) => async_ffi_func_id,
+                        Err(e /*: UncheckedException*/) => {
+                            return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                                e, &mut thread.stack, insc_ptr
+                            )));
+                        }
+                    };
+                let async_ffi_function: &'static dyn FFIAsyncFunction<_, _>
+                    = program.async_ffi_funcs[async_ffi_func_id];
+
+                let combustor: AsyncCombustor<A> = AsyncCombustor::new(
+                    thread.vm.serializer.clone(),
+                    thread.program
+                );
+
+                match async_ffi_function.call_rtlc(&combustor, &ffi_args[0..args_len]) {
+                    Ok(promise /*: Promise*/) => {
+                        let promise: Value = Value::new_owned(promise);
+                        thread.vm.get_shared_data_mut().alloc.add_managed(promise);
+                        slice.set_value(*ret, promise);
+                    },
+                    Err(e /*: FFIException*/) => {
+                        match e {
+                            FFIException::Checked(checked) => {
+                                let (new_slice, insc_ptr_next): (StackSlice, usize) =
+                                    checked_exception_unwind_stack(
+                                        get_vm!(thread),
+                                        program,
+                                        checked,
+                                        &mut thread.stack,
+                                        insc_ptr
+                                    )?;
+                                *slice = new_slice;
+                                insc_ptr = insc_ptr_next;
+                            },
+                            FFIException::Unchecked(unchecked) => {
+                                return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                                    unchecked, &mut thread.stack, insc_ptr
+                                )));
+                            }
+                        }
+                    }
+                }
+            },
+            #[cfg(feature = "async")]
             Insc::Await(promise, _) => {
                 let promise: Value = slice.get_value(*promise);
                 let wrapper: *mut Wrapper<()> = promise.ptr_repr.ptr as *mut Wrapper<()>;
@@ -684,8 +987,16 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
                     )));
                 }
 
-                let Promise(fut) = promise.move_out::<Promise<AL31F<A>>>();
+                let promise: Promise<AL31F<A>> = if (*promise.get_as_dyn_base()).dyn_type_id()
+                    == TypeId::of::<TaskHandle<A>>()
+                {
+                    promise.move_out::<TaskHandle<A>>().promise
+                } else {
+                    promise.move_out::<Promise<AL31F<A>>>()
+                };
                 (*wrapper).ownership_info = OwnershipInfo::MovedToRust as u8;
+                let Promise(fut) =
+                    cancellable(thread.vm.task_id, thread.vm.cancel_token(), promise);
 
                 this.insc_ptr = insc_ptr;
 
@@ -695,10 +1006,78 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
                 return Poll::Pending;
             },
             #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
-            Insc::Spawn(func, args) => {
+            Insc::Spawn(func, args, _) => {
                 let Promise(fut) = coroutine_spawn(thread, slice, *func, args);
                 this.awaiting_promise = Some(fut);
-                this.insc_ptr = insc_ptr + 1;
+                this.insc_ptr = insc_ptr;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            },
+            #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+            Insc::Cancel(task_handle) => {
+                let task_handle: Value = slice.get_value(*task_handle);
+                let wrapper: *mut Wrapper<()> = task_handle.ptr_repr.ptr as *mut Wrapper<()>;
+                if (*wrapper).ownership_info != OwnershipInfo::MovedToRust as u8 {
+                    let task_handle: *mut TaskHandle<A> =
+                        task_handle.get_as_mut_ptr_norm::<TaskHandle<A>>();
+                    thread.vm.cancel_task((*task_handle).task_id);
+                }
+            },
+            #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+            Insc::CreateTaskGroup(dest) => {
+                let (group_id, handle): (u32, Arc<()>) = thread.vm.create_task_group();
+                let task_group: TaskGroup<A> = TaskGroup {
+                    group_id,
+                    tasks: Vec::new(),
+                    handle
+                };
+                let task_group: Value = Value::new_owned(task_group);
+                get_vm!(thread).alloc.add_managed(task_group);
+                slice.set_value(*dest, task_group);
+            },
+            #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+            Insc::GroupAdd(task_group, task_handle) => {
+                let task_handle: Value = slice.get_value(*task_handle);
+                let wrapper: *mut Wrapper<()> = task_handle.ptr_repr.ptr as *mut Wrapper<()>;
+                if (*wrapper).ownership_info == OwnershipInfo::MovedToRust as u8 {
+                    return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                        UncheckedException::AlreadyAwaited { promise: task_handle },
+                        &mut thread.stack,
+                        insc_ptr
+                    )));
+                }
+
+                let task_handle: TaskHandle<A> = task_handle.move_out::<TaskHandle<A>>();
+                (*wrapper).ownership_info = OwnershipInfo::MovedToRust as u8;
+
+                let task_group: *mut TaskGroup<A> =
+                    slice.get_value(*task_group).get_as_mut_ptr_norm::<TaskGroup<A>>();
+                thread.vm.add_to_task_group((*task_group).group_id, task_handle.task_id);
+                (*task_group).tasks.push(task_handle);
+            },
+            #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+            Insc::AwaitGroup(task_group) => {
+                let task_group: Value = slice.get_value(*task_group);
+                let wrapper: *mut Wrapper<()> = task_group.ptr_repr.ptr as *mut Wrapper<()>;
+                if (*wrapper).ownership_info == OwnershipInfo::MovedToRust as u8 {
+                    return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                        UncheckedException::AlreadyAwaited { promise: task_group },
+                        &mut thread.stack,
+                        insc_ptr
+                    )));
+                }
+
+                let task_group: TaskGroup<A> = task_group.move_out::<TaskGroup<A>>();
+                (*wrapper).ownership_info = OwnershipInfo::MovedToRust as u8;
+                let promise: Promise<AL31F<A>> =
+                    task_group_join(thread.vm.serializer.clone(), task_group);
+                let Promise(fut) =
+                    cancellable(thread.vm.task_id, thread.vm.cancel_token(), promise);
+
+                this.insc_ptr = insc_ptr;
+
+                let thread: &'static VMThread<A> = transmute::<_, _>(thread);
+                this.awaiting_promise = Some(Box::pin(thread.vm.co_await(fut)));
                 cx.waker().wake_by_ref();
                 return Poll::Pending;
             },
@@ -731,6 +1110,67 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
             Insc::Jump(dest) => {
                 insc_ptr = *dest;
             },
+            Insc::IterBegin(src, dest) => {
+                let iterated: Value = slice.get_value(*src);
+                match iter_begin(program, iterated) {
+                    Ok(iter /*: VMIterator*/) => {
+                        let iter: Value = Value::new_owned(iter);
+                        get_vm!(thread).alloc.add_managed(iter);
+                        slice.set_value(*dest, iter);
+                    },
+                    Err(e /*: UncheckedException*/) => {
+                        return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                            e, &mut thread.stack, insc_ptr
+                        )));
+                    }
+                }
+            },
+            Insc::IterNext(iter, dest, end_addr) => {
+                let iter: &mut VMIterator = &mut *slice.get_value(*iter).get_as_mut_ptr_norm();
+                let result: Result<Option<Value>, FFIException> =
+                    if let IterState::Host(ffi_func_id) = iter.state {
+                        let ffi_function: &'static dyn FFIFunction<Combustor<A>>
+                            = program.ffi_funcs[ffi_func_id];
+                        let mut has_elem: Value = Value::new_null();
+                        let mut elem: Value = Value::new_null();
+                        let mut combustor: Combustor<A> =
+                            Combustor::new(NonNull::from(get_vm!(thread)));
+                        ffi_function.call_rtlc(
+                            &mut combustor,
+                            &[iter.iterated, Value::new_int(iter.index as i64)],
+                            &[&mut has_elem as *mut Value, &mut elem as *mut Value]
+                        ).map(|()| if has_elem.vt_data.inner.bool_value {
+                            iter.index += 1;
+                            Some(elem)
+                        } else {
+                            None
+                        })
+                    } else {
+                        Ok(iter_next_builtin(&mut get_vm!(thread).alloc, iter))
+                    };
+
+                match result {
+                    Ok(Some(elem /*: Value*/)) => slice.set_value(*dest, elem),
+                    Ok(None) => insc_ptr = *end_addr,
+                    Err(FFIException::Checked(checked)) => {
+                        let (new_slice, insc_ptr_next): (StackSlice, usize) =
+                            checked_exception_unwind_stack(
+                                get_vm!(thread),
+                                program,
+                                checked,
+                                &mut thread.stack,
+                                insc_ptr
+                            )?;
+                        *slice = new_slice;
+                        insc_ptr = insc_ptr_next;
+                    },
+                    Err(FFIException::Unchecked(unchecked)) => {
+                        return Poll::Ready(Err(unchecked_exception_unwind_stack(
+                            unchecked, &mut thread.stack, insc_ptr
+                        )));
+                    }
+                }
+            },
             Insc::CreateContainer(ctor, vt, dest) => {
                 let container: Value = Value::new_container(ctor(), vt.as_ref());
                 get_vm!(thread).alloc.add_managed(container);
@@ -816,6 +1256,239 @@ unsafe fn poll_unsafe<'a, A: Alloc, const S: bool>(
                 let vec: &VMGenericVec = &*(vec_value.get_as_mut_ptr() as *const _);
                 slice.set_value(*dst, Value::new_int(vec.inner.len() as i64));
             },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecPop(src, dst) => {
+                let vec_value: Value = slice.get_value(*src);
+                let vec: &mut VMGenericVec = &mut *(vec_value.get_as_mut_ptr());
+                if let Some(data) = vec.inner.pop() {
+                    slice.set_value(*dst, data);
+                } else {
+                    return Poll::Ready(Err(
+                        unchecked_exception_unwind_stack(
+                            UncheckedException::IndexOutOfBounds { indexed: vec_value, index: -1 },
+                            &mut thread.stack,
+                            insc_ptr
+                        )
+                    ));
+                }
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecInsert(src, index, value) => {
+                let vec_value: Value = slice.get_value(*src);
+                let vec: &mut VMGenericVec = &mut *(vec_value.get_as_mut_ptr());
+                let index: i64 = slice.get_value(*index).vt_data.inner.int_value;
+                if index >= 0 && index as usize <= vec.inner.len() {
+                    let value: Value = slice.get_value(*value);
+                    get_vm!(thread).alloc.mark_object(value);
+                    vec.inner.insert(index as usize, value);
+                } else {
+                    return Poll::Ready(Err(
+                        unchecked_exception_unwind_stack(
+                            UncheckedException::IndexOutOfBounds { indexed: vec_value, index },
+                            &mut thread.stack,
+                            insc_ptr
+                        )
+                    ));
+                }
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecRemove(src, index, dst) => {
+                let vec_value: Value = slice.get_value(*src);
+                let vec: &mut VMGenericVec = &mut *(vec_value.get_as_mut_ptr());
+                let index: i64 = slice.get_value(*index).vt_data.inner.int_value;
+                if index >= 0 && (index as usize) < vec.inner.len() {
+                    slice.set_value(*dst, vec.inner.remove(index as usize));
+                } else {
+                    return Poll::Ready(Err(
+                        unchecked_exception_unwind_stack(
+                            UncheckedException::IndexOutOfBounds { indexed: vec_value, index },
+                            &mut thread.stack,
+                            insc_ptr
+                        )
+                    ));
+                }
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecTruncate(src, len) => {
+                let vec_value: Value = slice.get_value(*src);
+                let vec: &mut VMGenericVec = &mut *(vec_value.get_as_mut_ptr());
+                let len: i64 = slice.get_value(*len).vt_data.inner.int_value;
+                if len >= 0 {
+                    vec.inner.truncate(len as usize);
+                } else {
+                    return Poll::Ready(Err(
+                        unchecked_exception_unwind_stack(
+                            UncheckedException::IndexOutOfBounds { indexed: vec_value, index: len },
+                            &mut thread.stack,
+                            insc_ptr
+                        )
+                    ));
+                }
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecClear(src) => {
+                let vec: &mut VMGenericVec = &mut *(slice.get_value(*src).get_as_mut_ptr());
+                vec.inner.clear();
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecExtend(src, other) => {
+                let vec: &mut VMGenericVec = &mut *(slice.get_value(*src).get_as_mut_ptr());
+                let other: &VMGenericVec =
+                    &*(slice.get_value(*other).get_as_mut_ptr() as *const _);
+                // `other` may be the same vector as `vec`, so elements are copied out first
+                let data: Vec<Value> = other.inner.clone();
+                for &data /*: Value*/ in data.iter() {
+                    get_vm!(thread).alloc.mark_object(data);
+                }
+                vec.inner.extend_from_slice(&data);
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecSlice(src, start, end, dst) => {
+                let vec_value: Value = slice.get_value(*src);
+                let vec: &VMGenericVec = &*(vec_value.get_as_mut_ptr() as *const _);
+                let start: i64 = slice.get_value(*start).vt_data.inner.int_value;
+                let end: i64 = slice.get_value(*end).vt_data.inner.int_value;
+                if start < 0 || start > end || end as usize > vec.inner.len() {
+                    let index: i64 = if start < 0 || start > end { start } else { end };
+                    return Poll::Ready(Err(
+                        unchecked_exception_unwind_stack(
+                            UncheckedException::IndexOutOfBounds { indexed: vec_value, index },
+                            &mut thread.stack,
+                            insc_ptr
+                        )
+                    ));
+                }
+
+                let sliced: VMGenericVec =
+                    VMGenericVec::from_values(vec.inner[start as usize..end as usize].to_vec());
+                let sliced: Value = if vec_value.is_container() {
+                    Value::new_container(
+                        move_to_heap(Wrapper::new_owned(sliced)).as_ptr() as *mut _,
+                        vec_value.ptr_repr.trivia as *const GenericTypeVT
+                    )
+                } else {
+                    Value::new_owned(sliced)
+                };
+                get_vm!(thread).alloc.add_managed(sliced);
+                slice.set_value(*dst, sliced);
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecReverse(src) => {
+                let vec: &mut VMGenericVec = &mut *(slice.get_value(*src).get_as_mut_ptr());
+                vec.inner.reverse();
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecContains(src, data, dst) => {
+                let vec: &VMGenericVec = &*(slice.get_value(*src).get_as_mut_ptr() as *const _);
+                let data: Value = slice.get_value(*data);
+                let contains: bool = vec.inner.iter()
+                    .any(|elem: &Value| elem.ptr_repr == data.ptr_repr);
+                slice.set_value(*dst, Value::new_bool(contains));
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecSort(src, cmp) => {
+                let vec: &mut VMGenericVec = &mut *(slice.get_value(*src).get_as_mut_ptr());
+                let cmp: Value = slice.get_value(*cmp);
+                if let Err(e) = vec_sort_by::<A, S>(thread, vec, cmp) {
+                    let (new_slice, insc_ptr_next): (StackSlice, usize) =
+                        callback_exception_unwind_stack(
+                            get_vm!(thread),
+                            program,
+                            e,
+                            &mut thread.stack,
+                            insc_ptr
+                        )?;
+                    *slice = new_slice;
+                    insc_ptr = insc_ptr_next;
+                }
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetInsert(src, data, dst) => {
+                let set: &mut VMGenericSet = &mut *(slice.get_value(*src).get_as_mut_ptr());
+                let data: Value = slice.get_value(*data);
+                let inserted: bool = set.insert(data);
+                if inserted {
+                    get_vm!(thread).alloc.mark_object(data);
+                }
+                slice.set_value(*dst, Value::new_bool(inserted));
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetRemove(src, data, dst) => {
+                let set: &mut VMGenericSet = &mut *(slice.get_value(*src).get_as_mut_ptr());
+                let removed: bool = set.remove(slice.get_value(*data));
+                slice.set_value(*dst, Value::new_bool(removed));
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetContains(src, data, dst) => {
+                let set: &VMGenericSet = &*(slice.get_value(*src).get_as_mut_ptr() as *const _);
+                let contains: bool = set.contains(slice.get_value(*data));
+                slice.set_value(*dst, Value::new_bool(contains));
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetLen(src, dst) => {
+                let set: &VMGenericSet = &*(slice.get_value(*src).get_as_mut_ptr() as *const _);
+                slice.set_value(*dst, Value::new_int(set.len() as i64));
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetElem(src, index, dst) => {
+                let set_value: Value = slice.get_value(*src);
+                let set: &VMGenericSet = &*(set_value.get_as_mut_ptr() as *const _);
+                let index: i64 = slice.get_value(*index).vt_data.inner.int_value;
+                if let Some(data) = set.get(index as usize) {
+                    slice.set_value(*dst, data);
+                } else {
+                    return Poll::Ready(Err(
+                        unchecked_exception_unwind_stack(
+                            UncheckedException::IndexOutOfBounds { indexed: set_value, index },
+                            &mut thread.stack,
+                            insc_ptr
+                        )
+                    ));
+                }
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapGet(src, key, dst) => {
+                let map: &VMGenericMap = &*(slice.get_value(*src).get_as_mut_ptr() as *const _);
+                let value: Value = map.get(slice.get_value(*key)).unwrap_or(Value::new_null());
+                slice.set_value(*dst, value);
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapPut(src, key, value) => {
+                let map: &mut VMGenericMap = &mut *(slice.get_value(*src).get_as_mut_ptr());
+                let key: Value = slice.get_value(*key);
+                let value: Value = slice.get_value(*value);
+                get_vm!(thread).alloc.mark_object(key);
+                get_vm!(thread).alloc.mark_object(value);
+                map.put(key, value);
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapRemove(src, key, dst) => {
+                let map: &mut VMGenericMap = &mut *(slice.get_value(*src).get_as_mut_ptr());
+                let removed: bool = map.remove(slice.get_value(*key)).is_some();
+                slice.set_value(*dst, Value::new_bool(removed));
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapContains(src, key, dst) => {
+                let map: &VMGenericMap = &*(slice.get_value(*src).get_as_mut_ptr() as *const _);
+                let contains: bool = map.contains_key(slice.get_value(*key));
+                slice.set_value(*dst, Value::new_bool(contains));
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapLen(src, dst) => {
+                let map: &VMGenericMap = &*(slice.get_value(*src).get_as_mut_ptr() as *const _);
+                slice.set_value(*dst, Value::new_int(map.len() as i64));
+            },
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapKeys(src, vt, dst) => {
+                let map: &VMGenericMap = &*(slice.get_value(*src).get_as_mut_ptr() as *const _);
+                let keys: VMGenericVec = VMGenericVec::from_values(map.keys().to_vec());
+                let keys: Value = Value::new_container(
+                    move_to_heap(Wrapper::new_owned(keys)).as_ptr() as *mut _,
+                    vt.as_ref()
+                );
+                get_vm!(thread).alloc.add_managed(keys);
+                slice.set_value(*dst, keys);
+            },
 
             #[cfg(feature = "al31fm2-builtin-ops")]
             Insc::StrClone(src, dest) => {
@@ -890,9 +1563,15 @@ impl<'a, A: Alloc, const S: bool> Future for VMThreadRunFunctionFut<'a, A, S> {
     type Output = UncheckedSendSync<Result<Vec<Value>, Exception>>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this: &mut Self = Pin::into_inner(self);
         unsafe {
-            match poll_unsafe(Pin::into_inner(self), cx) {
-                Poll::Ready(r) => Poll::Ready(UncheckedSendSync::new(r)),
+            match poll_unsafe(this, cx) {
+                Poll::Ready(r) => {
+                    // return values are already copied out, and a function returning normally
+                    // leaves its frame on the stack. Reset so that the thread could be reused.
+                    this.thread.stack.reset();
+                    Poll::Ready(UncheckedSendSync::new(r))
+                },
                 Poll::Pending => Poll::Pending
             }
         }
@@ -904,8 +1583,6 @@ pub unsafe fn vm_thread_run_function<'a, A: Alloc, const S: bool>(
 ) -> Result<VMThreadRunFunctionFut<'a, A, S>, Exception> {
     let (thread, func_id, args) = arg_pack.into_inner();
 
-    get_vm!(thread).alloc.set_gc_allowed(true);
-
     let program: &CompiledProgram<A> = thread.program.as_ref();
     let compiled_function: &CompiledFunction = &program.functions[func_id];
     if compiled_function.arg_count != args.len() {
@@ -918,6 +1595,8 @@ pub unsafe fn vm_thread_run_function<'a, A: Alloc, const S: bool>(
     let slice: StackSlice =
         thread.stack.ext_func_call_grow_stack(func_id, compiled_function.stack_size, args);
     let insc_ptr: usize = compiled_function.start_addr;
+    // `args` may be only reachable from the caller until now
+    get_vm!(thread).alloc.set_gc_allowed(true);
 
     Ok(VMThreadRunFunctionFut {
         thread,
@@ -927,3 +1606,137 @@ pub unsafe fn vm_thread_run_function<'a, A: Alloc, const S: bool>(
         #[cfg(feature = "async")] awaiting_promise: None
     })
 }
+
+/// Run the initialization procedure of the program on `thread`, if there is one. This should be
+/// done once for each VM instance, before running any other function on it.
+pub unsafe fn vm_thread_run_init<'a, A: Alloc>(
+    thread: &'a mut VMThread<A>
+) -> impl Future<Output=Result<(), Exception>> + Send + 'a {
+    let init_proc: Option<usize> = thread.program.as_ref().init_proc;
+    let fut: UncheckedSendSync<Option<Result<VMThreadRunFunctionFut<'a, A, false>, Exception>>> =
+        UncheckedSendSync::new(init_proc.map(move |init_proc: usize| {
+            let arg_pack: (&'a mut VMThread<A>, usize, &[Value]) = (thread, init_proc, &[]);
+            vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+        }));
+
+    async move {
+        let fut: Option<VMThreadRunFunctionFut<'a, A, false>> = fut.into_inner().transpose()?;
+        if let Some(fut /*: VMThreadRunFunctionFut<'a, A, false>*/) = fut {
+            fut.await.into_inner()?;
+        }
+        Ok(())
+    }
+}
+
+/// Run function value `func`, which is either a function id or a closure, to completion with
+/// `args` on `thread`, while `thread` is in the middle of running some other function. This is
+/// used for calling script callbacks from builtin operations.
+///
+/// The callback runs on a fresh stack, with the stack of the interrupted function registered to
+/// the allocator meanwhile. The callback is polled synchronously, so callers must reject callbacks
+/// which [may await](CompiledProgram::may_await) beforehand.
+#[cfg(feature = "al31fm2-builtin-ops")]
+unsafe fn call_func_value_sync<A: Alloc, const S: bool>(
+    thread: &mut VMThread<A>,
+    func: Value,
+    args: &[Value]
+) -> Result<Vec<Value>, Exception> {
+    let (func_id, frame_args): (usize, SmallVec<[Value; 4]>) = if func.is_value() {
+        (func_value_id(func), SmallVec::from_slice(args))
+    } else {
+        let closure: &Closure = &*(func.get_as_mut_ptr::<Closure>() as *const _);
+        let mut frame_args: SmallVec<[Value; 4]> = SmallVec::from_slice(&closure.captures);
+        frame_args.extend_from_slice(args);
+        (closure.func_id, frame_args)
+    };
+
+    let program: &CompiledProgram<A> = thread.program.as_ref();
+    let compiled: &CompiledFunction = &program.functions[func_id];
+
+    let caller_stack: Stack = replace(&mut thread.stack, Stack::new());
+    get_vm!(thread).alloc.add_stack(&caller_stack);
+
+    let slice: StackSlice =
+        thread.stack.ext_func_call_grow_stack(func_id, compiled.stack_size, &frame_args);
+    let fut: VMThreadRunFunctionFut<'_, A, S> = VMThreadRunFunctionFut {
+        thread: &mut *thread,
+        slice,
+        insc_ptr: compiled.start_addr,
+
+        #[cfg(feature = "async")] awaiting_promise: None
+    };
+    let result: Result<Vec<Value>, Exception> = pollster::block_on(fut).into_inner();
+
+    get_vm!(thread).alloc.remove_stack(&caller_stack);
+    thread.stack = caller_stack;
+    result
+}
+
+/// Function ID of function value `func`, which is either a function id or a closure
+#[cfg(feature = "al31fm2-builtin-ops")]
+unsafe fn func_value_id(func: Value) -> usize {
+    if func.is_value() {
+        func.vt_data.inner.int_value as usize
+    } else {
+        (*(func.get_as_mut_ptr::<Closure>() as *const Closure)).func_id
+    }
+}
+
+/// Stable sort `vec` with script comparator `cmp`. Elements are moved out of `vec` and pinned
+/// while sorting, so `vec` appears empty to the comparator. Sorting stops comparing once the
+/// comparator throws, leaving elements in unspecified order.
+///
+/// Comparators which may await or do not return exactly one value are rejected before sorting.
+#[cfg(feature = "al31fm2-builtin-ops")]
+unsafe fn vec_sort_by<A: Alloc, const S: bool>(
+    thread: &mut VMThread<A>,
+    vec: &mut VMGenericVec,
+    cmp: Value
+) -> Result<(), Exception> {
+    let func_id: usize = func_value_id(cmp);
+    let program: &CompiledProgram<A> = thread.program.as_ref();
+    let ret_count: usize = program.functions[func_id].ret_count;
+    if ret_count != 1 {
+        let exception: UncheckedException = UncheckedException::RetCountMismatch {
+            func_id, expected: 1, got: ret_count
+        };
+        return Err(Exception::unchecked_exc(exception));
+    }
+    if program.may_await(func_id) {
+        return Err(Exception::unchecked_exc(UncheckedException::AsyncCallback { func_id }));
+    }
+
+    let mut elements: Vec<Value> = take(&mut vec.inner);
+    let pinned: *mut bool = get_vm!(thread).alloc.pin_objects(&elements);
+
+    let mut exception: Option<Exception> = None;
+    elements.sort_by(|lhs: &Value, rhs: &Value| {
+        if exception.is_some() {
+            return Ordering::Equal;
+        }
+
+        match call_func_value_sync::<A, S>(thread, cmp, &[*lhs, *rhs]) {
+            Ok(rets) if rets.len() == 1 => rets[0].vt_data.inner.int_value.cmp(&0),
+            Ok(rets) => {
+                exception = Some(Exception::unchecked_exc(UncheckedException::RetCountMismatch {
+                    func_id, expected: 1, got: rets.len()
+                }));
+                Ordering::Equal
+            },
+            Err(e) => {
+                exception = Some(e);
+                Ordering::Equal
+            }
+        }
+    });
+
+    if !pinned.is_null() {
+        *pinned = false;
+    }
+    vec.inner = elements;
+
+    match exception {
+        Some(e) => Err(e),
+        None => Ok(())
+    }
+}
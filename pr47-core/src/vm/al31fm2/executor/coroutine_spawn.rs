@@ -1,11 +1,19 @@
+use std::future::Future;
 use std::mem::transmute;
+use std::pin::Pin;
 use std::ptr::NonNull;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 
+use futures::future::select_all;
 use xjbutil::unchecked::{UncheckedSendFut, UncheckedSendSync};
+use xjbutil::void::Void;
 
 use crate::data::exception::{ExceptionInner, UncheckedException};
 use crate::data::Value;
-use crate::ffi::async_fn::{AsyncReturnType, Promise};
+use crate::data::traits::StaticBase;
+use crate::ffi::async_fn::{AsyncReturnType, Promise, PromiseResult};
+use crate::util::serializer::{CancelToken, CoroutineSharedData, Serializer};
 use crate::vm::al31fm2::AL31F;
 use crate::vm::al31fm2::alloc::Alloc;
 use crate::vm::al31fm2::compiled::CompiledProgram;
@@ -18,8 +26,168 @@ use crate::vm::al31fm2::stack::StackSlice;
 #[cfg(feature = "async-astd")] use async_std::task::JoinHandle;
 #[cfg(feature = "async-tokio")] use futures::TryFutureExt;
 #[cfg(feature = "async-tokio")] use tokio::task::{JoinError, JoinHandle};
+#[cfg(feature = "async-sim")] use std::convert::Infallible as JoinError;
+#[cfg(feature = "async-sim")] use crate::util::sim_runtime::task::JoinHandle;
 use crate::ffi::sync_fn::VMContext;
 
+/// Handle of a coroutine started by `Insc::Spawn`
+///
+/// Awaiting the handle yields the return values of the spawned function, or re-raises the exception
+/// thrown by it. The handle can also be used to request cancellation of the spawned coroutine.
+pub struct TaskHandle<A: Alloc> {
+    pub task_id: u32,
+    pub promise: Promise<AL31F<A>>
+}
+
+impl<A: Alloc> StaticBase<TaskHandle<A>> for Void {
+    fn type_name() -> String {
+        "task".to_string()
+    }
+}
+
+/// Group of coroutines created by `Insc::CreateTaskGroup`
+///
+/// Awaiting the group waits for all the coroutines in it. Once any of them fails, the rest get
+/// cancelled, and the first exception is re-raised after all of them have stopped.
+pub struct TaskGroup<A: Alloc> {
+    pub group_id: u32,
+    pub tasks: Vec<TaskHandle<A>>,
+    /// Keeps the group bookkeeping alive, see `CoroutineSharedData::create_task_group`
+    pub handle: Arc<()>
+}
+
+impl<A: Alloc> StaticBase<TaskGroup<A>> for Void {
+    fn type_name() -> String {
+        "task_group".to_string()
+    }
+}
+
+/// Make awaiting `promise` stop once the awaiting task gets cancelled
+///
+/// The cancel token is checked before each poll of `promise`, and the task gets woken on
+/// cancellation even if `promise` never completes (e.g. a long sleep or a channel never written).
+/// The pending `promise` then gets carried to `AsyncReturnType::resolve`, and dropped there with
+/// the running permit held, since it may hold guards modifying VM objects on drop.
+pub fn cancellable<A: Alloc>(
+    task_id: u32,
+    cancel_token: Arc<CancelToken>,
+    promise: Promise<AL31F<A>>
+) -> Promise<AL31F<A>> {
+    type PromiseFuture<A> = Pin<Box<dyn Future<Output=PromiseResult<AL31F<A>>> + Send>>;
+
+    struct Cancelled<A: Alloc> {
+        task_id: u32,
+        abandoned: PromiseFuture<A>
+    }
+
+    impl<A: Alloc> AsyncReturnType<AL31F<A>> for Cancelled<A> {
+        fn is_err(&self) -> bool {
+            true
+        }
+
+        fn resolve(
+            self: Box<Self>,
+            _locked_ctx: &mut AL31F<A>,
+            _dests: &[*mut Value]
+        ) -> Result<usize, ExceptionInner> {
+            let Cancelled { task_id, abandoned } = *self;
+            drop(abandoned);
+            Err(ExceptionInner::Unchecked(UncheckedException::TaskCancelled { task_id }))
+        }
+    }
+
+    // the abandoned future is never polled again, it only gets moved around and then dropped
+    unsafe impl<A: Alloc> Sync for Cancelled<A> {}
+
+    struct CancellableFut<A: Alloc> {
+        task_id: u32,
+        cancel_token: Arc<CancelToken>,
+        promise: Option<PromiseFuture<A>>
+    }
+
+    impl<A: Alloc> Future for CancellableFut<A> {
+        type Output = PromiseResult<AL31F<A>>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            self.cancel_token.register(cx.waker());
+            if !self.cancel_token.is_cancelled() {
+                let promise: &mut PromiseFuture<A> = self.promise.as_mut().unwrap();
+                if let Poll::Ready(result) = promise.as_mut().poll(cx) {
+                    return Poll::Ready(result);
+                }
+                if !self.cancel_token.is_cancelled() {
+                    return Poll::Pending;
+                }
+            }
+
+            Poll::Ready(Box::new(Cancelled {
+                task_id: self.task_id,
+                abandoned: self.promise.take().unwrap()
+            }))
+        }
+    }
+
+    let Promise(promise) = promise;
+    Promise(Box::pin(CancellableFut { task_id, cancel_token, promise: Some(promise) }))
+}
+
+pub fn task_group_join<A: Alloc>(
+    serializer: Serializer<(CoroutineSharedData, AL31F<A>)>,
+    task_group: TaskGroup<A>
+) -> Promise<AL31F<A>> {
+    struct AsyncRet<A: Alloc> {
+        /// Results of all tasks in the group. Results are dropped in `resolve`, since dropping
+        /// them touches VM data and requires the running permit.
+        results: Vec<PromiseResult<AL31F<A>>>,
+        first_err: Option<usize>
+    }
+
+    impl<A: Alloc> AsyncReturnType<AL31F<A>> for AsyncRet<A> {
+        fn is_err(&self) -> bool {
+            self.first_err.is_some()
+        }
+
+        fn resolve(
+            mut self: Box<Self>,
+            locked_ctx: &mut AL31F<A>,
+            _dests: &[*mut Value]
+        ) -> Result<usize, ExceptionInner> {
+            if let Some(first_err /*: usize*/) = self.first_err {
+                let result: PromiseResult<AL31F<A>> = self.results.swap_remove(first_err);
+                result.resolve(locked_ctx, &[]).map(|_| 0)
+            } else {
+                Ok(0)
+            }
+        }
+    }
+
+    let TaskGroup { group_id, tasks, .. } = task_group;
+    let mut futs: Vec<Pin<Box<dyn Future<Output=PromiseResult<AL31F<A>>> + Send>>> =
+        tasks.into_iter()
+            .map(|task_handle: TaskHandle<A>| task_handle.promise.0)
+            .collect();
+
+    // task results refer to VM data, but they are only dropped or resolved with the run permit
+    let fut = unsafe { UncheckedSendFut::new(async move {
+        let mut results: Vec<PromiseResult<AL31F<A>>> = Vec::with_capacity(futs.len());
+        let mut first_err: Option<usize> = None;
+        while !futs.is_empty() {
+            let (result, _idx, rest) = select_all(futs).await;
+            futs = rest;
+            if result.is_err() && first_err.is_none() {
+                first_err = Some(results.len());
+                serializer.lock().await.0.cancel_task_group(group_id);
+            }
+            results.push(result);
+        }
+        serializer.lock().await.0.remove_task_group(group_id);
+
+        Box::new(AsyncRet { results, first_err }) as Box<dyn AsyncReturnType<AL31F<A>>>
+    }) };
+
+    Promise(Box::pin(fut))
+}
+
 #[inline(never)]
 pub unsafe fn coroutine_spawn<A: Alloc>(
     thread: &mut VMThread<A>,
@@ -91,12 +259,12 @@ pub unsafe fn coroutine_spawn<A: Alloc>(
     unsafe impl Sync for AsyncRet {}
 
     pub struct AsyncRet2<A: Alloc> {
-        result: Result<Promise<AL31F<A>>, JoinError>
+        result: Result<TaskHandle<A>, JoinError>
     }
 
     impl<A: Alloc> AsyncRet2<A> {
-        pub fn new(join_handle: Promise<AL31F<A>>) -> Self {
-            Self { result: Ok(join_handle) }
+        pub fn new(task_handle: TaskHandle<A>) -> Self {
+            Self { result: Ok(task_handle) }
         }
     }
 
@@ -111,11 +279,11 @@ pub unsafe fn coroutine_spawn<A: Alloc>(
             dests: &[*mut Value]
         ) -> Result<usize, ExceptionInner> {
             match self.result {
-                Ok(join_handle) => {
-                    let join_handle_value: Value = Value::new_owned(join_handle);
+                Ok(task_handle) => {
+                    let task_handle_value: Value = Value::new_owned(task_handle);
                     unsafe {
-                        locked_ctx.add_heap_managed(join_handle_value);
-                        **dests.get_unchecked(0) = join_handle_value;
+                        locked_ctx.add_heap_managed(task_handle_value);
+                        **dests.get_unchecked(0) = task_handle_value;
                     }
                     Ok(1)
                 },
@@ -133,26 +301,27 @@ pub unsafe fn coroutine_spawn<A: Alloc>(
     let arg_pack: UncheckedSendSync<_> = UncheckedSendSync::new((args, program));
 
     let get_join_handle = async move {
-        let join_handle: JoinHandle<Box<dyn AsyncReturnType<AL31F<A>>>> = thread.vm.co_spawn_task(
-            |child_context, (func_id, arg_pack)| UncheckedSendFut::new(async move {
-                let (args, program): (Box<[Value]>, NonNull<CompiledProgram<A>>) =
-                    arg_pack.into_inner();
-                let mut new_thread: Box<VMThread<A>> =
-                    create_vm_child_thread(child_context, program);
-                let arg_pack = UncheckedSendSync::new(
-                    (new_thread.as_mut(), func_id, args.as_ref())
-                );
-
-                match vm_thread_run_function::<_, false>(arg_pack) {
-                    Ok(f) => Box::new(AsyncRet::new_in(
-                        f.await.into_inner(),
-                        &mut new_thread.vm.get_shared_data_mut().alloc
-                    )) as _,
-                    Err(err) => Box::new(AsyncRet::new_unchecked_exc(err)) as _
-                }
-            }),
-            (func_id, arg_pack)
-        ).await;
+        let (task_id, join_handle): (u32, JoinHandle<Box<dyn AsyncReturnType<AL31F<A>>>>) =
+            thread.vm.co_spawn_task(
+                |child_context, (func_id, arg_pack)| UncheckedSendFut::new(async move {
+                    let (args, program): (Box<[Value]>, NonNull<CompiledProgram<A>>) =
+                        arg_pack.into_inner();
+                    let mut new_thread: Box<VMThread<A>> =
+                        create_vm_child_thread(child_context, program);
+                    let arg_pack = UncheckedSendSync::new(
+                        (new_thread.as_mut(), func_id, args.as_ref())
+                    );
+
+                    match vm_thread_run_function::<_, false>(arg_pack) {
+                        Ok(f) => Box::new(AsyncRet::new_in(
+                            f.await.into_inner(),
+                            &mut new_thread.vm.get_shared_data_mut().alloc
+                        )) as _,
+                        Err(err) => Box::new(AsyncRet::new_unchecked_exc(err)) as _
+                    }
+                }),
+                (func_id, arg_pack)
+            ).await;
 
         #[cfg(feature = "async-tokio")]
         let join_handle = join_handle.map_ok_or_else(
@@ -162,8 +331,11 @@ pub unsafe fn coroutine_spawn<A: Alloc>(
             |data| data
         );
 
-        let join_handle_promise: Promise<AL31F<A>> = Promise(Box::pin(join_handle));
-        Box::new(AsyncRet2::new(join_handle_promise)) as Box<dyn AsyncReturnType<AL31F<A>>>
+        let task_handle: TaskHandle<A> = TaskHandle {
+            task_id,
+            promise: Promise(Box::pin(join_handle))
+        };
+        Box::new(AsyncRet2::new(task_handle)) as Box<dyn AsyncReturnType<AL31F<A>>>
     };
 
     Promise(Box::pin(get_join_handle))
@@ -0,0 +1,65 @@
+use std::any::TypeId;
+
+use crate::builtins::iter::{IterState, VMIterator};
+use crate::builtins::object::Object;
+use crate::builtins::vec::VMGenericVec;
+use crate::data::Value;
+use crate::data::exception::UncheckedException;
+use crate::ffi::iter::ITER_ELEM_METHOD;
+use crate::vm::al31fm2::alloc::Alloc;
+use crate::vm::al31fm2::compiled::{CompiledProgram, Method, MethodFunc};
+use crate::vm::al31fm2::executor::rtti::receiver_type_id;
+
+/// Create the iterator over `iterated` for `ITER-BEGIN`
+#[inline(never)]
+pub unsafe fn iter_begin<A: Alloc>(
+    program: &CompiledProgram<A>,
+    iterated: Value
+) -> Result<VMIterator, UncheckedException> {
+    let type_id: TypeId = receiver_type_id(iterated)
+        .ok_or(UncheckedException::UnexpectedNull { value: iterated })?;
+    let state: IterState = if type_id == TypeId::of::<VMGenericVec>() {
+        IterState::Vector
+    } else if type_id == TypeId::of::<Object>() {
+        let object: &Object = &*iterated.get_as_mut_ptr_norm::<Object>();
+        IterState::Object(object.fields.keys().cloned().collect())
+    } else if type_id == TypeId::of::<String>() {
+        IterState::String
+    } else if let Some(Method { func: MethodFunc::Sync(ffi_func_id), .. }) =
+        program.methods.get(type_id, ITER_ELEM_METHOD)
+    {
+        IterState::Host(*ffi_func_id)
+    } else {
+        return Err(UncheckedException::NotIterable { value: iterated });
+    };
+    Ok(VMIterator::new(iterated, state))
+}
+
+/// Advance iterator `iter` over a builtin type, `None` if there's no element left. Field names
+/// of objects are created as new strings, and get managed by `alloc`.
+#[inline(never)]
+pub unsafe fn iter_next_builtin<A: Alloc>(alloc: &mut A, iter: &mut VMIterator) -> Option<Value> {
+    let elem: Value = match &iter.state {
+        IterState::Vector => {
+            let vec: &VMGenericVec = &*(iter.iterated.get_as_mut_ptr() as *const _);
+            let elem: Value = *vec.inner.get(iter.index)?;
+            iter.index += 1;
+            elem
+        },
+        IterState::Object(field_names) => {
+            let field_name: String = field_names.get(iter.index)?.clone();
+            iter.index += 1;
+            let field_name: Value = Value::new_owned(field_name);
+            alloc.add_managed(field_name);
+            field_name
+        },
+        IterState::String => {
+            let string: &String = &*(iter.iterated.get_as_mut_ptr_norm() as *const _);
+            let ch: char = string.get(iter.index..)?.chars().next()?;
+            iter.index += ch.len_utf8();
+            Value::new_char(ch)
+        },
+        IterState::Host(_) => unreachable!("host iterators are advanced through FFI")
+    };
+    Some(elem)
+}
@@ -0,0 +1,152 @@
+use std::any::TypeId;
+use std::ptr::NonNull;
+
+use crate::builtins::object::Object;
+use crate::data::Value;
+use crate::data::exception::UncheckedException;
+use crate::data::tyck::{FunctionTyckInfo, TyckInfo};
+use crate::data::wrapper::{OWN_INFO_READ_MASK, OWN_INFO_WRITE_MASK};
+use crate::vm::al31fm2::alloc::Alloc;
+use crate::vm::al31fm2::compiled::{CompiledProgram, Method, MethodFunc, Property};
+use crate::vm::al31fm2::executor::rtti::{check_type, receiver_type_id};
+
+unsafe fn lookup_method<A: Alloc>(
+    program: &CompiledProgram<A>,
+    method_id: usize,
+    receiver: Value
+) -> Result<&Method, UncheckedException> {
+    let type_id: TypeId = receiver_type_id(receiver)
+        .ok_or(UncheckedException::UnexpectedNull { value: receiver })?;
+    program.methods.get_by_id(method_id, type_id)
+        .ok_or(UncheckedException::MethodNotFound { object: receiver, method_id })
+}
+
+/// Check arguments `args`, including the receiver, against the parameter types of `method`,
+/// which gets called as FFI function `func_id`
+unsafe fn check_method_args(
+    method: &Method,
+    func_id: usize,
+    args: &[Value]
+) -> Result<(), UncheckedException> {
+    if let TyckInfo::Function(FunctionTyckInfo { params, .. }) = method.func_type.as_ref() {
+        let params: &[NonNull<TyckInfo>] = params.as_ref();
+        if params.len() != args.len() {
+            return Err(UncheckedException::ArgCountMismatch {
+                func_id,
+                expected: params.len(),
+                got: args.len()
+            });
+        }
+        for (arg, param) /*: (&Value, &NonNull<TyckInfo>)*/ in args.iter().zip(params.iter()) {
+            if !check_type(*arg, *param) {
+                return Err(UncheckedException::TypeCheckFailure {
+                    object: *arg,
+                    expected_type: *param
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolve method `method_id` of receiver `args[0]` to an FFI function ID, and check `args`
+/// against the signature of the resolved method
+#[inline(never)]
+pub unsafe fn resolve_method<A: Alloc>(
+    program: &CompiledProgram<A>,
+    method_id: usize,
+    args: &[Value]
+) -> Result<usize, UncheckedException> {
+    let receiver: Value = *args.get_unchecked(0);
+    let method: &Method = lookup_method(program, method_id, receiver)?;
+    match method.func {
+        MethodFunc::Sync(ffi_func_id) => {
+            check_method_args(method, ffi_func_id, args)?;
+            Ok(ffi_func_id)
+        },
+        #[cfg(feature = "async")]
+        MethodFunc::Async(_) =>
+            Err(UncheckedException::MethodNotFound { object: receiver, method_id })
+    }
+}
+
+/// Resolve method `method_id` of receiver `args[0]` to an async FFI function ID, and check
+/// `args` against the signature of the resolved method
+#[cfg(feature = "async")]
+#[inline(never)]
+pub unsafe fn resolve_async_method<A: Alloc>(
+    program: &CompiledProgram<A>,
+    method_id: usize,
+    args: &[Value]
+) -> Result<usize, UncheckedException> {
+    let receiver: Value = *args.get_unchecked(0);
+    let method: &Method = lookup_method(program, method_id, receiver)?;
+    match method.func {
+        MethodFunc::Async(async_ffi_func_id) => {
+            check_method_args(method, async_ffi_func_id, args)?;
+            Ok(async_ffi_func_id)
+        },
+        MethodFunc::Sync(_) =>
+            Err(UncheckedException::MethodNotFound { object: receiver, method_id })
+    }
+}
+
+/// How a property access on some receiver should be carried out
+pub enum PropertyAccess<'a> {
+    /// Call the getter or setter of a property registered on the receiver type
+    Property(&'a Property),
+    /// Get or put the field of a builtin `object` receiver, named after the property
+    ObjectField(&'a str)
+}
+
+/// Resolve property `property_id` of `receiver`
+#[inline(never)]
+pub unsafe fn resolve_property<A: Alloc>(
+    program: &CompiledProgram<A>,
+    property_id: usize,
+    receiver: Value
+) -> Result<PropertyAccess<'_>, UncheckedException> {
+    let type_id: TypeId = receiver_type_id(receiver)
+        .ok_or(UncheckedException::UnexpectedNull { value: receiver })?;
+    if let Some(property /*: &Property*/) = program.properties.get_by_id(property_id, type_id) {
+        Ok(PropertyAccess::Property(property))
+    } else if type_id == TypeId::of::<Object>() {
+        program.properties.name(property_id)
+            .map(PropertyAccess::ObjectField)
+            .ok_or(UncheckedException::PropertyNotFound { object: receiver, property_id })
+    } else {
+        Err(UncheckedException::PropertyNotFound { object: receiver, property_id })
+    }
+}
+
+/// Get field `field` of builtin `object` value `object`, `null` if there's no such field
+pub unsafe fn object_field_get(object: Value, field: &str) -> Result<Value, UncheckedException> {
+    if !object.ownership_info().is_readable() {
+        return Err(UncheckedException::OwnershipCheckFailure {
+            object,
+            expected_mask: OWN_INFO_READ_MASK
+        });
+    }
+
+    let object_ref: &Object = &*object.get_as_mut_ptr_norm::<Object>();
+    Ok(*object_ref.fields.get(field).unwrap_or(&Value::new_null()))
+}
+
+/// Put `value` to field `field` of builtin `object` value `object`. The caller should mark
+/// `value` as reachable from `object`.
+pub unsafe fn object_field_put(
+    object: Value,
+    field: &str,
+    value: Value
+) -> Result<(), UncheckedException> {
+    if !object.ownership_info().is_writeable() {
+        return Err(UncheckedException::OwnershipCheckFailure {
+            object,
+            expected_mask: OWN_INFO_WRITE_MASK
+        });
+    }
+
+    let object_ref: &mut Object = &mut *object.get_as_mut_ptr_norm::<Object>();
+    object_ref.fields.insert(field.to_string(), value);
+    Ok(())
+}
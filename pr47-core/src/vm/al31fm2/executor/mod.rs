@@ -7,6 +7,8 @@
 pub mod checked_bin_ops;
 pub mod checked_cast_ops;
 pub mod checked_unary_ops;
+pub mod iter;
+pub mod method;
 pub mod overload;
 pub mod rtti;
 pub mod unwinding;
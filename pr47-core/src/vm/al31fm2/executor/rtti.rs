@@ -59,3 +59,23 @@ pub unsafe fn check_type(value: Value, tyck_info: NonNull<TyckInfo>) -> bool {
         }
     }
 }
+
+/// Runtime type of `value` used for method dispatch, `None` if `value` is `null`
+#[inline(never)]
+pub unsafe fn receiver_type_id(value: Value) -> Option<TypeId> {
+    if value.is_null() {
+        None
+    } else if value.is_value() {
+        Some(match ValueTypeTag::unsafe_from((value.vt_data.tag as u8) & VALUE_TYPE_TAG_MASK) {
+            ValueTypeTag::Int => TypeId::of::<i64>(),
+            ValueTypeTag::Float => TypeId::of::<f64>(),
+            ValueTypeTag::Char => TypeId::of::<char>(),
+            ValueTypeTag::Bool => TypeId::of::<bool>(),
+        })
+    } else if value.is_container() {
+        let vt: *const GenericTypeVT = value.ptr_repr.trivia as *const GenericTypeVT;
+        Some((*vt).tyck_info.as_ref().type_id)
+    } else {
+        Some(value.get_as_dyn_base().as_ref().unchecked_unwrap().dyn_type_id())
+    }
+}
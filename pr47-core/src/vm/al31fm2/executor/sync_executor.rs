@@ -8,7 +8,8 @@ use crate::vm::al31fm2::AL31F;
 use crate::vm::al31fm2::alloc::Alloc;
 use crate::vm::al31fm2::compiled::CompiledProgram;
 use crate::vm::al31fm2::exception::Exception;
-use crate::vm::al31fm2::executor::{VMThread, vm_thread_run_function};
+use crate::vm::al31fm2::globals::Globals;
+use crate::vm::al31fm2::executor::{VMThread, vm_thread_run_function, vm_thread_run_init};
 use crate::vm::al31fm2::stack::Stack;
 
 #[cfg(feature = "async")]
@@ -20,7 +21,7 @@ pub unsafe fn vm_run_function_sync<A: Alloc>(
     func_id: usize,
     args: &[Value]
 ) -> Result<Vec<Value>, Exception> {
-    let vm: AL31F<A> = AL31F::new(alloc);
+    let vm: AL31F<A> = AL31F::with_globals(alloc, Globals::for_program(program));
 
     #[cfg(feature = "async")]
     return pollster::block_on(async {
@@ -32,6 +33,7 @@ pub unsafe fn vm_run_function_sync<A: Alloc>(
             _phantom: PhantomPinned::default()
         };
         thread.vm.get_shared_data_mut().alloc.add_stack(&thread.stack);
+        vm_thread_run_init(&mut thread).await?;
         vm_thread_run_function::<_, true>(UncheckedSendSync::new((&mut thread, func_id, args)))?
             .await
             .into_inner()
@@ -46,6 +48,7 @@ pub unsafe fn vm_run_function_sync<A: Alloc>(
             _phantom: PhantomPinned
         };
         thread.vm.alloc.add_stack(&thread.stack);
+        vm_thread_run_init(&mut thread).await?;
         vm_thread_run_function::<_, true>(UncheckedSendSync::new((&mut thread, func_id, args)))?
             .await
             .into_inner()
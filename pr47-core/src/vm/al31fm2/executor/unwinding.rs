@@ -2,7 +2,7 @@ use std::any::TypeId;
 
 use unchecked_unwrap::UncheckedUnwrap;
 
-use crate::data::exception::{CheckedException, UncheckedException};
+use crate::data::exception::{CheckedException, ExceptionInner, UncheckedException};
 use crate::data::Value;
 use crate::vm::al31fm2::AL31F;
 use crate::vm::al31fm2::alloc::Alloc;
@@ -16,8 +16,14 @@ pub unsafe fn unchecked_exception_unwind_stack(
     stack: &mut Stack,
     insc_ptr: usize
 ) -> Exception {
-    let mut exception: Exception = Exception::unchecked_exc(unchecked_exception);
+    unwind_stack_no_handler(Exception::unchecked_exc(unchecked_exception), stack, insc_ptr)
+}
 
+unsafe fn unwind_stack_no_handler(
+    mut exception: Exception,
+    stack: &mut Stack,
+    insc_ptr: usize
+) -> Exception {
     let mut insc_ptr: usize = insc_ptr;
     while !stack.frames.is_empty() {
         let last_frame: &FrameInfo = stack.frames.last().unchecked_unwrap();
@@ -26,6 +32,9 @@ pub unsafe fn unchecked_exception_unwind_stack(
 
         stack.unwind_shrink_slice();
     }
+
+    #[cfg(feature = "with-log")] log::debug!("uncaught exception: {}", exception);
+    #[cfg(feature = "with-tracing")] tracing::debug!("uncaught exception: {}", exception);
     exception
 }
 
@@ -38,8 +47,44 @@ pub unsafe fn checked_exception_unwind_stack<A: Alloc>(
     insc_ptr: usize
 ) -> Result<(StackSlice, usize), Exception> {
     let exception_type_id: TypeId = (*checked_exception.get_as_dyn_base()).dyn_type_id();
+    unwind_stack_checked(
+        vm,
+        program,
+        exception_type_id,
+        Exception::checked_exc(checked_exception),
+        stack,
+        insc_ptr
+    )
+}
 
-    let mut exception: Exception = Exception::checked_exc(checked_exception);
+/// Re-raise `exception`, which was thrown by a script callback invoked from a builtin operation
+/// (for example the comparator of `VEC-SORT`). Stack trace of the callback is kept, with frames
+/// of `stack` appended after it.
+#[inline(never)]
+pub unsafe fn callback_exception_unwind_stack<A: Alloc>(
+    vm: &mut AL31F<A>,
+    program: &CompiledProgram<A>,
+    exception: Exception,
+    stack: &mut Stack,
+    insc_ptr: usize
+) -> Result<(StackSlice, usize), Exception> {
+    let exception_type_id: TypeId = match &exception.inner {
+        ExceptionInner::Checked(checked) => (*checked.get_as_dyn_base()).dyn_type_id(),
+        ExceptionInner::Unchecked(_) => return Err(unwind_stack_no_handler(
+            exception, stack, insc_ptr
+        ))
+    };
+    unwind_stack_checked(vm, program, exception_type_id, exception, stack, insc_ptr)
+}
+
+unsafe fn unwind_stack_checked<A: Alloc>(
+    vm: &mut AL31F<A>,
+    program: &CompiledProgram<A>,
+    exception_type_id: TypeId,
+    mut exception: Exception,
+    stack: &mut Stack,
+    insc_ptr: usize
+) -> Result<(StackSlice, usize), Exception> {
     let mut insc_ptr: usize = insc_ptr;
 
     while !stack.frames.is_empty() {
@@ -75,5 +120,7 @@ pub unsafe fn checked_exception_unwind_stack<A: Alloc>(
         stack.unwind_shrink_slice();
     }
 
+    #[cfg(feature = "with-log")] log::debug!("uncaught exception: {}", exception);
+    #[cfg(feature = "with-tracing")] tracing::debug!("uncaught exception: {}", exception);
     Err(exception)
 }
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+use crate::data::Value;
+use crate::vm::al31fm2::alloc::Alloc;
+use crate::vm::al31fm2::compiled::CompiledProgram;
+
+/// Module-level mutable variables of one `AL31F` instance
+///
+/// Global variables are addressed by index from VM code (`LOAD-GLOBAL`/`STORE-GLOBAL`), and by
+/// name from the host side. Each instance also gets its own copy of the constant pool of the
+/// program (`LOAD-CONST`/`SAVE-CONST`), so that constants computed by the initialization procedure
+/// never get written into a program shared by several instances. Values stored here are treated as
+/// GC roots.
+pub struct Globals {
+    values: Vec<Value>,
+    names: HashMap<String, usize>,
+    consts: Box<[Value]>
+}
+
+// `Values` stored here are only dereferenced by VM code running on the owning `AL31F` instance,
+// which holds the `Serializer` run permit in async builds. `Globals` itself never dereferences
+// them, just like the allocator which also keeps `Value`s as GC roots.
+unsafe impl Send for Globals {}
+unsafe impl Sync for Globals {}
+
+impl Globals {
+    pub fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            names: HashMap::new(),
+            consts: Box::new([])
+        }
+    }
+
+    /// Create a globals table for running `program`, with global variables declared by `program`,
+    /// and constant slots initialized from the constant pool of `program`
+    pub fn for_program<A: Alloc>(program: &CompiledProgram<A>) -> Self {
+        let mut ret: Globals = Self::with_names(&program.globals);
+        ret.consts = program.const_pool.clone();
+        ret
+    }
+
+    /// Create a globals table with variables `names` declared in order, all initialized to `null`
+    pub fn with_names(names: &[String]) -> Self {
+        let mut ret: Globals = Self::new();
+        for name /*: &String*/ in names.iter() {
+            ret.declare(name);
+        }
+        ret
+    }
+
+    /// Declare a global variable named `name`, initialized to `null`, returning its index. If such
+    /// variable has already been declared, its index is returned and its value is left untouched.
+    pub fn declare(&mut self, name: &str) -> usize {
+        if let Some(idx /*: &usize*/) = self.names.get(name) {
+            return *idx;
+        }
+
+        let idx: usize = self.values.len();
+        self.values.push(Value::new_null());
+        self.names.insert(name.to_string(), idx);
+        idx
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    /// Get the value of global variable named `name`
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.index_of(name).map(|idx: usize| self.values[idx])
+    }
+
+    /// Set the value of global variable named `name`, returns `false` if no such variable
+    pub fn set(&mut self, name: &str, value: Value) -> bool {
+        if let Some(idx /*: usize*/) = self.index_of(name) {
+            self.values[idx] = value;
+            true
+        } else {
+            false
+        }
+    }
+
+    #[inline(always)] pub unsafe fn get_unchecked(&self, idx: usize) -> Value {
+        *self.values.get_unchecked(idx)
+    }
+
+    #[inline(always)] pub unsafe fn set_unchecked(&mut self, idx: usize, value: Value) {
+        *self.values.get_unchecked_mut(idx) = value;
+    }
+
+    #[inline(always)] pub unsafe fn get_const_unchecked(&self, idx: usize) -> Value {
+        *self.consts.get_unchecked(idx)
+    }
+
+    #[inline(always)] pub unsafe fn set_const_unchecked(&mut self, idx: usize, value: Value) {
+        *self.consts.get_unchecked_mut(idx) = value;
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn consts(&self) -> &[Value] {
+        &self.consts
+    }
+}
+
+impl Default for Globals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -366,11 +366,21 @@ pub enum Insc {
 
     /// `SAVE-CONST [CONST] [CONST-ID]`
     ///
-    /// Save the value in register `CONST` to constant pool location `CONST-ID`. Using this
-    /// instruction outside the initialization stage is a logical error. Compiler should
-    /// not generate codes in such a way.
+    /// Save the value in register `CONST` to constant slot `CONST-ID` of the current VM instance.
+    /// Using this instruction outside the initialization procedure is a logical error. Compiler
+    /// should not generate codes in such a way.
     SaveConst(usize, usize),
 
+    /// `LOAD-GLOBAL [GLOBAL-ID] [DEST]`
+    ///
+    /// Load global variable `GLOBAL-ID` from globals table, and put it to register `DEST`.
+    LoadGlobal(usize, usize),
+
+    /// `STORE-GLOBAL [SRC] [GLOBAL-ID]`
+    ///
+    /// Store the value in register `SRC` to global variable `GLOBAL-ID`.
+    StoreGlobal(usize, usize),
+
     /// `CAST-FLOAT-INT [FLOAT@SRC] [DEST]`
     ///
     /// Convert the float in `SRC` to integer, put the result to register `DEST`.
@@ -436,6 +446,19 @@ pub enum Insc {
     /// `CALL-OVERLOAD [OVERLOAD-TBL] [ARGS..] [RETS..]`
     CallOverload(usize, &'static [usize], &'static [usize]),
 
+    /// `TAIL-CALL [FUNC-ID] [ARGS..]`
+    ///
+    /// Call the function denoted by `FUNC-ID` with given `ARGS`, reusing the stack frame of the
+    /// current function. Values returned by the callee go directly to the caller of the current
+    /// function. **No type checking**.
+    TailCall(usize, &'static [usize]),
+
+    /// `TAIL-CALL-PTR [SRC] [ARGS..]`
+    ///
+    /// Similar to `TAIL-CALL`, but calls the function pointer or closure stored in `SRC`.
+    /// **No type checking**.
+    TailCallPtr(usize, &'static [usize]),
+
     /// `RETURN-NOTHING`
     ReturnNothing,
 
@@ -452,6 +475,29 @@ pub enum Insc {
     #[cfg(feature = "optimized-rtlc")]
     FFICall(usize, &'static [usize], &'static [usize]),
 
+    /// `CALL-METHOD [METHOD-ID] [ARGS..] [RETS..]`
+    ///
+    /// Look up method `METHOD-ID` of the runtime type of the receiver `ARGS[0]`, call the FFI
+    /// function implementing that method with given `ARGS`, store the return values to `RETS`.
+    /// Throws unchecked exception if the receiver is `null`, or its type does not have such a
+    /// method. The FFI call always has RTLC.
+    CallMethod(usize, &'static [usize], &'static [usize]),
+
+    /// `GET-PROPERTY [PROPERTY-ID] [OBJ] [DEST]`
+    ///
+    /// Look up property `PROPERTY-ID` of the runtime type of `OBJ`, call its getter and store the
+    /// result to `DEST`. For builtin `object`s without such a property, get the field named after
+    /// the property instead. Throws unchecked exception if `OBJ` is `null`, or its type does not
+    /// have such a property.
+    GetProperty(usize, usize, usize),
+
+    /// `SET-PROPERTY [PROPERTY-ID] [OBJ] [VALUE]`
+    ///
+    /// Similar to `GET-PROPERTY`, but call the setter of the property with `VALUE`, or put `VALUE`
+    /// to the field of builtin `object`s. Besides, throws unchecked exception if the property is
+    /// read-only, `VALUE` does not match the property type, or `OBJ` is not writeable.
+    SetProperty(usize, usize, usize),
+
     /// `FFI-CALL-ASYNC [FUNC-ID] [ARGS..] [RET]`
     ///
     /// Call the async function denoted by `FUNC-ID` with given `ARGS`, store the returned
@@ -460,14 +506,53 @@ pub enum Insc {
     #[cfg(feature = "async")]
     FFICallAsync(usize, &'static [usize], usize),
 
+    /// `CALL-METHOD-ASYNC [METHOD-ID] [ARGS..] [RET]`
+    ///
+    /// Similar to `CALL-METHOD`, but the method is implemented by an async FFI function. Store
+    /// the returned promise to `RET`.
+    #[cfg(feature = "async")]
+    CallMethodAsync(usize, &'static [usize], usize),
+
     /// `AWAIT [FUT] [RETS..]`
     ///
-    /// Await the given promise, store its results into given destinations.
+    /// Await the given promise or task handle, store its results into given destinations.
     #[cfg(feature = "async")]
     Await(usize, &'static [usize]),
 
+    /// `SPAWN [FUNC-ID] [ARGS..] [DEST]`
+    ///
+    /// Start function `FUNC-ID` with given arguments in a new coroutine, put the task handle to
+    /// register `DEST`. Awaiting the task handle yields the return values of the spawned function.
+    #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+    Spawn(usize, &'static [usize], usize),
+
+    /// `CANCEL [TASK]`
+    ///
+    /// Request the coroutine denoted by task handle `TASK` to be cancelled. The cancelled
+    /// coroutine stops with an unchecked exception when it regains running permission.
     #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
-    Spawn(usize, &'static [usize]),
+    Cancel(usize),
+
+    /// `CREATE-TASK-GROUP [DEST]`
+    ///
+    /// Create a task group owned by the current coroutine, put it to register `DEST`. Cancelling
+    /// the current coroutine also cancels all coroutines in this group.
+    #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+    CreateTaskGroup(usize),
+
+    /// `GROUP-ADD [GROUP] [TASK]`
+    ///
+    /// Move task handle `TASK` into task group `GROUP`. The task handle cannot be awaited or
+    /// cancelled separately afterwards.
+    #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+    GroupAdd(usize, usize),
+
+    /// `AWAIT-GROUP [GROUP]`
+    ///
+    /// Wait for all coroutines in task group `GROUP` to stop. If any of them fails, the others get
+    /// cancelled, and the first exception is re-raised.
+    #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+    AwaitGroup(usize),
 
     /// `RAISE [EXCEPTION]`
     Raise(usize),
@@ -476,6 +561,19 @@ pub enum Insc {
     JumpIfFalse(usize, usize),
     Jump(usize),
 
+    /// `ITER-BEGIN [SRC] [DEST]`
+    ///
+    /// Create an iterator over `SRC` and store it to `DEST`. Builtin vectors are iterated by
+    /// element, builtin objects by field name, strings by character, and host types through their
+    /// `IterElem` method. Throws unchecked exception if `SRC` is `null` or not iterable.
+    IterBegin(usize, usize),
+
+    /// `ITER-NEXT [ITER] [DEST] [END-ADDR]`
+    ///
+    /// Advance iterator `ITER` and store the next element to `DEST`, or jump to `END-ADDR` if
+    /// there's no element left.
+    IterNext(usize, usize, usize),
+
     CreateContainer(GenericTypeCtor, NonNull<GenericTypeVT>, usize),
 
     CreateClosure(usize, &'static [usize], NonNull<GenericTypeVT>, usize),
@@ -487,6 +585,91 @@ pub enum Insc {
     #[cfg(feature = "al31fm2-builtin-ops")] VecIndexPut(usize, usize, usize),
     #[cfg(feature = "al31fm2-builtin-ops")] VecPush(usize, usize),
     #[cfg(feature = "al31fm2-builtin-ops")] VecLen(usize, usize),
+    /// `VEC-POP [VEC] [DEST]`
+    ///
+    /// Remove the last element of `VEC` and store it to `DEST`. Throws unchecked exception if
+    /// `VEC` is empty.
+    #[cfg(feature = "al31fm2-builtin-ops")] VecPop(usize, usize),
+    /// `VEC-INSERT [VEC] [INDEX] [VALUE]`
+    ///
+    /// Insert `VALUE` into `VEC` at `INDEX`, shifting all elements after it. Throws unchecked
+    /// exception if `INDEX` is greater than the length of `VEC`.
+    #[cfg(feature = "al31fm2-builtin-ops")] VecInsert(usize, usize, usize),
+    /// `VEC-REMOVE [VEC] [INDEX] [DEST]`
+    ///
+    /// Remove the `INDEX`-th element of `VEC`, shifting all elements after it, and store the
+    /// removed element to `DEST`. Throws unchecked exception if `INDEX` is out of bounds.
+    #[cfg(feature = "al31fm2-builtin-ops")] VecRemove(usize, usize, usize),
+    /// `VEC-TRUNCATE [VEC] [LEN]`
+    ///
+    /// Shorten `VEC` to `LEN` elements, does nothing if `VEC` is not longer than `LEN`. Throws
+    /// unchecked exception if `LEN` is negative.
+    #[cfg(feature = "al31fm2-builtin-ops")] VecTruncate(usize, usize),
+    #[cfg(feature = "al31fm2-builtin-ops")] VecClear(usize),
+    /// `VEC-EXTEND [VEC] [OTHER]`
+    ///
+    /// Append all elements of `OTHER` to `VEC`.
+    #[cfg(feature = "al31fm2-builtin-ops")] VecExtend(usize, usize),
+    /// `VEC-SLICE [VEC] [START] [END] [DEST]`
+    ///
+    /// Create a new vector of elements `START..END` of `VEC`, with the same virtual table as
+    /// `VEC`, and store it to `DEST`. Throws unchecked exception if the range is out of bounds.
+    #[cfg(feature = "al31fm2-builtin-ops")] VecSlice(usize, usize, usize, usize),
+    #[cfg(feature = "al31fm2-builtin-ops")] VecReverse(usize),
+    /// `VEC-CONTAINS [VEC] [VALUE] [DEST]`
+    ///
+    /// Store whether `VEC` contains `VALUE` to `DEST`. Elements are compared in the same way as
+    /// `EQ-ANY`.
+    #[cfg(feature = "al31fm2-builtin-ops")] VecContains(usize, usize, usize),
+    /// `VEC-SORT [VEC] [CMP]`
+    ///
+    /// Stable sort `VEC` with comparator `CMP`, which is either a function id or a closure. The
+    /// comparator is called with two elements and returns a negative integer, zero or a positive
+    /// integer if the first element is less than, equal to or greater than the second one.
+    ///
+    /// `VEC` appears empty to the comparator while sorting. The comparator runs to completion
+    /// before the sorting coroutine continues, so comparators which may await (see
+    /// [`CompiledProgram::may_await`](crate::vm::al31fm2::compiled::CompiledProgram::may_await))
+    /// or do not return exactly one value are rejected with an unchecked exception before
+    /// sorting. Exceptions thrown by the comparator are propagated from this instruction.
+    #[cfg(feature = "al31fm2-builtin-ops")] VecSort(usize, usize),
+
+    /// `SET-INSERT [SET] [VALUE] [DEST]`
+    ///
+    /// Insert `VALUE` into `SET`, store whether `VALUE` was absent to `DEST`. Set elements are
+    /// compared in the same way as `EQ-ANY`.
+    #[cfg(feature = "al31fm2-builtin-ops")] SetInsert(usize, usize, usize),
+    /// `SET-REMOVE [SET] [VALUE] [DEST]`
+    ///
+    /// Remove `VALUE` from `SET`, store whether `VALUE` was present to `DEST`.
+    #[cfg(feature = "al31fm2-builtin-ops")] SetRemove(usize, usize, usize),
+    #[cfg(feature = "al31fm2-builtin-ops")] SetContains(usize, usize, usize),
+    #[cfg(feature = "al31fm2-builtin-ops")] SetLen(usize, usize),
+    /// `SET-ELEM [SET] [INDEX] [DEST]`
+    ///
+    /// Store the `INDEX`-th element of `SET` to `DEST`, for iterating over `SET` with indices
+    /// `0..SET-LEN`. Throws unchecked exception if `INDEX` is out of bounds.
+    #[cfg(feature = "al31fm2-builtin-ops")] SetElem(usize, usize, usize),
+
+    /// `MAP-GET [MAP] [KEY] [DEST]`
+    ///
+    /// Store the value associated with `KEY` in `MAP` to `DEST`, or `null` if there's no such
+    /// value. Map keys are compared in the same way as `EQ-ANY`.
+    #[cfg(feature = "al31fm2-builtin-ops")] MapGet(usize, usize, usize),
+    /// `MAP-PUT [MAP] [KEY] [VALUE]`
+    ///
+    /// Associate `VALUE` with `KEY` in `MAP`, replacing the previously associated value.
+    #[cfg(feature = "al31fm2-builtin-ops")] MapPut(usize, usize, usize),
+    /// `MAP-REMOVE [MAP] [KEY] [DEST]`
+    ///
+    /// Remove `KEY` from `MAP`, store whether `KEY` was present to `DEST`.
+    #[cfg(feature = "al31fm2-builtin-ops")] MapRemove(usize, usize, usize),
+    #[cfg(feature = "al31fm2-builtin-ops")] MapContains(usize, usize, usize),
+    #[cfg(feature = "al31fm2-builtin-ops")] MapLen(usize, usize),
+    /// `MAP-KEYS [MAP] [VT] [DEST]`
+    ///
+    /// Create a vector of all keys in `MAP`, with virtual table `VT`, and store it to `DEST`.
+    #[cfg(feature = "al31fm2-builtin-ops")] MapKeys(usize, NonNull<GenericTypeVT>, usize),
 
     #[cfg(feature = "al31fm2-builtin-ops")] StrClone(usize, usize),
     #[cfg(feature = "al31fm2-builtin-ops")] StrConcat(&'static [usize], usize),
@@ -517,6 +700,8 @@ impl Insc {
             Insc::MakeNull(dst) => format!("%{} = null", dst),
             Insc::LoadConst(const_id, dst) => format!("%{} = load {}", dst, const_id),
             Insc::SaveConst(src, const_id) => format!("store {}, %{}", const_id, src),
+            Insc::LoadGlobal(global_id, dst) => format!("%{} = load global G.{}", dst, global_id),
+            Insc::StoreGlobal(src, global_id) => format!("store global G.{}, %{}", global_id, src),
             Insc::Call(func_id, args, rets) => {
                 let mut result: String = String::from("[");
                 for (i, ret) /*: (usize, &usize)*/ in rets.iter().enumerate() {
@@ -563,6 +748,34 @@ impl Insc {
                 }
                 result
             },
+            Insc::TailCall(func_id, args) => {
+                let mut result: String = String::from("tail-call F.");
+                result.push_str(&func_id.to_string());
+                result.push(' ');
+                for (i, arg) /*: (usize, &usize)*/ in args.iter().enumerate() {
+                    result.push('%');
+                    result.push_str(&arg.to_string());
+                    if i != args.len() - 1 {
+                        result.push(',');
+                        result.push(' ');
+                    }
+                }
+                result
+            },
+            Insc::TailCallPtr(func, args) => {
+                let mut result: String = String::from("tail-call %");
+                result.push_str(&func.to_string());
+                result.push(' ');
+                for (i, arg) /*: (usize, &usize)*/ in args.iter().enumerate() {
+                    result.push('%');
+                    result.push_str(&arg.to_string());
+                    if i != args.len() - 1 {
+                        result.push(',');
+                        result.push(' ');
+                    }
+                }
+                result
+            },
             Insc::FFICallRtlc(ffi_func_id, args, rets) => {
                 let mut result: String = String::from("[");
                 for (i, ret) /*: (usize, &usize)*/ in rets.iter().enumerate() {
@@ -586,6 +799,50 @@ impl Insc {
                 }
                 result
             },
+            Insc::CallMethod(method_id, args, rets) => {
+                let mut result: String = String::from("[");
+                for (i, ret) /*: (usize, &usize)*/ in rets.iter().enumerate() {
+                    result.push('%');
+                    result.push_str(&ret.to_string());
+                    if i != rets.len() - 1 {
+                        result.push(',');
+                        result.push(' ');
+                    }
+                }
+                result.push_str("] = call-method M.");
+                result.push_str(&method_id.to_string());
+                result.push(' ');
+                for (i, arg) /*: (usize, &usize)*/ in args.iter().enumerate() {
+                    result.push('%');
+                    result.push_str(&arg.to_string());
+                    if i != args.len() - 1 {
+                        result.push(',');
+                        result.push(' ');
+                    }
+                }
+                result
+            },
+            #[cfg(feature = "async")]
+            Insc::CallMethodAsync(method_id, args, ret) => {
+                let mut result: String = String::from("%");
+                result.push_str(&ret.to_string());
+                result.push_str(" = call-method-async M.");
+                result.push_str(&method_id.to_string());
+                result.push(' ');
+                for (i, arg) /*: (usize, &usize)*/ in args.iter().enumerate() {
+                    result.push('%');
+                    result.push_str(&arg.to_string());
+                    if i != args.len() - 1 {
+                        result.push(',');
+                        result.push(' ');
+                    }
+                }
+                result
+            },
+            Insc::GetProperty(property_id, obj_loc, dest) =>
+                format!("%{} = get-property P.{} %{}", dest, property_id, obj_loc),
+            Insc::SetProperty(property_id, obj_loc, value_loc) =>
+                format!("set-property P.{} %{}, %{}", property_id, obj_loc, value_loc),
             Insc::ReturnNothing => "ret".into(),
             Insc::ReturnOne(ret_value_loc) => format!("ret %{}", ret_value_loc),
             Insc::Return(ret_value_locs) => {
@@ -607,6 +864,9 @@ impl Insc {
             Insc::JumpIfTrue(condition, dest) => format!("if %{} goto L.{}", condition, dest),
             Insc::JumpIfFalse(condition, dest) => format!("if not %{} goto L.{}", condition, dest),
             Insc::Jump(dest) => format!("goto L.{}", dest),
+            Insc::IterBegin(src, dest) => format!("%{} = iter-begin %{}", dest, src),
+            Insc::IterNext(iter, dest, end_addr) =>
+                format!("%{} = iter-next %{} else goto L.{}", dest, iter, end_addr),
             Insc::CreateClosure(func_id, captures, _, dest) => {
                 let mut result: String = String::from("%");
                 result.push_str(&dest.to_string());
@@ -643,13 +903,68 @@ impl Insc {
             Insc::VecIndexPut(vec_loc, idx, value_loc) =>
                 format!("vec-index-put %{}, %{}, %{}", vec_loc, idx, value_loc),
             Insc::VecPush(vec_loc, value_loc) => format!("vec-push %{} %{}", vec_loc, value_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecPop(vec_loc, dest) => format!("%{} = vec-pop %{}", dest, vec_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecInsert(vec_loc, idx, value_loc) =>
+                format!("vec-insert %{}, %{}, %{}", vec_loc, idx, value_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecRemove(vec_loc, idx, dest) =>
+                format!("%{} = vec-remove %{}, %{}", dest, vec_loc, idx),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecTruncate(vec_loc, len) => format!("vec-truncate %{}, %{}", vec_loc, len),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecClear(vec_loc) => format!("vec-clear %{}", vec_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecExtend(vec_loc, other_loc) =>
+                format!("vec-extend %{}, %{}", vec_loc, other_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecSlice(vec_loc, start, end, dest) =>
+                format!("%{} = vec-slice %{}, %{}, %{}", dest, vec_loc, start, end),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecReverse(vec_loc) => format!("vec-reverse %{}", vec_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecContains(vec_loc, value_loc, dest) =>
+                format!("%{} = vec-contains %{}, %{}", dest, vec_loc, value_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::VecSort(vec_loc, cmp_loc) => format!("vec-sort %{}, %{}", vec_loc, cmp_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetInsert(set_loc, value_loc, dest) =>
+                format!("%{} = set-insert %{}, %{}", dest, set_loc, value_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetRemove(set_loc, value_loc, dest) =>
+                format!("%{} = set-remove %{}, %{}", dest, set_loc, value_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetContains(set_loc, value_loc, dest) =>
+                format!("%{} = set-contains %{}, %{}", dest, set_loc, value_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetLen(set_loc, dest) => format!("%{} = set-len %{}", dest, set_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::SetElem(set_loc, idx, dest) =>
+                format!("%{} = set-elem %{}, %{}", dest, set_loc, idx),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapGet(map_loc, key_loc, dest) =>
+                format!("%{} = map-get %{}, %{}", dest, map_loc, key_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapPut(map_loc, key_loc, value_loc) =>
+                format!("map-put %{}, %{}, %{}", map_loc, key_loc, value_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapRemove(map_loc, key_loc, dest) =>
+                format!("%{} = map-remove %{}, %{}", dest, map_loc, key_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapContains(map_loc, key_loc, dest) =>
+                format!("%{} = map-contains %{}, %{}", dest, map_loc, key_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapLen(map_loc, dest) => format!("%{} = map-len %{}", dest, map_loc),
+            #[cfg(feature = "al31fm2-builtin-ops")]
+            Insc::MapKeys(map_loc, _, dest) => format!("%{} = map-keys %{}", dest, map_loc),
             Insc::ObjectGetDyn(obj_loc, field_name, dest) =>
                 format!("%{} = object-get %{}, %{}", dest, obj_loc, field_name),
             Insc::ObjectPutDyn(obj_loc, field_name, value_loc) =>
                 format!("object-put %{}, %{}, %{}", obj_loc, field_name, value_loc),
-            #[cfg(feature = "async")]
-            Insc::Spawn(func_id, args) => {
-                let mut result: String = String::from("spawn F.");
+            #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+            Insc::Spawn(func_id, args, dest) => {
+                let mut result: String = format!("%{} = spawn F.", dest);
                 result.push_str(&func_id.to_string());
                 result.push(' ');
                 for (i, arg) /*: (usize, &usize)*/ in args.iter().enumerate() {
@@ -662,6 +977,15 @@ impl Insc {
                 }
                 result
             },
+            #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+            Insc::Cancel(task_loc) => format!("cancel %{}", task_loc),
+            #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+            Insc::CreateTaskGroup(dest) => format!("%{} = new task-group", dest),
+            #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+            Insc::GroupAdd(group_loc, task_loc) =>
+                format!("group-add %{}, %{}", group_loc, task_loc),
+            #[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+            Insc::AwaitGroup(group_loc) => format!("await group %{}", group_loc),
             #[cfg(feature = "async")]
             Insc::Await(task_loc, dests) => {
                 let mut result = String::from("[");
@@ -0,0 +1,70 @@
+//! # `isolate.rs`: independent VM instances sharing one compiled program
+//!
+//! An `Isolate` owns a complete `AL31F` VM: its own allocator, globals and `Serializer`. Nothing
+//! but the (immutable) `CompiledProgram` is shared between isolates, so different isolates never
+//! contend for the same run permit, and they may run in parallel on a multithreaded runtime.
+//! Values must not be passed from one isolate to another.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use xjbutil::unchecked::UncheckedSendSync;
+
+use crate::data::Value;
+use crate::vm::al31fm2::AL31F;
+use crate::vm::al31fm2::alloc::Alloc;
+use crate::vm::al31fm2::compiled::CompiledProgram;
+use crate::vm::al31fm2::exception::Exception;
+use crate::vm::al31fm2::executor::{
+    VMThread,
+    VMThreadRunFunctionFut,
+    create_vm_main_thread,
+    vm_thread_run_function,
+    vm_thread_run_init
+};
+
+pub struct Isolate<A: Alloc> {
+    // `thread` refers to `program`, so it must be dropped first
+    thread: Box<VMThread<A>>,
+    program: Arc<CompiledProgram<A>>
+}
+
+impl<A: Alloc> Isolate<A> {
+    /// Create an isolate running `program`, running the initialization procedure of `program` on
+    /// it. Exception thrown by the initialization procedure is returned as error.
+    pub async fn new(alloc: A, program: Arc<CompiledProgram<A>>) -> Result<Self, Exception> {
+        let mut thread: Box<VMThread<A>> = create_vm_main_thread(alloc, &program).await;
+        unsafe { vm_thread_run_init(&mut thread).await?; }
+        Ok(Self { thread, program })
+    }
+
+    pub fn program(&self) -> &Arc<CompiledProgram<A>> {
+        &self.program
+    }
+
+    /// Get the VM of this isolate. Only available while no function is running on this isolate.
+    pub fn vm_mut(&mut self) -> &mut AL31F<A> {
+        self.thread.vm.get_shared_data_mut()
+    }
+
+    /// Run function `func_id` of the program on this isolate. Arguments are copied onto the VM
+    /// stack before this function returns, so the returned future does not borrow `args`.
+    pub fn run_function<'a>(
+        &'a mut self,
+        func_id: usize,
+        args: &[Value]
+    ) -> impl Future<Output=Result<Vec<Value>, Exception>> + Send + 'a {
+        let arg_pack: (&'a mut VMThread<A>, usize, &[Value]) = (&mut self.thread, func_id, args);
+        let fut: UncheckedSendSync<Result<VMThreadRunFunctionFut<'a, A, false>, Exception>> =
+            unsafe {
+                UncheckedSendSync::new(
+                    vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+                )
+            };
+
+        async move {
+            let fut: VMThreadRunFunctionFut<'a, A, false> = fut.into_inner()?;
+            fut.await.into_inner()
+        }
+    }
+}
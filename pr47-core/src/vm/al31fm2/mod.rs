@@ -2,9 +2,12 @@ pub mod alloc;
 pub mod compiled;
 pub mod exception;
 pub mod executor;
+pub mod globals;
 pub mod insc;
 pub mod stack;
 
+#[cfg(feature = "async")] pub mod isolate;
+
 #[cfg(all(test, feature = "async"))]      pub mod test_async;
 #[cfg(all(test, not(feature = "async")))] pub mod test_sync;
 #[cfg(any(test, feature = "bench"))]      pub mod test_program;
@@ -14,6 +17,7 @@ use std::ptr::NonNull;
 use crate::data::Value;
 use crate::ffi::sync_fn::VMContext;
 use crate::vm::al31fm2::alloc::Alloc;
+use crate::vm::al31fm2::globals::Globals;
 
 #[cfg(feature = "async")] use crate::ffi::async_fn::AsyncVMContext;
 #[cfg(feature = "async")] use crate::ffi::async_fn::LockedCtx;
@@ -21,12 +25,29 @@ use crate::vm::al31fm2::alloc::Alloc;
 #[cfg(feature = "async")] use crate::vm::al31fm2::compiled::CompiledProgram;
 
 pub struct AL31F<A: Alloc> {
-    pub alloc: A
+    pub alloc: A,
+    pub globals: Box<Globals>
 }
 
 impl<A: Alloc> AL31F<A> {
     pub fn new(alloc: A) -> Self {
-        Self { alloc }
+        Self::with_globals(alloc, Globals::new())
+    }
+
+    pub fn with_globals(mut alloc: A, globals: Globals) -> Self {
+        let globals: Box<Globals> = Box::new(globals);
+        unsafe { alloc.set_globals(globals.as_ref()); }
+        Self { alloc, globals }
+    }
+
+    /// Get the value of global variable named `name`
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.globals.get(name)
+    }
+
+    /// Set the value of global variable named `name`, returns `false` if no such variable
+    pub fn set_global(&mut self, name: &str, value: Value) -> bool {
+        self.globals.set(name, value)
     }
 }
 
@@ -45,6 +66,13 @@ impl<A: Alloc> VMContext for AL31F<A> {
             self.alloc.mark_object(value);
         }
     }
+
+    #[inline(always)]
+    fn pin_objects(&mut self, values: &[Value]) -> *mut bool {
+        unsafe {
+            self.alloc.pin_objects(values)
+        }
+    }
 }
 
 #[cfg(feature = "async")]
@@ -68,6 +96,10 @@ impl<A: Alloc> VMContext for Combustor<A> {
     fn mark(&mut self, value: Value) {
         unsafe { self.vm.as_mut().alloc.mark_object(value); }
     }
+
+    fn pin_objects(&mut self, values: &[Value]) -> *mut bool {
+        unsafe { self.vm.as_mut().alloc.pin_objects(values) }
+    }
 }
 
 #[cfg(feature = "async")]
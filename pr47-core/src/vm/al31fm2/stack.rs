@@ -1,5 +1,6 @@
 use std::ptr::NonNull;
 
+use smallvec::SmallVec;
 use unchecked_unwrap::UncheckedUnwrap;
 
 use crate::data::Value;
@@ -90,6 +91,12 @@ impl Stack {
         }
     }
 
+    /// Drop all frames, for running another function from scratch
+    pub fn reset(&mut self) {
+        self.values.clear();
+        self.frames.clear();
+    }
+
     pub unsafe fn ext_func_call_grow_stack(
         &mut self,
         func_id: usize,
@@ -169,6 +176,46 @@ impl Stack {
         new_slice
     }
 
+    pub unsafe fn func_tail_call_reuse_stack(
+        &mut self,
+        func_id: usize,
+        frame_size: usize,
+        arg_locs: &[usize]
+    ) -> StackSlice {
+        self.closure_tail_call_reuse_stack(func_id, frame_size, &[], arg_locs)
+    }
+
+    pub unsafe fn closure_tail_call_reuse_stack(
+        &mut self,
+        func_id: usize,
+        frame_size: usize,
+        captures: &[Value],
+        arg_locs: &[usize]
+    ) -> StackSlice {
+        let this_frame: &mut FrameInfo = self.frames.last_mut().unwrap();
+        let this_frame_start: usize = this_frame.frame_start;
+        assert_eq!(this_frame.frame_end, self.values.len());
+
+        // arguments may overlap with the new frame, so copy them out before resizing
+        let mut args: SmallVec<[Value; 8]> = SmallVec::from_slice(captures);
+        for arg_loc /*: &usize*/ in arg_locs.iter() {
+            args.push(self.values[this_frame_start + *arg_loc].unwrap());
+        }
+
+        let new_frame_end: usize = this_frame_start + frame_size;
+        this_frame.frame_end = new_frame_end;
+        this_frame.func_id = func_id;
+
+        self.values.truncate(this_frame_start);
+        self.values.resize(new_frame_end, None);
+        let mut new_slice: StackSlice =
+            StackSlice(&mut self.values[this_frame_start..new_frame_end] as *mut _);
+        for (i /*: usize*/, arg /*: Value*/) in args.into_iter().enumerate() {
+            new_slice.set_value(i, arg);
+        }
+        new_slice
+    }
+
     pub unsafe fn done_func_call_shrink_stack0(&mut self) -> Option<(StackSlice, usize)> {
         self.done_func_call_shrink_stack(&[])
     }
@@ -268,6 +315,12 @@ impl Stack {
         }
     }
 
+    /// Drop all frames, for running another function from scratch
+    pub fn reset(&mut self) {
+        self.values.clear();
+        self.frames.clear();
+    }
+
     pub unsafe fn ext_func_call_grow_stack(
         &mut self,
         func_id: usize,
@@ -342,6 +395,46 @@ impl Stack {
         StackSlice(new_slice_ptr)
     }
 
+    #[inline] pub unsafe fn func_tail_call_reuse_stack(
+        &mut self,
+        func_id: usize,
+        frame_size: usize,
+        arg_locs: &[usize]
+    ) -> StackSlice {
+        self.closure_tail_call_reuse_stack(func_id, frame_size, &[], arg_locs)
+    }
+
+    pub unsafe fn closure_tail_call_reuse_stack(
+        &mut self,
+        func_id: usize,
+        frame_size: usize,
+        captures: &[Value],
+        arg_locs: &[usize]
+    ) -> StackSlice {
+        let this_frame: &mut FrameInfo = self.frames.last_mut().unchecked_unwrap();
+        let this_frame_start: usize = this_frame.frame_start;
+        let this_slice_ptr: *mut Value = self.values.as_mut_ptr().add(this_frame_start);
+
+        let mut args: SmallVec<[Value; 8]> = SmallVec::from_slice(captures);
+        for i /*: usize*/ in 0..arg_locs.len() {
+            let arg_loc: usize = *arg_locs.get_unchecked(i);
+            args.push(*this_slice_ptr.add(arg_loc));
+        }
+
+        let new_frame_end: usize = this_frame_start + frame_size;
+        this_frame.frame_end = new_frame_end;
+        this_frame.func_id = func_id;
+
+        // registers beyond arguments must not carry values over from the previous function
+        self.values.truncate(this_frame_start + args.len());
+        self.values.resize(new_frame_end, Value::new_null());
+        let new_slice_ptr: *mut Value = self.values.as_mut_ptr().add(this_frame_start);
+        for i /*: usize*/ in 0..args.len() {
+            *new_slice_ptr.add(i) = *args.get_unchecked(i);
+        }
+        StackSlice(new_slice_ptr)
+    }
+
     #[inline] pub unsafe fn done_func_call_shrink_stack0(&mut self) -> Option<(StackSlice, usize)> {
         let frame_count = self.frames.len();
         if frame_count == 1 {
@@ -419,3 +512,81 @@ impl Stack {
         self.values.truncate(frame.frame_start);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::data::Value;
+    use crate::vm::al31fm2::stack::{Stack, StackSlice};
+
+    #[cfg(debug_assertions)]
+    fn is_cleared(stack: &Stack, idx: usize) -> bool {
+        stack.values[idx].is_none()
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn is_cleared(stack: &Stack, idx: usize) -> bool {
+        stack.values[idx].is_null()
+    }
+
+    #[test]
+    fn test_func_tail_call() {
+        let mut stack: Stack = Stack::new();
+        unsafe {
+            // application_start(%0) of `tail_call_program`
+            let mut slice: StackSlice = stack.ext_func_call_grow_stack(0, 2, &[Value::new_int(3)]);
+            slice.set_value(1, Value::new_int(0));
+
+            // sum(%0, %1), calling itself until %0 reaches zero
+            for i /*: i64*/ in (1..=3).rev() {
+                slice = stack.func_tail_call_reuse_stack(1, 4, &[0, 1]);
+                assert_eq!(stack.frames.len(), 1);
+                assert_eq!(stack.values.len(), 4);
+                assert_eq!(stack.frames[0].func_id, 1);
+                assert_eq!(slice.get_value(0).vt_data.inner.int_value, i);
+                assert!(is_cleared(&stack, 2));
+                assert!(is_cleared(&stack, 3));
+
+                slice.set_value(2, Value::new_int(0));
+                slice.set_value(3, Value::new_bool(false));
+                let sum: i64 = slice.get_value(1).vt_data.inner.int_value;
+                slice.set_value(1, Value::new_int(sum + i));
+                slice.set_value(0, Value::new_int(i - 1));
+            }
+            assert_eq!(slice.get_value(1).vt_data.inner.int_value, 6);
+        }
+    }
+
+    #[test]
+    fn test_closure_tail_call() {
+        let mut stack: Stack = Stack::new();
+        unsafe {
+            let mut slice: StackSlice = stack.ext_func_call_grow_stack(
+                0, 4, &[Value::new_int(1), Value::new_int(2)]
+            );
+            slice.set_value(2, Value::new_int(3));
+            slice.set_value(3, Value::new_int(4));
+
+            // shrinking frame, captures go before arguments
+            slice = stack.closure_tail_call_reuse_stack(1, 3, &[Value::new_int(5)], &[3]);
+            assert_eq!(stack.frames.len(), 1);
+            assert_eq!(stack.values.len(), 3);
+            assert_eq!(stack.frames[0].func_id, 1);
+            assert_eq!(slice.get_value(0).vt_data.inner.int_value, 5);
+            assert_eq!(slice.get_value(1).vt_data.inner.int_value, 4);
+            assert!(is_cleared(&stack, 2));
+
+            // growing frame
+            slice.set_value(2, Value::new_int(6));
+            slice = stack.closure_tail_call_reuse_stack(2, 6, &[Value::new_int(7)], &[2, 0]);
+            assert_eq!(stack.frames.len(), 1);
+            assert_eq!(stack.values.len(), 6);
+            assert_eq!(stack.frames[0].func_id, 2);
+            assert_eq!(slice.get_value(0).vt_data.inner.int_value, 7);
+            assert_eq!(slice.get_value(1).vt_data.inner.int_value, 6);
+            assert_eq!(slice.get_value(2).vt_data.inner.int_value, 5);
+            for i /*: usize*/ in 3..6 {
+                assert!(is_cleared(&stack, i));
+            }
+        }
+    }
+}
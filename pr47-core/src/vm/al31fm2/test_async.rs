@@ -1,27 +1,64 @@
 use std::any::TypeId;
+use std::ptr::NonNull;
 
-use xjbutil::async_utils::block_on_future;
 use xjbutil::std_ext::ExpectSilentExt;
 use xjbutil::unchecked::UncheckedSendSync;
 
 use crate::builtins::object::Object;
+use crate::builtins::vec::{VMGenericVec, create_vm_vec_vt, vec_ctor};
 use crate::data::Value;
-use crate::data::wrapper::DynBase;
+use crate::data::exception::{ExceptionInner, UncheckedException};
+use crate::data::generic::GenericTypeVT;
+use crate::data::tyck::{TyckInfo, TyckInfoPool};
+use crate::data::wrapper::{DynBase, OWN_INFO_WRITE_MASK, OwnershipInfo};
 use crate::data::value_typed::{VALUE_TYPE_TAG_MASK, ValueTypeTag};
+use crate::ffi::{DataOption, RetArity, Signature, VariadicTail, VariadicType};
+use crate::ffi::async_fn::AsyncFunctionBase;
+use crate::ffi::sync_fn::FunctionBase;
+use crate::std47::futures::{CancelScopeAttachBind, JoinBind, SelectBind, TimeoutBind};
+use crate::std47::io::PrintBind;
+use crate::util::async_utils::block_on_future;
 use crate::vm::al31fm2::alloc::default_alloc::DefaultAlloc;
 use crate::vm::al31fm2::compiled::CompiledProgram;
 use crate::vm::al31fm2::exception::Exception;
 use crate::vm::al31fm2::executor::{VMThread, create_vm_main_thread, vm_thread_run_function};
 use crate::vm::al31fm2::test_program::{
+    Pr47Binder_ffi_checked_div,
+    async_channel_program,
     async_ffi_call_program,
+    async_spawn_await_program,
     async_spawn_program,
+    async_task_group_program,
+    async_timeout_program,
     basic_fn_call_program,
     basic_program,
     exception_no_eh_program,
     exception_program,
     fibonacci_program,
     ffi_call_program,
-    ffi_call_program2
+    ffi_bind_program,
+    ffi_call_program2,
+    globals_program,
+    TestPoint,
+    TestRange,
+    iter_program,
+    method_call_program,
+    property_program,
+    tail_call_program,
+    tail_call_ptr_program
+};
+
+#[cfg(not(feature = "async-sim"))] use std::sync::Arc;
+#[cfg(not(feature = "async-sim"))] use tokio::task::JoinHandle;
+#[cfg(feature = "compiler")] use xjbutil::boxed_slice;
+#[cfg(not(feature = "async-sim"))] use crate::vm::al31fm2::isolate::Isolate;
+#[cfg(feature = "compiler")] use crate::diag::location::SourceLoc;
+#[cfg(feature = "compiler")] use crate::diag::source::SourceManager;
+#[cfg(feature = "compiler")] use crate::vm::al31fm2::compiled::DebugInfo;
+#[cfg(feature = "al31fm2-builtin-ops")] use crate::vm::al31fm2::test_program::{
+    map_program,
+    vec_program,
+    vec_sort_callback_program
 };
 
 async fn basic_program_eval() {
@@ -102,6 +139,95 @@ async fn fibonacci_call() {
     }
 }
 
+async fn tail_call() {
+    let tail_call_program: CompiledProgram<DefaultAlloc> = tail_call_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &tail_call_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 0, &[Value::new_int(100_000)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Ok(result /*: Vec<Value>*/) = result {
+        assert_eq!(result.len(), 1);
+        assert!(result[0].is_value());
+        unsafe {
+            assert_eq!(result[0].vt_data.tag & (VALUE_TYPE_TAG_MASK as usize),
+                       ValueTypeTag::Int as usize);
+            assert_eq!(result[0].vt_data.inner.int_value, 5_000_050_000);
+        }
+    } else {
+        panic!()
+    }
+}
+
+async fn tail_call_ptr() {
+    let tail_call_ptr_program: CompiledProgram<DefaultAlloc> = tail_call_ptr_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &tail_call_ptr_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 0, &[Value::new_int(1_000)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Ok(result /*: Vec<Value>*/) = result {
+        assert_eq!(result.len(), 1);
+        assert!(result[0].is_value());
+        unsafe {
+            assert_eq!(result[0].vt_data.tag & (VALUE_TYPE_TAG_MASK as usize),
+                       ValueTypeTag::Int as usize);
+            assert_eq!(result[0].vt_data.inner.int_value, 500_500);
+        }
+    } else {
+        panic!()
+    }
+}
+
+async fn globals() {
+    let globals_program: CompiledProgram<DefaultAlloc> = globals_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &globals_program).await;
+    assert!(vm_thread.vm.get_shared_data_mut().set_global("counter", Value::new_int(41)));
+    assert!(!vm_thread.vm.get_shared_data_mut().set_global("no_such_var", Value::new_int(0)));
+
+    for expected /*: i64*/ in 42..=44 {
+        let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) = (&mut vm_thread, 0, &[]);
+        let result: Result<Vec<Value>, Exception> = unsafe {
+            vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+                .expect_silent("damn it")
+                .await
+                .into_inner()
+        };
+        if let Ok(result /*: Vec<Value>*/) = result {
+            assert_eq!(result.len(), 1);
+            unsafe {
+                assert_eq!(result[0].vt_data.inner.int_value, expected);
+            }
+        } else {
+            panic!()
+        }
+    }
+
+    let counter: Value = vm_thread.vm.get_shared_data_mut().get_global("counter").unwrap();
+    unsafe {
+        assert_eq!(counter.vt_data.tag & (VALUE_TYPE_TAG_MASK as usize),
+                   ValueTypeTag::Int as usize);
+        assert_eq!(counter.vt_data.inner.int_value, 44);
+    }
+}
+
 async fn exception_no_eh_call() {
     let exception_no_eh_program: CompiledProgram<DefaultAlloc> = exception_no_eh_program();
     let alloc: DefaultAlloc = DefaultAlloc::new();
@@ -130,6 +256,48 @@ async fn exception_no_eh_call() {
     }
 }
 
+#[cfg(feature = "compiler")]
+async fn exception_symbolized_trace() {
+    let source: &str =
+        "func foo() int {\n    return bar();\n}\nfunc bar() {\n    throw object();\n}\n";
+    let mut source_mgr: SourceManager = SourceManager::new();
+    let file_id: u32 = source_mgr.add_file("test.pr47", source);
+
+    let mut exception_no_eh_program: CompiledProgram<DefaultAlloc> = exception_no_eh_program();
+    exception_no_eh_program.debug_info = Some(Box::new(DebugInfo::new(
+        boxed_slice!["foo".into(), "bar".into()],
+        boxed_slice![
+            SourceLoc::new(file_id, 28),
+            SourceLoc::new(file_id, 21),
+            SourceLoc::new(file_id, 60),
+            SourceLoc::new(file_id, 54)
+        ],
+        boxed_slice![boxed_slice![None], boxed_slice![Some("e".into())]]
+    )));
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &exception_no_eh_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) = (&mut vm_thread, 0, &[]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+
+    if let Err(e /*: Exception*/) = result {
+        let rendered: String = e.render_trace(&exception_no_eh_program, &source_mgr);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("uncaught exception: "));
+        assert_eq!(lines[1], "    at bar (test.pr47:5:5)");
+        assert_eq!(lines[2], "    at foo (test.pr47:2:12)");
+    } else {
+        panic!()
+    }
+}
+
 async fn exception_call() {
     let exception_program: CompiledProgram<DefaultAlloc> = exception_program();
     let alloc: DefaultAlloc = DefaultAlloc::new();
@@ -199,6 +367,454 @@ async fn ffi_call2() {
     }
 }
 
+async fn ffi_bind_call() {
+    let ffi_bind_program: CompiledProgram<DefaultAlloc> = ffi_bind_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &ffi_bind_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 0, &[Value::new_int(114514), Value::new_int(2)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Ok(result /*: Vec<Value>*/) = result {
+        assert_eq!(result.len(), 1);
+        assert_eq!(unsafe { result[0].vt_data.inner.int_value }, 114514 / 2);
+    } else {
+        panic!()
+    }
+
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 0, &[Value::new_int(114514), Value::new_int(0)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Err(e /*: Exception*/) = result {
+        unsafe {
+            let checked: Value = e.assert_checked();
+            let dyn_base: *mut dyn DynBase = checked.get_as_dyn_base();
+            assert_eq!(dyn_base.as_ref().unwrap().dyn_type_id(), TypeId::of::<String>());
+            assert_eq!(&*checked.get_as_mut_ptr::<String>(), "divided by zero");
+        }
+    } else {
+        panic!()
+    }
+}
+
+async fn method_call() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let method_call_program: CompiledProgram<DefaultAlloc> =
+        method_call_program(&mut tyck_info_pool);
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &method_call_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 0, &[Value::new_int(42)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Ok(result /*: Vec<Value>*/) = result {
+        assert_eq!(result.len(), 1);
+        assert_eq!(unsafe { result[0].vt_data.inner.int_value }, 42);
+    } else {
+        panic!()
+    }
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(DefaultAlloc::new(), &method_call_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 1, &[Value::new_int(42)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Err(e /*: Exception*/) = result {
+        assert!(matches!(e.inner,
+                         ExceptionInner::Unchecked(UncheckedException::UnexpectedNull { .. })));
+    } else {
+        panic!()
+    }
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(DefaultAlloc::new(), &method_call_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 2, &[Value::new_int(42)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Err(e /*: Exception*/) = result {
+        assert!(matches!(
+            e.inner,
+            ExceptionInner::Unchecked(UncheckedException::MethodNotFound { method_id: 0, .. })
+        ));
+    } else {
+        panic!()
+    }
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(DefaultAlloc::new(), &method_call_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 0, &[Value::new_float(4.2)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Err(e /*: Exception*/) = result {
+        assert!(matches!(e.inner,
+                         ExceptionInner::Unchecked(UncheckedException::TypeCheckFailure { .. })));
+    } else {
+        panic!()
+    }
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(DefaultAlloc::new(), &method_call_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 3, &[Value::new_int(42)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Err(e /*: Exception*/) = result {
+        assert!(matches!(
+            e.inner,
+            ExceptionInner::Unchecked(UncheckedException::ArgCountMismatch {
+                func_id: 0, expected: 2, got: 1
+            })
+        ));
+    } else {
+        panic!()
+    }
+}
+
+async fn run_property_program(
+    program: &CompiledProgram<DefaultAlloc>,
+    func_id: usize,
+    args: &[Value]
+) -> Result<Vec<Value>, Exception> {
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(DefaultAlloc::new(), program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, func_id, args);
+    unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    }
+}
+
+async fn property_access() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let program: CompiledProgram<DefaultAlloc> = property_program(&mut tyck_info_pool);
+    let point: Value = Value::new_owned(TestPoint { x: 114, label: "point".to_string() });
+
+    let result: Vec<Value> = run_property_program(&program, 0, &[point]).await.unwrap();
+    assert_eq!(unsafe { result[0].vt_data.inner.int_value }, 114);
+
+    let result: Vec<Value> =
+        run_property_program(&program, 1, &[point, Value::new_int(514)]).await.unwrap();
+    assert_eq!(unsafe { result[0].vt_data.inner.int_value }, 514);
+    assert_eq!(unsafe { (*point.get_as_mut_ptr::<TestPoint>()).x }, 514);
+
+    let result: Vec<Value> = run_property_program(&program, 3, &[Value::new_int(42)])
+        .await
+        .unwrap();
+    assert_eq!(unsafe { result[0].vt_data.inner.int_value }, 42);
+
+    let e: Exception =
+        run_property_program(&program, 1, &[point, Value::new_bool(true)]).await.unwrap_err();
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::TypeCheckFailure { .. })
+    ));
+
+    let e: Exception =
+        run_property_program(&program, 2, &[point, Value::new_int(1)]).await.unwrap_err();
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::ReadOnlyProperty { property_id: 1, .. })
+    ));
+
+    let e: Exception = run_property_program(&program, 0, &[Value::new_int(42)])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::PropertyNotFound { property_id: 0, .. })
+    ));
+
+    let e: Exception = run_property_program(&program, 0, &[Value::new_null()])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::UnexpectedNull { .. })
+    ));
+
+    unsafe { point.set_ownership_info(OwnershipInfo::SharedToRust); }
+    let result: Vec<Value> = run_property_program(&program, 0, &[point]).await.unwrap();
+    assert_eq!(unsafe { result[0].vt_data.inner.int_value }, 514);
+    let e: Exception =
+        run_property_program(&program, 1, &[point, Value::new_int(1919)]).await.unwrap_err();
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::OwnershipCheckFailure {
+            expected_mask: OWN_INFO_WRITE_MASK,
+            ..
+        })
+    ));
+    assert_eq!(unsafe { (*point.get_as_mut_ptr::<TestPoint>()).x }, 514);
+
+    unsafe {
+        point.set_ownership_info(OwnershipInfo::VMOwned);
+        let test_point: TestPoint = point.move_out_norm();
+        assert_eq!(test_point.label, "point");
+        drop(Box::from_raw(point.ptr));
+    }
+}
+
+async fn run_iter_program(
+    program: &CompiledProgram<DefaultAlloc>,
+    func_id: usize,
+    args: &[Value]
+) -> Result<Vec<Value>, Exception> {
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(DefaultAlloc::new(), program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, func_id, args);
+    unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    }
+}
+
+async fn iter_loop() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let program: CompiledProgram<DefaultAlloc> = iter_program(&mut tyck_info_pool);
+
+    let range: Value = Value::new_owned(TestRange { end: 5 });
+    let result: Vec<Value> = run_iter_program(&program, 0, &[range]).await.unwrap();
+    assert_eq!(unsafe { result[0].vt_data.inner.int_value }, 10);
+
+    let int_type: NonNull<TyckInfo> = tyck_info_pool.get_int_type();
+    let vec_vt: GenericTypeVT = create_vm_vec_vt(&mut tyck_info_pool, int_type);
+    let vec: Value = Value::new_container(vec_ctor(), &vec_vt as _);
+    unsafe {
+        let vec: &mut VMGenericVec = &mut *vec.get_as_mut_ptr();
+        vec.inner.extend([114, 514, 1919].iter().map(|x: &i64| Value::new_int(*x)));
+    }
+    let result: Vec<Value> = run_iter_program(&program, 0, &[vec]).await.unwrap();
+    assert_eq!(unsafe { result[0].vt_data.inner.int_value }, 2547);
+
+    let string: Value = Value::new_owned("héllo".to_string());
+    let result: Vec<Value> = run_iter_program(&program, 1, &[string]).await.unwrap();
+    unsafe {
+        assert_eq!(result[0].vt_data.inner.int_value, 5);
+        assert_eq!(result[1].vt_data.inner.char_value, 'o');
+    }
+
+    let mut object: Object = Object::new();
+    object.fields.insert("a".to_string(), Value::new_int(1));
+    object.fields.insert("b".to_string(), Value::new_int(2));
+    let object: Value = Value::new_owned(object);
+    // field names are allocated by the VM, so keep the VM alive while checking them
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(DefaultAlloc::new(), &program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) = (&mut vm_thread, 1, &[object]);
+    unsafe {
+        let result: Vec<Value> =
+            vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+                .expect_silent("damn it")
+                .await
+                .into_inner()
+                .unwrap();
+        assert_eq!(result[0].vt_data.inner.int_value, 2);
+        let field_name: &String = &*(result[1].get_as_mut_ptr_norm() as *const _);
+        assert!(field_name == "a" || field_name == "b");
+    }
+    drop(vm_thread);
+
+    let e: Exception = run_iter_program(&program, 0, &[Value::new_int(42)]).await.unwrap_err();
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::NotIterable { .. })
+    ));
+
+    let e: Exception = run_iter_program(&program, 0, &[Value::new_null()]).await.unwrap_err();
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::UnexpectedNull { .. })
+    ));
+
+    unsafe {
+        (vec_vt.drop_fn)(vec.untagged_ptr_field() as *mut _);
+        for value /*: &Value*/ in [range, string, object].iter() {
+            drop(Box::from_raw(value.ptr));
+        }
+    }
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+async fn map_ops() {
+    let map_program: CompiledProgram<DefaultAlloc> = map_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &map_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) = (&mut vm_thread, 0, &[]);
+    let result: Vec<Value> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+            .unwrap()
+    };
+
+    assert_eq!(result.len(), 6);
+    unsafe {
+        assert_eq!(result[0].vt_data.inner.int_value, 1919);
+        assert!(result[1].vt_data.inner.bool_value);
+        assert!(!result[2].vt_data.inner.bool_value);
+        assert_eq!(result[3].vt_data.inner.int_value, 1);
+        assert!(result[4].is_null());
+
+        assert!(result[5].is_container());
+        let keys: &VMGenericVec = &*(result[5].get_as_mut_ptr() as *const _);
+        assert_eq!(keys.inner.len(), 1);
+        assert_eq!(keys.inner[0].vt_data.inner.int_value, 1);
+    }
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+async fn vec_ops() {
+    let vec_program: CompiledProgram<DefaultAlloc> = vec_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &vec_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) = (&mut vm_thread, 0, &[]);
+    let result: Vec<Value> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+            .unwrap()
+    };
+
+    assert_eq!(result.len(), 6);
+    unsafe {
+        let vec: &VMGenericVec = &*(result[0].get_as_mut_ptr() as *const _);
+        let vec: Vec<i64> = vec.inner.iter()
+            .map(|value: &Value| value.vt_data.inner.int_value)
+            .collect();
+        assert_eq!(vec, [3, 2, 3]);
+
+        assert_eq!(result[1].vt_data.inner.int_value, 5);
+        assert_eq!(result[2].vt_data.inner.int_value, 1);
+        assert!(!result[3].vt_data.inner.bool_value);
+
+        assert!(result[4].is_container());
+        assert_eq!(result[4].ptr_repr.trivia, result[0].ptr_repr.trivia);
+        let sliced: &VMGenericVec = &*(result[4].get_as_mut_ptr() as *const _);
+        let sliced: Vec<i64> = sliced.inner.iter()
+            .map(|value: &Value| value.vt_data.inner.int_value)
+            .collect();
+        assert_eq!(sliced, [2, 3]);
+
+        assert_eq!(result[5].vt_data.inner.int_value, 0);
+    }
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+async fn vec_ops_exception(
+    vec_program: CompiledProgram<DefaultAlloc>,
+    func_id: usize
+) -> Exception {
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &vec_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, func_id, &[]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Err(e /*: Exception*/) = result {
+        e
+    } else {
+        panic!()
+    }
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+async fn vec_pop_empty() {
+    let e: Exception = vec_ops_exception(vec_program(), 2).await;
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::IndexOutOfBounds { index: -1, .. })
+    ));
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+async fn vec_sort_throw() {
+    let e: Exception = vec_ops_exception(vec_program(), 3).await;
+    assert!(matches!(e.inner, ExceptionInner::Unchecked(UncheckedException::DivideByZero)));
+    assert_eq!(e.trace.len(), 2);
+    assert_eq!(e.trace[0].func_id, 4);
+    assert_eq!(e.trace[1].func_id, 3);
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+async fn vec_sort_await() {
+    let program: CompiledProgram<DefaultAlloc> = vec_sort_callback_program();
+    assert!(!program.may_await(0));
+    assert!(program.may_await(1));
+    assert!(program.may_await(2));
+    assert!(!program.may_await(4));
+
+    let e: Exception = vec_ops_exception(program, 0).await;
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::AsyncCallback { func_id: 1 })
+    ));
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+async fn vec_sort_no_ret() {
+    let e: Exception = vec_ops_exception(vec_sort_callback_program(), 3).await;
+    assert!(matches!(
+        e.inner,
+        ExceptionInner::Unchecked(UncheckedException::RetCountMismatch {
+            func_id: 4, expected: 1, got: 0
+        })
+    ));
+}
+
 #[cfg(feature = "async")]
 async fn async_ffi_call() {
     let async_ffi_call_program: CompiledProgram<DefaultAlloc> = async_ffi_call_program();
@@ -242,6 +858,210 @@ async fn async_spawn() {
     }
 }
 
+#[cfg(feature = "async")]
+async fn async_spawn_await() {
+    let async_spawn_await_program: CompiledProgram<DefaultAlloc> = async_spawn_await_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &async_spawn_await_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 0, &[Value::new_int(21)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Ok(result /*: Vec<Value>*/) = result {
+        assert_eq!(result.len(), 1);
+        unsafe {
+            assert_eq!(result[0].vt_data.tag & (VALUE_TYPE_TAG_MASK as usize),
+                       ValueTypeTag::Int as usize);
+            assert_eq!(result[0].vt_data.inner.int_value, 42);
+        }
+    } else {
+        panic!()
+    }
+}
+
+#[cfg(feature = "async")]
+async fn async_spawn_await_exception() {
+    let async_spawn_await_program: CompiledProgram<DefaultAlloc> = async_spawn_await_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &async_spawn_await_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) =
+        (&mut vm_thread, 1, &[Value::new_int(21)]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Err(e /*: Exception*/) = result {
+        assert!(matches!(e.inner, ExceptionInner::Unchecked(UncheckedException::DivideByZero)));
+    } else {
+        panic!()
+    }
+}
+
+#[cfg(feature = "async")]
+async fn async_spawn_cancel() {
+    let async_spawn_await_program: CompiledProgram<DefaultAlloc> = async_spawn_await_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &async_spawn_await_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) = (&mut vm_thread, 2, &[]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Err(e /*: Exception*/) = result {
+        assert!(matches!(
+            e.inner,
+            ExceptionInner::Unchecked(UncheckedException::TaskCancelled { .. })
+        ));
+    } else {
+        panic!()
+    }
+}
+
+#[cfg(feature = "async")]
+async fn async_channel() {
+    let async_channel_program: CompiledProgram<DefaultAlloc> = async_channel_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &async_channel_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) = (&mut vm_thread, 0, &[]);
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Ok(result /*: Vec<Value>*/) = result {
+        assert_eq!(result.len(), 3);
+        unsafe {
+            assert_eq!(result[0].vt_data.inner.int_value, 13);
+            assert_eq!(result[1].vt_data.inner.int_value, 42);
+            assert!(!result[2].vt_data.inner.bool_value);
+        }
+    } else {
+        panic!()
+    }
+}
+
+#[cfg(feature = "async")]
+async fn async_timeout(sleep_millis: i64, timeout_millis: i64, expect_timeout: bool) {
+    let async_timeout_program: CompiledProgram<DefaultAlloc> = async_timeout_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &async_timeout_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) = (
+        &mut vm_thread,
+        0,
+        &[Value::new_int(sleep_millis), Value::new_int(timeout_millis)]
+    );
+    let result: Result<Vec<Value>, Exception> = unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    };
+    if let Ok(result /*: Vec<Value>*/) = result {
+        assert_eq!(result.len(), 1);
+        unsafe { assert_eq!(result[0].vt_data.inner.bool_value, expect_timeout); }
+    } else {
+        panic!()
+    }
+}
+
+#[cfg(feature = "async")]
+async fn async_task_group(func_id: usize) -> Result<Vec<Value>, Exception> {
+    let async_task_group_program: CompiledProgram<DefaultAlloc> = async_task_group_program();
+    let alloc: DefaultAlloc = DefaultAlloc::new();
+
+    let mut vm_thread: Box<VMThread<DefaultAlloc>> =
+        create_vm_main_thread(alloc, &async_task_group_program).await;
+    let arg_pack: (&mut VMThread<DefaultAlloc>, usize, &[Value]) = (&mut vm_thread, func_id, &[]);
+    unsafe {
+        vm_thread_run_function::<_, false>(UncheckedSendSync::new(arg_pack))
+            .expect_silent("damn it")
+            .await
+            .into_inner()
+    }
+}
+
+#[cfg(feature = "async")]
+async fn async_task_group_ok() {
+    if let Ok(result /*: Vec<Value>*/) = async_task_group(0).await {
+        assert_eq!(result.len(), 0);
+    } else {
+        panic!()
+    }
+}
+
+#[cfg(feature = "async")]
+async fn async_task_group_fail() {
+    if let Err(e /*: Exception*/) = async_task_group(1).await {
+        assert!(matches!(e.inner, ExceptionInner::Unchecked(UncheckedException::DivideByZero)));
+    } else {
+        panic!()
+    }
+}
+
+/// One member of the group fails, while the others are blocked on a long sleep and on a channel
+/// never written. The blocked members should get cancelled, instead of hanging the group.
+#[cfg(feature = "async")]
+async fn async_task_group_fail_blocked() {
+    if let Err(e /*: Exception*/) = async_task_group(5).await {
+        assert!(matches!(e.inner, ExceptionInner::Unchecked(UncheckedException::DivideByZero)));
+    } else {
+        panic!()
+    }
+}
+
+#[cfg(not(feature = "async-sim"))]
+async fn isolates_parallel() {
+    fn fibonacci(n: i64) -> i64 {
+        if n <= 1 { n.max(0) } else { fibonacci(n - 1) + fibonacci(n - 2) }
+    }
+
+    let program: Arc<CompiledProgram<DefaultAlloc>> = Arc::new(fibonacci_program());
+
+    let handles: Vec<JoinHandle<(i64, i64)>> = (0..8).map(|i: i64| {
+        let program: Arc<CompiledProgram<DefaultAlloc>> = program.clone();
+        tokio::spawn(async move {
+            let mut isolate: Isolate<DefaultAlloc> =
+                Isolate::new(DefaultAlloc::new(), program).await.expect_silent("damn it");
+            let n: i64 = 15 + i;
+            // arguments are copied before `run_function` returns, the array is not kept alive
+            let fut = isolate.run_function(0, &[Value::new_int(n)]);
+            let result: Vec<Value> = fut.await.expect_silent("damn it");
+            assert_eq!(result.len(), 1);
+            unsafe {
+                assert_eq!(result[0].vt_data.tag & (VALUE_TYPE_TAG_MASK as usize),
+                           ValueTypeTag::Int as usize);
+                (n, result[0].vt_data.inner.int_value)
+            }
+        })
+    }).collect();
+
+    for handle /*: JoinHandle<(i64, i64)>*/ in handles {
+        let (n, result) = handle.await.unwrap();
+        assert_eq!(result, fibonacci(n));
+    }
+
+    assert_eq!(Arc::strong_count(&program), 1);
+}
+
 #[test] fn test_basic_program_eval() {
     block_on_future(basic_program_eval());
 }
@@ -254,10 +1074,27 @@ async fn async_spawn() {
     block_on_future(fibonacci_call());
 }
 
+#[test] fn test_tail_call() {
+    block_on_future(tail_call());
+}
+
+#[test] fn test_tail_call_ptr() {
+    block_on_future(tail_call_ptr());
+}
+
+#[test] fn test_globals() {
+    block_on_future(globals());
+}
+
 #[test] fn test_exception_no_eh() {
     block_on_future(exception_no_eh_call());
 }
 
+#[cfg(feature = "compiler")]
+#[test] fn test_exception_symbolized_trace() {
+    block_on_future(exception_symbolized_trace());
+}
+
 #[test] fn test_exception() {
     block_on_future(exception_call());
 }
@@ -266,6 +1103,89 @@ async fn async_spawn() {
 
 #[test] fn test_ffi_call2() { block_on_future(ffi_call2()); }
 
+#[test] fn test_ffi_bind_call() { block_on_future(ffi_bind_call()); }
+
+#[test] fn test_method_call() { block_on_future(method_call()); }
+
+#[test] fn test_property_access() { block_on_future(property_access()); }
+
+#[test] fn test_iter_loop() { block_on_future(iter_loop()); }
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+#[test] fn test_map_ops() { block_on_future(map_ops()); }
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+#[test] fn test_vec_ops() { block_on_future(vec_ops()); }
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+#[test] fn test_vec_pop_empty() { block_on_future(vec_pop_empty()); }
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+#[test] fn test_vec_sort_throw() { block_on_future(vec_sort_throw()); }
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+#[test] fn test_vec_sort_await() { block_on_future(vec_sort_await()); }
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+#[test] fn test_vec_sort_no_ret() { block_on_future(vec_sort_no_ret()); }
+
+#[test] fn test_ffi_bind_signature() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let signature: Signature = Pr47Binder_ffi_checked_div::signature(&mut tyck_info_pool);
+    assert!(signature.param_options[..] == [DataOption::Copy, DataOption::Copy]);
+    assert!(signature.ret_option[..] == [DataOption::Copy]);
+    assert!(signature.accepts_arg_count(2));
+    assert!(signature.variadic.is_none());
+}
+
+#[test] fn test_print_bind_signature() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let signature: Signature = <PrintBind as FunctionBase>::signature(&mut tyck_info_pool);
+    assert!(signature.param_options.is_empty());
+    assert!(signature.ret_option.is_empty());
+    assert_eq!(signature.ret_arity, RetArity::Fixed);
+
+    let variadic: &VariadicTail = signature.variadic.as_ref().unwrap();
+    assert!(matches!(variadic.var_type, VariadicType::Any));
+    assert!(variadic.data_option == DataOption::Share);
+    assert_eq!(variadic.min_count, 0);
+    assert!(signature.accepts_arg_count(0));
+    assert!(signature.accepts_arg_count(3));
+}
+
+#[test] fn test_join_select_bind_signature() {
+    let mut tyck_info_pool: TyckInfoPool = TyckInfoPool::new();
+    let join_signature: Signature =
+        <JoinBind as AsyncFunctionBase>::signature(&mut tyck_info_pool);
+    let select_signature: Signature =
+        <SelectBind as AsyncFunctionBase>::signature(&mut tyck_info_pool);
+    // `select` returns the index of the first resolved promise before its results
+    assert!(join_signature.ret_option.is_empty());
+    assert!(select_signature.ret_option[..] == [DataOption::Copy]);
+    assert_eq!(join_signature.ret_arity, RetArity::FixedThenEachPromise);
+    assert_eq!(select_signature.ret_arity, RetArity::FixedThenOnePromise);
+
+    // interrupting wrappers return the results of the wrapped promise
+    let timeout_signature: Signature =
+        <TimeoutBind as AsyncFunctionBase>::signature(&mut tyck_info_pool);
+    let attach_signature: Signature =
+        <CancelScopeAttachBind as AsyncFunctionBase>::signature(&mut tyck_info_pool);
+    assert_eq!(timeout_signature.ret_arity, RetArity::FixedThenPromiseParam(0));
+    assert_eq!(attach_signature.ret_arity, RetArity::FixedThenPromiseParam(1));
+
+    for signature /*: &Signature*/ in [&join_signature, &select_signature] {
+        assert!(signature.param_options.is_empty());
+
+        let variadic: &VariadicTail = signature.variadic.as_ref().unwrap();
+        assert!(matches!(variadic.var_type, VariadicType::AnyPromise));
+        assert!(variadic.data_option == DataOption::Move);
+        assert_eq!(variadic.min_count, 1);
+        assert!(!signature.accepts_arg_count(0));
+        assert!(signature.accepts_arg_count(1));
+        assert!(signature.accepts_arg_count(2));
+    }
+}
+
 #[cfg(feature = "async")]
 #[test] fn test_async_ffi_call() {
     block_on_future(async_ffi_call())
@@ -276,3 +1196,53 @@ async fn async_spawn() {
     let fut = async_spawn();
     block_on_future(fut)
 }
+
+#[cfg(feature = "async")]
+#[test] fn test_async_spawn_await() {
+    block_on_future(async_spawn_await());
+}
+
+#[cfg(feature = "async")]
+#[test] fn test_async_spawn_await_exception() {
+    block_on_future(async_spawn_await_exception());
+}
+
+#[cfg(feature = "async")]
+#[test] fn test_async_spawn_cancel() {
+    block_on_future(async_spawn_cancel());
+}
+
+#[cfg(feature = "async")]
+#[test] fn test_async_channel() {
+    block_on_future(async_channel());
+}
+
+#[cfg(feature = "async")]
+#[test] fn test_async_timeout() {
+    block_on_future(async_timeout(1000, 10, true));
+    block_on_future(async_timeout(10, 1000, false));
+}
+
+#[cfg(feature = "async")]
+#[test] fn test_async_task_group_ok() {
+    block_on_future(async_task_group_ok());
+}
+
+#[cfg(feature = "async")]
+#[test] fn test_async_task_group_fail() {
+    block_on_future(async_task_group_fail());
+}
+
+#[cfg(feature = "async")]
+#[test] fn test_async_task_group_fail_blocked() {
+    block_on_future(async_task_group_fail_blocked());
+}
+
+#[cfg(not(feature = "async-sim"))]
+#[test] fn test_isolates_parallel() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .build()
+        .unwrap()
+        .block_on(isolates_parallel());
+}
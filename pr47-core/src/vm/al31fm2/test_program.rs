@@ -1,15 +1,35 @@
+use std::any::TypeId;
+use std::ptr::NonNull;
+
+use pr47_codegen::{StaticBase, pr47_function_bind};
 use xjbutil::boxed_slice;
 use xjbutil::slice_arena::SliceArena;
 use xjbutil::void::Void;
 
+use crate::builtins::closure::create_closure_vt;
 use crate::builtins::object::Object;
 use crate::data::Value;
+use crate::data::generic::GenericTypeVT;
 use crate::data::traits::StaticBase;
 use crate::data::tyck::TyckInfoPool;
 use crate::ffi::{FFIException, Signature};
+use crate::ffi::iter::{HostIterable, ITER_ELEM_METHOD, IterElem};
+use crate::ffi::property::{PropertyGetter, PropertySetter};
 use crate::ffi::sync_fn::{FunctionBase, OwnershipGuard, VMContext, value_into_ref};
+use crate::ffi::sync_fn::Function as FFIFunction;
+use crate::vm::al31fm2::Combustor;
 use crate::vm::al31fm2::alloc::Alloc;
-use crate::vm::al31fm2::compiled::{CompiledFunction, CompiledProgram, ExceptionHandlingBlock};
+use crate::vm::al31fm2::compiled::{
+    CompiledFunction,
+    CompiledProgram,
+    ExceptionHandlingBlock,
+    ExportTable,
+    Method,
+    MethodFunc,
+    MethodTable,
+    Property,
+    PropertyTable
+};
 use crate::vm::al31fm2::insc::Insc;
 
 #[cfg(feature = "async")] use crate::data::exception::ExceptionInner;
@@ -20,8 +40,16 @@ use crate::vm::al31fm2::insc::Insc;
     Promise
 };
 #[cfg(feature = "async")] use crate::ffi::async_fn::LockedCtx;
-#[cfg(feature = "async")] use crate::std47::futures::SLEEP_MS_BIND;
+#[cfg(feature = "async")] use crate::std47::channel::{
+    CLOSE_BIND,
+    NEW_CHANNEL_BIND,
+    RECV_BIND,
+    SEND_BIND
+};
+#[cfg(feature = "async")] use crate::std47::futures::{SLEEP_MS_BIND, TIMEOUT_BIND, TimeoutError};
 #[cfg(feature = "async")] use crate::std47::io::PRINT_BIND;
+#[cfg(feature = "al31fm2-builtin-ops")] use crate::builtins::map::{create_vm_map_vt, map_ctor};
+#[cfg(feature = "al31fm2-builtin-ops")] use crate::builtins::vec::{create_vm_vec_vt, vec_ctor};
 
 pub fn basic_program<A: Alloc>() -> CompiledProgram<A> {
     let (slice_arena, code) = unsafe {
@@ -37,13 +65,88 @@ pub fn basic_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 2, 1, 2, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        #[cfg(feature = "async")]
+        async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
+    }
+}
+
+pub fn echo_program<A: Alloc>() -> CompiledProgram<A> {
+    let (slice_arena, code) = unsafe {
+        let slice_arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+            Insc::Return(slice_arena.unsafe_make(&[0, 1]))
+        ];
+        (slice_arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 2, 2, 2, boxed_slice![])
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![],
         #[cfg(feature = "async")]
         async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
+    }
+}
+
+pub fn init_proc_program<A: Alloc>() -> CompiledProgram<A> {
+    let code: Box<[Insc]> = boxed_slice![
+                                                             // init()
+        /*00*/ Insc::MakeIntConst(6, 0),                     // %0 = $6
+        /*01*/ Insc::MakeIntConst(7, 1),                     // %1 = $7
+        /*02*/ Insc::MulInt(0, 1, 0),                        // %0 = mul int %0, %1
+        /*03*/ Insc::SaveConst(0, 0),                        // store .0, %0
+        /*04*/ Insc::ReturnNothing,                          // ret
+
+                                                             // get() -> (int)
+        /*05*/ Insc::LoadConst(0, 0),                        // %0 = load .0
+        /*06*/ Insc::ReturnOne(0),                           // ret %0
+
+                                                             // set(%0)
+        /*07*/ Insc::SaveConst(0, 0),                        // store .0, %0
+        /*08*/ Insc::ReturnNothing                           // ret
+    ];
+
+    CompiledProgram {
+        slice_arena: SliceArena::new(),
+        code,
+        const_pool: boxed_slice![Value::new_null()],
+        globals: boxed_slice![],
+        init_proc: Some(0),
+        functions: boxed_slice![
+            CompiledFunction::new(0, 0, 0, 2, boxed_slice![]), // init
+            CompiledFunction::new(5, 0, 1, 1, boxed_slice![]), // get
+            CompiledFunction::new(7, 1, 0, 1, boxed_slice![])  // set
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        #[cfg(feature = "async")]
+        async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
     }
 }
 
@@ -69,14 +172,20 @@ pub fn basic_fn_call_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 0, 1, 2, boxed_slice![]), // application_start
             CompiledFunction::new(4, 2, 1, 2, boxed_slice![]), // sum
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![],
         #[cfg(feature = "async")]
         async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
     }
 }
 
@@ -108,13 +217,152 @@ pub fn fibonacci_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 1, 1, 4, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        #[cfg(feature = "async")]
+        async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
+    }
+}
+
+pub fn tail_call_program<A: Alloc>() -> CompiledProgram<A> {
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                              // application_start(%0) -> (int)
+            /*00*/ Insc::MakeIntConst(0, 1),                  // %1 = $0
+            /*01*/ Insc::TailCall(1, arena.unsafe_make(&[0, 1])), // tail-call sum(%0, %1)
+
+                                                              // sum(%0, %1) -> (int)
+            /*02*/ Insc::MakeIntConst(0, 2),                  // %2 = $0
+            /*03*/ Insc::EqValue(0, 2, 3),                    // %3 = eq int %0, %2
+            /*04*/ Insc::JumpIfTrue(3, 8),                    // if %3 goto L.8
+            /*05*/ Insc::AddInt(1, 0, 1),                     // %1 = add int %1, %0
+            /*06*/ Insc::DecrInt(0),                          // %0 = decr %0
+            /*07*/ Insc::TailCall(1, arena.unsafe_make(&[0, 1])), // tail-call sum(%0, %1)
+            /*08*/ Insc::ReturnOne(1)                         // return %1
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 1, 1, 2, boxed_slice![]), // application_start
+            CompiledFunction::new(2, 2, 1, 4, boxed_slice![])  // sum
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        #[cfg(feature = "async")]
+        async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
+    }
+}
+
+fn leak_closure_vt() -> NonNull<GenericTypeVT> {
+    let tyck_info_pool: &'static mut TyckInfoPool = Box::leak(Box::new(TyckInfoPool::new()));
+    let int_type = tyck_info_pool.get_int_type();
+    let vt: GenericTypeVT = create_closure_vt(tyck_info_pool, &[int_type]);
+    NonNull::from(Box::leak(Box::new(vt)))
+}
+
+pub fn tail_call_ptr_program<A: Alloc>() -> CompiledProgram<A> {
+    let closure_vt: NonNull<GenericTypeVT> = leak_closure_vt();
+
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                              // application_start(%0) -> (int)
+            /*00*/ Insc::MakeIntConst(0, 1),                  // %1 = $0
+            /*01*/ Insc::MakeIntConst(1, 2),                  // %2 = func sum_ptr
+            /*02*/ Insc::TailCallPtr(2, arena.unsafe_make(&[0, 1])), // tail-call %2(%0, %1)
+
+                                                              // sum_ptr(%0, %1) -> (int)
+            /*03*/ Insc::MakeIntConst(0, 2),                  // %2 = $0
+            /*04*/ Insc::EqValue(0, 2, 3),                    // %3 = eq int %0, %2
+            /*05*/ Insc::JumpIfTrue(3, 9),                    // if %3 goto L.9
+            /*06*/ Insc::MakeIntConst(1, 2),                  // %2 = $1
+            /*07*/ Insc::CreateClosure(2, arena.unsafe_make(&[2]), closure_vt, 4),
+                                                              // %4 = closure sum_closure [%2]
+            /*08*/ Insc::TailCallPtr(4, arena.unsafe_make(&[0, 1])), // tail-call %4(%0, %1)
+            /*09*/ Insc::ReturnOne(1),                        // return %1
+
+                                                              // sum_closure[%0](%1, %2) -> (int)
+            /*10*/ Insc::AddInt(2, 1, 2),                     // %2 = add int %2, %1
+            /*11*/ Insc::SubInt(1, 0, 1),                     // %1 = sub int %1, %0
+            /*12*/ Insc::MakeIntConst(1, 3),                  // %3 = func sum_ptr
+            /*13*/ Insc::TailCallPtr(3, arena.unsafe_make(&[1, 2]))  // tail-call %3(%1, %2)
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 1, 1, 3, boxed_slice![]),  // application_start
+            CompiledFunction::new(3, 2, 1, 5, boxed_slice![]),  // sum_ptr
+            CompiledFunction::new(10, 2, 1, 4, boxed_slice![])  // sum_closure
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![],
         #[cfg(feature = "async")]
         async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
+    }
+}
+
+pub fn globals_program<A: Alloc>() -> CompiledProgram<A> {
+    let (slice_arena, code) = {
+        let slice_arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                            // increment() -> (int)
+            /*00*/ Insc::LoadGlobal(0, 0),  // %0 = load global G.0
+            /*01*/ Insc::IncrInt(0),        // %0 = incr %0
+            /*02*/ Insc::StoreGlobal(0, 0), // store global G.0, %0
+            /*03*/ Insc::ReturnOne(0)       // return %0
+        ];
+        (slice_arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice!["counter".to_string()],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 0, 1, 1, boxed_slice![]) // increment
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        #[cfg(feature = "async")]
+        async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
     }
 }
 
@@ -140,13 +388,19 @@ pub fn alloc_1m_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 0, 0, 4, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![],
         #[cfg(feature = "async")]
         async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
     }
 }
 
@@ -181,7 +435,8 @@ pub fn exception_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new_with_exc(0, 0, 1, 1, boxed_slice![], boxed_slice![
                 ExceptionHandlingBlock::new(0, 2, <Void as StaticBase<Object>>::type_id(), 3)
@@ -189,9 +444,14 @@ pub fn exception_program<A: Alloc>() -> CompiledProgram<A> {
             CompiledFunction::new(5, 0, 0, 0, boxed_slice![]),
             CompiledFunction::new(7, 0, 0, 1, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![],
         #[cfg(feature = "async")]
-        async_ffi_funcs: boxed_slice![]
+        async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
     }
 }
 
@@ -215,14 +475,20 @@ pub fn exception_no_eh_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 0, 1, 1, boxed_slice![]),
             CompiledFunction::new(2, 0, 1, 1, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![],
         #[cfg(feature = "async")]
-        async_ffi_funcs: boxed_slice![]
+        async_ffi_funcs: boxed_slice![],
+        #[cfg(feature = "compiler")]
+        debug_info: None
     }
 }
 
@@ -289,12 +555,17 @@ pub fn ffi_call_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 0, 0, 1, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![PR47BINDER_FFI_FUNCTION as _],
-        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![]
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
     }
 }
 
@@ -318,12 +589,17 @@ pub fn bench_raw_iter_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 0, 0, 5, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![],
-        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![]
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
     }
 }
 
@@ -352,12 +628,17 @@ pub fn bench_ffi_call_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 0, 0, 5, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![PR47BINDER_FFI_FUNCTION as _],
-        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![]
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
     }
 }
 
@@ -418,12 +699,53 @@ pub fn ffi_call_program2<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 2, 1, 2, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![PR47_BINDER_FFI_FUNCTION2 as _],
-        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![]
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
+    }
+}
+
+#[pr47_function_bind(local)]
+pub fn ffi_checked_div(a: i64, b: i64) -> Result<i64, String> {
+    a.checked_div(b).ok_or_else(|| "divided by zero".to_string())
+}
+
+pub fn ffi_bind_program<A: Alloc>() -> CompiledProgram<A> {
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                                 // application_start(%0, %1) -> i64
+            /*00*/ Insc::FFICallRtlc(0,                          // %0 = ffi-call-rtlc @0(%0, %1)
+                                     arena.unsafe_make(&[0, 1]),
+                                     arena.unsafe_make(&[0])),
+            /*01*/ Insc::ReturnOne(0)                            // return %0
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 2, 1, 2, boxed_slice![])
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![PR47BINDER_FFI_CHECKED_DIV as _],
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
     }
 }
 
@@ -458,12 +780,427 @@ pub fn bench_ffi_call_program2<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 0, 0, 6, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![PR47_BINDER_FFI_FUNCTION2 as _],
-        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![]
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
+    }
+}
+
+#[pr47_function_bind(local)]
+pub fn ffi_object_field_count(object: &Object, extra: i64) -> i64 {
+    object.fields.len() as i64 + extra
+}
+
+pub fn method_call_program<A: Alloc>(tyck_info_pool: &mut TyckInfoPool) -> CompiledProgram<A> {
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                                 // call_on_object(%0) -> i64
+            /*00*/ Insc::CreateObject(1),                        // %1 = create-object
+            /*01*/ Insc::CallMethod(0,                           // %0 = call-method M.0(%1, %0)
+                                    arena.unsafe_make(&[1, 0]),
+                                    arena.unsafe_make(&[0])),
+            /*02*/ Insc::ReturnOne(0),                           // return %0
+                                                                 // call_on_null(%0) -> i64
+            /*03*/ Insc::MakeNull(1),                            // %1 = null
+            /*04*/ Insc::CallMethod(0,                           // %0 = call-method M.0(%1, %0)
+                                    arena.unsafe_make(&[1, 0]),
+                                    arena.unsafe_make(&[0])),
+            /*05*/ Insc::ReturnOne(0),                           // return %0
+                                                                 // call_on_int(%0) -> i64
+            /*06*/ Insc::CallMethod(0,                           // %0 = call-method M.0(%0, %0)
+                                    arena.unsafe_make(&[0, 0]),
+                                    arena.unsafe_make(&[0])),
+            /*07*/ Insc::ReturnOne(0),                           // return %0
+                                                                 // call_missing_arg(%0) -> i64
+            /*08*/ Insc::CreateObject(1),                        // %1 = create-object
+            /*09*/ Insc::CallMethod(0,                           // %0 = call-method M.0(%1)
+                                    arena.unsafe_make(&[1]),
+                                    arena.unsafe_make(&[0])),
+            /*10*/ Insc::ReturnOne(0)                            // return %0
+        ];
+        (arena, code)
+    };
+
+    let mut methods: MethodTable = MethodTable::new();
+    assert!(methods.add(TypeId::of::<Object>(), "fieldCount", Method {
+        func: MethodFunc::Sync(0),
+        mutable_self: false,
+        func_type: <Pr47Binder_ffi_object_field_count as FunctionBase>::signature(tyck_info_pool)
+            .func_type
+    }));
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 1, 1, 2, boxed_slice![]),
+            CompiledFunction::new(3, 1, 1, 2, boxed_slice![]),
+            CompiledFunction::new(6, 1, 1, 1, boxed_slice![]),
+            CompiledFunction::new(8, 1, 1, 2, boxed_slice![])
+        ],
+        exports: ExportTable::new(),
+        methods,
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![PR47BINDER_FFI_OBJECT_FIELD_COUNT as _],
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
+    }
+}
+
+#[derive(StaticBase)]
+#[pr47(local, name = "test.Point")]
+pub struct TestPoint {
+    pub x: i64,
+    pub label: String
+}
+
+pub fn property_program<A: Alloc>(tyck_info_pool: &mut TyckInfoPool) -> CompiledProgram<A> {
+    let (slice_arena, code) = {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                                 // get_x(%0) -> i64
+            /*00*/ Insc::GetProperty(0, 0, 1),                   // %1 = get-property P.0 %0
+            /*01*/ Insc::ReturnOne(1),                           // return %1
+                                                                 // set_x(%0, %1) -> i64
+            /*02*/ Insc::SetProperty(0, 0, 1),                   // set-property P.0 %0, %1
+            /*03*/ Insc::GetProperty(0, 0, 2),                   // %2 = get-property P.0 %0
+            /*04*/ Insc::ReturnOne(2),                           // return %2
+                                                                 // set_label(%0, %1)
+            /*05*/ Insc::SetProperty(1, 0, 1),                   // set-property P.1 %0, %1
+            /*06*/ Insc::ReturnNothing,                          // return
+                                                                 // object_x(%0) -> any
+            /*07*/ Insc::CreateObject(1),                        // %1 = create-object
+            /*08*/ Insc::SetProperty(0, 1, 0),                   // set-property P.0 %1, %0
+            /*09*/ Insc::GetProperty(0, 1, 2),                   // %2 = get-property P.0 %1
+            /*10*/ Insc::ReturnOne(2)                            // return %2
+        ];
+        (arena, code)
+    };
+
+    let ffi_funcs: Box<[&'static dyn FFIFunction<Combustor<A>>]> = boxed_slice![
+        Box::leak(Box::new(PropertyGetter::new(|point: &TestPoint| point.x))) as _,
+        Box::leak(Box::new(PropertySetter::new(|point: &mut TestPoint, x: i64| point.x = x))) as _,
+        Box::leak(Box::new(PropertyGetter::new(|point: &TestPoint| point.label.clone()))) as _
+    ];
+
+    let point_type: TypeId = TypeId::of::<TestPoint>();
+    let mut properties: PropertyTable = PropertyTable::new();
+    assert!(properties.add(point_type, "x", Property {
+        getter: 0,
+        setter: Some(1),
+        ty: tyck_info_pool.get_int_type()
+    }));
+    assert!(properties.add(point_type, "label", Property {
+        getter: 2,
+        setter: None,
+        ty: tyck_info_pool.get_string_type()
+    }));
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 1, 1, 2, boxed_slice![]),
+            CompiledFunction::new(2, 2, 1, 3, boxed_slice![]),
+            CompiledFunction::new(5, 2, 0, 2, boxed_slice![]),
+            CompiledFunction::new(7, 1, 1, 3, boxed_slice![])
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties,
+        ffi_funcs,
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
+    }
+}
+
+#[derive(StaticBase)]
+#[pr47(local, name = "test.Range")]
+pub struct TestRange {
+    pub end: i64
+}
+
+impl HostIterable for TestRange {
+    type Elem = i64;
+
+    fn iter_elem(&self, index: usize) -> Option<i64> {
+        if (index as i64) < self.end { Some(index as i64) } else { None }
+    }
+}
+
+pub fn iter_program<A: Alloc>(tyck_info_pool: &mut TyckInfoPool) -> CompiledProgram<A> {
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                                 // sum(%0) -> i64
+            /*00*/ Insc::MakeIntConst(0, 1),                     // %1 = $0
+            /*01*/ Insc::IterBegin(0, 2),                        // %2 = iter-begin %0
+            /*02*/ Insc::IterNext(2, 3, 5),                      // %3 = iter-next %2 else L.5
+            /*03*/ Insc::AddInt(1, 3, 1),                        // %1 = add int %1, %3
+            /*04*/ Insc::Jump(2),                                // goto L.2
+            /*05*/ Insc::ReturnOne(1),                           // return %1
+                                                                 // count_last(%0) -> (i64, any)
+            /*06*/ Insc::MakeIntConst(0, 1),                     // %1 = $0
+            /*07*/ Insc::MakeIntConst(1, 4),                     // %4 = $1
+            /*08*/ Insc::MakeNull(5),                            // %5 = null
+            /*09*/ Insc::IterBegin(0, 2),                        // %2 = iter-begin %0
+            /*10*/ Insc::IterNext(2, 3, 14),                     // %3 = iter-next %2 else L.14
+            /*11*/ Insc::AddInt(1, 4, 1),                        // %1 = add int %1, %4
+            /*12*/ Insc::Move(3, 5),                             // %5 = %3
+            /*13*/ Insc::Jump(10),                               // goto L.10
+            /*14*/ Insc::Return(arena.unsafe_make(&[1, 5]))      // return [%1, %5]
+        ];
+        (arena, code)
+    };
+
+    let iter_elem: &'static dyn FFIFunction<Combustor<A>> =
+        Box::leak(Box::new(IterElem::<TestRange>::new()));
+    let mut methods: MethodTable = MethodTable::new();
+    assert!(methods.add(TypeId::of::<TestRange>(), ITER_ELEM_METHOD, Method {
+        func: MethodFunc::Sync(0),
+        mutable_self: false,
+        func_type: iter_elem.signature(tyck_info_pool).func_type
+    }));
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 1, 1, 4, boxed_slice![]),
+            CompiledFunction::new(6, 1, 2, 6, boxed_slice![])
+        ],
+        exports: ExportTable::new(),
+        methods,
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![iter_elem],
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
+    }
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+fn leak_map_vts() ->(NonNull<GenericTypeVT>, NonNull<GenericTypeVT>) {
+    let tyck_info_pool: &'static mut TyckInfoPool = Box::leak(Box::new(TyckInfoPool::new()));
+    let int_type = tyck_info_pool.get_int_type();
+    let map_vt: GenericTypeVT = create_vm_map_vt(tyck_info_pool, int_type, int_type);
+    let keys_vt: GenericTypeVT = create_vm_vec_vt(tyck_info_pool, int_type);
+    (NonNull::from(Box::leak(Box::new(map_vt))), NonNull::from(Box::leak(Box::new(keys_vt))))
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+pub fn map_program<A: Alloc>() -> CompiledProgram<A> {
+    let (map_vt, keys_vt): (NonNull<GenericTypeVT>, NonNull<GenericTypeVT>) = leak_map_vts();
+
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                                 // map_ops()
+            /*00*/ Insc::CreateContainer(map_ctor, map_vt, 0),   // %0 = create-container map
+            /*01*/ Insc::MakeIntConst(1, 1),                     // %1 = $1
+            /*02*/ Insc::MakeIntConst(114, 2),                   // %2 = $114
+            /*03*/ Insc::MapPut(0, 1, 2),                        // map-put %0, %1, %2
+            /*04*/ Insc::MakeIntConst(2, 3),                     // %3 = $2
+            /*05*/ Insc::MakeIntConst(514, 4),                   // %4 = $514
+            /*06*/ Insc::MapPut(0, 3, 4),                        // map-put %0, %3, %4
+            /*07*/ Insc::MakeIntConst(1919, 2),                  // %2 = $1919
+            /*08*/ Insc::MapPut(0, 1, 2),                        // map-put %0, %1, %2
+            /*09*/ Insc::MapGet(0, 1, 5),                        // %5 = map-get %0, %1
+            /*10*/ Insc::MapRemove(0, 3, 6),                     // %6 = map-remove %0, %3
+            /*11*/ Insc::MapContains(0, 3, 7),                   // %7 = map-contains %0, %3
+            /*12*/ Insc::MapLen(0, 8),                           // %8 = map-len %0
+            /*13*/ Insc::MapGet(0, 3, 9),                        // %9 = map-get %0, %3
+            /*14*/ Insc::MapKeys(0, keys_vt, 10),                // %10 = map-keys %0
+            /*15*/ Insc::Return(                                 // return [ %5, %6, %7, %8,
+                arena.unsafe_make(&[5, 6, 7, 8, 9, 10])          //          %9, %10 ]
+            )
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 0, 6, 11, boxed_slice![])
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
+    }
+}
+
+#[cfg(feature = "al31fm2-builtin-ops")]
+pub fn vec_program<A: Alloc>() -> CompiledProgram<A> {
+    let tyck_info_pool: &'static mut TyckInfoPool = Box::leak(Box::new(TyckInfoPool::new()));
+    let int_type = tyck_info_pool.get_int_type();
+    let vec_vt: GenericTypeVT = create_vm_vec_vt(tyck_info_pool, int_type);
+    let vec_vt: NonNull<GenericTypeVT> = NonNull::from(Box::leak(Box::new(vec_vt)));
+
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                                 // vec_ops()
+            /*00*/ Insc::CreateContainer(vec_ctor, vec_vt, 0),   // %0 = create-container vec
+            /*01*/ Insc::MakeIntConst(3, 1),                     // %1 = $3
+            /*02*/ Insc::VecPush(0, 1),                          // vec-push %0, %1
+            /*03*/ Insc::MakeIntConst(1, 1),                     // %1 = $1
+            /*04*/ Insc::VecPush(0, 1),                          // vec-push %0, %1
+            /*05*/ Insc::MakeIntConst(2, 1),                     // %1 = $2
+            /*06*/ Insc::VecPush(0, 1),                          // vec-push %0, %1
+            /*07*/ Insc::MakeIntConst(0, 2),                     // %2 = $0
+            /*08*/ Insc::MakeIntConst(5, 1),                     // %1 = $5
+            /*09*/ Insc::VecInsert(0, 2, 1),                     // vec-insert %0, %2, %1
+            /*10*/ Insc::MakeIntConst(1, 3),                     // %3 = $1 (compare)
+            /*11*/ Insc::VecSort(0, 3),                          // vec-sort %0, %3
+            /*12*/ Insc::VecPop(0, 4),                           // %4 = vec-pop %0
+            /*13*/ Insc::VecRemove(0, 2, 5),                     // %5 = vec-remove %0, %2
+            /*14*/ Insc::VecContains(0, 1, 6),                   // %6 = vec-contains %0, %1
+            /*15*/ Insc::VecExtend(0, 0),                        // vec-extend %0, %0
+            /*16*/ Insc::VecReverse(0),                          // vec-reverse %0
+            /*17*/ Insc::MakeIntConst(1, 7),                     // %7 = $1
+            /*18*/ Insc::MakeIntConst(3, 8),                     // %8 = $3
+            /*19*/ Insc::VecSlice(0, 7, 8, 9),                   // %9 = vec-slice %0, %7, %8
+            /*20*/ Insc::VecTruncate(0, 8),                      // vec-truncate %0, %8
+            /*21*/ Insc::VecSlice(0, 2, 8, 10),                  // %10 = vec-slice %0, %2, %8
+            /*22*/ Insc::VecClear(10),                           // vec-clear %10
+            /*23*/ Insc::VecLen(10, 11),                         // %11 = vec-len %10
+            /*24*/ Insc::Return(                                 // return [ %0, %4, %5, %6,
+                arena.unsafe_make(&[0, 4, 5, 6, 9, 11])          //          %9, %11 ]
+            ),
+                                                                 // compare(%0, %1)
+            /*25*/ Insc::SubInt(0, 1, 2),                        // %2 = sub int %0, %1
+            /*26*/ Insc::Return(arena.unsafe_make(&[2])),        // return [ %2 ]
+                                                                 // vec_pop_empty()
+            /*27*/ Insc::CreateContainer(vec_ctor, vec_vt, 0),   // %0 = create-container vec
+            /*28*/ Insc::VecPop(0, 1),                           // %1 = vec-pop %0
+            /*29*/ Insc::Return(arena.unsafe_make(&[1])),        // return [ %1 ]
+                                                                 // vec_sort_throw()
+            /*30*/ Insc::CreateContainer(vec_ctor, vec_vt, 0),   // %0 = create-container vec
+            /*31*/ Insc::MakeIntConst(1, 1),                     // %1 = $1
+            /*32*/ Insc::VecPush(0, 1),                          // vec-push %0, %1
+            /*33*/ Insc::VecPush(0, 1),                          // vec-push %0, %1
+            /*34*/ Insc::MakeIntConst(4, 2),                     // %2 = $4 (compare_throw)
+            /*35*/ Insc::VecSort(0, 2),                          // vec-sort %0, %2
+            /*36*/ Insc::Return(arena.unsafe_make(&[0])),        // return [ %0 ]
+                                                                 // compare_throw(%0, %1)
+            /*37*/ Insc::MakeIntConst(0, 2),                     // %2 = $0
+            /*38*/ Insc::DivInt(0, 2, 3),                        // %3 = div int %0, %2
+            /*39*/ Insc::Return(arena.unsafe_make(&[3]))         // return [ %3 ]
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 0, 6, 12, boxed_slice![]),
+            CompiledFunction::new(25, 2, 1, 3, boxed_slice![]),
+            CompiledFunction::new(27, 0, 1, 2, boxed_slice![]),
+            CompiledFunction::new(30, 0, 1, 3, boxed_slice![]),
+            CompiledFunction::new(37, 2, 1, 4, boxed_slice![])
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        #[cfg(feature="async")] async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
+    }
+}
+
+#[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+pub fn vec_sort_callback_program<A: Alloc>() -> CompiledProgram<A> {
+    let tyck_info_pool: &'static mut TyckInfoPool = Box::leak(Box::new(TyckInfoPool::new()));
+    let int_type = tyck_info_pool.get_int_type();
+    let vec_vt: GenericTypeVT = create_vm_vec_vt(tyck_info_pool, int_type);
+    let vec_vt: NonNull<GenericTypeVT> = NonNull::from(Box::leak(Box::new(vec_vt)));
+
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                                 // sort_await()
+            /*00*/ Insc::CreateContainer(vec_ctor, vec_vt, 0),   // %0 = create-container vec
+            /*01*/ Insc::MakeIntConst(1, 1),                     // %1 = $1
+            /*02*/ Insc::VecPush(0, 1),                          // vec-push %0, %1
+            /*03*/ Insc::VecPush(0, 1),                          // vec-push %0, %1
+            /*04*/ Insc::MakeIntConst(1, 2),                     // %2 = $1 (compare_call)
+            /*05*/ Insc::VecSort(0, 2),                          // vec-sort %0, %2
+            /*06*/ Insc::Return(arena.unsafe_make(&[0])),        // return [ %0 ]
+                                                                 // compare_call(%0, %1)
+            /*07*/ Insc::Call(                                   // [ %2 ] = call compare_await
+                2,                                               //   (%0, %1)
+                arena.unsafe_make(&[0, 1]),
+                arena.unsafe_make(&[2])
+            ),
+            /*08*/ Insc::Return(arena.unsafe_make(&[2])),        // return [ %2 ]
+                                                                 // compare_await(%0, %1)
+            /*09*/ Insc::MakeBoolConst(false, 2),                // %2 = false
+            /*10*/ Insc::JumpIfFalse(2, 12),                     // if !%2 goto L.12
+            /*11*/ Insc::Await(0, arena.unsafe_make(&[3])),      // [ %3 ] = await %0
+            /*12*/ Insc::SubInt(0, 1, 3),                        // %3 = sub int %0, %1
+            /*13*/ Insc::Return(arena.unsafe_make(&[3])),        // return [ %3 ]
+                                                                 // sort_no_ret()
+            /*14*/ Insc::CreateContainer(vec_ctor, vec_vt, 0),   // %0 = create-container vec
+            /*15*/ Insc::MakeIntConst(1, 1),                     // %1 = $1
+            /*16*/ Insc::VecPush(0, 1),                          // vec-push %0, %1
+            /*17*/ Insc::VecPush(0, 1),                          // vec-push %0, %1
+            /*18*/ Insc::MakeIntConst(4, 2),                     // %2 = $4 (compare_no_ret)
+            /*19*/ Insc::VecSort(0, 2),                          // vec-sort %0, %2
+            /*20*/ Insc::Return(arena.unsafe_make(&[0])),        // return [ %0 ]
+                                                                 // compare_no_ret(%0, %1)
+            /*21*/ Insc::ReturnNothing                           // return
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 0, 1, 3, boxed_slice![]),
+            CompiledFunction::new(7, 2, 1, 3, boxed_slice![]),
+            CompiledFunction::new(9, 2, 1, 4, boxed_slice![]),
+            CompiledFunction::new(14, 0, 1, 3, boxed_slice![]),
+            // declares one return value, but actually returns nothing
+            CompiledFunction::new(21, 2, 1, 2, boxed_slice![])
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        async_ffi_funcs: boxed_slice![],
+        #[cfg(feature="compiler")] debug_info: None
     }
 }
 
@@ -472,6 +1209,9 @@ pub fn bench_ffi_call_program2<A: Alloc>() -> CompiledProgram<A> {
     #[cfg(feature = "async-astd")]
     return async_std::fs::read_to_string("./Cargo.toml").await;
 
+    #[cfg(feature = "async-sim")]
+    return std::fs::read_to_string("./Cargo.toml");
+
     #[cfg(feature = "async-tokio")]
     tokio::fs::read_to_string("./Cargo.toml").await
 }
@@ -551,12 +1291,18 @@ pub fn async_ffi_call_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 0, 1, 1, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![],
-        async_ffi_funcs: boxed_slice![PR47BINDER_ASYNC_FFI_FUNCTION as _]
+        async_ffi_funcs: boxed_slice![PR47BINDER_ASYNC_FFI_FUNCTION as _],
+        #[cfg(feature = "compiler")]
+        debug_info: None
     }
 }
 
@@ -579,39 +1325,38 @@ pub fn async_spawn_program<A: Alloc>() -> CompiledProgram<A> {
         let code: Box<[Insc]> = boxed_slice![
                                                                // application_start()
             /*00*/ Insc::MakeIntConst(1, 0),                   // %0 = $1
-            /*01*/ Insc::Spawn(1, arena.unsafe_make(&[])),     // spawn $1, []
-            /*02*/ Insc::Await(0, arena.unsafe_make(&[0])),    // %0 = #spawn-result
-            /*03*/ Insc::LoadConst(0, 1),                      // %1 = load-const .string1
-            /*04*/ Insc::FFICallRtlc(0,                        // ffi-call print(%1)
+            /*01*/ Insc::Spawn(1, arena.unsafe_make(&[]), 0),  // %0 = spawn F.1
+            /*02*/ Insc::LoadConst(0, 1),                      // %1 = load-const .string1
+            /*03*/ Insc::FFICallRtlc(0,                        // ffi-call print(%1)
                                      arena.unsafe_make(&[1]),
                                      arena.unsafe_make(&[])),
-            /*05*/ Insc::MakeIntConst(1000, 1),                // %1 = $1000
-            /*06*/ Insc::FFICallAsync(0,                       // %1 = ffi-call-async sleep_ms(%1)
+            /*04*/ Insc::MakeIntConst(1000, 1),                // %1 = $1000
+            /*05*/ Insc::FFICallAsync(0,                       // %1 = ffi-call-async sleep_ms(%1)
                                       arena.unsafe_make(&[1]),
                                       1),
-            /*07*/ Insc::Await(1, arena.unsafe_make(&[])),     // await %1
-            /*08*/ Insc::LoadConst(1, 1),                      // %1 = load-const .string2
-            /*09*/ Insc::FFICallRtlc(0,                        // ffi-call print(%1)
+            /*06*/ Insc::Await(1, arena.unsafe_make(&[])),     // await %1
+            /*07*/ Insc::LoadConst(1, 1),                      // %1 = load-const .string2
+            /*08*/ Insc::FFICallRtlc(0,                        // ffi-call print(%1)
                                      arena.unsafe_make(&[1]),
                                      arena.unsafe_make(&[])),
-            /*10*/ Insc::Await(0, arena.unsafe_make(&[])),     // await %0
-            /*11*/ Insc::ReturnNothing,                        // ret
+            /*09*/ Insc::Await(0, arena.unsafe_make(&[])),     // await %0
+            /*10*/ Insc::ReturnNothing,                        // ret
 
                                                                // spawned_task_main()
-            /*12*/ Insc::LoadConst(2, 0),                      // %0 = load-const .string3
-            /*13*/ Insc::FFICallRtlc(0,                        // ffi-call print(%0)
+            /*11*/ Insc::LoadConst(2, 0),                      // %0 = load-const .string3
+            /*12*/ Insc::FFICallRtlc(0,                        // ffi-call print(%0)
                                      arena.unsafe_make(&[0]),
                                      arena.unsafe_make(&[])),
-            /*14*/ Insc::MakeIntConst(1000, 0),                // %0 = $1000
-            /*15*/ Insc::FFICallAsync(0,                       // %0 = ffi-call-async sleep_ms(%0)
+            /*13*/ Insc::MakeIntConst(1000, 0),                // %0 = $1000
+            /*14*/ Insc::FFICallAsync(0,                       // %0 = ffi-call-async sleep_ms(%0)
                                       arena.unsafe_make(&[0]),
                                       0),
-            /*16*/ Insc::Await(0, arena.unsafe_make(&[])),     // await %0
-            /*17*/ Insc::LoadConst(3, 0),                      // %0 = load-const .string4
-            /*18*/ Insc::FFICallRtlc(0,                        // ffi-call print(%0)
+            /*15*/ Insc::Await(0, arena.unsafe_make(&[])),     // await %0
+            /*16*/ Insc::LoadConst(3, 0),                      // %0 = load-const .string4
+            /*17*/ Insc::FFICallRtlc(0,                        // ffi-call print(%0)
                                      arena.unsafe_make(&[0]),
                                      arena.unsafe_make(&[])),
-            /*19*/ Insc::ReturnNothing                         // ret
+            /*18*/ Insc::ReturnNothing                         // ret
         ];
         (arena, code)
     };
@@ -620,12 +1365,297 @@ pub fn async_spawn_program<A: Alloc>() -> CompiledProgram<A> {
         slice_arena,
         code,
         const_pool: boxed_slice![string1, string2, string3, string4],
-        init_proc: 0,
+        globals: boxed_slice![],
+        init_proc: None,
         functions: boxed_slice![
             CompiledFunction::new(0, 0, 0, 2, boxed_slice![]),
-            CompiledFunction::new(12, 0, 0, 1, boxed_slice![])
+            CompiledFunction::new(11, 0, 0, 1, boxed_slice![])
         ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
         ffi_funcs: boxed_slice![PRINT_BIND as _],
-        async_ffi_funcs: boxed_slice![SLEEP_MS_BIND as _]
+        async_ffi_funcs: boxed_slice![SLEEP_MS_BIND as _],
+        #[cfg(feature = "compiler")]
+        debug_info: None
+    }
+}
+
+#[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+pub fn async_spawn_await_program<A: Alloc>() -> CompiledProgram<A> {
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                               // spawn_double(%0) -> (int)
+            /*00*/ Insc::Spawn(3, arena.unsafe_make(&[0]), 1), // %1 = spawn F.3 %0
+            /*01*/ Insc::Await(1, arena.unsafe_make(&[2])),    // [%2] = await %1
+            /*02*/ Insc::ReturnOne(2),                         // ret %2
+
+                                                               // spawn_div_zero(%0) -> (int)
+            /*03*/ Insc::Spawn(4, arena.unsafe_make(&[0]), 1), // %1 = spawn F.4 %0
+            /*04*/ Insc::Await(1, arena.unsafe_make(&[2])),    // [%2] = await %1
+            /*05*/ Insc::ReturnOne(2),                         // ret %2
+
+                                                               // spawn_cancel()
+            /*06*/ Insc::MakeIntConst(1000, 0),                // %0 = $1000
+            /*07*/ Insc::Spawn(5, arena.unsafe_make(&[0]), 1), // %1 = spawn F.5 %0
+            /*08*/ Insc::Cancel(1),                            // cancel %1
+            /*09*/ Insc::Await(1, arena.unsafe_make(&[])),     // await %1
+            /*10*/ Insc::ReturnNothing,                        // ret
+
+                                                               // double(%0) -> (int)
+            /*11*/ Insc::AddInt(0, 0, 0),                      // %0 = add int %0, %0
+            /*12*/ Insc::ReturnOne(0),                         // ret %0
+
+                                                               // div_zero(%0) -> (int)
+            /*13*/ Insc::MakeIntConst(0, 1),                   // %1 = $0
+            /*14*/ Insc::DivInt(0, 1, 0),                      // %0 = div int %0, %1
+            /*15*/ Insc::ReturnOne(0),                         // ret %0
+
+                                                               // sleep(%0)
+            /*16*/ Insc::FFICallAsync(0,                       // %0 = ffi-call-async sleep_ms(%0)
+                                      arena.unsafe_make(&[0]),
+                                      0),
+            /*17*/ Insc::Await(0, arena.unsafe_make(&[])),     // await %0
+            /*18*/ Insc::ReturnNothing                         // ret
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 1, 1, 3, boxed_slice![]),  // spawn_double
+            CompiledFunction::new(3, 1, 1, 3, boxed_slice![]),  // spawn_div_zero
+            CompiledFunction::new(6, 0, 0, 2, boxed_slice![]),  // spawn_cancel
+            CompiledFunction::new(11, 1, 1, 1, boxed_slice![]), // double
+            CompiledFunction::new(13, 1, 1, 2, boxed_slice![]), // div_zero
+            CompiledFunction::new(16, 1, 0, 1, boxed_slice![])  // sleep
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        async_ffi_funcs: boxed_slice![SLEEP_MS_BIND as _],
+        #[cfg(feature = "compiler")]
+        debug_info: None
+    }
+}
+
+#[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+pub fn async_channel_program<A: Alloc>() -> CompiledProgram<A> {
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                               // application_start()
+            /*00*/ Insc::MakeIntConst(0, 1),                   // %1 = $0
+            /*01*/ Insc::FFICallRtlc(0,                        // %0 = ffi-call new_channel(%1)
+                                     arena.unsafe_make(&[1]),
+                                     arena.unsafe_make(&[0])),
+            /*02*/ Insc::Spawn(1, arena.unsafe_make(&[0]), 2), // %2 = spawn F.1 %0
+            /*03*/ Insc::FFICallAsync(1,                       // %3 = ffi-call-async recv(%0)
+                                      arena.unsafe_make(&[0]),
+                                      3),
+            /*04*/ Insc::Await(3, arena.unsafe_make(&[3, 4])), // [%3, %4] = await %3
+            /*05*/ Insc::FFICallAsync(1,                       // %5 = ffi-call-async recv(%0)
+                                      arena.unsafe_make(&[0]),
+                                      5),
+            /*06*/ Insc::Await(5, arena.unsafe_make(&[5, 6])), // [%5, %6] = await %5
+            /*07*/ Insc::FFICallAsync(1,                       // %7 = ffi-call-async recv(%0)
+                                      arena.unsafe_make(&[0]),
+                                      7),
+            /*08*/ Insc::Await(7, arena.unsafe_make(&[7, 8])), // [%7, %8] = await %7
+            /*09*/ Insc::Await(2, arena.unsafe_make(&[])),     // await %2
+            /*10*/ Insc::Return(                              // ret %3, %5, %8
+                       arena.unsafe_make(&[3, 5, 8])
+                   ),
+
+                                                               // producer(%0)
+            /*11*/ Insc::MakeIntConst(13, 1),                  // %1 = $13
+            /*12*/ Insc::FFICallAsync(0,                       // %2 = ffi-call-async send(%0, %1)
+                                      arena.unsafe_make(&[0, 1]),
+                                      2),
+            /*13*/ Insc::Await(2, arena.unsafe_make(&[2])),    // [%2] = await %2
+            /*14*/ Insc::MakeIntConst(42, 1),                  // %1 = $42
+            /*15*/ Insc::FFICallAsync(0,                       // %2 = ffi-call-async send(%0, %1)
+                                      arena.unsafe_make(&[0, 1]),
+                                      2),
+            /*16*/ Insc::Await(2, arena.unsafe_make(&[2])),    // [%2] = await %2
+            /*17*/ Insc::FFICallRtlc(1,                        // ffi-call close(%0)
+                                     arena.unsafe_make(&[0]),
+                                     arena.unsafe_make(&[])),
+            /*18*/ Insc::ReturnNothing                         // ret
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 0, 3, 9, boxed_slice![]),  // application_start
+            CompiledFunction::new(11, 1, 0, 3, boxed_slice![])  // producer
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![NEW_CHANNEL_BIND as _, CLOSE_BIND as _],
+        async_ffi_funcs: boxed_slice![SEND_BIND as _, RECV_BIND as _],
+        #[cfg(feature = "compiler")]
+        debug_info: None
+    }
+}
+
+#[cfg(feature = "async")]
+pub fn async_timeout_program<A: Alloc>() -> CompiledProgram<A> {
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                               // sleep_timeout(%0, %1) -> (bool)
+            /*00*/ Insc::FFICallAsync(0,                       // %0 = ffi-call-async sleep_ms(%0)
+                                      arena.unsafe_make(&[0]),
+                                      0),
+            /*01*/ Insc::FFICallAsync(1,                       // %0 = ffi-call-async timeout(%0,%1)
+                                      arena.unsafe_make(&[0, 1]),
+                                      0),
+            /*02*/ Insc::Await(0, arena.unsafe_make(&[])),     // await %0
+            /*03*/ Insc::MakeBoolConst(false, 0),              // %0 = false
+            /*04*/ Insc::ReturnOne(0),                         // ret %0
+
+                                                               // sleep_timeout:eh:TimeoutError
+            /*05*/ Insc::MakeBoolConst(true, 0),               // %0 = true
+            /*06*/ Insc::ReturnOne(0)                          // ret %0
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new_with_exc(0, 2, 1, 3, boxed_slice![], boxed_slice![
+                ExceptionHandlingBlock::new(0, 3, <Void as StaticBase<TimeoutError>>::type_id(), 5)
+            ])
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![],
+        async_ffi_funcs: boxed_slice![SLEEP_MS_BIND as _, TIMEOUT_BIND as _],
+        #[cfg(feature = "compiler")]
+        debug_info: None
+    }
+}
+
+#[cfg(all(feature = "async", feature = "al31fm2-builtin-ops"))]
+pub fn async_task_group_program<A: Alloc>() -> CompiledProgram<A> {
+    let (slice_arena, code) = unsafe {
+        let arena: SliceArena<8192, 8> = SliceArena::new();
+        let code: Box<[Insc]> = boxed_slice![
+                                                               // group_ok()
+            /*00*/ Insc::CreateTaskGroup(0),                   // %0 = new task-group
+            /*01*/ Insc::MakeIntConst(21, 1),                  // %1 = $21
+            /*02*/ Insc::Spawn(2, arena.unsafe_make(&[1]), 2), // %2 = spawn F.2 %1
+            /*03*/ Insc::GroupAdd(0, 2),                       // group-add %0, %2
+            /*04*/ Insc::Spawn(2, arena.unsafe_make(&[1]), 2), // %2 = spawn F.2 %1
+            /*05*/ Insc::GroupAdd(0, 2),                       // group-add %0, %2
+            /*06*/ Insc::AwaitGroup(0),                        // await group %0
+            /*07*/ Insc::ReturnNothing,                        // ret
+
+                                                               // group_fail()
+            /*08*/ Insc::CreateTaskGroup(0),                   // %0 = new task-group
+            /*09*/ Insc::MakeIntConst(100, 1),                 // %1 = $100
+            /*10*/ Insc::Spawn(4, arena.unsafe_make(&[1]), 2), // %2 = spawn F.4 %1
+            /*11*/ Insc::GroupAdd(0, 2),                       // group-add %0, %2
+            /*12*/ Insc::Spawn(3, arena.unsafe_make(&[1]), 2), // %2 = spawn F.3 %1
+            /*13*/ Insc::GroupAdd(0, 2),                       // group-add %0, %2
+            /*14*/ Insc::AwaitGroup(0),                        // await group %0
+            /*15*/ Insc::ReturnNothing,                        // ret
+
+                                                               // double(%0) -> (int)
+            /*16*/ Insc::AddInt(0, 0, 0),                      // %0 = add int %0, %0
+            /*17*/ Insc::ReturnOne(0),                         // ret %0
+
+                                                               // div_zero(%0) -> (int)
+            /*18*/ Insc::MakeIntConst(0, 1),                   // %1 = $0
+            /*19*/ Insc::DivInt(0, 1, 0),                      // %0 = div int %0, %1
+            /*20*/ Insc::ReturnOne(0),                         // ret %0
+
+                                                               // sleep(%0)
+            /*21*/ Insc::FFICallAsync(0,                       // %0 = ffi-call-async sleep_ms(%0)
+                                      arena.unsafe_make(&[0]),
+                                      0),
+            /*22*/ Insc::Await(0, arena.unsafe_make(&[])),     // await %0
+            /*23*/ Insc::ReturnNothing,                        // ret
+
+                                                               // group_fail_blocked()
+            /*24*/ Insc::CreateTaskGroup(0),                   // %0 = new task-group
+            /*25*/ Insc::MakeIntConst(0, 1),                   // %1 = $0
+            /*26*/ Insc::FFICallRtlc(0,                        // %1 = ffi-call new_channel(%1)
+                                     arena.unsafe_make(&[1]),
+                                     arena.unsafe_make(&[1])),
+            /*27*/ Insc::Spawn(6, arena.unsafe_make(&[1]), 2), // %2 = spawn F.6 %1
+            /*28*/ Insc::GroupAdd(0, 2),                       // group-add %0, %2
+            /*29*/ Insc::MakeIntConst(3_600_000, 3),           // %3 = $3600000
+            /*30*/ Insc::Spawn(4, arena.unsafe_make(&[3]), 2), // %2 = spawn F.4 %3
+            /*31*/ Insc::GroupAdd(0, 2),                       // group-add %0, %2
+            /*32*/ Insc::MakeIntConst(10, 3),                  // %3 = $10
+            /*33*/ Insc::Spawn(7, arena.unsafe_make(&[3]), 2), // %2 = spawn F.7 %3
+            /*34*/ Insc::GroupAdd(0, 2),                       // group-add %0, %2
+            /*35*/ Insc::AwaitGroup(0),                        // await group %0
+            /*36*/ Insc::ReturnNothing,                        // ret
+
+                                                               // recv(%0)
+            /*37*/ Insc::FFICallAsync(1,                       // %0 = ffi-call-async recv(%0)
+                                      arena.unsafe_make(&[0]),
+                                      0),
+            /*38*/ Insc::Await(0, arena.unsafe_make(&[0, 1])), // [%0, %1] = await %0
+            /*39*/ Insc::ReturnNothing,                        // ret
+
+                                                               // sleep_div_zero(%0) -> (int)
+            /*40*/ Insc::FFICallAsync(0,                       // %0 = ffi-call-async sleep_ms(%0)
+                                      arena.unsafe_make(&[0]),
+                                      0),
+            /*41*/ Insc::Await(0, arena.unsafe_make(&[])),     // await %0
+            /*42*/ Insc::MakeIntConst(0, 1),                   // %1 = $0
+            /*43*/ Insc::DivInt(1, 1, 0),                      // %0 = div int %1, %1
+            /*44*/ Insc::ReturnOne(0)                          // ret %0
+        ];
+        (arena, code)
+    };
+
+    CompiledProgram {
+        slice_arena,
+        code,
+        const_pool: boxed_slice![],
+        globals: boxed_slice![],
+        init_proc: None,
+        functions: boxed_slice![
+            CompiledFunction::new(0, 0, 0, 3, boxed_slice![]),  // group_ok
+            CompiledFunction::new(8, 0, 0, 3, boxed_slice![]),  // group_fail
+            CompiledFunction::new(16, 1, 1, 1, boxed_slice![]), // double
+            CompiledFunction::new(18, 1, 1, 2, boxed_slice![]), // div_zero
+            CompiledFunction::new(21, 1, 0, 1, boxed_slice![]), // sleep
+            CompiledFunction::new(24, 0, 0, 4, boxed_slice![]), // group_fail_blocked
+            CompiledFunction::new(37, 1, 0, 2, boxed_slice![]), // recv
+            CompiledFunction::new(40, 1, 1, 2, boxed_slice![])  // sleep_div_zero
+        ],
+        exports: ExportTable::new(),
+        methods: MethodTable::new(),
+        properties: PropertyTable::new(),
+        ffi_funcs: boxed_slice![NEW_CHANNEL_BIND as _],
+        async_ffi_funcs: boxed_slice![SLEEP_MS_BIND as _, RECV_BIND as _],
+        #[cfg(feature = "compiler")]
+        debug_info: None
     }
 }
@@ -4,7 +4,11 @@ use crate::ffi::sync_fn::VMContext;
 use crate::util::serializer::{CoroutineSharedData, Serializer};
 use crate::vm::al31fu::exports::AsyncCombustor;
 use crate::vm::al31fu::imports::{Alloc, Combustor};
-use crate::vm::al31fu::imports::{pr47_al31fu_cxx_add_managed, pr47_al31fu_cxx_alloc_mark_object};
+use crate::vm::al31fu::imports::{
+    pr47_al31fu_cxx_add_managed,
+    pr47_al31fu_cxx_alloc_mark_object,
+    pr47_al31fu_cxx_alloc_pin_objects
+};
 
 impl VMContext for Combustor {
     #[inline(always)]
@@ -20,6 +24,13 @@ impl VMContext for Combustor {
             pr47_al31fu_cxx_alloc_mark_object(self.alloc, value);
         }
     }
+
+    #[inline(always)]
+    fn pin_objects(&mut self, values: &[Value]) -> *mut bool {
+        unsafe {
+            pr47_al31fu_cxx_alloc_pin_objects(self.alloc, values.as_ptr(), values.len())
+        }
+    }
 }
 
 pub struct LockedAsyncContext {
@@ -40,6 +51,13 @@ impl VMContext for LockedAsyncContext {
             pr47_al31fu_cxx_alloc_mark_object(self.alloc, wide_ptr);
         }
     }
+
+    #[inline(always)]
+    fn pin_objects(&mut self, values: &[Value]) -> *mut bool {
+        unsafe {
+            pr47_al31fu_cxx_alloc_pin_objects(self.alloc, values.as_ptr(), values.len())
+        }
+    }
 }
 
 unsafe impl Send for LockedAsyncContext {}